@@ -0,0 +1,100 @@
+use graphql_executor::{Location, ServerError};
+use graphql_parser::ParsingError;
+use std::fmt::Write;
+use std::path::Path;
+
+/// A problem found in a file, rendered with the line it points at when it has a location.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Diagnostic {
+    pub message: String,
+    pub location: Option<Location>,
+}
+
+impl Diagnostic {
+    pub fn new<T: Into<String>>(message: T) -> Self {
+        Diagnostic {
+            message: message.into(),
+            location: None,
+        }
+    }
+
+    pub fn render(&self, path: &Path, source: &str) -> String {
+        let mut output = String::new();
+        writeln!(output, "error: {}", self.message).unwrap();
+        let location = match self.location {
+            Some(location) => location,
+            None => {
+                writeln!(output, "  --> {}", path.display()).unwrap();
+                return output;
+            }
+        };
+        writeln!(
+            output,
+            "  --> {}:{}:{}",
+            path.display(),
+            location.line,
+            location.column
+        )
+        .unwrap();
+        if let Some(line) = source.lines().nth(location.line.saturating_sub(1)) {
+            let number = location.line.to_string();
+            let gutter = " ".repeat(number.len());
+            writeln!(output, "{} |", gutter).unwrap();
+            writeln!(output, "{} | {}", number, line).unwrap();
+            let marker: String = line
+                .chars()
+                .take(location.column.saturating_sub(1))
+                .map(|c| if c == '\t' { '\t' } else { ' ' })
+                .collect();
+            writeln!(output, "{} | {}^", gutter, marker).unwrap();
+        }
+        output
+    }
+}
+
+impl From<ParsingError<'_>> for Diagnostic {
+    fn from(error: ParsingError<'_>) -> Self {
+        Diagnostic {
+            message: "Syntax Error".to_owned(),
+            location: Some((&error.position()).into()),
+        }
+    }
+}
+
+impl From<ServerError> for Diagnostic {
+    fn from(error: ServerError) -> Self {
+        Diagnostic {
+            message: error.message,
+            location: error.locations.first().copied(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn render() {
+        let source = "query {\n\thero { email }\n}\n";
+        let diagnostic = Diagnostic {
+            message: "Cannot query field \"email\" on type \"Hero\".".to_owned(),
+            location: Some(Location { line: 2, column: 9 }),
+        };
+        assert_eq!(
+            diagnostic.render(Path::new("hero.graphql"), source),
+            concat!(
+                "error: Cannot query field \"email\" on type \"Hero\".\n",
+                "  --> hero.graphql:2:9\n",
+                "  |\n",
+                "2 | \thero { email }\n",
+                "  | \t       ^\n",
+            )
+        );
+        assert_eq!(
+            Diagnostic::new("Schema does not define a query type.")
+                .render(Path::new("schema.graphql"), source),
+            "error: Schema does not define a query type.\n  --> schema.graphql\n"
+        );
+    }
+}
@@ -0,0 +1,346 @@
+#![warn(clippy::all)]
+
+mod diagnostic;
+mod path;
+
+use diagnostic::*;
+use graphql_executor::{validate, Schema};
+use graphql_parser::*;
+use graphql_tools::{
+    apply_lint_fixes, lint_operation_set, lint_schema, LintConfig, LintRule, LintViolation,
+};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+const USAGE: &str = "Usage:
+  graphql check <SCHEMA> [DOCUMENT]...   Validate a schema and the documents written against it
+  graphql fmt [--check] <FILE>...        Rewrite files in the canonical format
+  graphql lint [OPTION]... <FILE>...     Check schemas and operations against the lint rules
+      --fix                              Rewrite files with the fixes of the violations
+      --disable <RULE>                   Skip a rule
+      --schema <SCHEMA>                  Check operations against a schema
+      --max-depth <N>                    Report operations nested deeper than N fields
+      --fragment-pattern <PATTERN>       Report fragments named otherwise, as in {type}Fields
+  graphql print-ast <FILE>               Print the syntax tree of a file
+
+Directories stand for every .graphql and .gql file below them, and patterns such as
+queries/**/*.graphql are expanded when the shell leaves them as they are.
+
+Exits with 1 when problems are found, and with 2 when the command cannot run.";
+
+/// How many times `lint --fix` lints the files again after fixing them, so that fixes which
+/// keep producing new violations cannot run forever.
+const MAX_FIX_ROUND: usize = 10;
+
+fn main() {
+    let argument_list: Vec<String> = std::env::args().skip(1).collect();
+    let code = match run(&argument_list) {
+        Ok(true) => 0,
+        Ok(false) => 1,
+        Err(message) => {
+            eprintln!("{}", message);
+            2
+        }
+    };
+    std::process::exit(code);
+}
+
+/// Runs a command, returning whether it found no problem.
+fn run(argument_list: &[String]) -> Result<bool, String> {
+    match argument_list.split_first() {
+        Some((command, argument_list)) => match command.as_str() {
+            "check" => check(argument_list),
+            "fmt" => fmt(argument_list),
+            "lint" => lint(argument_list),
+            "print-ast" => print_ast(argument_list),
+            "help" | "-h" | "--help" => {
+                println!("{}", USAGE);
+                Ok(true)
+            }
+            command => Err(format!("Unknown command \"{}\".\n\n{}", command, USAGE)),
+        },
+        None => Err(USAGE.to_owned()),
+    }
+}
+
+fn check(argument_list: &[String]) -> Result<bool, String> {
+    let (schema_path, document_argument_list) = match argument_list.split_first() {
+        Some((schema_path, document_argument_list)) => {
+            (Path::new(schema_path), document_argument_list)
+        }
+        None => return Err(USAGE.to_owned()),
+    };
+    let schema = match load_schema(schema_path)? {
+        Some(schema) => schema,
+        None => return Ok(false),
+    };
+
+    let mut is_clean = true;
+    for path in expand(document_argument_list)? {
+        let source = read(&path)?;
+        let diagnostic_list: Vec<Diagnostic> = match parse_executable(&source) {
+            Ok(document) => validate(&schema, &document)
+                .into_iter()
+                .map(Diagnostic::from)
+                .collect(),
+            Err(error) => vec![error.into()],
+        };
+        is_clean &= report(&path, &source, &diagnostic_list);
+    }
+    Ok(is_clean)
+}
+
+fn fmt(argument_list: &[String]) -> Result<bool, String> {
+    let is_check = argument_list.iter().any(|argument| argument == "--check");
+    let file_argument_list: Vec<String> = argument_list
+        .iter()
+        .filter(|argument| *argument != "--check")
+        .cloned()
+        .collect();
+    if file_argument_list.is_empty() {
+        return Err(USAGE.to_owned());
+    }
+
+    let mut is_clean = true;
+    for path in expand(&file_argument_list)? {
+        let source = read(&path)?;
+        let document = match parse_document(&source) {
+            Ok(document) => document,
+            Err(error) => {
+                is_clean &= report(&path, &source, &[error.into()]);
+                continue;
+            }
+        };
+        if has_comment(&source) {
+            let diagnostic = Diagnostic::new("Comments would be lost by formatting this file.");
+            is_clean &= report(&path, &source, &[diagnostic]);
+            continue;
+        }
+        let formatted = print_document(&document);
+        if formatted == source {
+            continue;
+        }
+        if is_check {
+            eprintln!("{} is not formatted.", path.display());
+            is_clean = false;
+        } else {
+            std::fs::write(&path, formatted)
+                .map_err(|error| format!("Cannot write {}: {}", path.display(), error))?;
+        }
+    }
+    Ok(is_clean)
+}
+
+fn lint(argument_list: &[String]) -> Result<bool, String> {
+    let mut config = LintConfig::default();
+    let mut is_fix = false;
+    let mut schema_path = None;
+    let mut file_argument_list = vec![];
+    let mut argument_iter = argument_list.iter();
+    while let Some(argument) = argument_iter.next() {
+        match argument.as_str() {
+            "--fix" => is_fix = true,
+            "--disable" => {
+                let name = argument_iter.next().ok_or_else(|| USAGE.to_owned())?;
+                let rule = LintRule::from_name(name).ok_or_else(|| {
+                    let name_list: Vec<&str> =
+                        LintRule::ALL.iter().map(|rule| rule.name()).collect();
+                    format!(
+                        "Unknown lint rule \"{}\". The rules are {}.",
+                        name,
+                        name_list.join(", ")
+                    )
+                })?;
+                config = config.without_rule(rule);
+            }
+            "--schema" => {
+                schema_path = Some(argument_iter.next().ok_or_else(|| USAGE.to_owned())?);
+            }
+            "--max-depth" => {
+                let depth = argument_iter.next().ok_or_else(|| USAGE.to_owned())?;
+                let depth = depth
+                    .parse()
+                    .map_err(|_| format!("Invalid maximum depth \"{}\".", depth))?;
+                config = config.with_max_depth(depth);
+            }
+            "--fragment-pattern" => {
+                let pattern = argument_iter.next().ok_or_else(|| USAGE.to_owned())?;
+                config = config.with_fragment_name_pattern(pattern.as_str());
+            }
+            _ => file_argument_list.push(argument.clone()),
+        }
+    }
+    if file_argument_list.is_empty() {
+        return Err(USAGE.to_owned());
+    }
+    let schema = match schema_path {
+        Some(schema_path) => match load_schema(Path::new(schema_path))? {
+            Some(schema) => Some(schema),
+            None => return Ok(false),
+        },
+        None => None,
+    };
+
+    // Operation files are linted together, since fragments often live in files of their
+    // own; schema files are linted one by one.
+    let mut is_clean = true;
+    let mut operation_file_list = vec![];
+    for path in expand(&file_argument_list)? {
+        let source = read(&path)?;
+        let document = match parse_document(&source) {
+            Ok(document) => document,
+            Err(error) => {
+                is_clean &= report(&path, &source, &[error.into()]);
+                continue;
+            }
+        };
+        let is_executable = document.definition_list.iter().any(|definition| {
+            matches!(
+                definition.node,
+                DocumentDefinition::Operation(_) | DocumentDefinition::Fragment(_)
+            )
+        });
+        let is_parsed = if is_executable {
+            parse_executable(&source).map(|_| ())
+        } else {
+            parse_type_system(&source).map(|_| ())
+        };
+        if let Err(error) = is_parsed {
+            is_clean &= report(&path, &source, &[error.into()]);
+        } else if is_executable {
+            operation_file_list.push((path, source));
+        } else {
+            is_clean &= lint_files(vec![(path, source)], is_fix, |source_list| {
+                let document = parse_type_system(source_list[0]).ok()?;
+                Some(vec![lint_schema(&document, &config)])
+            })?;
+        }
+    }
+    if !operation_file_list.is_empty() {
+        is_clean &= lint_files(operation_file_list, is_fix, |source_list| {
+            let document_list = source_list
+                .iter()
+                .map(|source| parse_executable(source).ok())
+                .collect::<Option<Vec<_>>>()?;
+            let document_list: Vec<&ExecutableDocument> = document_list.iter().collect();
+            Some(lint_operation_set(&document_list, schema.as_ref(), &config))
+        })?;
+    }
+    Ok(is_clean)
+}
+
+/// Lints files with `lint`, which returns the violations of every source, or `None` when
+/// one does not parse. With `is_fix`, the fixes are applied and the files rewritten.
+fn lint_files<F>(
+    mut file_list: Vec<(PathBuf, String)>,
+    is_fix: bool,
+    lint: F,
+) -> Result<bool, String>
+where
+    F: Fn(&[&str]) -> Option<Vec<Vec<LintViolation>>>,
+{
+    let source_list = |file_list: &[(PathBuf, String)]| -> Vec<String> {
+        file_list.iter().map(|(_, source)| source.clone()).collect()
+    };
+    let lint_list = |source_list: &[String]| {
+        let source_list: Vec<&str> = source_list.iter().map(String::as_str).collect();
+        lint(&source_list)
+    };
+    let mut violation_list_list =
+        lint_list(&source_list(&file_list)).ok_or("Cannot lint files that do not parse.")?;
+    // Fixes that overlap are applied one round at a time.
+    let mut round = 0;
+    while is_fix
+        && round < MAX_FIX_ROUND
+        && violation_list_list
+            .iter()
+            .flatten()
+            .any(|violation| !violation.fix_list.is_empty())
+    {
+        round += 1;
+        let fixed_list: Vec<String> = file_list
+            .iter()
+            .zip(violation_list_list.iter())
+            .map(|((_, source), violation_list)| apply_lint_fixes(source, violation_list))
+            .collect();
+        if fixed_list == source_list(&file_list) {
+            break;
+        }
+        violation_list_list = match lint_list(&fixed_list) {
+            Some(violation_list_list) => violation_list_list,
+            None => break,
+        };
+        for ((path, source), fixed) in file_list.iter_mut().zip(fixed_list) {
+            if *source != fixed {
+                std::fs::write(&path, &fixed)
+                    .map_err(|error| format!("Cannot write {}: {}", path.display(), error))?;
+                *source = fixed;
+            }
+        }
+    }
+
+    let mut is_clean = true;
+    for ((path, source), violation_list) in file_list.iter().zip(violation_list_list) {
+        let diagnostic_list: Vec<Diagnostic> = violation_list
+            .into_iter()
+            .map(|violation| Diagnostic {
+                message: format!("{} ({})", violation.message, violation.rule),
+                location: Some((&violation.position).into()),
+            })
+            .collect();
+        is_clean &= report(path, source, &diagnostic_list);
+    }
+    Ok(is_clean)
+}
+
+fn print_ast(argument_list: &[String]) -> Result<bool, String> {
+    let path = match argument_list {
+        [path] => Path::new(path),
+        _ => return Err(USAGE.to_owned()),
+    };
+    let source = read(path)?;
+    match parse_document(&source) {
+        Ok(document) => {
+            // A closed pipe, as with `| head`, is not worth a panic.
+            let _ = writeln!(std::io::stdout(), "{:#?}", document);
+            Ok(true)
+        }
+        Err(error) => Ok(report(path, &source, &[error.into()])),
+    }
+}
+
+/// Builds the schema in `path`, reporting why it cannot be built.
+fn load_schema(path: &Path) -> Result<Option<Schema>, String> {
+    let source = read(path)?;
+    let document = match parse_type_system(&source) {
+        Ok(document) => document,
+        Err(error) => {
+            report(path, &source, &[error.into()]);
+            return Ok(None);
+        }
+    };
+    match Schema::build(&document) {
+        Ok(schema) => Ok(Some(schema)),
+        Err(error) => {
+            report(path, &source, &[Diagnostic::new(error.to_string())]);
+            Ok(None)
+        }
+    }
+}
+
+fn read(path: &Path) -> Result<String, String> {
+    std::fs::read_to_string(path)
+        .map_err(|error| format!("Cannot read {}: {}", path.display(), error))
+}
+
+fn expand(argument_list: &[String]) -> Result<Vec<PathBuf>, String> {
+    path::expand(argument_list).map_err(|error| error.to_string())
+}
+
+/// Prints every diagnostic to stderr, returning whether there was none.
+fn report(path: &Path, source: &str, diagnostic_list: &[Diagnostic]) -> bool {
+    for diagnostic in diagnostic_list.iter() {
+        eprintln!("{}", diagnostic.render(path, source));
+    }
+    diagnostic_list.is_empty()
+}
@@ -0,0 +1,135 @@
+use std::io;
+use std::path::{Component, Path, PathBuf};
+
+const EXTENSION_LIST: [&str; 2] = ["graphql", "gql"];
+
+/// Expands command line arguments into the files they name, in a stable order.
+///
+/// A directory stands for every `.graphql` and `.gql` file below it. `*` matches within a
+/// path segment and `**` matches any number of segments, for shells that leave patterns
+/// unexpanded.
+pub fn expand(argument_list: &[String]) -> io::Result<Vec<PathBuf>> {
+    let mut path_list = vec![];
+    for argument in argument_list.iter() {
+        if !argument.contains('*') {
+            let path = PathBuf::from(argument);
+            if path.is_dir() {
+                let mut file_list = vec![];
+                walk(&path, &mut file_list)?;
+                path_list.extend(file_list.into_iter().filter(|path| has_extension(path)));
+            } else {
+                path_list.push(path);
+            }
+            continue;
+        }
+
+        let pattern = Path::new(argument);
+        let root: PathBuf = pattern
+            .components()
+            .take_while(|component| !component.as_os_str().to_string_lossy().contains('*'))
+            .collect();
+        let pattern_list = segment_list(pattern);
+        let mut file_list = vec![];
+        let start = if root.as_os_str().is_empty() {
+            Path::new(".")
+        } else {
+            root.as_path()
+        };
+        if start.is_dir() {
+            walk(start, &mut file_list)?;
+        }
+        let matched_list: Vec<PathBuf> = file_list
+            .into_iter()
+            .filter(|path| matches_segment_list(&pattern_list, &segment_list(path)))
+            .collect();
+        if matched_list.is_empty() {
+            return Err(io::Error::new(
+                io::ErrorKind::NotFound,
+                format!("No file matches \"{}\".", argument),
+            ));
+        }
+        path_list.extend(matched_list);
+    }
+
+    Ok(path_list)
+}
+
+fn walk(directory: &Path, file_list: &mut Vec<PathBuf>) -> io::Result<()> {
+    let mut entry_list = std::fs::read_dir(directory)?
+        .map(|entry| entry.map(|entry| entry.path()))
+        .collect::<io::Result<Vec<_>>>()?;
+    entry_list.sort();
+    for path in entry_list {
+        if path.is_dir() {
+            walk(&path, file_list)?;
+        } else {
+            file_list.push(path);
+        }
+    }
+    Ok(())
+}
+
+fn has_extension(path: &Path) -> bool {
+    path.extension()
+        .map(|extension| EXTENSION_LIST.contains(&extension.to_string_lossy().as_ref()))
+        .unwrap_or(false)
+}
+
+fn segment_list(path: &Path) -> Vec<String> {
+    path.components()
+        .filter(|component| !matches!(component, Component::CurDir))
+        .map(|component| component.as_os_str().to_string_lossy().into_owned())
+        .collect()
+}
+
+fn matches_segment_list(pattern_list: &[String], segment_list: &[String]) -> bool {
+    match pattern_list.split_first() {
+        None => segment_list.is_empty(),
+        Some((pattern, rest)) if pattern == "**" => (0..=segment_list.len())
+            .any(|skipped| matches_segment_list(rest, &segment_list[skipped..])),
+        Some((pattern, rest)) => match segment_list.split_first() {
+            Some((segment, segment_rest)) => {
+                matches_segment(pattern, segment) && matches_segment_list(rest, segment_rest)
+            }
+            None => false,
+        },
+    }
+}
+
+fn matches_segment(pattern: &str, segment: &str) -> bool {
+    match pattern.find('*') {
+        None => pattern == segment,
+        Some(index) => {
+            let (prefix, rest) = (&pattern[..index], &pattern[index + 1..]);
+            segment.starts_with(prefix)
+                && (prefix.len()..=segment.len())
+                    .filter(|index| segment.is_char_boundary(*index))
+                    .any(|index| matches_segment(rest, &segment[index..]))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn matches(pattern: &str, path: &str) -> bool {
+        matches_segment_list(
+            &segment_list(Path::new(pattern)),
+            &segment_list(Path::new(path)),
+        )
+    }
+
+    #[test]
+    fn pattern() {
+        assert!(matches("queries/**/*.graphql", "queries/user.graphql"));
+        assert!(matches(
+            "queries/**/*.graphql",
+            "queries/admin/team/list.graphql"
+        ));
+        assert!(matches("./*.gql", "hero.gql"));
+        assert!(!matches("queries/**/*.graphql", "queries/user.gql"));
+        assert!(!matches("queries/*.graphql", "queries/admin/list.graphql"));
+        assert!(!matches("queries/*.graphql", "fragments/user.graphql"));
+    }
+}
@@ -0,0 +1,144 @@
+use std::path::PathBuf;
+use std::process::{Command, Output};
+
+const SCHEMA: &str = r#"type Query {
+  user: User
+}
+
+type User {
+  name: String
+}
+"#;
+
+/// A directory of its own below the temporary directory, removed when dropped.
+struct TempDir(PathBuf);
+
+impl TempDir {
+    fn new(name: &str) -> Self {
+        let path =
+            std::env::temp_dir().join(format!("graphql-cli-{}-{}", name, std::process::id()));
+        let _ = std::fs::remove_dir_all(&path);
+        std::fs::create_dir_all(&path).unwrap();
+        TempDir(path)
+    }
+
+    fn write(&self, name: &str, source: &str) {
+        std::fs::write(self.0.join(name), source).unwrap();
+    }
+
+    fn read(&self, name: &str) -> String {
+        std::fs::read_to_string(self.0.join(name)).unwrap()
+    }
+
+    /// Runs the binary in the directory, so that arguments name its files.
+    fn graphql(&self, argument_list: &[&str]) -> Output {
+        Command::new(env!("CARGO_BIN_EXE_graphql"))
+            .args(argument_list)
+            .current_dir(&self.0)
+            .output()
+            .unwrap()
+    }
+}
+
+impl Drop for TempDir {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_dir_all(&self.0);
+    }
+}
+
+fn stderr(output: &Output) -> String {
+    String::from_utf8(output.stderr.clone()).unwrap()
+}
+
+#[test]
+fn check() {
+    let dir = TempDir::new("check");
+    dir.write("schema.graphql", SCHEMA);
+    dir.write("name.graphql", "query Name {\n  user {\n    name\n  }\n}\n");
+    dir.write("age.graphql", "query Age {\n  user {\n    age\n  }\n}\n");
+
+    let output = dir.graphql(&["check", "schema.graphql", "name.graphql"]);
+    assert_eq!(output.status.code(), Some(0));
+    assert_eq!(stderr(&output), "");
+
+    let output = dir.graphql(&["check", "schema.graphql", "name.graphql", "age.graphql"]);
+    assert_eq!(output.status.code(), Some(1));
+    assert_eq!(
+        stderr(&output),
+        "error: Cannot query field \"age\" on type \"User\".
+  --> age.graphql:3:5
+  |
+3 |     age
+  |     ^
+
+"
+    );
+}
+
+#[test]
+fn fmt_check() {
+    let dir = TempDir::new("fmt");
+    let source = "type Query{user:User}\ntype User{name:String}\n";
+    dir.write("schema.graphql", source);
+
+    let output = dir.graphql(&["fmt", "--check", "schema.graphql"]);
+    assert_eq!(output.status.code(), Some(1));
+    assert_eq!(stderr(&output), "schema.graphql is not formatted.\n");
+    assert_eq!(dir.read("schema.graphql"), source);
+
+    let output = dir.graphql(&["fmt", "schema.graphql"]);
+    assert_eq!(output.status.code(), Some(0));
+    assert_eq!(dir.read("schema.graphql"), SCHEMA);
+
+    let output = dir.graphql(&["fmt", "--check", "schema.graphql"]);
+    assert_eq!(output.status.code(), Some(0));
+    assert_eq!(stderr(&output), "");
+}
+
+#[test]
+fn lint_fix() {
+    let dir = TempDir::new("lint");
+    dir.write("schema.graphql", "type query {\n  user_name: String\n}\n");
+
+    let output = dir.graphql(&[
+        "lint",
+        "--disable",
+        "description-required",
+        "schema.graphql",
+    ]);
+    assert_eq!(output.status.code(), Some(1));
+    let stderr_text = stderr(&output);
+    assert!(stderr_text.contains("(type-name-pascal-case)"));
+    assert!(stderr_text.contains("(field-name-camel-case)"));
+
+    let output = dir.graphql(&[
+        "lint",
+        "--fix",
+        "--disable",
+        "description-required",
+        "schema.graphql",
+    ]);
+    assert_eq!(output.status.code(), Some(0));
+    assert_eq!(stderr(&output), "");
+    assert_eq!(
+        dir.read("schema.graphql"),
+        "type Query {\n  userName: String\n}\n"
+    );
+}
+
+#[test]
+fn cannot_run() {
+    let dir = TempDir::new("cannot-run");
+
+    let output = dir.graphql(&["check", "missing.graphql"]);
+    assert_eq!(output.status.code(), Some(2));
+    assert!(stderr(&output).starts_with("Cannot read missing.graphql: "));
+
+    let output = dir.graphql(&["lint", "--disable", "no-such-rule", "missing.graphql"]);
+    assert_eq!(output.status.code(), Some(2));
+    assert!(stderr(&output).starts_with("Unknown lint rule \"no-such-rule\"."));
+
+    let output = dir.graphql(&["deploy"]);
+    assert_eq!(output.status.code(), Some(2));
+    assert!(stderr(&output).starts_with("Unknown command \"deploy\"."));
+}
@@ -0,0 +1,457 @@
+use crate::*;
+use graphql_parser::*;
+use serde_json::Value as JsonValue;
+use std::collections::BTreeMap;
+
+/// Coerces the raw JSON variables of a request into the values declared by `operation`.
+///
+/// Defaults are applied to missing variables, and every error found is reported against
+/// the position of the offending variable definition.
+pub fn coerce_variable_values(
+    schema: &Schema,
+    operation: &OperationDefinition,
+    variables: &Variables,
+) -> Result<BTreeMap<String, Value>, Vec<ServerError>> {
+    let mut coerced = BTreeMap::new();
+    let mut error_list = vec![];
+
+    for definition in operation.variable_list.iter() {
+        let definition = &definition.node;
+        let name = &definition.name.node;
+        let ty = &definition.ty.node;
+        let error = |message: String| ServerError::new(message).at(&definition.name.start);
+
+        match schema.get_type(ty.name()) {
+            Some(meta_type) if meta_type.is_input() => {}
+            _ => {
+                error_list.push(error(format!(
+                    "Variable \"${}\" cannot be non-input type \"{}\".",
+                    name, ty
+                )));
+                continue;
+            }
+        }
+
+        match variables.get(name) {
+            Some(value) => match coerce_input_value(schema, ty, value) {
+                Ok(value) => {
+                    coerced.insert(name.clone(), value);
+                }
+                Err(coercion) => error_list.push(error(coercion.message_for(
+                    &format!("Variable \"${}\"", name),
+                    name,
+                    value,
+                ))),
+            },
+            None => match &definition.default_value {
+                Some(default_value) => {
+                    match coerce_default_value(schema, ty, &default_value.node) {
+                        Ok(value) => {
+                            coerced.insert(name.clone(), value);
+                        }
+                        Err(coercion) => error_list.push(error(coercion.message_for(
+                            &format!("Variable \"${}\"", name),
+                            name,
+                            value_to_json(&default_value.node),
+                        ))),
+                    }
+                }
+                None if ty.is_non_null() => error_list.push(error(format!(
+                    "Variable \"${}\" of required type \"{}\" was not provided.",
+                    name, ty
+                ))),
+                None => {}
+            },
+        }
+    }
+
+    if error_list.is_empty() {
+        Ok(coerced)
+    } else {
+        Err(error_list)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct CoercionError {
+    pub path: Vec<PathSegment>,
+    pub message: String,
+}
+
+impl CoercionError {
+    fn new(message: String) -> Self {
+        CoercionError {
+            path: vec![],
+            message,
+        }
+    }
+
+    fn prefixed(mut self, segment: PathSegment) -> Self {
+        self.path.insert(0, segment);
+        self
+    }
+
+    pub(crate) fn message_for(
+        &self,
+        subject: &str,
+        root: &str,
+        value: impl std::fmt::Display,
+    ) -> String {
+        if self.path.is_empty() {
+            format!("{} got invalid value {}; {}", subject, value, self.message)
+        } else {
+            let mut path = root.to_owned();
+            for segment in self.path.iter() {
+                match segment {
+                    PathSegment::Field(field) => {
+                        path.push('.');
+                        path.push_str(field);
+                    }
+                    PathSegment::Index(index) => path.push_str(&format!("[{}]", index)),
+                }
+            }
+            format!(
+                "{} got invalid value {} at \"{}\"; {}",
+                subject, value, path, self.message
+            )
+        }
+    }
+}
+
+/// Coerces one JSON input value to `ty` following the input coercion rules of the spec.
+pub fn coerce_input_value(
+    schema: &Schema,
+    ty: &Type,
+    value: &JsonValue,
+) -> Result<Value, CoercionError> {
+    match (ty, value) {
+        (Type::NonNull(_), JsonValue::Null) => Err(CoercionError::new(format!(
+            "Expected non-nullable type \"{}\" not to be null.",
+            ty
+        ))),
+        (Type::NonNull(ty), value) => coerce_input_value(schema, ty, value),
+        (_, JsonValue::Null) => Ok(Value::Null),
+        (Type::List(item_ty), JsonValue::Array(item_list)) => item_list
+            .iter()
+            .enumerate()
+            .map(|(index, item)| {
+                coerce_input_value(schema, item_ty, item)
+                    .map_err(|error| error.prefixed(PathSegment::Index(index)))
+            })
+            .collect::<Result<_, _>>()
+            .map(Value::List),
+        (Type::List(item_ty), value) => {
+            coerce_input_value(schema, item_ty, value).map(|value| Value::List(vec![value]))
+        }
+        (Type::Named(name), value) => {
+            let meta_type = schema
+                .get_type(name)
+                .ok_or_else(|| CoercionError::new(format!("Unknown type \"{}\".", name)))?;
+            match meta_type.kind {
+                TypeKind::Scalar => match schema.scalar_codec(name) {
+                    Some(codec) => codec.parse_value(value).map_err(CoercionError::new),
+                    None => Ok(json_to_value(value)),
+                },
+                TypeKind::Enum => match value {
+                    JsonValue::String(enum_value) if meta_type.enum_value(enum_value).is_some() => {
+                        Ok(Value::Enum(enum_value.clone()))
+                    }
+                    value => Err(CoercionError::new(format!(
+                        "Value {} does not exist in \"{}\" enum.",
+                        value, name
+                    ))),
+                },
+                TypeKind::InputObject => coerce_input_object_value(schema, meta_type, value),
+                _ => Err(CoercionError::new(format!(
+                    "Type \"{}\" is not an input type.",
+                    name
+                ))),
+            }
+        }
+    }
+}
+
+/// Coerces a literal from the executable document to `ty`, substituting the already
+/// coerced `variables` wherever the literal refers to one.
+pub fn coerce_literal_value(
+    schema: &Schema,
+    ty: &Type,
+    value: &Value,
+    variables: &BTreeMap<String, Value>,
+) -> Result<Value, CoercionError> {
+    match (ty, value) {
+        (_, Value::Variable(name)) => {
+            let value = variables.get(name).cloned().unwrap_or(Value::Null);
+            match (ty, value) {
+                (Type::NonNull(_), Value::Null) => Err(CoercionError::new(format!(
+                    "Expected non-nullable type \"{}\" not to be null.",
+                    ty
+                ))),
+                (_, value) => Ok(value),
+            }
+        }
+        (Type::NonNull(_), Value::Null) => Err(CoercionError::new(format!(
+            "Expected non-nullable type \"{}\" not to be null.",
+            ty
+        ))),
+        (Type::NonNull(ty), value) => coerce_literal_value(schema, ty, value, variables),
+        (_, Value::Null) => Ok(Value::Null),
+        (Type::List(item_ty), Value::List(item_list)) => item_list
+            .iter()
+            .enumerate()
+            .map(|(index, item)| {
+                coerce_literal_value(schema, item_ty, item, variables)
+                    .map_err(|error| error.prefixed(PathSegment::Index(index)))
+            })
+            .collect::<Result<_, _>>()
+            .map(Value::List),
+        (Type::List(item_ty), value) => coerce_literal_value(schema, item_ty, value, variables)
+            .map(|value| Value::List(vec![value])),
+        (Type::Named(name), value) => {
+            let meta_type = schema
+                .get_type(name)
+                .ok_or_else(|| CoercionError::new(format!("Unknown type \"{}\".", name)))?;
+            match (meta_type.kind, value) {
+                (TypeKind::Scalar, value) => match schema.scalar_codec(name) {
+                    Some(codec) => codec.parse_literal(value).map_err(CoercionError::new),
+                    None => Ok(value.clone()),
+                },
+                (TypeKind::Enum, Value::Enum(enum_value))
+                    if meta_type.enum_value(enum_value).is_some() =>
+                {
+                    Ok(Value::Enum(enum_value.clone()))
+                }
+                (TypeKind::Enum, value) => Err(CoercionError::new(format!(
+                    "Value {} does not exist in \"{}\" enum.",
+                    value_to_json(value),
+                    name
+                ))),
+                (TypeKind::InputObject, Value::Object(object)) => {
+                    if let Some(field_name) = object
+                        .keys()
+                        .find(|field_name| meta_type.input_field(field_name).is_none())
+                    {
+                        return Err(CoercionError::new(format!(
+                            "Field \"{}\" is not defined by type \"{}\".",
+                            field_name, meta_type.name
+                        )));
+                    }
+
+                    let mut coerced = BTreeMap::new();
+                    for field in meta_type.input_field_list.iter() {
+                        let value = match object.get(&field.name) {
+                            Some(Value::Variable(name)) if !variables.contains_key(name) => None,
+                            value => value,
+                        };
+                        match value {
+                            Some(value) => {
+                                let value =
+                                    coerce_literal_value(schema, &field.ty, value, variables)
+                                        .map_err(|error| {
+                                            error.prefixed(PathSegment::Field(field.name.clone()))
+                                        })?;
+                                coerced.insert(field.name.clone(), value);
+                            }
+                            None => match &field.default_value {
+                                Some(default_value) => {
+                                    let value =
+                                        coerce_default_value(schema, &field.ty, default_value)
+                                            .map_err(|error| {
+                                                error.prefixed(PathSegment::Field(
+                                                    field.name.clone(),
+                                                ))
+                                            })?;
+                                    coerced.insert(field.name.clone(), value);
+                                }
+                                None if field.ty.is_non_null() => {
+                                    return Err(CoercionError::new(format!(
+                                        "Field \"{}\" of required type \"{}\" was not provided.",
+                                        field.name, field.ty
+                                    )))
+                                }
+                                None => {}
+                            },
+                        }
+                    }
+
+                    Ok(Value::Object(coerced))
+                }
+                (TypeKind::InputObject, value) => Err(CoercionError::new(format!(
+                    "Expected type \"{}\" to be an object, not {}.",
+                    name,
+                    value_to_json(value)
+                ))),
+                _ => Err(CoercionError::new(format!(
+                    "Type \"{}\" is not an input type.",
+                    name
+                ))),
+            }
+        }
+    }
+}
+
+/// Coerces the default of a variable, argument or input field, written as a literal, the
+/// same way as a value that was supplied.
+pub(crate) fn coerce_default_value(
+    schema: &Schema,
+    ty: &Type,
+    value: &Value,
+) -> Result<Value, CoercionError> {
+    coerce_literal_value(schema, ty, value, &BTreeMap::new())
+}
+
+fn coerce_input_object_value(
+    schema: &Schema,
+    meta_type: &MetaType,
+    value: &JsonValue,
+) -> Result<Value, CoercionError> {
+    let object = match value {
+        JsonValue::Object(object) => object,
+        value => {
+            return Err(CoercionError::new(format!(
+                "Expected type \"{}\" to be an object, not {}.",
+                meta_type.name, value
+            )))
+        }
+    };
+
+    if let Some(name) = object
+        .keys()
+        .find(|name| meta_type.input_field(name).is_none())
+    {
+        return Err(CoercionError::new(format!(
+            "Field \"{}\" is not defined by type \"{}\".",
+            name, meta_type.name
+        )));
+    }
+
+    let mut coerced = BTreeMap::new();
+    for field in meta_type.input_field_list.iter() {
+        match object.get(&field.name) {
+            Some(value) => {
+                let value = coerce_input_value(schema, &field.ty, value)
+                    .map_err(|error| error.prefixed(PathSegment::Field(field.name.clone())))?;
+                coerced.insert(field.name.clone(), value);
+            }
+            None => match &field.default_value {
+                Some(default_value) => {
+                    let value = coerce_default_value(schema, &field.ty, default_value)
+                        .map_err(|error| error.prefixed(PathSegment::Field(field.name.clone())))?;
+                    coerced.insert(field.name.clone(), value);
+                }
+                None if field.ty.is_non_null() => {
+                    return Err(CoercionError::new(format!(
+                        "Field \"{}\" of required type \"{}\" was not provided.",
+                        field.name, field.ty
+                    )))
+                }
+                None => {}
+            },
+        }
+    }
+
+    Ok(Value::Object(coerced))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    const SCHEMA: &str = r#"
+type Query {
+  search(filter: Filter): String
+}
+
+enum Color {
+  RED
+  GREEN
+}
+
+input Filter {
+  text: String!
+  colorList: [Color!] = [RED]
+  limit: Int = 10
+  ratio: Float = 1
+  nested: Nested
+}
+
+input Nested {
+  idList: [ID!]!
+}
+"#;
+
+    fn coerce(
+        variable_list: &str,
+        variables: serde_json::Value,
+    ) -> Result<BTreeMap<String, Value>, Vec<String>> {
+        let schema = Schema::build(&parse_type_system(SCHEMA).unwrap()).unwrap();
+        let source = format!("query Search{} {{ search }}", variable_list);
+        let document = parse_executable(&source).unwrap();
+        let operation = get_operation(&document, None).unwrap();
+        let variables = match variables {
+            serde_json::Value::Object(variables) => variables,
+            _ => Variables::new(),
+        };
+
+        coerce_variable_values(&schema, operation, &variables)
+            .map_err(|error_list| error_list.into_iter().map(|error| error.message).collect())
+    }
+
+    #[test]
+    fn defaults_and_lists() {
+        let coerced = coerce(
+            "($filter: Filter!, $color: [Color] = [GREEN], $limit: Int, $scale: Float = 2)",
+            json!({ "filter": { "text": "a", "nested": { "idList": 7 } } }),
+        )
+        .unwrap();
+
+        let mut nested = BTreeMap::new();
+        nested.insert(
+            "idList".to_owned(),
+            Value::List(vec![Value::String("7".to_owned())]),
+        );
+        let mut filter = BTreeMap::new();
+        filter.insert("text".to_owned(), Value::String("a".to_owned()));
+        filter.insert(
+            "colorList".to_owned(),
+            Value::List(vec![Value::Enum("RED".to_owned())]),
+        );
+        filter.insert("limit".to_owned(), Value::Int(10));
+        filter.insert("ratio".to_owned(), Value::Float(1.0));
+        filter.insert("nested".to_owned(), Value::Object(nested));
+        assert_eq!(coerced["filter"], Value::Object(filter));
+        assert_eq!(
+            coerced["color"],
+            Value::List(vec![Value::Enum("GREEN".to_owned())])
+        );
+        assert!(!coerced.contains_key("limit"));
+        assert_eq!(coerced["scale"], Value::Float(2.0));
+    }
+
+    #[test]
+    fn errors_with_path() {
+        assert_eq!(
+            coerce(
+                "($filter: Filter!, $limit: Int!)",
+                json!({ "filter": { "text": "a", "colorList": ["RED", "BLUE"] }, "limit": 3000000000u64 }),
+            ),
+            Err(vec![
+                "Variable \"$filter\" got invalid value {\"text\":\"a\",\"colorList\":[\"RED\",\"BLUE\"]} at \"filter.colorList[1]\"; Value \"BLUE\" does not exist in \"Color\" enum.".to_owned(),
+                "Variable \"$limit\" got invalid value 3000000000; Int cannot represent non 32-bit signed integer value: 3000000000".to_owned(),
+            ])
+        );
+        assert_eq!(
+            coerce("($filter: Filter!)", json!({ "filter": { "txt": "a" } })),
+            Err(vec![
+                "Variable \"$filter\" got invalid value {\"txt\":\"a\"}; Field \"txt\" is not defined by type \"Filter\".".to_owned(),
+            ])
+        );
+        assert_eq!(
+            coerce("($filter: Filter!)", json!({ "filter": null })),
+            Err(vec![
+                "Variable \"$filter\" got invalid value null; Expected non-nullable type \"Filter!\" not to be null.".to_owned(),
+            ])
+        );
+    }
+}
@@ -0,0 +1,493 @@
+use crate::*;
+use graphql_parser::*;
+use std::collections::HashMap;
+
+/// The measurements of an operation's selection tree, with fragments expanded.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Complexity {
+    /// The deepest level of nested fields, where root fields are at depth 1.
+    pub depth: usize,
+    /// The number of fields selected, counting a fragment once per spread.
+    pub field_count: usize,
+    pub alias_count: usize,
+    /// The weighted cost, where list fields multiply the cost of their selection.
+    pub score: u64,
+}
+
+/// How the complexity of an operation is weighted, and the budget it must fit in.
+///
+/// A limit of `None` is not enforced.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ComplexityConfig {
+    pub max_depth: Option<usize>,
+    pub max_field_count: Option<usize>,
+    pub max_alias_count: Option<usize>,
+    pub max_score: Option<u64>,
+    /// The directive whose `weight` argument overrides the cost of a field definition,
+    /// or of every field returning the type it is applied to.
+    pub cost_directive: String,
+    /// The arguments read as the size of a list field, in order of preference.
+    pub list_size_argument_list: Vec<String>,
+    /// The size of a list field that has none of the size arguments.
+    pub default_list_size: u64,
+}
+
+impl Default for ComplexityConfig {
+    fn default() -> Self {
+        ComplexityConfig {
+            max_depth: None,
+            max_field_count: None,
+            max_alias_count: None,
+            max_score: None,
+            cost_directive: "cost".to_owned(),
+            list_size_argument_list: vec!["first".to_owned(), "limit".to_owned()],
+            default_list_size: 10,
+        }
+    }
+}
+
+/// Measures the operation of `document` selected by `operation_name`, and rejects it
+/// with an error for every limit of `config` it exceeds.
+///
+/// Fields without a cost directive weigh 1 when they return a composite type and 0
+/// otherwise. The analysis is an upper bound: every branch of an abstract selection is
+/// counted, and `@skip` and `@include` are ignored.
+pub fn analyze_complexity(
+    schema: &Schema,
+    document: &ExecutableDocument,
+    operation_name: Option<&str>,
+    variables: &Variables,
+    config: &ComplexityConfig,
+) -> Result<Complexity, Vec<ServerError>> {
+    let operation = get_operation(document, operation_name).map_err(|error| vec![error])?;
+    let position = document
+        .definition_list
+        .iter()
+        .find_map(|definition| match &definition.node {
+            ExecutableDefinition::Operation(positioned)
+                if std::ptr::eq(&positioned.node, operation) =>
+            {
+                Some(positioned.start.clone())
+            }
+            _ => None,
+        })
+        .unwrap_or_default();
+
+    let mut analyzer = Analyzer {
+        schema,
+        config,
+        fragment_map: document
+            .definition_list
+            .iter()
+            .filter_map(|definition| match &definition.node {
+                ExecutableDefinition::Fragment(fragment) => {
+                    Some((fragment.node.name.node.as_str(), &fragment.node))
+                }
+                ExecutableDefinition::Operation(_) => None,
+            })
+            .collect(),
+        fragment_stack: vec![],
+        fragment_summary_map: HashMap::new(),
+        variable_map: operation
+            .variable_list
+            .iter()
+            .filter_map(|definition| {
+                let name = definition.node.name.node.as_str();
+                match (variables.get(name), &definition.node.default_value) {
+                    (Some(value), _) => Some((name, json_to_value(value))),
+                    (None, Some(default_value)) => Some((name, default_value.node.clone())),
+                    (None, None) => None,
+                }
+            })
+            .collect(),
+        complexity: Complexity::default(),
+        depth_position: None,
+        field_count_position: None,
+        alias_count_position: None,
+    };
+    let root_type = schema.root_type(&operation.ty);
+    analyzer.complexity.score =
+        analyzer.analyze_selection_list(root_type, &operation.selection_list, 1);
+
+    let complexity = analyzer.complexity;
+    let mut error_list = vec![];
+    let limit_list = [
+        (
+            complexity.depth as u64,
+            config.max_depth.map(|max| max as u64),
+            "depth",
+            analyzer.depth_position,
+        ),
+        (
+            complexity.field_count as u64,
+            config.max_field_count.map(|max| max as u64),
+            "field count",
+            analyzer.field_count_position,
+        ),
+        (
+            complexity.alias_count as u64,
+            config.max_alias_count.map(|max| max as u64),
+            "alias count",
+            analyzer.alias_count_position,
+        ),
+        (complexity.score, config.max_score, "complexity", None),
+    ];
+    for (value, max, what, exceeded_at) in limit_list.iter() {
+        if let Some(max) = max.filter(|max| value > max) {
+            error_list.push(
+                ServerError::new(format!(
+                    "Operation has {} of {}, which exceeds the maximum allowed {} of {}.",
+                    what, value, what, max
+                ))
+                .at(exceeded_at.as_ref().unwrap_or(&position)),
+            );
+        }
+    }
+
+    if error_list.is_empty() {
+        Ok(complexity)
+    } else {
+        Err(error_list)
+    }
+}
+
+struct Analyzer<'a> {
+    schema: &'a Schema,
+    config: &'a ComplexityConfig,
+    fragment_map: HashMap<&'a str, &'a FragmentDefinition>,
+    fragment_stack: Vec<&'a str>,
+    /// What each fragment adds wherever it is spread, so that fragments spreading others
+    /// several times are measured once rather than once per path to them.
+    fragment_summary_map: HashMap<&'a str, FragmentSummary>,
+    variable_map: HashMap<&'a str, Value>,
+    complexity: Complexity,
+    depth_position: Option<Position>,
+    field_count_position: Option<Position>,
+    alias_count_position: Option<Position>,
+}
+
+#[derive(Debug, Clone, Copy)]
+struct FragmentSummary {
+    score: u64,
+    /// The levels of nested fields in the fragment, 0 when it selects none.
+    depth: usize,
+    field_count: usize,
+    alias_count: usize,
+}
+
+impl<'a> Analyzer<'a> {
+    fn analyze_selection_list(
+        &mut self,
+        parent_type: Option<&'a MetaType>,
+        selection_list: &'a [Positioned<Selection>],
+        depth: usize,
+    ) -> u64 {
+        let mut score: u64 = 0;
+        for selection in selection_list.iter() {
+            let selection_score = match &selection.node {
+                Selection::Field(field) => {
+                    self.analyze_field(parent_type, field, &selection.start, depth)
+                }
+                Selection::FragmentSpread(spread) => {
+                    let name = spread.name.node.as_str();
+                    if let Some(summary) = self.fragment_summary_map.get(name).copied() {
+                        self.add_fragment_summary(&summary, depth, &selection.start);
+                        summary.score
+                    } else {
+                        let fragment = match self.fragment_map.get(name) {
+                            Some(fragment) if !self.fragment_stack.contains(&name) => *fragment,
+                            _ => continue,
+                        };
+                        self.analyze_fragment(name, fragment, depth)
+                    }
+                }
+                Selection::InlineFragment(inline) => {
+                    let ty = match &inline.on {
+                        Some(on) => self.schema.get_type(&on.node),
+                        None => parent_type,
+                    };
+                    self.analyze_selection_list(ty, &inline.selection_list, depth)
+                }
+            };
+            score = score.saturating_add(selection_score);
+        }
+        score
+    }
+
+    /// Measures a fragment spread at `depth` for the first time, remembering its summary.
+    fn analyze_fragment(
+        &mut self,
+        name: &'a str,
+        fragment: &'a FragmentDefinition,
+        depth: usize,
+    ) -> u64 {
+        let field_count = self.complexity.field_count;
+        let alias_count = self.complexity.alias_count;
+        let outer_depth = std::mem::take(&mut self.complexity.depth);
+
+        let ty = self.schema.get_type(&fragment.on.node);
+        self.fragment_stack.push(name);
+        let score = self.analyze_selection_list(ty, &fragment.selection_list, depth);
+        self.fragment_stack.pop();
+
+        let inner_depth = self.complexity.depth;
+        self.complexity.depth = outer_depth.max(inner_depth);
+        let summary = FragmentSummary {
+            score,
+            depth: (inner_depth + 1).saturating_sub(depth),
+            field_count: self.complexity.field_count - field_count,
+            alias_count: self.complexity.alias_count - alias_count,
+        };
+        self.fragment_summary_map.insert(name, summary);
+        score
+    }
+
+    /// Counts a fragment measured before, locating the limits it exceeds at the spread.
+    fn add_fragment_summary(
+        &mut self,
+        summary: &FragmentSummary,
+        depth: usize,
+        position: &Position,
+    ) {
+        let exceeds = |count: usize, max: Option<usize>| max.is_some_and(|max| count > max);
+        if summary.depth > 0 {
+            let deepest = depth + summary.depth - 1;
+            self.complexity.depth = self.complexity.depth.max(deepest);
+            if self.depth_position.is_none() && exceeds(deepest, self.config.max_depth) {
+                self.depth_position = Some(position.clone());
+            }
+        }
+        let field_count = self.complexity.field_count;
+        self.complexity.field_count = field_count.saturating_add(summary.field_count);
+        if !exceeds(field_count, self.config.max_field_count)
+            && exceeds(self.complexity.field_count, self.config.max_field_count)
+        {
+            self.field_count_position = Some(position.clone());
+        }
+        let alias_count = self.complexity.alias_count;
+        self.complexity.alias_count = alias_count.saturating_add(summary.alias_count);
+        if !exceeds(alias_count, self.config.max_alias_count)
+            && exceeds(self.complexity.alias_count, self.config.max_alias_count)
+        {
+            self.alias_count_position = Some(position.clone());
+        }
+    }
+
+    fn analyze_field(
+        &mut self,
+        parent_type: Option<&'a MetaType>,
+        field: &'a Field,
+        position: &Position,
+        depth: usize,
+    ) -> u64 {
+        let is_first_over =
+            |count: usize, max: Option<usize>| Some(count) == max.map(|max| max + 1);
+        self.complexity.depth = self.complexity.depth.max(depth);
+        if self.depth_position.is_none() && is_first_over(depth, self.config.max_depth) {
+            self.depth_position = Some(position.clone());
+        }
+        self.complexity.field_count = self.complexity.field_count.saturating_add(1);
+        if is_first_over(self.complexity.field_count, self.config.max_field_count) {
+            self.field_count_position = Some(position.clone());
+        }
+        if let Some(alias) = &field.alias {
+            self.complexity.alias_count = self.complexity.alias_count.saturating_add(1);
+            if is_first_over(self.complexity.alias_count, self.config.max_alias_count) {
+                self.alias_count_position = Some(alias.start.clone());
+            }
+        }
+
+        let definition = parent_type.and_then(|ty| ty.field(&field.name.node));
+        let field_type =
+            definition.and_then(|definition| self.schema.get_type(definition.ty.name()));
+        let is_composite = match field_type {
+            Some(ty) => ty.is_composite(),
+            None => !field.selection_list.is_empty(),
+        };
+        let weight = definition
+            .and_then(|definition| self.cost(&definition.directive_list))
+            .or_else(|| field_type.and_then(|ty| self.cost(&ty.directive_list)))
+            .unwrap_or(if is_composite { 1 } else { 0 });
+        let selection_score =
+            self.analyze_selection_list(field_type, &field.selection_list, depth + 1);
+        let multiplier = match definition {
+            Some(definition) if is_list(&definition.ty) => self.list_size(definition, field),
+            _ => 1,
+        };
+
+        multiplier.saturating_mul(weight.saturating_add(selection_score))
+    }
+
+    fn cost(&self, directive_list: &[Directive]) -> Option<u64> {
+        directive_list
+            .iter()
+            .find(|directive| directive.name.node == self.config.cost_directive)
+            .and_then(|directive| {
+                directive
+                    .argument_list
+                    .iter()
+                    .find(|argument| argument.node.name.node == "weight")
+            })
+            .and_then(|argument| match argument.node.value.node {
+                Value::Int(weight) if weight >= 0 => Some(weight as u64),
+                _ => None,
+            })
+    }
+
+    fn list_size(&self, definition: &MetaField, field: &Field) -> u64 {
+        for name in self.config.list_size_argument_list.iter() {
+            let value = match field
+                .argument_list
+                .iter()
+                .find(|argument| &argument.node.name.node == name)
+            {
+                Some(argument) => match &argument.node.value.node {
+                    Value::Variable(variable) => self.variable_map.get(variable.as_str()).cloned(),
+                    value => Some(value.clone()),
+                },
+                None => definition
+                    .argument_list
+                    .iter()
+                    .find(|argument| &argument.name == name)
+                    .and_then(|argument| argument.default_value.clone()),
+            };
+            if let Some(Value::Int(size)) = value {
+                if size >= 0 {
+                    return size as u64;
+                }
+            }
+        }
+        self.config.default_list_size
+    }
+}
+
+fn is_list(ty: &Type) -> bool {
+    match ty {
+        Type::NonNull(ty) => is_list(ty),
+        Type::List(_) => true,
+        Type::Named(_) => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SCHEMA: &str = r#"
+directive @cost(weight: Int!) on FIELD_DEFINITION | OBJECT
+
+type Query {
+  user(id: ID!): User
+  users(first: Int, limit: Int = 5): [User!]!
+  report: Report
+  search(text: String!): [User!]! @cost(weight: 4)
+}
+
+type User {
+  id: ID!
+  name: String
+  friends(first: Int): [User!]!
+}
+
+type Report @cost(weight: 7) {
+  total: Int
+}
+"#;
+
+    const QUERY: &str = r#"query Feed($count: Int) {
+  users(first: $count) {
+    name
+    ...Friends
+  }
+  me: user(id: "1") { id }
+  report { total }
+  search(text: "a") { id }
+}
+
+fragment Friends on User {
+  friends { name }
+}
+"#;
+
+    #[test]
+    fn complexity() {
+        let schema = Schema::build(&parse_type_system(SCHEMA).unwrap()).unwrap();
+        let document = parse_executable(QUERY).unwrap();
+        let variables = match serde_json::json!({"count": 3}) {
+            serde_json::Value::Object(variables) => variables,
+            _ => unreachable!(),
+        };
+
+        let config = ComplexityConfig::default();
+        assert_eq!(
+            analyze_complexity(&schema, &document, None, &variables, &config),
+            Ok(Complexity {
+                depth: 3,
+                field_count: 10,
+                alias_count: 1,
+                score: 3 * (1 + 10) + 1 + 7 + 10 * 4,
+            })
+        );
+
+        let config = ComplexityConfig {
+            max_depth: Some(2),
+            max_field_count: Some(5),
+            max_alias_count: Some(0),
+            max_score: Some(80),
+            ..ComplexityConfig::default()
+        };
+        let error_list = analyze_complexity(&schema, &document, None, &variables, &config)
+            .unwrap_err()
+            .into_iter()
+            .map(|error| {
+                let location = error.locations[0];
+                format!("{}:{}: {}", location.line, location.column, error.message)
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(
+            error_list,
+            vec![
+                "12:13: Operation has depth of 3, which exceeds the maximum allowed depth of 2.",
+                "6:23: Operation has field count of 10, which exceeds the maximum allowed field count of 5.",
+                "6:3: Operation has alias count of 1, which exceeds the maximum allowed alias count of 0.",
+                "1:1: Operation has complexity of 81, which exceeds the maximum allowed complexity of 80.",
+            ]
+        );
+    }
+
+    #[test]
+    fn repeated_fragments() {
+        let schema = Schema::build(&parse_type_system(SCHEMA).unwrap()).unwrap();
+        let mut query = "{ user(id: \"1\") { ...F0 } }\n".to_owned();
+        for index in 0..24 {
+            query += &format!(
+                "fragment F{} on User {{ ...F{} me: friends {{ id }} ...F{} }}\n",
+                index,
+                index + 1,
+                index + 1
+            );
+        }
+        query += "fragment F24 on User { name }\n";
+        let document = parse_executable(&query).unwrap();
+
+        let complexity = analyze_complexity(
+            &schema,
+            &document,
+            None,
+            &Variables::new(),
+            &ComplexityConfig::default(),
+        )
+        .unwrap();
+        assert_eq!(complexity.depth, 3);
+        assert_eq!(complexity.field_count, 1 + (1 << 24) + 2 * ((1 << 24) - 1));
+        assert_eq!(complexity.alias_count, (1 << 24) - 1);
+
+        let config = ComplexityConfig {
+            max_field_count: Some(1000),
+            ..ComplexityConfig::default()
+        };
+        let error_list =
+            analyze_complexity(&schema, &document, None, &Variables::new(), &config).unwrap_err();
+        assert_eq!(error_list.len(), 1);
+    }
+}
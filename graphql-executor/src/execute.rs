@@ -0,0 +1,1139 @@
+use crate::*;
+use futures::{
+    executor::block_on,
+    future::{join_all, BoxFuture, FutureExt},
+};
+use graphql_parser::*;
+use serde_json::{Map, Value as JsonValue};
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::sync::{Arc, Mutex};
+
+pub type Variables = Map<String, JsonValue>;
+
+/// Executes an operation, blocking the current thread until every resolver has finished.
+///
+/// Resolvers returning `Resolved::Future` are polled on the calling thread, so this is
+/// only suitable for futures that do not depend on a specific async runtime.
+pub fn execute(
+    schema: &Schema,
+    document: &ExecutableDocument,
+    operation_name: Option<&str>,
+    variables: &Variables,
+    root: &dyn Resolver,
+) -> Response {
+    block_on(execute_async(
+        schema,
+        document,
+        operation_name,
+        variables,
+        root,
+    ))
+}
+
+/// Executes an operation, resolving sibling fields concurrently.
+///
+/// The root fields of a mutation are resolved serially, in document order.
+pub async fn execute_async(
+    schema: &Schema,
+    document: &ExecutableDocument,
+    operation_name: Option<&str>,
+    variables: &Variables,
+    root: &dyn Resolver,
+) -> Response {
+    let (operation, root_type, variables) =
+        match prepare_operation(schema, document, operation_name, variables) {
+            Ok(prepared) => prepared,
+            Err(error_list) => return Response::from_errors(error_list),
+        };
+    if operation.ty == OperationType::Subscription {
+        return Response::from_errors(vec![ServerError::new(
+            "Subscription operations must be executed with `subscribe`.",
+        )]);
+    }
+
+    let context = ExecutionContext::new(schema, document, variables);
+    let grouped_field_list = context.collect_fields(root_type, &[&operation.selection_list]);
+    let is_serial = operation.ty == OperationType::Mutation;
+    let data = context
+        .execute_fields(root_type, root, grouped_field_list, &[], is_serial)
+        .await
+        .map(JsonValue::Object)
+        .unwrap_or(JsonValue::Null);
+
+    Response {
+        data: Some(data),
+        errors: context.error_list.into_inner().unwrap(),
+    }
+}
+
+pub(crate) type PreparedOperation<'a> = (
+    &'a OperationDefinition,
+    &'a MetaType,
+    BTreeMap<String, Value>,
+);
+
+pub(crate) fn prepare_operation<'a>(
+    schema: &'a Schema,
+    document: &'a ExecutableDocument,
+    operation_name: Option<&str>,
+    variables: &Variables,
+) -> Result<PreparedOperation<'a>, Vec<ServerError>> {
+    let operation = get_operation(document, operation_name).map_err(|error| vec![error])?;
+    let root_type = schema.root_type(&operation.ty).ok_or_else(|| {
+        vec![ServerError::new(format!(
+            "Schema is not configured for {} operations.",
+            operation_type_name(&operation.ty)
+        ))]
+    })?;
+    let variables = coerce_variable_values(schema, operation, variables)?;
+
+    Ok((operation, root_type, variables))
+}
+
+#[allow(clippy::result_large_err)]
+pub fn get_operation<'a>(
+    document: &'a ExecutableDocument,
+    operation_name: Option<&str>,
+) -> Result<&'a OperationDefinition, ServerError> {
+    let mut operation_list =
+        document
+            .definition_list
+            .iter()
+            .filter_map(|definition| match &definition.node {
+                ExecutableDefinition::Operation(operation) => Some(&operation.node),
+                ExecutableDefinition::Fragment(_) => None,
+            });
+
+    match operation_name {
+        Some(operation_name) => operation_list
+            .find(|operation| {
+                operation
+                    .name
+                    .as_ref()
+                    .map(|name| name.node == operation_name)
+                    .unwrap_or(false)
+            })
+            .ok_or_else(|| {
+                ServerError::new(format!("Unknown operation named \"{}\".", operation_name))
+            }),
+        None => match (operation_list.next(), operation_list.next()) {
+            (Some(operation), None) => Ok(operation),
+            (Some(_), Some(_)) => Err(ServerError::new(
+                "Must provide operation name if query contains multiple operations.",
+            )),
+            (None, _) => Err(ServerError::new("Must provide an operation.")),
+        },
+    }
+}
+
+fn operation_type_name(ty: &OperationType) -> &'static str {
+    match ty {
+        OperationType::Query => "query",
+        OperationType::Mutation => "mutation",
+        OperationType::Subscription => "subscription",
+    }
+}
+
+pub(crate) type GroupedFieldList<'a> = Vec<(&'a str, Vec<&'a Field>)>;
+
+pub(crate) struct ExecutionContext<'a> {
+    schema: &'a Schema,
+    fragment_map: HashMap<&'a str, &'a FragmentDefinition>,
+    variables: BTreeMap<String, Value>,
+    pub(crate) error_list: Mutex<Vec<ServerError>>,
+    /// The work postponed by `@defer` and `@stream`, which are ignored when this is `None`.
+    deferred_list: Option<Mutex<Vec<Deferred<'a>>>>,
+}
+
+impl<'a> ExecutionContext<'a> {
+    pub(crate) fn new(
+        schema: &'a Schema,
+        document: &'a ExecutableDocument,
+        variables: BTreeMap<String, Value>,
+    ) -> Self {
+        let fragment_map = document
+            .definition_list
+            .iter()
+            .filter_map(|definition| match &definition.node {
+                ExecutableDefinition::Fragment(fragment) => {
+                    Some((fragment.node.name.node.as_str(), &fragment.node))
+                }
+                ExecutableDefinition::Operation(_) => None,
+            })
+            .collect();
+
+        ExecutionContext {
+            schema,
+            fragment_map,
+            variables,
+            error_list: Mutex::new(vec![]),
+            deferred_list: None,
+        }
+    }
+
+    /// Postpones the fragments marked with `@defer` and the list items past the
+    /// `initialCount` of `@stream` instead of executing them in place.
+    pub(crate) fn deferring(mut self) -> Self {
+        self.deferred_list = Some(Mutex::new(vec![]));
+        self
+    }
+
+    /// The errors raised so far, and the work postponed for later payloads.
+    pub(crate) fn finish(self) -> (Vec<ServerError>, Vec<Deferred<'a>>) {
+        let deferred_list = match self.deferred_list {
+            Some(deferred_list) => deferred_list.into_inner().unwrap(),
+            None => vec![],
+        };
+        (self.error_list.into_inner().unwrap(), deferred_list)
+    }
+
+    pub(crate) fn push_deferred(&self, deferred: Deferred<'a>) {
+        if let Some(deferred_list) = &self.deferred_list {
+            deferred_list.lock().unwrap().push(deferred);
+        }
+    }
+
+    /// Postpones the fragments deferred while collecting the fields of the object `path`
+    /// points to.
+    pub(crate) fn defer_fragments(
+        &self,
+        object_type: &'a MetaType,
+        resolver: &Arc<dyn Resolver>,
+        fragment_list: Vec<DeferredFragment<'a>>,
+        path: &[PathSegment],
+    ) {
+        for fragment in fragment_list {
+            self.push_deferred(Deferred {
+                label: fragment.label,
+                path: path.to_vec(),
+                work: DeferredWork::Fragment {
+                    object_type,
+                    resolver: resolver.clone(),
+                    selection_list: fragment.selection_list,
+                },
+            });
+        }
+    }
+
+    pub(crate) fn report(&self, message: String, field: &Field, path: &[PathSegment]) {
+        self.error_list.lock().unwrap().push(
+            ServerError::new(message)
+                .at(&field_position(field))
+                .with_path(path),
+        );
+    }
+
+    /// Reports an error returned by a resolver, keeping its extensions.
+    pub(crate) fn report_error(&self, error: FieldError, field: &Field, path: &[PathSegment]) {
+        self.error_list.lock().unwrap().push(ServerError {
+            message: error.message,
+            locations: vec![(&field_position(field)).into()],
+            path: path.to_vec(),
+            extensions: error.extensions,
+        });
+    }
+
+    /// Collects the fields of a selection set, for contexts that are not `deferring`.
+    pub(crate) fn collect_fields(
+        &self,
+        object_type: &MetaType,
+        selection_list_list: &[&'a Vec<Positioned<Selection>>],
+    ) -> GroupedFieldList<'a> {
+        self.collect_deferred_fields(object_type, selection_list_list)
+            .0
+    }
+
+    /// Collects the fields of a selection set along with the fragments postponed by
+    /// `@defer`.
+    pub(crate) fn collect_deferred_fields(
+        &self,
+        object_type: &MetaType,
+        selection_list_list: &[&'a Vec<Positioned<Selection>>],
+    ) -> (GroupedFieldList<'a>, Vec<DeferredFragment<'a>>) {
+        let mut grouped_field_list = vec![];
+        let mut deferred_fragment_list = vec![];
+        let mut visited_fragment_list = HashSet::new();
+        for selection_list in selection_list_list {
+            self.collect_fields_into(
+                object_type,
+                selection_list,
+                &mut grouped_field_list,
+                &mut deferred_fragment_list,
+                &mut visited_fragment_list,
+            );
+        }
+
+        (grouped_field_list, deferred_fragment_list)
+    }
+
+    fn collect_fields_into(
+        &self,
+        object_type: &MetaType,
+        selection_list: &'a [Positioned<Selection>],
+        grouped_field_list: &mut GroupedFieldList<'a>,
+        deferred_fragment_list: &mut Vec<DeferredFragment<'a>>,
+        visited_fragment_list: &mut HashSet<&'a str>,
+    ) {
+        for selection in selection_list.iter() {
+            match &selection.node {
+                Selection::Field(field) => {
+                    if !self.should_include(&field.directive_list) {
+                        continue;
+                    }
+                    let response_key = field.alias.as_ref().unwrap_or(&field.name).node.as_str();
+                    match grouped_field_list
+                        .iter_mut()
+                        .find(|(key, _)| *key == response_key)
+                    {
+                        Some((_, field_list)) => field_list.push(field),
+                        None => grouped_field_list.push((response_key, vec![field])),
+                    }
+                }
+                Selection::FragmentSpread(spread) => {
+                    let name = spread.name.node.as_str();
+                    if !self.should_include(&spread.directive_list) {
+                        continue;
+                    }
+                    let fragment = match self.fragment_map.get(name) {
+                        Some(fragment) => fragment,
+                        None => continue,
+                    };
+                    if !self.does_fragment_type_apply(object_type, &fragment.on.node) {
+                        continue;
+                    }
+                    if let Some(defer) = self.incremental_directive(&spread.directive_list, "defer")
+                    {
+                        deferred_fragment_list.push(DeferredFragment {
+                            label: self.label(defer),
+                            selection_list: &fragment.selection_list,
+                        });
+                    } else if visited_fragment_list.insert(name) {
+                        self.collect_fields_into(
+                            object_type,
+                            &fragment.selection_list,
+                            grouped_field_list,
+                            deferred_fragment_list,
+                            visited_fragment_list,
+                        );
+                    }
+                }
+                Selection::InlineFragment(fragment) => {
+                    if !self.should_include(&fragment.directive_list) {
+                        continue;
+                    }
+                    if let Some(on) = &fragment.on {
+                        if !self.does_fragment_type_apply(object_type, &on.node) {
+                            continue;
+                        }
+                    }
+                    if let Some(defer) =
+                        self.incremental_directive(&fragment.directive_list, "defer")
+                    {
+                        deferred_fragment_list.push(DeferredFragment {
+                            label: self.label(defer),
+                            selection_list: &fragment.selection_list,
+                        });
+                        continue;
+                    }
+                    self.collect_fields_into(
+                        object_type,
+                        &fragment.selection_list,
+                        grouped_field_list,
+                        deferred_fragment_list,
+                        visited_fragment_list,
+                    );
+                }
+            }
+        }
+    }
+
+    fn should_include(&self, directive_list: &[Positioned<Directive>]) -> bool {
+        directive_list.iter().all(|directive| {
+            let condition = || self.argument_value(&directive.node, "if");
+            match directive.node.name.node.as_str() {
+                "skip" => condition() != Some(Value::Boolean(true)),
+                "include" => condition() == Some(Value::Boolean(true)),
+                _ => true,
+            }
+        })
+    }
+
+    /// The `@defer` or `@stream` directive named `name`, when this context is `deferring`
+    /// and the directive is not disabled through its `if` argument.
+    fn incremental_directive<'b>(
+        &self,
+        directive_list: &'b [Positioned<Directive>],
+        name: &str,
+    ) -> Option<&'b Directive> {
+        self.deferred_list.as_ref()?;
+        directive_list
+            .iter()
+            .map(|directive| &directive.node)
+            .find(|directive| {
+                directive.name.node == name
+                    && self.argument_value(directive, "if") != Some(Value::Boolean(false))
+            })
+    }
+
+    fn label(&self, directive: &Directive) -> Option<String> {
+        match self.argument_value(directive, "label") {
+            Some(Value::String(label)) => Some(label),
+            _ => None,
+        }
+    }
+
+    fn argument_value(&self, directive: &Directive, name: &str) -> Option<Value> {
+        directive
+            .argument_list
+            .iter()
+            .find(|argument| argument.node.name.node == name)
+            .map(|argument| self.resolve_value(&argument.node.value.node))
+    }
+
+    fn does_fragment_type_apply(&self, object_type: &MetaType, type_condition: &str) -> bool {
+        self.schema
+            .is_possible_type(type_condition, &object_type.name)
+    }
+
+    fn resolve_value(&self, value: &Value) -> Value {
+        match value {
+            Value::Variable(name) => self.variables.get(name).cloned().unwrap_or(Value::Null),
+            Value::List(list) => {
+                Value::List(list.iter().map(|value| self.resolve_value(value)).collect())
+            }
+            Value::Object(object) => Value::Object(
+                object
+                    .iter()
+                    .map(|(name, value)| (name.clone(), self.resolve_value(value)))
+                    .collect(),
+            ),
+            value => value.clone(),
+        }
+    }
+
+    pub(crate) fn coerce_argument_values(
+        &self,
+        definition_list: &[MetaInputValue],
+        argument_list: &[Positioned<Argument>],
+    ) -> Result<Arguments, String> {
+        let mut coerced = Arguments::new();
+        for definition in definition_list.iter() {
+            let value = match argument_list
+                .iter()
+                .find(|argument| argument.node.name.node == definition.name)
+                .map(|argument| &argument.node.value.node)
+            {
+                Some(Value::Variable(name)) if !self.variables.contains_key(name) => None,
+                value => value,
+            };
+            match value {
+                Some(value) => {
+                    let value =
+                        coerce_literal_value(self.schema, &definition.ty, value, &self.variables)
+                            .map_err(|error| {
+                            error.message_for(
+                                &format!("Argument \"{}\"", definition.name),
+                                &definition.name,
+                                value_to_json(value),
+                            )
+                        })?;
+                    coerced.insert(definition.name.clone(), value);
+                }
+                None => match &definition.default_value {
+                    Some(default_value) => {
+                        let value =
+                            coerce_default_value(self.schema, &definition.ty, default_value)
+                                .map_err(|error| {
+                                    error.message_for(
+                                        &format!("Argument \"{}\"", definition.name),
+                                        &definition.name,
+                                        value_to_json(default_value),
+                                    )
+                                })?;
+                        coerced.insert(definition.name.clone(), value);
+                    }
+                    None if definition.ty.is_non_null() => {
+                        return Err(format!(
+                            "Argument \"{}\" of required type \"{}\" was not provided.",
+                            definition.name, definition.ty
+                        ))
+                    }
+                    None => {}
+                },
+            }
+        }
+
+        Ok(coerced)
+    }
+
+    pub(crate) fn execute_fields<'b>(
+        &'b self,
+        object_type: &'a MetaType,
+        resolver: &'b dyn Resolver,
+        grouped_field_list: GroupedFieldList<'a>,
+        path: &'b [PathSegment],
+        is_serial: bool,
+    ) -> BoxFuture<'b, Result<Map<String, JsonValue>, ()>> {
+        async move {
+            let field_future_list =
+                grouped_field_list
+                    .into_iter()
+                    .map(|(response_key, field_list)| {
+                        let path = child_path(path, PathSegment::Field(response_key.to_owned()));
+                        async move {
+                            let value = self
+                                .execute_field(object_type, resolver, &field_list, &path)
+                                .await;
+                            (response_key, value)
+                        }
+                    });
+            let value_list = if is_serial {
+                let mut value_list = vec![];
+                for field_future in field_future_list {
+                    value_list.push(field_future.await);
+                }
+                value_list
+            } else {
+                join_all(field_future_list).await
+            };
+
+            let mut map = Map::new();
+            for (response_key, value) in value_list {
+                map.insert(response_key.to_owned(), value?);
+            }
+
+            Ok(map)
+        }
+        .boxed()
+    }
+
+    async fn execute_field(
+        &self,
+        object_type: &'a MetaType,
+        resolver: &dyn Resolver,
+        field_list: &[&'a Field],
+        path: &[PathSegment],
+    ) -> Result<JsonValue, ()> {
+        let field = field_list[0];
+        let field_name = field.name.node.as_str();
+        if field_name == "__typename" {
+            return Ok(JsonValue::String(object_type.name.clone()));
+        }
+
+        let introspection_field = if object_type.name == self.schema.query_type().name {
+            self.schema.introspection_field(field_name)
+        } else {
+            None
+        };
+        let definition = match introspection_field.or_else(|| object_type.field(field_name)) {
+            Some(definition) => definition,
+            None => {
+                self.report(
+                    format!(
+                        "Cannot query field \"{}\" on type \"{}\".",
+                        field_name, object_type.name
+                    ),
+                    field,
+                    path,
+                );
+                return Ok(JsonValue::Null);
+            }
+        };
+        let arguments =
+            match self.coerce_argument_values(&definition.argument_list, &field.argument_list) {
+                Ok(arguments) => arguments,
+                Err(message) => {
+                    self.report(message, field, path);
+                    return null_for(&definition.ty);
+                }
+            };
+        let info = ResolveInfo {
+            schema: self.schema,
+            parent_type: &object_type.name,
+            field_name,
+            arguments: &arguments,
+        };
+        let resolved = if introspection_field.is_some() {
+            IntrospectionRoot(self.schema.clone()).resolve(&info)
+        } else {
+            resolver.resolve(&info)
+        };
+        let resolved = match resolved {
+            Ok(Resolved::Future(future)) => future.await,
+            resolved => resolved,
+        };
+        match resolved {
+            Ok(resolved) => {
+                self.complete_value(&definition.ty, field_list, resolved, path)
+                    .await
+            }
+            Err(error) => {
+                self.report_error(error, field, path);
+                null_for(&definition.ty)
+            }
+        }
+    }
+
+    pub(crate) fn complete_value<'b>(
+        &'b self,
+        ty: &'b Type,
+        field_list: &'b [&'a Field],
+        resolved: Resolved,
+        path: &'b [PathSegment],
+    ) -> BoxFuture<'b, Result<JsonValue, ()>> {
+        async move {
+            match ty {
+                Type::NonNull(ty) => match self
+                    .complete_nullable_value(ty, field_list, resolved, path)
+                    .await?
+                {
+                    JsonValue::Null => {
+                        self.report(
+                            format!(
+                                "Cannot return null for non-nullable field \"{}\".",
+                                field_list[0].name.node
+                            ),
+                            field_list[0],
+                            path,
+                        );
+                        Err(())
+                    }
+                    value => Ok(value),
+                },
+                _ => Ok(self
+                    .complete_nullable_value(ty, field_list, resolved, path)
+                    .await
+                    .unwrap_or(JsonValue::Null)),
+            }
+        }
+        .boxed()
+    }
+
+    async fn complete_nullable_value(
+        &self,
+        ty: &Type,
+        field_list: &[&'a Field],
+        resolved: Resolved,
+        path: &[PathSegment],
+    ) -> Result<JsonValue, ()> {
+        let field = field_list[0];
+        match (ty, resolved) {
+            (_, Resolved::Null) => Ok(JsonValue::Null),
+            (_, Resolved::Future(future)) => match future.await {
+                Ok(resolved) => self.complete_value(ty, field_list, resolved, path).await,
+                Err(error) => {
+                    self.report_error(error, field, path);
+                    Err(())
+                }
+            },
+            (Type::NonNull(_), resolved) => {
+                self.complete_value(ty, field_list, resolved, path).await
+            }
+            (Type::List(item_ty), Resolved::List(mut item_list)) => {
+                let streamed_list = self
+                    .incremental_directive(&field.directive_list, "stream")
+                    .map(|stream| {
+                        let initial_count = match self.argument_value(stream, "initialCount") {
+                            Some(Value::Int(count)) => count.max(0) as usize,
+                            _ => 0,
+                        };
+                        let index = initial_count.min(item_list.len());
+                        (self.label(stream), index, item_list.split_off(index))
+                    });
+                let item_future_list = item_list.into_iter().enumerate().map(|(index, item)| {
+                    let path = child_path(path, PathSegment::Index(index));
+                    async move { self.complete_value(item_ty, field_list, item, &path).await }
+                });
+                let value_list = join_all(item_future_list)
+                    .await
+                    .into_iter()
+                    .collect::<Result<Vec<_>, _>>()?;
+                if let Some((label, index, item_list)) = streamed_list {
+                    if !item_list.is_empty() {
+                        self.push_deferred(Deferred {
+                            label,
+                            path: path.to_vec(),
+                            work: DeferredWork::Stream {
+                                item_ty: (**item_ty).clone(),
+                                field_list: field_list.to_vec(),
+                                index,
+                                item_list: item_list.into_iter(),
+                            },
+                        });
+                    }
+                }
+                Ok(JsonValue::Array(value_list))
+            }
+            (Type::List(_), _) => {
+                self.report(
+                    format!("Expected a list for field \"{}\".", field.name.node),
+                    field,
+                    path,
+                );
+                Err(())
+            }
+            (Type::Named(name), resolved) => {
+                let ty = match self.schema.get_type(name) {
+                    Some(ty) => ty,
+                    None => {
+                        self.report(format!("Unknown type \"{}\".", name), field, path);
+                        return Err(());
+                    }
+                };
+                match (ty.kind, resolved) {
+                    (TypeKind::Scalar, Resolved::Value(value)) => {
+                        match self.schema.scalar_codec(&ty.name) {
+                            Some(codec) => codec
+                                .serialize(&value)
+                                .map_err(|message| self.report(message, field, path)),
+                            None => Ok(value),
+                        }
+                    }
+                    (TypeKind::Enum, Resolved::Value(JsonValue::String(value)))
+                        if ty.enum_value(&value).is_some() =>
+                    {
+                        Ok(JsonValue::String(value))
+                    }
+                    (TypeKind::Enum, Resolved::Value(value)) => {
+                        self.report(
+                            format!("Enum \"{}\" cannot represent value: {}", ty.name, value),
+                            field,
+                            path,
+                        );
+                        Err(())
+                    }
+                    (TypeKind::Object, Resolved::Object(resolver))
+                    | (TypeKind::Interface, Resolved::Object(resolver))
+                    | (TypeKind::Union, Resolved::Object(resolver)) => {
+                        let object_type = self.resolve_object_type(ty, &*resolver, field, path)?;
+                        let selection_list_list: Vec<_> = field_list
+                            .iter()
+                            .map(|field| &field.selection_list)
+                            .collect();
+                        let (grouped_field_list, deferred_fragment_list) =
+                            self.collect_deferred_fields(object_type, &selection_list_list);
+                        let map = self
+                            .execute_fields(
+                                object_type,
+                                &*resolver,
+                                grouped_field_list,
+                                path,
+                                false,
+                            )
+                            .await?;
+                        self.defer_fragments(object_type, &resolver, deferred_fragment_list, path);
+                        Ok(JsonValue::Object(map))
+                    }
+                    (_, resolved) => {
+                        self.report(
+                            format!(
+                                "Cannot complete value {:?} as type \"{}\".",
+                                resolved, ty.name
+                            ),
+                            field,
+                            path,
+                        );
+                        Err(())
+                    }
+                }
+            }
+        }
+    }
+
+    fn resolve_object_type(
+        &self,
+        ty: &'a MetaType,
+        resolver: &dyn Resolver,
+        field: &Field,
+        path: &[PathSegment],
+    ) -> Result<&'a MetaType, ()> {
+        if ty.kind == TypeKind::Object {
+            return Ok(ty);
+        }
+
+        match resolver
+            .type_name()
+            .and_then(|name| self.schema.get_type(name))
+        {
+            Some(object_type)
+                if object_type.kind == TypeKind::Object
+                    && self.schema.is_possible_type(&ty.name, &object_type.name) =>
+            {
+                Ok(object_type)
+            }
+            _ => {
+                self.report(
+                    format!(
+                        "Abstract type \"{}\" must resolve to an Object type at runtime for field \"{}\".",
+                        ty.name, field.name.node
+                    ),
+                    field,
+                    path,
+                );
+                Err(())
+            }
+        }
+    }
+}
+
+pub(crate) fn field_position(field: &Field) -> Position {
+    field.alias.as_ref().unwrap_or(&field.name).start.clone()
+}
+
+pub(crate) fn child_path(path: &[PathSegment], segment: PathSegment) -> Vec<PathSegment> {
+    let mut path = path.to_vec();
+    path.push(segment);
+    path
+}
+
+pub(crate) fn null_for(ty: &Type) -> Result<JsonValue, ()> {
+    if ty.is_non_null() {
+        Err(())
+    } else {
+        Ok(JsonValue::Null)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+    use std::sync::Arc;
+
+    const SCHEMA: &str = r#"
+type Query {
+  hero(episode: Episode): Character
+  greeting(name: String = "World"): String!
+  required: String!
+  broken: String
+}
+
+type Mutation {
+  rename(name: String!): String
+}
+
+enum Episode {
+  NEWHOPE
+  EMPIRE
+  JEDI
+}
+
+interface Character {
+  id: ID!
+  name: String
+  friendList: [Character!]
+}
+
+type Human implements Character {
+  id: ID!
+  name: String
+  friendList: [Character!]
+  height: Float
+}
+
+type Droid implements Character {
+  id: ID!
+  name: String
+  friendList: [Character!]
+  primaryFunction: String
+}
+"#;
+
+    struct Root;
+
+    impl Resolver for Root {
+        fn resolve(&self, info: &ResolveInfo<'_>) -> FieldResult<Resolved> {
+            match info.field_name {
+                "hero" => match info.arguments.get("episode") {
+                    Some(Value::Enum(episode)) if episode == "EMPIRE" => {
+                        Ok(Resolved::object(Character::Human("Luke")))
+                    }
+                    _ => Ok(Resolved::object(Character::Droid("R2-D2"))),
+                },
+                "greeting" => match info.arguments.get("name") {
+                    Some(Value::String(name)) => Ok(Resolved::value(format!("Hello, {}!", name))),
+                    _ => Err("name is not a string".into()),
+                },
+                "required" => Ok(Resolved::Null),
+                "broken" => Err("broken resolver".into()),
+                "rename" => Ok(Resolved::Value(crate::value_to_json(
+                    &info.arguments["name"],
+                ))),
+                _ => Ok(Resolved::Null),
+            }
+        }
+    }
+
+    enum Character {
+        Human(&'static str),
+        Droid(&'static str),
+    }
+
+    impl Resolver for Character {
+        fn type_name(&self) -> Option<&str> {
+            match self {
+                Character::Human(_) => Some("Human"),
+                Character::Droid(_) => Some("Droid"),
+            }
+        }
+
+        fn resolve(&self, info: &ResolveInfo<'_>) -> FieldResult<Resolved> {
+            match (self, info.field_name) {
+                (Character::Human(name), "id") | (Character::Droid(name), "id") => {
+                    Ok(Resolved::value(name.to_lowercase()))
+                }
+                (Character::Human(name), "name") | (Character::Droid(name), "name") => {
+                    Ok(Resolved::value(*name))
+                }
+                (Character::Human(_), "height") => Ok(Resolved::value(1.72)),
+                (Character::Droid(_), "primaryFunction") => Ok(Resolved::value("Astromech")),
+                (Character::Human(_), "friendList") => Ok(Resolved::list(vec![
+                    Resolved::Object(Arc::new(Character::Droid("R2-D2"))),
+                    Resolved::Null,
+                ])),
+                _ => Ok(Resolved::Null),
+            }
+        }
+    }
+
+    fn run(source: &str, operation_name: Option<&str>, variables: JsonValue) -> JsonValue {
+        let schema = Schema::build(&parse_type_system(SCHEMA).unwrap()).unwrap();
+        let document = parse_executable(source).unwrap();
+        let variables = match variables {
+            JsonValue::Object(variables) => variables,
+            _ => Variables::new(),
+        };
+        let response = execute(&schema, &document, operation_name, &variables, &Root);
+        serde_json::to_value(response).unwrap()
+    }
+
+    #[test]
+    fn fragments_and_abstract_types() {
+        let response = run(
+            r#"
+query Hero($episode: Episode) {
+  hero(episode: $episode) {
+    __typename
+    ...Common
+    ... on Human {
+      height
+    }
+    ... on Droid {
+      primaryFunction
+    }
+  }
+  hello: greeting
+}
+
+fragment Common on Character {
+  id
+  name
+}
+"#,
+            None,
+            json!({ "episode": "JEDI" }),
+        );
+
+        assert_eq!(
+            response,
+            json!({
+                "data": {
+                    "hero": {
+                        "__typename": "Droid",
+                        "id": "r2-d2",
+                        "name": "R2-D2",
+                        "primaryFunction": "Astromech"
+                    },
+                    "hello": "Hello, World!"
+                }
+            })
+        );
+    }
+
+    #[test]
+    fn null_propagation() {
+        let response = run(
+            r#"
+{
+  hero(episode: EMPIRE) {
+    friendList {
+      name
+    }
+  }
+  broken
+}
+"#,
+            None,
+            json!({}),
+        );
+
+        assert_eq!(
+            response,
+            json!({
+                "data": {
+                    "hero": {
+                        "friendList": null
+                    },
+                    "broken": null
+                },
+                "errors": [
+                    {
+                        "message": "Cannot return null for non-nullable field \"friendList\".",
+                        "locations": [{ "line": 4, "column": 5 }],
+                        "path": ["hero", "friendList", 1]
+                    },
+                    {
+                        "message": "broken resolver",
+                        "locations": [{ "line": 8, "column": 3 }],
+                        "path": ["broken"]
+                    }
+                ]
+            })
+        );
+
+        let response = run("{ required }", None, json!({}));
+        assert_eq!(response["data"], JsonValue::Null);
+        assert_eq!(response["errors"][0]["path"], json!(["required"]));
+    }
+
+    #[test]
+    fn operation_selection() {
+        let source = r#"
+query A {
+  greeting(name: "A")
+}
+
+mutation B($name: String!) {
+  rename(name: $name)
+}
+"#;
+
+        let response = run(source, None, json!({}));
+        assert_eq!(
+            response["errors"][0]["message"],
+            "Must provide operation name if query contains multiple operations."
+        );
+
+        let response = run(source, Some("B"), json!({}));
+        assert_eq!(
+            response["errors"][0]["message"],
+            "Variable \"$name\" of required type \"String!\" was not provided."
+        );
+
+        let response = run(source, Some("B"), json!({ "name": "Leia" }));
+        assert_eq!(response, json!({ "data": { "rename": "Leia" } }));
+    }
+
+    struct YieldNow(bool);
+
+    impl std::future::Future for YieldNow {
+        type Output = ();
+
+        fn poll(
+            mut self: std::pin::Pin<&mut Self>,
+            cx: &mut std::task::Context<'_>,
+        ) -> std::task::Poll<()> {
+            if self.0 {
+                std::task::Poll::Ready(())
+            } else {
+                self.0 = true;
+                cx.waker().wake_by_ref();
+                std::task::Poll::Pending
+            }
+        }
+    }
+
+    #[test]
+    fn concurrent_query_and_serial_mutation() {
+        let schema = Schema::build(
+            &parse_type_system(
+                r#"
+type Query {
+  a: String
+  b: String
+}
+
+type Mutation {
+  a: String
+  b: String
+}
+"#,
+            )
+            .unwrap(),
+        )
+        .unwrap();
+        let log = Arc::new(Mutex::new(vec![]));
+        let root = {
+            let log = log.clone();
+            move |info: &ResolveInfo<'_>| {
+                let log = log.clone();
+                let name = info.field_name.to_owned();
+                Ok(Resolved::future(async move {
+                    log.lock().unwrap().push(format!("start {}", name));
+                    YieldNow(false).await;
+                    log.lock().unwrap().push(format!("end {}", name));
+                    Ok(Resolved::value(name))
+                }))
+            }
+        };
+
+        let document = parse_executable("{ a b }").unwrap();
+        let response = block_on(execute_async(
+            &schema,
+            &document,
+            None,
+            &Variables::new(),
+            &root,
+        ));
+        assert_eq!(response.data, Some(json!({ "a": "a", "b": "b" })));
+        assert_eq!(
+            log.lock().unwrap().drain(..).collect::<Vec<_>>(),
+            ["start a", "start b", "end a", "end b"]
+        );
+
+        let document = parse_executable("mutation { a b }").unwrap();
+        let response = block_on(execute_async(
+            &schema,
+            &document,
+            None,
+            &Variables::new(),
+            &root,
+        ));
+        assert_eq!(response.data, Some(json!({ "a": "a", "b": "b" })));
+        assert_eq!(
+            log.lock().unwrap().drain(..).collect::<Vec<_>>(),
+            ["start a", "end a", "start b", "end b"]
+        );
+    }
+
+    #[test]
+    fn future_error_extensions() {
+        let schema =
+            Schema::build(&parse_type_system("type Query { list: [String] }").unwrap()).unwrap();
+        let root = |_: &ResolveInfo<'_>| {
+            Ok(Resolved::list(vec![
+                Resolved::value("ok"),
+                Resolved::future(async {
+                    Err(FieldError {
+                        message: "not allowed".to_owned(),
+                        extensions: Some(json!({ "code": "FORBIDDEN" })),
+                    })
+                }),
+            ]))
+        };
+        let document = parse_executable("{ list }").unwrap();
+        let response = execute(&schema, &document, None, &Variables::new(), &root);
+        assert_eq!(
+            serde_json::to_value(response).unwrap(),
+            json!({
+                "data": { "list": ["ok", null] },
+                "errors": [{
+                    "message": "not allowed",
+                    "locations": [{ "line": 1, "column": 3 }],
+                    "path": ["list", 1],
+                    "extensions": { "code": "FORBIDDEN" }
+                }]
+            })
+        );
+    }
+}
@@ -0,0 +1,390 @@
+use crate::*;
+use futures::{
+    future::{self, BoxFuture, FutureExt},
+    stream::{self, BoxStream, FuturesUnordered, StreamExt},
+};
+use graphql_parser::*;
+use serde::Serialize;
+use serde_json::{json, Value as JsonValue};
+use std::collections::BTreeMap;
+use std::sync::Arc;
+
+/// One payload of an incrementally delivered response.
+///
+/// The first payload carries the `data` and `errors` of the operation, leaving out what
+/// `@defer` and `@stream` postponed; every later one carries postponed results in
+/// `incremental`. `has_next` is false on the last payload.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct IncrementalResponse {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub data: Option<JsonValue>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub errors: Vec<ServerError>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub incremental: Vec<IncrementalPayload>,
+    pub has_next: bool,
+}
+
+impl From<Response> for IncrementalResponse {
+    fn from(response: Response) -> Self {
+        IncrementalResponse {
+            data: response.data,
+            errors: response.errors,
+            incremental: vec![],
+            has_next: false,
+        }
+    }
+}
+
+/// The result of a deferred fragment, in `data`, or of streamed list items, in `items`.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct IncrementalPayload {
+    /// The fields of the fragment, to be merged into the object at `path`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub data: Option<JsonValue>,
+    /// The items to be inserted into the list at `path`, starting at its last index.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub items: Option<JsonValue>,
+    pub path: Vec<PathSegment>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub label: Option<String>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub errors: Vec<ServerError>,
+}
+
+/// A fragment marked with `@defer`, as found while collecting fields.
+pub(crate) struct DeferredFragment<'a> {
+    pub(crate) label: Option<String>,
+    pub(crate) selection_list: &'a Vec<Positioned<Selection>>,
+}
+
+/// Work postponed to a later payload.
+pub(crate) struct Deferred<'a> {
+    pub(crate) label: Option<String>,
+    /// The path of the object a fragment belongs to, or of the list items are streamed into.
+    pub(crate) path: Vec<PathSegment>,
+    pub(crate) work: DeferredWork<'a>,
+}
+
+pub(crate) enum DeferredWork<'a> {
+    Fragment {
+        object_type: &'a MetaType,
+        resolver: Arc<dyn Resolver>,
+        selection_list: &'a Vec<Positioned<Selection>>,
+    },
+    /// The items of a list not yet delivered, the first of which is at `index`.
+    Stream {
+        item_ty: Type,
+        field_list: Vec<&'a Field>,
+        index: usize,
+        item_list: std::vec::IntoIter<Resolved>,
+    },
+}
+
+/// Executes an operation, delivering fragments marked with `@defer` and list items
+/// marked with `@stream` in payloads following the initial one.
+///
+/// Postponed work runs concurrently once the payload it belongs to has been produced, and
+/// its payloads come in order of completion; the items of a list always come in order.
+/// An operation without either directive yields a single payload.
+pub fn execute_incremental<'a>(
+    schema: &'a Schema,
+    document: &'a ExecutableDocument,
+    operation_name: Option<&str>,
+    variables: &Variables,
+    root: Arc<dyn Resolver>,
+) -> BoxStream<'a, IncrementalResponse> {
+    let once = |response: Response| stream::once(future::ready(response.into())).boxed();
+    let (operation, root_type, variables) =
+        match prepare_operation(schema, document, operation_name, variables) {
+            Ok(prepared) => prepared,
+            Err(error_list) => return once(Response::from_errors(error_list)),
+        };
+    if operation.ty == OperationType::Subscription {
+        return once(Response::from_errors(vec![ServerError::new(
+            "Subscription operations must be executed with `subscribe`.",
+        )]));
+    }
+
+    let variables = Arc::new(variables);
+    let initial = {
+        let context = ExecutionContext::new(schema, document, (*variables).clone()).deferring();
+        async move {
+            let (grouped_field_list, deferred_fragment_list) =
+                context.collect_deferred_fields(root_type, &[&operation.selection_list]);
+            let is_serial = operation.ty == OperationType::Mutation;
+            let data = match context
+                .execute_fields(root_type, &*root, grouped_field_list, &[], is_serial)
+                .await
+            {
+                Ok(map) => {
+                    context.defer_fragments(root_type, &root, deferred_fragment_list, &[]);
+                    JsonValue::Object(map)
+                }
+                Err(()) => JsonValue::Null,
+            };
+            let (error_list, mut deferred_list) = context.finish();
+            retain_reachable(&mut deferred_list, &data, &[]);
+            (data, error_list, deferred_list)
+        }
+    };
+
+    stream::once(initial)
+        .flat_map(move |(data, error_list, deferred_list)| {
+            let pending: FuturesUnordered<_> = deferred_list
+                .into_iter()
+                .map(|deferred| execute_deferred(schema, document, &variables, deferred))
+                .collect();
+            let initial = IncrementalResponse {
+                data: Some(data),
+                errors: error_list,
+                incremental: vec![],
+                has_next: !pending.is_empty(),
+            };
+            let variables = variables.clone();
+            let subsequent =
+                stream::unfold(pending, move |mut pending| {
+                    let variables = variables.clone();
+                    async move {
+                        let (payload, deferred_list) = pending.next().await?;
+                        pending.extend(deferred_list.into_iter().map(|deferred| {
+                            execute_deferred(schema, document, &variables, deferred)
+                        }));
+                        let response = IncrementalResponse {
+                            data: None,
+                            errors: vec![],
+                            incremental: vec![payload],
+                            has_next: !pending.is_empty(),
+                        };
+                        Some((response, pending))
+                    }
+                });
+            stream::once(future::ready(initial)).chain(subsequent)
+        })
+        .boxed()
+}
+
+/// Runs postponed work, returning its payload and the work it postponed in turn.
+fn execute_deferred<'a>(
+    schema: &'a Schema,
+    document: &'a ExecutableDocument,
+    variables: &BTreeMap<String, Value>,
+    deferred: Deferred<'a>,
+) -> BoxFuture<'a, (IncrementalPayload, Vec<Deferred<'a>>)> {
+    let context = ExecutionContext::new(schema, document, variables.clone()).deferring();
+    async move {
+        let Deferred { label, path, work } = deferred;
+        let (data, items, path, completed) = match work {
+            DeferredWork::Fragment {
+                object_type,
+                resolver,
+                selection_list,
+            } => {
+                let (grouped_field_list, deferred_fragment_list) =
+                    context.collect_deferred_fields(object_type, &[selection_list]);
+                let data = match context
+                    .execute_fields(object_type, &*resolver, grouped_field_list, &path, false)
+                    .await
+                {
+                    Ok(map) => {
+                        context.defer_fragments(
+                            object_type,
+                            &resolver,
+                            deferred_fragment_list,
+                            &path,
+                        );
+                        JsonValue::Object(map)
+                    }
+                    Err(()) => JsonValue::Null,
+                };
+                (Some(data.clone()), None, path, data)
+            }
+            DeferredWork::Stream {
+                item_ty,
+                field_list,
+                index,
+                mut item_list,
+            } => {
+                let item_path = child_path(&path, PathSegment::Index(index));
+                let item = item_list.next().unwrap_or(Resolved::Null);
+                let (items, completed) = match context
+                    .complete_value(&item_ty, &field_list, item, &item_path)
+                    .await
+                {
+                    Ok(value) => (json!([value]), value),
+                    Err(()) => (JsonValue::Null, JsonValue::Null),
+                };
+                if item_list.len() > 0 {
+                    context.push_deferred(Deferred {
+                        label: label.clone(),
+                        path,
+                        work: DeferredWork::Stream {
+                            item_ty,
+                            field_list,
+                            index: index + 1,
+                            item_list,
+                        },
+                    });
+                }
+                (None, Some(items), item_path, completed)
+            }
+        };
+        let (error_list, mut deferred_list) = context.finish();
+        retain_reachable(&mut deferred_list, &completed, &path);
+        let payload = IncrementalPayload {
+            data,
+            items,
+            path,
+            label,
+            errors: error_list,
+        };
+        (payload, deferred_list)
+    }
+    .boxed()
+}
+
+/// Drops the work postponed under a path that null propagation wiped out of `data`, the
+/// value completed at `base`. Work outside `base`, such as the rest of a stream, is kept.
+fn retain_reachable(deferred_list: &mut Vec<Deferred<'_>>, data: &JsonValue, base: &[PathSegment]) {
+    deferred_list.retain(|deferred| {
+        let rest = match deferred.path.strip_prefix(base) {
+            Some(rest) => rest,
+            None => return true,
+        };
+        let mut value = data;
+        for segment in rest {
+            let child = match segment {
+                PathSegment::Field(name) => value.get(name),
+                PathSegment::Index(index) => value.get(index),
+            };
+            value = match child {
+                Some(child) => child,
+                None => return false,
+            };
+        }
+        !value.is_null()
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use futures::executor::block_on;
+
+    const SCHEMA: &str = r#"
+type Query {
+  hero: Hero
+}
+
+type Hero {
+  name: String
+  age: Int
+  friendList: [Hero!]!
+  friend: Hero
+  broken: String!
+}
+"#;
+
+    struct Hero(&'static str);
+
+    impl Resolver for Hero {
+        fn resolve(&self, info: &ResolveInfo<'_>) -> FieldResult<Resolved> {
+            match info.field_name {
+                "name" => Ok(Resolved::value(self.0)),
+                "age" => Ok(Resolved::future(async { Ok(Resolved::value(20)) })),
+                "friendList" => Ok(Resolved::list(vec![
+                    Resolved::object(Hero("Han")),
+                    Resolved::object(Hero("Leia")),
+                ])),
+                "friend" => Ok(Resolved::object(Hero("Han"))),
+                _ => Ok(Resolved::Null),
+            }
+        }
+    }
+
+    fn run(source: &str) -> Vec<JsonValue> {
+        let schema = Schema::build(&parse_type_system(SCHEMA).unwrap()).unwrap();
+        let document = parse_executable(source).unwrap();
+        let root: Arc<dyn Resolver> =
+            Arc::new(|_: &ResolveInfo<'_>| Ok(Resolved::object(Hero("Luke"))));
+        let stream = execute_incremental(&schema, &document, None, &Variables::new(), root);
+        block_on(stream.collect::<Vec<_>>())
+            .into_iter()
+            .map(|response| serde_json::to_value(response).unwrap())
+            .collect()
+    }
+
+    #[test]
+    fn defer_and_stream() {
+        let response_list = run(r#"
+{
+  hero {
+    name
+    ...Age @defer(label: "age")
+    friendList @stream(initialCount: 1) {
+      name
+    }
+  }
+}
+
+fragment Age on Hero {
+  age
+}
+"#);
+        assert_eq!(
+            response_list,
+            vec![
+                json!({
+                    "data": {"hero": {"name": "Luke", "friendList": [{"name": "Han"}]}},
+                    "hasNext": true,
+                }),
+                json!({
+                    "incremental": [{"items": [{"name": "Leia"}], "path": ["hero", "friendList", 1]}],
+                    "hasNext": true,
+                }),
+                json!({
+                    "incremental": [{"data": {"age": 20}, "path": ["hero"], "label": "age"}],
+                    "hasNext": false,
+                }),
+            ]
+        );
+    }
+
+    #[test]
+    fn disabled() {
+        let response_list = run(r#"
+{
+  hero {
+    ... @defer(if: false) { name }
+    friendList @stream(if: false) { name }
+  }
+}
+"#);
+        assert_eq!(
+            response_list,
+            vec![json!({
+                "data": {"hero": {"name": "Luke", "friendList": [{"name": "Han"}, {"name": "Leia"}]}},
+                "hasNext": false,
+            })]
+        );
+    }
+
+    #[test]
+    fn nulled_path() {
+        let response_list = run(r#"
+{
+  hero {
+    friend {
+      ... @defer(label: "name") { name }
+      friendList @stream(initialCount: 0) { name }
+    }
+    broken
+  }
+}
+"#);
+        assert_eq!(response_list.len(), 1);
+        assert_eq!(response_list[0]["data"], json!({"hero": null}));
+        assert_eq!(response_list[0]["hasNext"], json!(false));
+    }
+}
@@ -0,0 +1,756 @@
+use crate::*;
+use graphql_parser::*;
+
+/// The query used by tools to fetch the full description of a schema.
+pub const INTROSPECTION_QUERY: &str = r#"
+query IntrospectionQuery {
+  __schema {
+    description
+    queryType { name }
+    mutationType { name }
+    subscriptionType { name }
+    types {
+      ...FullType
+    }
+    directives {
+      name
+      description
+      isRepeatable
+      locations
+      args(includeDeprecated: true) {
+        ...InputValue
+      }
+    }
+  }
+}
+
+fragment FullType on __Type {
+  kind
+  name
+  description
+  specifiedByURL
+  fields(includeDeprecated: true) {
+    name
+    description
+    args(includeDeprecated: true) {
+      ...InputValue
+    }
+    type {
+      ...TypeRef
+    }
+    isDeprecated
+    deprecationReason
+  }
+  inputFields(includeDeprecated: true) {
+    ...InputValue
+  }
+  interfaces {
+    ...TypeRef
+  }
+  enumValues(includeDeprecated: true) {
+    name
+    description
+    isDeprecated
+    deprecationReason
+  }
+  possibleTypes {
+    ...TypeRef
+  }
+}
+
+fragment InputValue on __InputValue {
+  name
+  description
+  type {
+    ...TypeRef
+  }
+  defaultValue
+  isDeprecated
+  deprecationReason
+}
+
+fragment TypeRef on __Type {
+  kind
+  name
+  ofType {
+    kind
+    name
+    ofType {
+      kind
+      name
+      ofType {
+        kind
+        name
+        ofType {
+          kind
+          name
+          ofType {
+            kind
+            name
+            ofType {
+              kind
+              name
+              ofType {
+                kind
+                name
+              }
+            }
+          }
+        }
+      }
+    }
+  }
+}
+"#;
+
+pub(crate) const INTROSPECTION_TYPE_LIST: &str = r#"
+"""
+A GraphQL Schema defines the capabilities of a GraphQL server.
+"""
+type __Schema {
+  description: String
+  types: [__Type!]!
+  queryType: __Type!
+  mutationType: __Type
+  subscriptionType: __Type
+  directives: [__Directive!]!
+}
+
+"""
+Describes every type of a schema, including the wrapping List and Non-Null types.
+"""
+type __Type {
+  kind: __TypeKind!
+  name: String
+  description: String
+  specifiedByURL: String
+  fields(includeDeprecated: Boolean = false): [__Field!]
+  interfaces: [__Type!]
+  possibleTypes: [__Type!]
+  enumValues(includeDeprecated: Boolean = false): [__EnumValue!]
+  inputFields(includeDeprecated: Boolean = false): [__InputValue!]
+  ofType: __Type
+}
+
+"""
+An enum describing what kind of type a given `__Type` is.
+"""
+enum __TypeKind {
+  SCALAR
+  OBJECT
+  INTERFACE
+  UNION
+  ENUM
+  INPUT_OBJECT
+  LIST
+  NON_NULL
+}
+
+"""
+A field of an Object or Interface type.
+"""
+type __Field {
+  name: String!
+  description: String
+  args(includeDeprecated: Boolean = false): [__InputValue!]!
+  type: __Type!
+  isDeprecated: Boolean!
+  deprecationReason: String
+}
+
+"""
+An argument or an input object field, with an optional default value.
+"""
+type __InputValue {
+  name: String!
+  description: String
+  type: __Type!
+  defaultValue: String
+  isDeprecated: Boolean!
+  deprecationReason: String
+}
+
+"""
+One possible value of an Enum type.
+"""
+type __EnumValue {
+  name: String!
+  description: String
+  isDeprecated: Boolean!
+  deprecationReason: String
+}
+
+"""
+A directive supported by the schema, with the locations it may be used at.
+"""
+type __Directive {
+  name: String!
+  description: String
+  isRepeatable: Boolean!
+  locations: [__DirectiveLocation!]!
+  args(includeDeprecated: Boolean = false): [__InputValue!]!
+}
+
+"""
+A location a directive may be used at.
+"""
+enum __DirectiveLocation {
+  QUERY
+  MUTATION
+  SUBSCRIPTION
+  FIELD
+  FRAGMENT_DEFINITION
+  FRAGMENT_SPREAD
+  INLINE_FRAGMENT
+  VARIABLE_DEFINITION
+  SCHEMA
+  SCALAR
+  OBJECT
+  FIELD_DEFINITION
+  ARGUMENT_DEFINITION
+  INTERFACE
+  UNION
+  ENUM
+  ENUM_VALUE
+  INPUT_OBJECT
+  INPUT_FIELD_DEFINITION
+}
+"#;
+
+/// The meta fields implicitly available on the query root type.
+pub(crate) fn root_field_list() -> Vec<MetaField> {
+    let field = |name: &str, ty: Type, argument_list: Vec<MetaInputValue>| MetaField {
+        name: name.to_owned(),
+        description: None,
+        argument_list,
+        ty,
+        deprecation: None,
+        directive_list: vec![],
+    };
+
+    vec![
+        field(
+            "__schema",
+            Type::NonNull(Box::new(Type::Named("__Schema".to_owned()))),
+            vec![],
+        ),
+        field(
+            "__type",
+            Type::Named("__Type".to_owned()),
+            vec![MetaInputValue {
+                name: "name".to_owned(),
+                description: None,
+                ty: Type::NonNull(Box::new(Type::Named("String".to_owned()))),
+                default_value: None,
+                directive_list: vec![],
+            }],
+        ),
+    ]
+}
+
+/// Resolves `__schema` and `__type` on the query root.
+pub(crate) struct IntrospectionRoot(pub(crate) Schema);
+
+impl Resolver for IntrospectionRoot {
+    fn resolve(&self, info: &ResolveInfo<'_>) -> FieldResult<Resolved> {
+        match info.field_name {
+            "__schema" => Ok(Resolved::object(SchemaResolver(self.0.clone()))),
+            "__type" => match info.arguments.get("name") {
+                Some(Value::String(name)) if self.0.get_type(name).is_some() => {
+                    Ok(named_type(&self.0, name))
+                }
+                _ => Ok(Resolved::Null),
+            },
+            _ => Ok(Resolved::Null),
+        }
+    }
+}
+
+struct SchemaResolver(Schema);
+
+impl Resolver for SchemaResolver {
+    fn resolve(&self, info: &ResolveInfo<'_>) -> FieldResult<Resolved> {
+        let schema = &self.0;
+        Ok(match info.field_name {
+            "description" => optional_string(schema.description()),
+            "types" => Resolved::list(schema.type_list().map(|ty| named_type(schema, &ty.name))),
+            "queryType" => named_type(schema, &schema.query_type().name),
+            "mutationType" => schema
+                .mutation_type()
+                .map(|ty| named_type(schema, &ty.name))
+                .unwrap_or(Resolved::Null),
+            "subscriptionType" => schema
+                .subscription_type()
+                .map(|ty| named_type(schema, &ty.name))
+                .unwrap_or(Resolved::Null),
+            "directives" => Resolved::list(schema.directive_list().map(|directive| {
+                Resolved::object(DirectiveResolver {
+                    schema: schema.clone(),
+                    directive: directive.clone(),
+                })
+            })),
+            _ => Resolved::Null,
+        })
+    }
+}
+
+struct TypeResolver {
+    schema: Schema,
+    ty: Type,
+}
+
+fn named_type(schema: &Schema, name: &str) -> Resolved {
+    type_resolver(schema, &Type::Named(name.to_owned()))
+}
+
+fn type_resolver(schema: &Schema, ty: &Type) -> Resolved {
+    Resolved::object(TypeResolver {
+        schema: schema.clone(),
+        ty: ty.clone(),
+    })
+}
+
+impl Resolver for TypeResolver {
+    fn resolve(&self, info: &ResolveInfo<'_>) -> FieldResult<Resolved> {
+        let meta = match &self.ty {
+            Type::NonNull(of_type) | Type::List(of_type) => {
+                let kind = if self.ty.is_non_null() {
+                    "NON_NULL"
+                } else {
+                    "LIST"
+                };
+                return Ok(match info.field_name {
+                    "kind" => Resolved::value(kind),
+                    "ofType" => type_resolver(&self.schema, of_type),
+                    _ => Resolved::Null,
+                });
+            }
+            Type::Named(name) => match self.schema.get_type(name) {
+                Some(meta) => meta,
+                None => return Ok(Resolved::Null),
+            },
+        };
+        let include_deprecated = matches!(
+            info.arguments.get("includeDeprecated"),
+            Some(Value::Boolean(true))
+        );
+        let has_fields = matches!(meta.kind, TypeKind::Object | TypeKind::Interface);
+
+        Ok(match info.field_name {
+            "kind" => Resolved::value(match meta.kind {
+                TypeKind::Scalar => "SCALAR",
+                TypeKind::Object => "OBJECT",
+                TypeKind::Interface => "INTERFACE",
+                TypeKind::Union => "UNION",
+                TypeKind::Enum => "ENUM",
+                TypeKind::InputObject => "INPUT_OBJECT",
+            }),
+            "name" => Resolved::value(meta.name.clone()),
+            "description" => optional_string(meta.description.as_deref()),
+            "specifiedByURL" => optional_string(
+                directive_argument(&meta.directive_list, "specifiedBy", "url").and_then(|url| {
+                    match url {
+                        Value::String(url) => Some(url.as_str()),
+                        _ => None,
+                    }
+                }),
+            ),
+            "fields" if has_fields => Resolved::list(
+                meta.field_list
+                    .iter()
+                    .filter(|field| include_deprecated || field.deprecation.is_none())
+                    .map(|field| {
+                        Resolved::object(FieldResolver {
+                            schema: self.schema.clone(),
+                            field: field.clone(),
+                        })
+                    }),
+            ),
+            "interfaces" if has_fields => Resolved::list(
+                meta.interface_list
+                    .iter()
+                    .map(|name| named_type(&self.schema, name)),
+            ),
+            "possibleTypes" if meta.is_abstract() => Resolved::list(
+                meta.possible_type_list
+                    .iter()
+                    .map(|name| named_type(&self.schema, name)),
+            ),
+            "enumValues" if meta.kind == TypeKind::Enum => Resolved::list(
+                meta.enum_value_list
+                    .iter()
+                    .filter(|value| include_deprecated || value.deprecation.is_none())
+                    .map(|value| Resolved::object(EnumValueResolver(value.clone()))),
+            ),
+            "inputFields" if meta.kind == TypeKind::InputObject => {
+                input_value_list(&self.schema, &meta.input_field_list, include_deprecated)
+            }
+            _ => Resolved::Null,
+        })
+    }
+}
+
+struct FieldResolver {
+    schema: Schema,
+    field: MetaField,
+}
+
+impl Resolver for FieldResolver {
+    fn resolve(&self, info: &ResolveInfo<'_>) -> FieldResult<Resolved> {
+        let field = &self.field;
+        Ok(match info.field_name {
+            "name" => Resolved::value(field.name.clone()),
+            "description" => optional_string(field.description.as_deref()),
+            "args" => input_value_list(
+                &self.schema,
+                &field.argument_list,
+                matches!(
+                    info.arguments.get("includeDeprecated"),
+                    Some(Value::Boolean(true))
+                ),
+            ),
+            "type" => type_resolver(&self.schema, &field.ty),
+            "isDeprecated" => Resolved::value(field.deprecation.is_some()),
+            "deprecationReason" => optional_string(field.deprecation.as_deref()),
+            _ => Resolved::Null,
+        })
+    }
+}
+
+struct InputValueResolver {
+    schema: Schema,
+    value: MetaInputValue,
+}
+
+fn input_value_list(
+    schema: &Schema,
+    value_list: &[MetaInputValue],
+    include_deprecated: bool,
+) -> Resolved {
+    Resolved::list(
+        value_list
+            .iter()
+            .filter(|value| include_deprecated || input_value_deprecation(value).is_none())
+            .map(|value| {
+                Resolved::object(InputValueResolver {
+                    schema: schema.clone(),
+                    value: value.clone(),
+                })
+            }),
+    )
+}
+
+fn input_value_deprecation(value: &MetaInputValue) -> Option<String> {
+    if !value
+        .directive_list
+        .iter()
+        .any(|directive| directive.name.node == "deprecated")
+    {
+        return None;
+    }
+
+    Some(
+        match directive_argument(&value.directive_list, "deprecated", "reason") {
+            Some(Value::String(reason)) => reason.clone(),
+            _ => DEFAULT_DEPRECATION_REASON.to_owned(),
+        },
+    )
+}
+
+impl Resolver for InputValueResolver {
+    fn resolve(&self, info: &ResolveInfo<'_>) -> FieldResult<Resolved> {
+        let value = &self.value;
+        Ok(match info.field_name {
+            "name" => Resolved::value(value.name.clone()),
+            "description" => optional_string(value.description.as_deref()),
+            "type" => type_resolver(&self.schema, &value.ty),
+            "defaultValue" => value
+                .default_value
+                .as_ref()
+                .map(|default_value| Resolved::value(default_value.to_string()))
+                .unwrap_or(Resolved::Null),
+            "isDeprecated" => Resolved::value(input_value_deprecation(value).is_some()),
+            "deprecationReason" => optional_string(input_value_deprecation(value).as_deref()),
+            _ => Resolved::Null,
+        })
+    }
+}
+
+struct EnumValueResolver(MetaEnumValue);
+
+impl Resolver for EnumValueResolver {
+    fn resolve(&self, info: &ResolveInfo<'_>) -> FieldResult<Resolved> {
+        let value = &self.0;
+        Ok(match info.field_name {
+            "name" => Resolved::value(value.name.clone()),
+            "description" => optional_string(value.description.as_deref()),
+            "isDeprecated" => Resolved::value(value.deprecation.is_some()),
+            "deprecationReason" => optional_string(value.deprecation.as_deref()),
+            _ => Resolved::Null,
+        })
+    }
+}
+
+struct DirectiveResolver {
+    schema: Schema,
+    directive: MetaDirective,
+}
+
+impl Resolver for DirectiveResolver {
+    fn resolve(&self, info: &ResolveInfo<'_>) -> FieldResult<Resolved> {
+        let directive = &self.directive;
+        Ok(match info.field_name {
+            "name" => Resolved::value(directive.name.clone()),
+            "description" => optional_string(directive.description.as_deref()),
+            "isRepeatable" => Resolved::value(directive.is_repeatable),
+            "locations" => Resolved::list(
+                directive
+                    .location_list
+                    .iter()
+                    .map(|location| Resolved::value(location.to_string())),
+            ),
+            "args" => input_value_list(
+                &self.schema,
+                &directive.argument_list,
+                matches!(
+                    info.arguments.get("includeDeprecated"),
+                    Some(Value::Boolean(true))
+                ),
+            ),
+            _ => Resolved::Null,
+        })
+    }
+}
+
+fn optional_string(string: Option<&str>) -> Resolved {
+    string
+        .map(|string| Resolved::value(string.to_owned()))
+        .unwrap_or(Resolved::Null)
+}
+
+fn directive_argument<'a>(
+    directive_list: &'a [Directive],
+    directive: &str,
+    argument: &str,
+) -> Option<&'a Value> {
+    directive_list
+        .iter()
+        .find(|candidate| candidate.name.node == directive)
+        .and_then(|directive| {
+            directive
+                .argument_list
+                .iter()
+                .find(|candidate| candidate.node.name.node == argument)
+        })
+        .map(|argument| &argument.node.value.node)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    const SCHEMA: &str = r#"
+"""
+The Star Wars schema.
+"""
+schema {
+  query: Query
+}
+
+type Query {
+  """
+    The hero of an episode.
+      Defaults to the hero of the whole saga.
+  """
+  hero(episode: Episode = NEW_HOPE): Character
+}
+
+"""
+A character in the Star Wars trilogy.
+"""
+interface Character {
+  name: String!
+  friends: [Character!]
+  nickname: String @deprecated(reason: "Use `name`.")
+  age: Int @deprecated
+}
+
+type Human implements Character {
+  name: String!
+  friends: [Character!]
+  nickname: String @deprecated(reason: "Use `name`.")
+  age: Int @deprecated
+}
+
+enum Episode {
+  NEW_HOPE
+  EMPIRE
+  JEDI
+}
+
+directive @tag(name: String!) repeatable on FIELD_DEFINITION
+"#;
+
+    fn run(source: &str) -> serde_json::Value {
+        let schema = Schema::build(&parse_type_system(SCHEMA).unwrap()).unwrap();
+        let document = parse_executable(source).unwrap();
+        let root = |_: &ResolveInfo<'_>| Ok(Resolved::Null);
+
+        serde_json::to_value(execute(&schema, &document, None, &Variables::new(), &root)).unwrap()
+    }
+
+    #[test]
+    fn type_with_deprecated_fields() {
+        assert_eq!(
+            run(r#"
+{
+  __type(name: "Character") {
+    kind
+    name
+    description
+    fields(includeDeprecated: true) {
+      name
+      type { kind name ofType { kind ofType { kind name } } }
+      isDeprecated
+      deprecationReason
+    }
+    possibleTypes { name }
+  }
+  missing: __type(name: "Droid") { name }
+}
+"#),
+            json!({
+                "data": {
+                    "__type": {
+                        "kind": "INTERFACE",
+                        "name": "Character",
+                        "description": "A character in the Star Wars trilogy.",
+                        "fields": [
+                            {
+                                "name": "name",
+                                "type": { "kind": "NON_NULL", "name": null, "ofType": { "kind": "SCALAR", "ofType": null } },
+                                "isDeprecated": false,
+                                "deprecationReason": null
+                            },
+                            {
+                                "name": "friends",
+                                "type": { "kind": "LIST", "name": null, "ofType": { "kind": "NON_NULL", "ofType": { "kind": "INTERFACE", "name": "Character" } } },
+                                "isDeprecated": false,
+                                "deprecationReason": null
+                            },
+                            {
+                                "name": "nickname",
+                                "type": { "kind": "SCALAR", "name": "String", "ofType": null },
+                                "isDeprecated": true,
+                                "deprecationReason": "Use `name`."
+                            },
+                            {
+                                "name": "age",
+                                "type": { "kind": "SCALAR", "name": "Int", "ofType": null },
+                                "isDeprecated": true,
+                                "deprecationReason": "No longer supported"
+                            }
+                        ],
+                        "possibleTypes": [{ "name": "Human" }]
+                    },
+                    "missing": null
+                }
+            })
+        );
+        assert_eq!(
+            run(r#"{ __type(name: "Human") { fields { name } interfaces { name } } }"#),
+            json!({
+                "data": {
+                    "__type": {
+                        "fields": [{ "name": "name" }, { "name": "friends" }],
+                        "interfaces": [{ "name": "Character" }]
+                    }
+                }
+            })
+        );
+    }
+
+    #[test]
+    fn schema() {
+        let response = run(r#"
+{
+  __typename
+  __schema {
+    description
+    queryType { name }
+    mutationType { name }
+    types { name }
+    directives {
+      name
+      isRepeatable
+      locations
+      args { name defaultValue }
+    }
+  }
+  __type(name: "Query") {
+    fields { args { name type { name } defaultValue } }
+  }
+}
+"#);
+        let schema = &response["data"]["__schema"];
+        assert_eq!(response["data"]["__typename"], "Query");
+        assert_eq!(schema["description"], "The Star Wars schema.");
+        assert_eq!(schema["queryType"], json!({ "name": "Query" }));
+        assert_eq!(schema["mutationType"], json!(null));
+        assert!(schema["types"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .any(|ty| ty["name"] == "__Schema"));
+        let directive = |name: &str| {
+            schema["directives"]
+                .as_array()
+                .unwrap()
+                .iter()
+                .find(|directive| directive["name"] == name)
+                .unwrap()
+                .clone()
+        };
+        assert_eq!(
+            directive("deprecated"),
+            json!({
+                "name": "deprecated",
+                "isRepeatable": false,
+                "locations": ["FIELD_DEFINITION", "ENUM_VALUE"],
+                "args": [{ "name": "reason", "defaultValue": "\"No longer supported\"" }]
+            })
+        );
+        assert_eq!(directive("tag")["isRepeatable"], json!(true));
+        assert_eq!(
+            response["data"]["__type"]["fields"][0]["args"],
+            json!([{ "name": "episode", "type": { "name": "Episode" }, "defaultValue": "NEW_HOPE" }])
+        );
+    }
+
+    #[test]
+    fn block_string_description() {
+        assert_eq!(
+            run(r#"{ __type(name: "Query") { fields { description } } }"#),
+            json!({
+                "data": {
+                    "__type": {
+                        "fields": [{
+                            "description": "The hero of an episode.\n  Defaults to the hero of the whole saga."
+                        }]
+                    }
+                }
+            })
+        );
+    }
+
+    #[test]
+    fn introspection_query() {
+        let response = run(INTROSPECTION_QUERY);
+        assert_eq!(response.get("errors"), None);
+        assert_eq!(
+            response["data"]["__schema"]["queryType"],
+            json!({ "name": "Query" })
+        );
+    }
+}
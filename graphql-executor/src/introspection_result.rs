@@ -0,0 +1,508 @@
+use crate::*;
+use graphql_parser::*;
+use serde_json::Value as JsonValue;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct IntrospectionError {
+    pub path: String,
+    pub message: String,
+}
+
+impl std::fmt::Display for IntrospectionError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "Invalid introspection result at \"{}\": {}",
+            self.path, self.message
+        )
+    }
+}
+
+impl std::error::Error for IntrospectionError {}
+
+const BUILTIN_DIRECTIVE_NAME_LIST: [&str; 6] = [
+    "skip",
+    "include",
+    "deprecated",
+    "specifiedBy",
+    "defer",
+    "stream",
+];
+
+/// Builds the type system document described by an introspection result, such as the
+/// response to `INTROSPECTION_QUERY`.
+///
+/// Both the bare `{"__schema": ...}` object and a full response wrapping it in `data` are
+/// accepted. Built-in scalars, directives and introspection types are left out, and every
+/// node is placed at the default position since there is no source text.
+pub fn parse_introspection(result: &JsonValue) -> Result<TypeSystemDocument, IntrospectionError> {
+    let schema = result
+        .get("data")
+        .unwrap_or(result)
+        .get("__schema")
+        .ok_or_else(|| error("", "Missing \"__schema\"."))?;
+    let mut definition_list = vec![];
+
+    let root_name = |name: &str| -> Result<Option<String>, IntrospectionError> {
+        match schema.get(name) {
+            None | Some(JsonValue::Null) => Ok(None),
+            Some(ty) => string(ty, "name", &format!("__schema.{}", name)).map(Some),
+        }
+    };
+    let root_list = [
+        (OperationType::Query, root_name("queryType")?, "Query"),
+        (
+            OperationType::Mutation,
+            root_name("mutationType")?,
+            "Mutation",
+        ),
+        (
+            OperationType::Subscription,
+            root_name("subscriptionType")?,
+            "Subscription",
+        ),
+    ];
+    if root_list[0].1.is_none() {
+        return Err(error("__schema.queryType", "Missing query type."));
+    }
+    let description = optional_string(schema, "description", "__schema")?;
+    let is_conventional = root_list
+        .iter()
+        .all(|(_, name, default)| name.as_deref().is_none_or(|name| name == *default));
+    if description.is_some() || !is_conventional {
+        definition_list.push(Positioned::new(TypeSystemDefinition::Schema(
+            Positioned::new(SchemaDefinition {
+                is_extend: false,
+                description: description.map(Positioned::new),
+                directive_list: vec![],
+                field_list: root_list
+                    .iter()
+                    .filter_map(|(ty, name, _)| {
+                        name.as_ref().map(|name| OperationField {
+                            ty: ty.clone(),
+                            name: Positioned::new(name.clone()),
+                        })
+                    })
+                    .collect(),
+            }),
+        )));
+    }
+
+    for (index, ty) in list(schema, "types", "__schema")?.iter().enumerate() {
+        let name = string(ty, "name", &format!("__schema.types[{}]", index))?;
+        if name.starts_with("__") || BUILTIN_SCALAR_LIST.contains(&name.as_str()) {
+            continue;
+        }
+        definition_list.push(Positioned::new(TypeSystemDefinition::Type(
+            Positioned::new(type_definition(ty, name)?),
+        )));
+    }
+
+    for (index, directive) in list(schema, "directives", "__schema")?.iter().enumerate() {
+        let name = string(
+            directive,
+            "name",
+            &format!("__schema.directives[{}]", index),
+        )?;
+        if BUILTIN_DIRECTIVE_NAME_LIST.contains(&name.as_str()) {
+            continue;
+        }
+        let path = format!("@{}", name);
+        let location_list = list(directive, "locations", &path)?
+            .iter()
+            .map(|location| {
+                location
+                    .as_str()
+                    .ok_or_else(|| format!("Expected a directive location, found {}.", location))
+                    .and_then(str::parse)
+                    .map(Positioned::new)
+                    .map_err(|message| error(&format!("{}.locations", path), &message))
+            })
+            .collect::<Result<_, _>>()?;
+        definition_list.push(Positioned::new(TypeSystemDefinition::Directive(
+            Positioned::new(DirectiveDefinition {
+                description: optional_string(directive, "description", &path)?.map(Positioned::new),
+                name: Positioned::new(name),
+                argument_list: field_argument_list(directive, "args", &path)?,
+                is_repeatable: directive
+                    .get("isRepeatable")
+                    .and_then(JsonValue::as_bool)
+                    .unwrap_or(false),
+                location_list,
+            }),
+        )));
+    }
+
+    Ok(TypeSystemDocument { definition_list })
+}
+
+fn type_definition(ty: &JsonValue, name: String) -> Result<TypeDefinition, IntrospectionError> {
+    let path = name.clone();
+    let description = optional_string(ty, "description", &path)?.map(Positioned::new);
+    let name = Positioned::new(name);
+    let name_list = |field: &str| -> Result<Vec<Positioned<String>>, IntrospectionError> {
+        list(ty, field, &path)?
+            .iter()
+            .enumerate()
+            .map(|(index, ty)| {
+                string(ty, "name", &format!("{}.{}[{}]", path, field, index)).map(Positioned::new)
+            })
+            .collect()
+    };
+
+    Ok(match string(ty, "kind", &path)?.as_str() {
+        "SCALAR" => TypeDefinition::Scalar(Positioned::new(ScalarType {
+            is_extend: false,
+            description,
+            name,
+            directive_list: match optional_string(ty, "specifiedByURL", &path)? {
+                Some(url) => vec![directive("specifiedBy", "url", url)],
+                None => vec![],
+            },
+        })),
+        "OBJECT" => TypeDefinition::Object(Positioned::new(ObjectType {
+            is_extend: false,
+            description,
+            interface_list: name_list("interfaces")?,
+            name,
+            field_list: field_definition_list(ty, &path)?,
+            directive_list: vec![],
+        })),
+        "INTERFACE" => TypeDefinition::Interface(Positioned::new(InterfaceType {
+            is_extend: false,
+            description,
+            name,
+            field_list: field_definition_list(ty, &path)?,
+            directive_list: vec![],
+        })),
+        "UNION" => TypeDefinition::Union(Positioned::new(UnionType {
+            is_extend: false,
+            description,
+            name,
+            member_list: name_list("possibleTypes")?,
+            directive_list: vec![],
+        })),
+        "ENUM" => TypeDefinition::Enum(Positioned::new(EnumType {
+            is_extend: false,
+            description,
+            name,
+            member_list: list(ty, "enumValues", &path)?
+                .iter()
+                .enumerate()
+                .map(|(index, value)| {
+                    let name = string(value, "name", &format!("{}.enumValues[{}]", path, index))?;
+                    let path = format!("{}.{}", path, name);
+                    Ok(Positioned::new(EnumMember {
+                        description: optional_string(value, "description", &path)?
+                            .map(Positioned::new),
+                        name: Positioned::new(name),
+                        directive_list: deprecation(value, &path)?,
+                    }))
+                })
+                .collect::<Result<_, _>>()?,
+            directive_list: vec![],
+        })),
+        "INPUT_OBJECT" => TypeDefinition::InputObject(Positioned::new(InputObjectType {
+            is_extend: false,
+            description,
+            name,
+            field_list: field_argument_list(ty, "inputFields", &path)?
+                .into_iter()
+                .map(|argument| {
+                    let argument = argument.node;
+                    Positioned::new(FieldDefinition {
+                        description: argument.description,
+                        name: argument.name,
+                        argument_list: vec![],
+                        ty: argument.ty,
+                        default_value: argument.default_value,
+                        directive_list: argument.directive_list,
+                    })
+                })
+                .collect(),
+            directive_list: vec![],
+        })),
+        kind => {
+            return Err(error(
+                &format!("{}.kind", path),
+                &format!("Unknown type kind \"{}\".", kind),
+            ))
+        }
+    })
+}
+
+fn field_definition_list(
+    ty: &JsonValue,
+    path: &str,
+) -> Result<Vec<Positioned<FieldDefinition>>, IntrospectionError> {
+    list(ty, "fields", path)?
+        .iter()
+        .enumerate()
+        .map(|(index, field)| {
+            let name = string(field, "name", &format!("{}.fields[{}]", path, index))?;
+            let path = format!("{}.{}", path, name);
+            Ok(Positioned::new(FieldDefinition {
+                description: optional_string(field, "description", &path)?.map(Positioned::new),
+                name: Positioned::new(name),
+                argument_list: field_argument_list(field, "args", &path)?,
+                ty: Positioned::new(type_ref(field, &path)?),
+                default_value: None,
+                directive_list: deprecation(field, &path)?,
+            }))
+        })
+        .collect()
+}
+
+fn field_argument_list(
+    parent: &JsonValue,
+    field: &str,
+    path: &str,
+) -> Result<Vec<Positioned<FieldArgument>>, IntrospectionError> {
+    list(parent, field, path)?
+        .iter()
+        .enumerate()
+        .map(|(index, argument)| {
+            let name = string(argument, "name", &format!("{}.{}[{}]", path, field, index))?;
+            let path = format!("{}.{}", path, name);
+            let default_value = optional_string(argument, "defaultValue", &path)?
+                .map(|default_value| match parse_value(&default_value) {
+                    Ok(value) => Ok(Positioned::new(value)),
+                    Err(_) => Err(error(
+                        &format!("{}.defaultValue", path),
+                        &format!("Cannot parse default value {}.", default_value),
+                    )),
+                })
+                .transpose()?;
+            Ok(Positioned::new(FieldArgument {
+                description: optional_string(argument, "description", &path)?.map(Positioned::new),
+                name: Positioned::new(name),
+                ty: Positioned::new(type_ref(argument, &path)?),
+                default_value,
+                directive_list: deprecation(argument, &path)?,
+            }))
+        })
+        .collect()
+}
+
+fn type_ref(parent: &JsonValue, path: &str) -> Result<Type, IntrospectionError> {
+    let path = format!("{}.type", path);
+    match parent.get("type") {
+        Some(ty) if ty.is_object() => type_reference(ty, &path),
+        _ => Err(error(&path, "Missing type reference.")),
+    }
+}
+
+fn type_reference(ty: &JsonValue, path: &str) -> Result<Type, IntrospectionError> {
+    let of_type = || match ty.get("ofType") {
+        Some(of_type) if of_type.is_object() => type_reference(of_type, path).map(Box::new),
+        _ => Err(error(
+            path,
+            "Missing \"ofType\"; the introspection query does not nest deep enough.",
+        )),
+    };
+
+    Ok(match string(ty, "kind", path)?.as_str() {
+        "NON_NULL" => Type::NonNull(of_type()?),
+        "LIST" => Type::List(of_type()?),
+        _ => Type::Named(string(ty, "name", path)?),
+    })
+}
+
+fn deprecation(
+    value: &JsonValue,
+    path: &str,
+) -> Result<Vec<Positioned<Directive>>, IntrospectionError> {
+    if value.get("isDeprecated") != Some(&JsonValue::Bool(true)) {
+        return Ok(vec![]);
+    }
+
+    Ok(vec![
+        match optional_string(value, "deprecationReason", path)? {
+            Some(reason) if reason != DEFAULT_DEPRECATION_REASON => {
+                directive("deprecated", "reason", reason)
+            }
+            _ => Positioned::new(Directive {
+                name: Positioned::new("deprecated".to_owned()),
+                argument_list: vec![],
+            }),
+        },
+    ])
+}
+
+fn directive(name: &str, argument: &str, value: String) -> Positioned<Directive> {
+    Positioned::new(Directive {
+        name: Positioned::new(name.to_owned()),
+        argument_list: vec![Positioned::new(Argument {
+            name: Positioned::new(argument.to_owned()),
+            value: Positioned::new(Value::String(value)),
+        })],
+    })
+}
+
+fn error(path: &str, message: &str) -> IntrospectionError {
+    IntrospectionError {
+        path: path.to_owned(),
+        message: message.to_owned(),
+    }
+}
+
+fn string(value: &JsonValue, field: &str, path: &str) -> Result<String, IntrospectionError> {
+    optional_string(value, field, path)?
+        .ok_or_else(|| error(path, &format!("Missing \"{}\".", field)))
+}
+
+fn optional_string(
+    value: &JsonValue,
+    field: &str,
+    path: &str,
+) -> Result<Option<String>, IntrospectionError> {
+    match value.get(field) {
+        None | Some(JsonValue::Null) => Ok(None),
+        Some(JsonValue::String(string)) => Ok(Some(string.clone())),
+        Some(other) => Err(error(
+            &format!("{}.{}", path, field),
+            &format!("Expected a string, found {}.", other),
+        )),
+    }
+}
+
+/// A missing or null list is read as empty, like the fields of a scalar type.
+fn list<'a>(
+    value: &'a JsonValue,
+    field: &str,
+    path: &str,
+) -> Result<&'a [JsonValue], IntrospectionError> {
+    match value.get(field) {
+        None | Some(JsonValue::Null) => Ok(&[]),
+        Some(JsonValue::Array(list)) => Ok(list),
+        Some(other) => Err(error(
+            &format!("{}.{}", path, field),
+            &format!("Expected a list, found {}.", other),
+        )),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    const SCHEMA: &str = r#""""
+Library service.
+"""
+schema {
+  query: Root
+}
+
+directive @cached(ttl: Int = 60) repeatable on FIELD_DEFINITION | OBJECT
+
+"""
+Anything with a title.
+"""
+interface Item {
+  title: String!
+}
+
+type Book implements Item {
+  title: String!
+  isbn: String @deprecated(reason: "Use `identifiers`.")
+  identifiers(kind: [Kind!] = [ISBN], first: Int = 10): [String!]!
+}
+
+enum Kind {
+  ISBN
+  DOI @deprecated
+}
+
+scalar Date @specifiedBy(url: "https://tools.ietf.org/html/rfc3339")
+
+input Filter {
+  """
+  Matches titles containing this text.
+  """
+  title: String = "\"any\""
+  since: Date
+}
+
+type Root {
+  search(filter: Filter = {title: "a"}): [SearchResult!]
+}
+
+union SearchResult = Book
+"#;
+
+    #[test]
+    fn round_trip() {
+        let schema = Schema::build(&parse_type_system(SCHEMA).unwrap()).unwrap();
+        let document = parse_executable(INTROSPECTION_QUERY).unwrap();
+        let root = |_: &ResolveInfo<'_>| Ok(Resolved::Null);
+        let response = execute(&schema, &document, None, &Variables::new(), &root);
+        let result = serde_json::to_value(response).unwrap();
+
+        assert_eq!(
+            print_type_system(&parse_introspection(&result).unwrap()),
+            r#""""
+Library service.
+"""
+schema {
+  query: Root
+}
+
+type Book implements Item {
+  title: String!
+  isbn: String @deprecated(reason: "Use `identifiers`.")
+  identifiers(kind: [Kind!] = [ISBN], first: Int = 10): [String!]!
+}
+
+scalar Date @specifiedBy(url: "https://tools.ietf.org/html/rfc3339")
+
+input Filter {
+  """
+  Matches titles containing this text.
+  """
+  title: String = "\"any\""
+  since: Date
+}
+
+"""
+Anything with a title.
+"""
+interface Item {
+  title: String!
+}
+
+enum Kind {
+  ISBN
+  DOI @deprecated
+}
+
+type Root {
+  search(filter: Filter = {title: "a"}): [SearchResult!]
+}
+
+union SearchResult = Book
+
+directive @cached(ttl: Int = 60) repeatable on FIELD_DEFINITION | OBJECT
+"#
+        );
+    }
+
+    #[test]
+    fn invalid_result() {
+        assert_eq!(
+            parse_introspection(&json!({ "data": null })),
+            Err(error("", "Missing \"__schema\"."))
+        );
+        let result = json!({
+            "__schema": {
+                "queryType": { "name": "Query" },
+                "types": [{ "kind": "OBJECT", "name": "Query", "fields": [{ "name": "id" }] }],
+                "directives": []
+            }
+        });
+        assert_eq!(
+            parse_introspection(&result).unwrap_err().to_string(),
+            "Invalid introspection result at \"Query.id.type\": Missing type reference."
+        );
+    }
+}
@@ -0,0 +1,32 @@
+#![warn(clippy::all)]
+
+mod coerce;
+mod complexity;
+mod execute;
+mod incremental;
+mod introspection;
+mod introspection_result;
+mod pubsub;
+mod resolver;
+mod response;
+mod scalar;
+mod schema;
+mod subscription;
+mod validate;
+mod value;
+
+pub use coerce::*;
+pub use complexity::*;
+pub use execute::*;
+pub use incremental::*;
+pub use introspection::INTROSPECTION_QUERY;
+use introspection::{root_field_list, IntrospectionRoot, INTROSPECTION_TYPE_LIST};
+pub use introspection_result::*;
+pub use pubsub::*;
+pub use resolver::*;
+pub use response::*;
+pub use scalar::*;
+pub use schema::*;
+pub use subscription::*;
+pub use validate::*;
+pub use value::*;
@@ -0,0 +1,44 @@
+use futures::{
+    channel::mpsc::{unbounded, UnboundedSender},
+    stream::{BoxStream, StreamExt},
+};
+use std::sync::Mutex;
+
+/// An in-process broadcast channel for feeding subscription resolvers.
+///
+/// Every subscriber receives each message published after it subscribed.
+/// Subscribers whose stream has been dropped are removed on the next publish.
+pub struct PubSub<T> {
+    sender_list: Mutex<Vec<UnboundedSender<T>>>,
+}
+
+impl<T: Clone + Send + 'static> PubSub<T> {
+    pub fn new() -> Self {
+        PubSub {
+            sender_list: Mutex::new(vec![]),
+        }
+    }
+
+    pub fn subscribe(&self) -> BoxStream<'static, T> {
+        let (sender, receiver) = unbounded();
+        self.sender_list.lock().unwrap().push(sender);
+        receiver.boxed()
+    }
+
+    pub fn publish(&self, message: T) {
+        self.sender_list
+            .lock()
+            .unwrap()
+            .retain(|sender| sender.unbounded_send(message.clone()).is_ok());
+    }
+
+    pub fn subscriber_count(&self) -> usize {
+        self.sender_list.lock().unwrap().len()
+    }
+}
+
+impl<T: Clone + Send + 'static> Default for PubSub<T> {
+    fn default() -> Self {
+        PubSub::new()
+    }
+}
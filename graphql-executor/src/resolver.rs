@@ -0,0 +1,109 @@
+use crate::Schema;
+use futures::{
+    future::{BoxFuture, FutureExt},
+    stream::{BoxStream, Stream, StreamExt},
+};
+use graphql_parser::Value;
+use std::collections::BTreeMap;
+use std::future::Future;
+use std::sync::Arc;
+
+pub type Arguments = BTreeMap<String, Value>;
+
+pub type FieldResult<T> = Result<T, FieldError>;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct FieldError {
+    pub message: String,
+    pub extensions: Option<serde_json::Value>,
+}
+
+impl<T: std::fmt::Display> From<T> for FieldError {
+    fn from(error: T) -> Self {
+        FieldError {
+            message: error.to_string(),
+            extensions: None,
+        }
+    }
+}
+
+pub enum Resolved {
+    Null,
+    Value(serde_json::Value),
+    Object(Arc<dyn Resolver>),
+    List(Vec<Resolved>),
+    Future(BoxFuture<'static, FieldResult<Resolved>>),
+    Stream(BoxStream<'static, FieldResult<Resolved>>),
+}
+
+impl Resolved {
+    pub fn value<T: Into<serde_json::Value>>(value: T) -> Self {
+        Resolved::Value(value.into())
+    }
+
+    pub fn object<R: Resolver + 'static>(resolver: R) -> Self {
+        Resolved::Object(Arc::new(resolver))
+    }
+
+    pub fn list<I: IntoIterator<Item = Resolved>>(iter: I) -> Self {
+        Resolved::List(iter.into_iter().collect())
+    }
+
+    pub fn future<F>(future: F) -> Self
+    where
+        F: Future<Output = FieldResult<Resolved>> + Send + 'static,
+    {
+        Resolved::Future(future.boxed())
+    }
+
+    pub fn stream<S>(stream: S) -> Self
+    where
+        S: Stream<Item = FieldResult<Resolved>> + Send + 'static,
+    {
+        Resolved::Stream(stream.boxed())
+    }
+}
+
+impl std::fmt::Debug for Resolved {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Resolved::Null => write!(f, "Null"),
+            Resolved::Value(value) => f.debug_tuple("Value").field(value).finish(),
+            Resolved::Object(resolver) => f
+                .debug_tuple("Object")
+                .field(&resolver.type_name())
+                .finish(),
+            Resolved::List(list) => f.debug_tuple("List").field(list).finish(),
+            Resolved::Future(_) => write!(f, "Future"),
+            Resolved::Stream(_) => write!(f, "Stream"),
+        }
+    }
+}
+
+pub struct ResolveInfo<'a> {
+    pub schema: &'a Schema,
+    pub parent_type: &'a str,
+    pub field_name: &'a str,
+    pub arguments: &'a Arguments,
+}
+
+/// Resolves the fields of one object value.
+///
+/// `type_name` must return the concrete object type whenever the value is
+/// returned from a field of interface or union type.
+pub trait Resolver: Send + Sync {
+    fn type_name(&self) -> Option<&str> {
+        None
+    }
+
+    fn resolve(&self, info: &ResolveInfo<'_>) -> FieldResult<Resolved>;
+}
+
+impl<F> Resolver for F
+where
+    F: Fn(&ResolveInfo<'_>) -> FieldResult<Resolved> + Send + Sync,
+{
+    fn resolve(&self, info: &ResolveInfo<'_>) -> FieldResult<Resolved> {
+        self(info)
+    }
+}
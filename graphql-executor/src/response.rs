@@ -0,0 +1,70 @@
+use graphql_parser::Position;
+use serde::Serialize;
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Response {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub data: Option<serde_json::Value>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub errors: Vec<ServerError>,
+}
+
+impl Response {
+    pub fn from_errors(errors: Vec<ServerError>) -> Self {
+        Response { data: None, errors }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ServerError {
+    pub message: String,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub locations: Vec<Location>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub path: Vec<PathSegment>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub extensions: Option<serde_json::Value>,
+}
+
+impl ServerError {
+    pub fn new<T: Into<String>>(message: T) -> Self {
+        ServerError {
+            message: message.into(),
+            locations: vec![],
+            path: vec![],
+            extensions: None,
+        }
+    }
+
+    pub fn at(mut self, position: &Position) -> Self {
+        self.locations.push(position.into());
+        self
+    }
+
+    pub fn with_path(mut self, path: &[PathSegment]) -> Self {
+        self.path = path.to_vec();
+        self
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub struct Location {
+    pub line: usize,
+    pub column: usize,
+}
+
+impl From<&Position> for Location {
+    fn from(position: &Position) -> Self {
+        Location {
+            line: position.line,
+            column: position.column,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(untagged)]
+pub enum PathSegment {
+    Field(String),
+    Index(usize),
+}
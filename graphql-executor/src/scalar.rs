@@ -0,0 +1,352 @@
+use graphql_parser::Value;
+use serde_json::Value as JsonValue;
+
+/// Parse and serialize hooks for a scalar type, registered by name on a `Schema`.
+///
+/// Scalars declared in the schema without a registered codec accept and return any value.
+pub trait ScalarCodec: Send + Sync {
+    /// Parses a literal written in the executable document.
+    fn parse_literal(&self, value: &Value) -> Result<Value, String>;
+
+    /// Parses a value provided through the JSON variables of a request.
+    fn parse_value(&self, value: &JsonValue) -> Result<Value, String>;
+
+    /// Serializes a value returned by a resolver into the response.
+    fn serialize(&self, value: &JsonValue) -> Result<JsonValue, String>;
+}
+
+impl std::fmt::Debug for dyn ScalarCodec {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "ScalarCodec")
+    }
+}
+
+fn int_in_range(int: i64) -> bool {
+    int >= i32::MIN as i64 && int <= i32::MAX as i64
+}
+
+pub struct IntCodec;
+
+impl ScalarCodec for IntCodec {
+    fn parse_literal(&self, value: &Value) -> Result<Value, String> {
+        match value {
+            Value::Int(int) if int_in_range(*int) => Ok(Value::Int(*int)),
+            Value::Int(int) => Err(format!(
+                "Int cannot represent non 32-bit signed integer value: {}",
+                int
+            )),
+            value => Err(format!(
+                "Int cannot represent non-integer value: {}",
+                crate::value_to_json(value)
+            )),
+        }
+    }
+
+    fn parse_value(&self, value: &JsonValue) -> Result<Value, String> {
+        match value.as_i64() {
+            Some(int) if int_in_range(int) => Ok(Value::Int(int)),
+            Some(_) => Err(format!(
+                "Int cannot represent non 32-bit signed integer value: {}",
+                value
+            )),
+            None => Err(format!("Int cannot represent non-integer value: {}", value)),
+        }
+    }
+
+    fn serialize(&self, value: &JsonValue) -> Result<JsonValue, String> {
+        let int = match value {
+            JsonValue::Number(number) => number.as_i64().or_else(|| {
+                number
+                    .as_f64()
+                    .filter(|float| float.fract() == 0.0 && float.abs() <= i64::MAX as f64)
+                    .map(|float| float as i64)
+            }),
+            _ => None,
+        };
+        match int {
+            Some(int) if int_in_range(int) => Ok(JsonValue::from(int)),
+            Some(_) => Err(format!(
+                "Int cannot represent non 32-bit signed integer value: {}",
+                value
+            )),
+            None => Err(format!("Int cannot represent non-integer value: {}", value)),
+        }
+    }
+}
+
+pub struct FloatCodec;
+
+impl ScalarCodec for FloatCodec {
+    fn parse_literal(&self, value: &Value) -> Result<Value, String> {
+        match value {
+            Value::Int(int) => Ok(Value::Float(*int as f64)),
+            Value::Float(float) => Ok(Value::Float(*float)),
+            value => Err(format!(
+                "Float cannot represent non numeric value: {}",
+                crate::value_to_json(value)
+            )),
+        }
+    }
+
+    fn parse_value(&self, value: &JsonValue) -> Result<Value, String> {
+        match value.as_f64() {
+            Some(float) if float.is_finite() => Ok(Value::Float(float)),
+            _ => Err(format!(
+                "Float cannot represent non numeric value: {}",
+                value
+            )),
+        }
+    }
+
+    fn serialize(&self, value: &JsonValue) -> Result<JsonValue, String> {
+        match value.as_f64() {
+            Some(float) if float.is_finite() => Ok(value.clone()),
+            _ => Err(format!(
+                "Float cannot represent non numeric value: {}",
+                value
+            )),
+        }
+    }
+}
+
+pub struct StringCodec;
+
+impl ScalarCodec for StringCodec {
+    fn parse_literal(&self, value: &Value) -> Result<Value, String> {
+        match value {
+            Value::String(string) => Ok(Value::String(string.clone())),
+            value => Err(format!(
+                "String cannot represent a non string value: {}",
+                crate::value_to_json(value)
+            )),
+        }
+    }
+
+    fn parse_value(&self, value: &JsonValue) -> Result<Value, String> {
+        match value {
+            JsonValue::String(string) => Ok(Value::String(string.clone())),
+            value => Err(format!(
+                "String cannot represent a non string value: {}",
+                value
+            )),
+        }
+    }
+
+    fn serialize(&self, value: &JsonValue) -> Result<JsonValue, String> {
+        match value {
+            JsonValue::String(_) => Ok(value.clone()),
+            JsonValue::Bool(_) | JsonValue::Number(_) => Ok(JsonValue::String(value.to_string())),
+            value => Err(format!("String cannot represent value: {}", value)),
+        }
+    }
+}
+
+pub struct BooleanCodec;
+
+impl ScalarCodec for BooleanCodec {
+    fn parse_literal(&self, value: &Value) -> Result<Value, String> {
+        match value {
+            Value::Boolean(boolean) => Ok(Value::Boolean(*boolean)),
+            value => Err(format!(
+                "Boolean cannot represent a non boolean value: {}",
+                crate::value_to_json(value)
+            )),
+        }
+    }
+
+    fn parse_value(&self, value: &JsonValue) -> Result<Value, String> {
+        match value {
+            JsonValue::Bool(boolean) => Ok(Value::Boolean(*boolean)),
+            value => Err(format!(
+                "Boolean cannot represent a non boolean value: {}",
+                value
+            )),
+        }
+    }
+
+    fn serialize(&self, value: &JsonValue) -> Result<JsonValue, String> {
+        match value {
+            JsonValue::Bool(_) => Ok(value.clone()),
+            value => Err(format!(
+                "Boolean cannot represent a non boolean value: {}",
+                value
+            )),
+        }
+    }
+}
+
+pub struct IdCodec;
+
+impl ScalarCodec for IdCodec {
+    fn parse_literal(&self, value: &Value) -> Result<Value, String> {
+        match value {
+            Value::String(string) => Ok(Value::String(string.clone())),
+            Value::Int(int) => Ok(Value::String(int.to_string())),
+            value => Err(format!(
+                "ID cannot represent a non-string and non-integer value: {}",
+                crate::value_to_json(value)
+            )),
+        }
+    }
+
+    fn parse_value(&self, value: &JsonValue) -> Result<Value, String> {
+        match value {
+            JsonValue::String(string) => Ok(Value::String(string.clone())),
+            JsonValue::Number(number) if number.is_i64() || number.is_u64() => {
+                Ok(Value::String(number.to_string()))
+            }
+            value => Err(format!(
+                "ID cannot represent a non-string and non-integer value: {}",
+                value
+            )),
+        }
+    }
+
+    fn serialize(&self, value: &JsonValue) -> Result<JsonValue, String> {
+        match value {
+            JsonValue::String(_) => Ok(value.clone()),
+            JsonValue::Number(number) if number.is_i64() || number.is_u64() => {
+                Ok(JsonValue::String(number.to_string()))
+            }
+            value => Err(format!("ID cannot represent value: {}", value)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::*;
+    use graphql_parser::{parse_executable, parse_type_system};
+    use serde_json::json;
+
+    struct DateCodec;
+
+    impl DateCodec {
+        fn parse(&self, date: &str) -> Result<Value, String> {
+            let is_date = date.len() == 10
+                && date.char_indices().all(|(index, c)| match index {
+                    4 | 7 => c == '-',
+                    _ => c.is_ascii_digit(),
+                });
+            if is_date {
+                Ok(Value::String(date.to_owned()))
+            } else {
+                Err(format!(
+                    "Expected a date in YYYY-MM-DD format, found \"{}\".",
+                    date
+                ))
+            }
+        }
+    }
+
+    impl ScalarCodec for DateCodec {
+        fn parse_literal(&self, value: &Value) -> Result<Value, String> {
+            match value {
+                Value::String(date) => self.parse(date),
+                _ => Err("Expected a date string.".to_owned()),
+            }
+        }
+
+        fn parse_value(&self, value: &JsonValue) -> Result<Value, String> {
+            match value {
+                JsonValue::String(date) => self.parse(date),
+                _ => Err("Expected a date string.".to_owned()),
+            }
+        }
+
+        fn serialize(&self, value: &JsonValue) -> Result<JsonValue, String> {
+            match value {
+                JsonValue::String(date) => self.parse(date).map(|_| value.clone()),
+                _ => Err("Expected a date string.".to_owned()),
+            }
+        }
+    }
+
+    fn run(source: &str, variables: JsonValue) -> JsonValue {
+        let mut schema = Schema::build(
+            &parse_type_system(
+                r#"
+scalar Date
+
+type Query {
+  echo(date: Date!): Date
+  since(date: Date = "02/29/2020"): Date
+  invalid: Date
+  big: Int
+  rounded: Int
+}
+"#,
+            )
+            .unwrap(),
+        )
+        .unwrap();
+        schema.register_scalar("Date", DateCodec);
+        let root = |info: &ResolveInfo<'_>| match info.field_name {
+            "echo" | "since" => Ok(Resolved::Value(value_to_json(&info.arguments["date"]))),
+            "invalid" => Ok(Resolved::value("yesterday")),
+            "big" => Ok(Resolved::value(1u64 << 40)),
+            "rounded" => Ok(Resolved::value(7.0)),
+            _ => Ok(Resolved::Null),
+        };
+        let document = parse_executable(source).unwrap();
+        let variables = match variables {
+            JsonValue::Object(variables) => variables,
+            _ => Variables::new(),
+        };
+
+        serde_json::to_value(execute(&schema, &document, None, &variables, &root)).unwrap()
+    }
+
+    #[test]
+    fn custom_scalar() {
+        assert_eq!(
+            run(r#"{ echo(date: "2020-02-29") }"#, json!({})),
+            json!({ "data": { "echo": "2020-02-29" } })
+        );
+        assert_eq!(
+            run(r#"{ echo(date: "tomorrow") }"#, json!({}))["errors"][0]["message"],
+            "Argument \"date\" got invalid value \"tomorrow\"; Expected a date in YYYY-MM-DD format, found \"tomorrow\"."
+        );
+        assert_eq!(
+            run(
+                "query ($date: Date!) { echo(date: $date) }",
+                json!({ "date": "02/29/2020" })
+            )["errors"][0]["message"],
+            "Variable \"$date\" got invalid value \"02/29/2020\"; Expected a date in YYYY-MM-DD format, found \"02/29/2020\"."
+        );
+        // Defaults go through the codec like supplied values.
+        assert_eq!(
+            run(r#"{ since }"#, json!({}))["errors"][0]["message"],
+            "Argument \"date\" got invalid value \"02/29/2020\"; Expected a date in YYYY-MM-DD format, found \"02/29/2020\"."
+        );
+        assert_eq!(
+            run(
+                r#"query ($date: Date = "2020-02-29") { since(date: $date) }"#,
+                json!({})
+            ),
+            json!({ "data": { "since": "2020-02-29" } })
+        );
+        assert_eq!(
+            run("{ invalid }", json!({})),
+            json!({
+                "data": { "invalid": null },
+                "errors": [{
+                    "message": "Expected a date in YYYY-MM-DD format, found \"yesterday\".",
+                    "locations": [{ "line": 1, "column": 3 }],
+                    "path": ["invalid"]
+                }]
+            })
+        );
+    }
+
+    #[test]
+    fn int_range() {
+        let response = run("{ big rounded }", json!({}));
+        assert_eq!(response["data"], json!({ "big": null, "rounded": 7 }));
+        assert_eq!(
+            response["errors"][0]["message"],
+            "Int cannot represent non 32-bit signed integer value: 1099511627776"
+        );
+    }
+}
@@ -0,0 +1,643 @@
+use crate::*;
+use graphql_parser::*;
+use std::collections::BTreeMap;
+use std::sync::Arc;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum TypeKind {
+    Scalar,
+    Object,
+    Interface,
+    Union,
+    Enum,
+    InputObject,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct MetaType {
+    pub kind: TypeKind,
+    pub name: String,
+    pub description: Option<String>,
+    pub field_list: Vec<MetaField>,
+    pub interface_list: Vec<String>,
+    pub possible_type_list: Vec<String>,
+    pub enum_value_list: Vec<MetaEnumValue>,
+    pub input_field_list: Vec<MetaInputValue>,
+    pub directive_list: Vec<Directive>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct MetaField {
+    pub name: String,
+    pub description: Option<String>,
+    pub argument_list: Vec<MetaInputValue>,
+    pub ty: Type,
+    pub deprecation: Option<String>,
+    pub directive_list: Vec<Directive>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct MetaInputValue {
+    pub name: String,
+    pub description: Option<String>,
+    pub ty: Type,
+    pub default_value: Option<Value>,
+    pub directive_list: Vec<Directive>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct MetaEnumValue {
+    pub name: String,
+    pub description: Option<String>,
+    pub deprecation: Option<String>,
+    pub directive_list: Vec<Directive>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct MetaDirective {
+    pub name: String,
+    pub description: Option<String>,
+    pub argument_list: Vec<MetaInputValue>,
+    pub is_repeatable: bool,
+    pub location_list: Vec<DirectiveLocation>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum SchemaError {
+    DuplicateType(String),
+    DuplicateDirective(String),
+    ExtendUndefinedType(String),
+    ExtendKindMismatch(String),
+    UnknownType(String),
+    MissingQueryType,
+}
+
+impl std::fmt::Display for SchemaError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SchemaError::DuplicateType(name) => {
+                write!(f, "Type \"{}\" is defined more than once.", name)
+            }
+            SchemaError::DuplicateDirective(name) => {
+                write!(f, "Directive \"@{}\" is defined more than once.", name)
+            }
+            SchemaError::ExtendUndefinedType(name) => {
+                write!(
+                    f,
+                    "Cannot extend type \"{}\" because it is not defined.",
+                    name
+                )
+            }
+            SchemaError::ExtendKindMismatch(name) => {
+                write!(f, "Cannot extend type \"{}\" with a different kind.", name)
+            }
+            SchemaError::UnknownType(name) => write!(f, "Unknown type \"{}\".", name),
+            SchemaError::MissingQueryType => write!(f, "Schema does not define a query type."),
+        }
+    }
+}
+
+impl std::error::Error for SchemaError {}
+
+#[derive(Debug, Clone)]
+pub struct Schema {
+    inner: Arc<SchemaInner>,
+}
+
+#[derive(Debug, Clone)]
+struct SchemaInner {
+    description: Option<String>,
+    query_type: String,
+    mutation_type: Option<String>,
+    subscription_type: Option<String>,
+    type_map: BTreeMap<String, MetaType>,
+    directive_map: BTreeMap<String, MetaDirective>,
+    scalar_codec_map: BTreeMap<String, Arc<dyn ScalarCodec>>,
+    introspection_field_list: Vec<MetaField>,
+}
+
+pub(crate) const BUILTIN_SCALAR_LIST: [&str; 5] = ["Int", "Float", "String", "Boolean", "ID"];
+
+const BUILTIN_DIRECTIVE_LIST: &str = r#"
+"""
+Directs the executor to skip this field or fragment when the `if` argument is true.
+"""
+directive @skip(if: Boolean!) on FIELD | FRAGMENT_SPREAD | INLINE_FRAGMENT
+
+"""
+Directs the executor to include this field or fragment only when the `if` argument is true.
+"""
+directive @include(if: Boolean!) on FIELD | FRAGMENT_SPREAD | INLINE_FRAGMENT
+
+"""
+Marks an element of a GraphQL schema as no longer supported.
+"""
+directive @deprecated(reason: String = "No longer supported") on FIELD_DEFINITION | ENUM_VALUE
+
+"""
+Exposes a URL that specifies the behaviour of this scalar.
+"""
+directive @specifiedBy(url: String!) on SCALAR
+
+"""
+Directs the executor to deliver this fragment in a later payload when the `if` argument is
+true.
+"""
+directive @defer(if: Boolean! = true, label: String) on FRAGMENT_SPREAD | INLINE_FRAGMENT
+
+"""
+Directs the executor to deliver the items of this list after the first `initialCount` in
+later payloads when the `if` argument is true.
+"""
+directive @stream(if: Boolean! = true, label: String, initialCount: Int = 0) on FIELD
+"#;
+
+pub(crate) const DEFAULT_DEPRECATION_REASON: &str = "No longer supported";
+
+impl Schema {
+    pub fn build(document: &TypeSystemDocument) -> Result<Schema, SchemaError> {
+        let mut type_map = BTreeMap::new();
+        let mut directive_map = BTreeMap::new();
+        let mut operation_list = vec![];
+        let mut schema_description = None;
+
+        for name in BUILTIN_SCALAR_LIST.iter() {
+            type_map.insert(name.to_string(), MetaType::new(TypeKind::Scalar, name));
+        }
+        let builtin = parse_type_system(BUILTIN_DIRECTIVE_LIST).expect("built-in directives");
+        for definition in builtin.definition_list.iter() {
+            if let TypeSystemDefinition::Directive(directive) = &definition.node {
+                let directive = meta_directive(&directive.node);
+                directive_map.insert(directive.name.clone(), directive);
+            }
+        }
+
+        let introspection =
+            parse_type_system(INTROSPECTION_TYPE_LIST).expect("introspection types");
+        for definition in introspection.definition_list.iter() {
+            if let TypeSystemDefinition::Type(definition) = &definition.node {
+                let (_, ty) = meta_type(&definition.node);
+                type_map.insert(ty.name.clone(), ty);
+            }
+        }
+
+        let (extension_list, definition_list): (Vec<_>, Vec<_>) = document
+            .definition_list
+            .iter()
+            .partition(|definition| is_extension(&definition.node));
+
+        for definition in definition_list.into_iter().chain(extension_list) {
+            match &definition.node {
+                TypeSystemDefinition::Schema(schema) => {
+                    if let Some(description) = description(&schema.node.description) {
+                        schema_description = Some(description);
+                    }
+                    for field in schema.node.field_list.iter() {
+                        operation_list.push((field.ty.clone(), field.name.node.clone()));
+                    }
+                }
+                TypeSystemDefinition::Type(definition) => {
+                    let (is_extend, ty) = meta_type(&definition.node);
+                    if is_extend {
+                        let current = type_map
+                            .get_mut(&ty.name)
+                            .ok_or_else(|| SchemaError::ExtendUndefinedType(ty.name.clone()))?;
+                        if current.kind != ty.kind {
+                            return Err(SchemaError::ExtendKindMismatch(ty.name));
+                        }
+                        current.extend(ty);
+                    } else if BUILTIN_SCALAR_LIST.contains(&ty.name.as_str())
+                        && ty.kind == TypeKind::Scalar
+                    {
+                        type_map.insert(ty.name.clone(), ty);
+                    } else if type_map.contains_key(&ty.name) {
+                        return Err(SchemaError::DuplicateType(ty.name));
+                    } else {
+                        type_map.insert(ty.name.clone(), ty);
+                    }
+                }
+                TypeSystemDefinition::Directive(directive) => {
+                    let directive = meta_directive(&directive.node);
+                    let is_builtin = builtin.definition_list.iter().any(|definition| {
+                        matches!(
+                            &definition.node,
+                            TypeSystemDefinition::Directive(builtin)
+                                if builtin.node.name.node == directive.name
+                        )
+                    });
+                    if directive_map.contains_key(&directive.name) && !is_builtin {
+                        return Err(SchemaError::DuplicateDirective(directive.name));
+                    }
+                    directive_map.insert(directive.name.clone(), directive);
+                }
+            }
+        }
+
+        let implementation_list: Vec<(String, String)> = type_map
+            .values()
+            .flat_map(|ty| {
+                ty.interface_list
+                    .iter()
+                    .map(move |interface| (interface.clone(), ty.name.clone()))
+            })
+            .collect();
+        for (interface, object) in implementation_list {
+            match type_map.get_mut(&interface) {
+                Some(ty) if ty.kind == TypeKind::Interface => ty.possible_type_list.push(object),
+                _ => return Err(SchemaError::UnknownType(interface)),
+            }
+        }
+
+        let root_type = |ty: OperationType, default: &str| {
+            operation_list
+                .iter()
+                .rev()
+                .find(|(operation, _)| *operation == ty)
+                .map(|(_, name)| name.clone())
+                .or_else(|| {
+                    if type_map.contains_key(default) {
+                        Some(default.to_owned())
+                    } else {
+                        None
+                    }
+                })
+        };
+        let mut schema = Schema {
+            inner: Arc::new(SchemaInner {
+                description: schema_description,
+                query_type: root_type(OperationType::Query, "Query")
+                    .ok_or(SchemaError::MissingQueryType)?,
+                mutation_type: root_type(OperationType::Mutation, "Mutation"),
+                subscription_type: root_type(OperationType::Subscription, "Subscription"),
+                type_map,
+                directive_map,
+                scalar_codec_map: BTreeMap::new(),
+                introspection_field_list: root_field_list(),
+            }),
+        };
+        schema.check_type_references()?;
+        schema.register_scalar("Int", IntCodec);
+        schema.register_scalar("Float", FloatCodec);
+        schema.register_scalar("String", StringCodec);
+        schema.register_scalar("Boolean", BooleanCodec);
+        schema.register_scalar("ID", IdCodec);
+
+        Ok(schema)
+    }
+
+    pub fn description(&self) -> Option<&str> {
+        self.inner.description.as_deref()
+    }
+
+    pub fn query_type(&self) -> &MetaType {
+        &self.inner.type_map[&self.inner.query_type]
+    }
+
+    pub fn mutation_type(&self) -> Option<&MetaType> {
+        self.inner
+            .mutation_type
+            .as_ref()
+            .map(|name| &self.inner.type_map[name])
+    }
+
+    pub fn subscription_type(&self) -> Option<&MetaType> {
+        self.inner
+            .subscription_type
+            .as_ref()
+            .map(|name| &self.inner.type_map[name])
+    }
+
+    pub fn root_type(&self, ty: &OperationType) -> Option<&MetaType> {
+        match ty {
+            OperationType::Query => Some(self.query_type()),
+            OperationType::Mutation => self.mutation_type(),
+            OperationType::Subscription => self.subscription_type(),
+        }
+    }
+
+    pub fn get_type(&self, name: &str) -> Option<&MetaType> {
+        self.inner.type_map.get(name)
+    }
+
+    pub fn type_list(&self) -> impl Iterator<Item = &MetaType> {
+        self.inner.type_map.values()
+    }
+
+    pub fn get_directive(&self, name: &str) -> Option<&MetaDirective> {
+        self.inner.directive_map.get(name)
+    }
+
+    pub fn directive_list(&self) -> impl Iterator<Item = &MetaDirective> {
+        self.inner.directive_map.values()
+    }
+
+    /// Attaches parse and serialize behaviour to the scalar type `name`, replacing any
+    /// codec registered before, including the built-in ones.
+    pub fn register_scalar<C: ScalarCodec + 'static>(&mut self, name: &str, codec: C) {
+        Arc::make_mut(&mut self.inner)
+            .scalar_codec_map
+            .insert(name.to_owned(), Arc::new(codec));
+    }
+
+    pub fn scalar_codec(&self, name: &str) -> Option<&dyn ScalarCodec> {
+        self.inner.scalar_codec_map.get(name).map(|codec| &**codec)
+    }
+
+    /// The `__schema` and `__type` meta fields, only available on the query root type.
+    pub(crate) fn introspection_field(&self, name: &str) -> Option<&MetaField> {
+        self.inner
+            .introspection_field_list
+            .iter()
+            .find(|field| field.name == name)
+    }
+
+    /// Whether `object` is a valid runtime type for the named type `abstract_type`.
+    pub fn is_possible_type(&self, abstract_type: &str, object: &str) -> bool {
+        abstract_type == object
+            || self
+                .get_type(abstract_type)
+                .map(|ty| ty.possible_type_list.iter().any(|name| name == object))
+                .unwrap_or(false)
+    }
+
+    fn check_type_references(&self) -> Result<(), SchemaError> {
+        let check = |ty: &Type| {
+            if self.inner.type_map.contains_key(ty.name()) {
+                Ok(())
+            } else {
+                Err(SchemaError::UnknownType(ty.name().to_owned()))
+            }
+        };
+
+        for ty in self.inner.type_map.values() {
+            for field in ty.field_list.iter() {
+                check(&field.ty)?;
+                for argument in field.argument_list.iter() {
+                    check(&argument.ty)?;
+                }
+            }
+            for field in ty.input_field_list.iter() {
+                check(&field.ty)?;
+            }
+            for name in ty.possible_type_list.iter() {
+                check(&Type::Named(name.clone()))?;
+            }
+        }
+        for directive in self.inner.directive_map.values() {
+            for argument in directive.argument_list.iter() {
+                check(&argument.ty)?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl MetaType {
+    fn new(kind: TypeKind, name: &str) -> Self {
+        MetaType {
+            kind,
+            name: name.to_owned(),
+            description: None,
+            field_list: vec![],
+            interface_list: vec![],
+            possible_type_list: vec![],
+            enum_value_list: vec![],
+            input_field_list: vec![],
+            directive_list: vec![],
+        }
+    }
+
+    pub fn field(&self, name: &str) -> Option<&MetaField> {
+        self.field_list.iter().find(|field| field.name == name)
+    }
+
+    pub fn input_field(&self, name: &str) -> Option<&MetaInputValue> {
+        self.input_field_list
+            .iter()
+            .find(|field| field.name == name)
+    }
+
+    pub fn enum_value(&self, name: &str) -> Option<&MetaEnumValue> {
+        self.enum_value_list.iter().find(|value| value.name == name)
+    }
+
+    pub fn is_composite(&self) -> bool {
+        matches!(
+            self.kind,
+            TypeKind::Object | TypeKind::Interface | TypeKind::Union
+        )
+    }
+
+    pub fn is_abstract(&self) -> bool {
+        matches!(self.kind, TypeKind::Interface | TypeKind::Union)
+    }
+
+    pub fn is_input(&self) -> bool {
+        matches!(
+            self.kind,
+            TypeKind::Scalar | TypeKind::Enum | TypeKind::InputObject
+        )
+    }
+
+    fn extend(&mut self, extension: MetaType) {
+        self.field_list.extend(extension.field_list);
+        self.interface_list.extend(extension.interface_list);
+        self.possible_type_list.extend(extension.possible_type_list);
+        self.enum_value_list.extend(extension.enum_value_list);
+        self.input_field_list.extend(extension.input_field_list);
+        self.directive_list.extend(extension.directive_list);
+    }
+}
+
+fn is_extension(definition: &TypeSystemDefinition) -> bool {
+    match definition {
+        TypeSystemDefinition::Schema(schema) => schema.node.is_extend,
+        TypeSystemDefinition::Type(ty) => match &ty.node {
+            TypeDefinition::Scalar(ty) => ty.node.is_extend,
+            TypeDefinition::Object(ty) => ty.node.is_extend,
+            TypeDefinition::Interface(ty) => ty.node.is_extend,
+            TypeDefinition::Union(ty) => ty.node.is_extend,
+            TypeDefinition::Enum(ty) => ty.node.is_extend,
+            TypeDefinition::InputObject(ty) => ty.node.is_extend,
+        },
+        TypeSystemDefinition::Directive(_) => false,
+    }
+}
+
+fn meta_type(definition: &TypeDefinition) -> (bool, MetaType) {
+    fn directive_list(directive_list: &[Positioned<Directive>]) -> Vec<Directive> {
+        directive_list
+            .iter()
+            .map(|directive| directive.node.clone())
+            .collect()
+    }
+
+    match definition {
+        TypeDefinition::Scalar(ty) => {
+            let ty = &ty.node;
+            let mut meta = MetaType::new(TypeKind::Scalar, &ty.name.node);
+            meta.description = description(&ty.description);
+            meta.directive_list = directive_list(&ty.directive_list);
+            (ty.is_extend, meta)
+        }
+        TypeDefinition::Object(ty) => {
+            let ty = &ty.node;
+            let mut meta = MetaType::new(TypeKind::Object, &ty.name.node);
+            meta.description = description(&ty.description);
+            meta.field_list = ty
+                .field_list
+                .iter()
+                .map(|field| meta_field(&field.node))
+                .collect();
+            meta.interface_list = ty
+                .interface_list
+                .iter()
+                .map(|interface| interface.node.clone())
+                .collect();
+            meta.directive_list = directive_list(&ty.directive_list);
+            (ty.is_extend, meta)
+        }
+        TypeDefinition::Interface(ty) => {
+            let ty = &ty.node;
+            let mut meta = MetaType::new(TypeKind::Interface, &ty.name.node);
+            meta.description = description(&ty.description);
+            meta.field_list = ty
+                .field_list
+                .iter()
+                .map(|field| meta_field(&field.node))
+                .collect();
+            meta.directive_list = directive_list(&ty.directive_list);
+            (ty.is_extend, meta)
+        }
+        TypeDefinition::Union(ty) => {
+            let ty = &ty.node;
+            let mut meta = MetaType::new(TypeKind::Union, &ty.name.node);
+            meta.description = description(&ty.description);
+            meta.possible_type_list = ty
+                .member_list
+                .iter()
+                .map(|member| member.node.clone())
+                .collect();
+            meta.directive_list = directive_list(&ty.directive_list);
+            (ty.is_extend, meta)
+        }
+        TypeDefinition::Enum(ty) => {
+            let ty = &ty.node;
+            let mut meta = MetaType::new(TypeKind::Enum, &ty.name.node);
+            meta.description = description(&ty.description);
+            meta.enum_value_list = ty
+                .member_list
+                .iter()
+                .map(|member| MetaEnumValue {
+                    name: member.node.name.node.clone(),
+                    description: description(&member.node.description),
+                    deprecation: deprecation(&member.node.directive_list),
+                    directive_list: directive_list(&member.node.directive_list),
+                })
+                .collect();
+            meta.directive_list = directive_list(&ty.directive_list);
+            (ty.is_extend, meta)
+        }
+        TypeDefinition::InputObject(ty) => {
+            let ty = &ty.node;
+            let mut meta = MetaType::new(TypeKind::InputObject, &ty.name.node);
+            meta.description = description(&ty.description);
+            meta.input_field_list = ty
+                .field_list
+                .iter()
+                .map(|field| MetaInputValue {
+                    name: field.node.name.node.clone(),
+                    description: description(&field.node.description),
+                    ty: field.node.ty.node.clone(),
+                    default_value: field
+                        .node
+                        .default_value
+                        .as_ref()
+                        .map(|value| value.node.clone()),
+                    directive_list: directive_list(&field.node.directive_list),
+                })
+                .collect();
+            meta.directive_list = directive_list(&ty.directive_list);
+            (ty.is_extend, meta)
+        }
+    }
+}
+
+fn meta_field(field: &FieldDefinition) -> MetaField {
+    MetaField {
+        name: field.name.node.clone(),
+        description: description(&field.description),
+        argument_list: field
+            .argument_list
+            .iter()
+            .map(|argument| meta_input_value(&argument.node))
+            .collect(),
+        ty: field.ty.node.clone(),
+        deprecation: deprecation(&field.directive_list),
+        directive_list: field
+            .directive_list
+            .iter()
+            .map(|directive| directive.node.clone())
+            .collect(),
+    }
+}
+
+fn meta_input_value(argument: &FieldArgument) -> MetaInputValue {
+    MetaInputValue {
+        name: argument.name.node.clone(),
+        description: description(&argument.description),
+        ty: argument.ty.node.clone(),
+        default_value: argument
+            .default_value
+            .as_ref()
+            .map(|value| value.node.clone()),
+        directive_list: argument
+            .directive_list
+            .iter()
+            .map(|directive| directive.node.clone())
+            .collect(),
+    }
+}
+
+fn meta_directive(directive: &DirectiveDefinition) -> MetaDirective {
+    MetaDirective {
+        name: directive.name.node.clone(),
+        description: description(&directive.description),
+        argument_list: directive
+            .argument_list
+            .iter()
+            .map(|argument| meta_input_value(&argument.node))
+            .collect(),
+        is_repeatable: directive.is_repeatable,
+        location_list: directive
+            .location_list
+            .iter()
+            .map(|location| location.node.clone())
+            .collect(),
+    }
+}
+
+fn description(description: &Option<Positioned<String>>) -> Option<String> {
+    description
+        .as_ref()
+        .map(|description| block_string_value(&description.node))
+}
+
+fn deprecation(directive_list: &[Positioned<Directive>]) -> Option<String> {
+    directive_list
+        .iter()
+        .find(|directive| directive.node.name.node == "deprecated")
+        .map(|directive| {
+            directive
+                .node
+                .argument_list
+                .iter()
+                .find(|argument| argument.node.name.node == "reason")
+                .and_then(|argument| match &argument.node.value.node {
+                    Value::String(reason) => Some(reason.clone()),
+                    _ => None,
+                })
+                .unwrap_or_else(|| DEFAULT_DEPRECATION_REASON.to_owned())
+        })
+}
@@ -0,0 +1,277 @@
+use crate::*;
+use futures::stream::{BoxStream, StreamExt};
+use graphql_parser::*;
+use serde_json::{Map, Value as JsonValue};
+
+/// Creates a stream of responses for a subscription operation.
+///
+/// The single root field is resolved once and must return `Resolved::Stream`; every event
+/// of that stream is then completed against the field's selection set. Errors raised before
+/// the source stream exists are returned as a single response.
+pub async fn subscribe<'a>(
+    schema: &'a Schema,
+    document: &'a ExecutableDocument,
+    operation_name: Option<&str>,
+    variables: &Variables,
+    root: &dyn Resolver,
+) -> Result<BoxStream<'a, Response>, Response> {
+    let (operation, root_type, variables) =
+        prepare_operation(schema, document, operation_name, variables)
+            .map_err(Response::from_errors)?;
+    if operation.ty != OperationType::Subscription {
+        return Err(Response::from_errors(vec![ServerError::new(
+            "Only subscription operations can be subscribed to.",
+        )]));
+    }
+
+    let context = ExecutionContext::new(schema, document, variables.clone());
+    let mut grouped_field_list = context.collect_fields(root_type, &[&operation.selection_list]);
+    if grouped_field_list.len() != 1 {
+        let message = match &operation.name {
+            Some(name) => format!(
+                "Subscription \"{}\" must select only one top level field.",
+                name.node
+            ),
+            None => "Anonymous Subscription must select only one top level field.".to_owned(),
+        };
+        let mut error = ServerError::new(message);
+        if let Some(position) = grouped_field_list
+            .get(1)
+            .map(|(_, field_list)| field_position(field_list[0]))
+        {
+            error = error.at(&position);
+        }
+        return Err(Response::from_errors(vec![error]));
+    }
+
+    let (response_key, field_list) = grouped_field_list.remove(0);
+    let field = field_list[0];
+    let path = vec![PathSegment::Field(response_key.to_owned())];
+    let field_error = |message: String| {
+        Response::from_errors(vec![ServerError::new(message)
+            .at(&field_position(field))
+            .with_path(&path)])
+    };
+    let definition = root_type.field(&field.name.node).ok_or_else(|| {
+        field_error(format!(
+            "Cannot query field \"{}\" on type \"{}\".",
+            field.name.node, root_type.name
+        ))
+    })?;
+    let arguments = context
+        .coerce_argument_values(&definition.argument_list, &field.argument_list)
+        .map_err(field_error)?;
+    let info = ResolveInfo {
+        schema,
+        parent_type: &root_type.name,
+        field_name: &field.name.node,
+        arguments: &arguments,
+    };
+    let resolved = match resolver_result(root.resolve(&info)).await {
+        Ok(resolved) => resolved,
+        Err(error) => {
+            return Err(Response::from_errors(vec![ServerError {
+                message: error.message,
+                locations: vec![(&field_position(field)).into()],
+                path,
+                extensions: error.extensions,
+            }]))
+        }
+    };
+    let source_stream = match resolved {
+        Resolved::Stream(source_stream) => source_stream,
+        resolved => {
+            return Err(field_error(format!(
+                "Subscription field \"{}\" must return a stream, not {:?}.",
+                field.name.node, resolved
+            )))
+        }
+    };
+
+    let response_stream = source_stream.then(move |event| {
+        let context = ExecutionContext::new(schema, document, variables.clone());
+        let field_list = field_list.clone();
+        let path = path.clone();
+        async move {
+            let value = match event {
+                Ok(resolved) => {
+                    context
+                        .complete_value(&definition.ty, &field_list, resolved, &path)
+                        .await
+                }
+                Err(error) => {
+                    context.report_error(error, field_list[0], &path);
+                    null_for(&definition.ty)
+                }
+            };
+            let data = match value {
+                Ok(value) => {
+                    let mut map = Map::new();
+                    map.insert(response_key.to_owned(), value);
+                    JsonValue::Object(map)
+                }
+                Err(()) => JsonValue::Null,
+            };
+
+            Response {
+                data: Some(data),
+                errors: context.error_list.into_inner().unwrap(),
+            }
+        }
+    });
+
+    Ok(response_stream.boxed())
+}
+
+async fn resolver_result(resolved: FieldResult<Resolved>) -> FieldResult<Resolved> {
+    match resolved {
+        Ok(Resolved::Future(future)) => future.await,
+        resolved => resolved,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use futures::executor::block_on;
+    use serde_json::json;
+    use std::sync::Arc;
+
+    const SCHEMA: &str = r#"
+type Query {
+  room: String
+}
+
+type Subscription {
+  messageAdded(room: String!): Message!
+  userJoined: String
+}
+
+type Message {
+  room: String!
+  text: String
+}
+"#;
+
+    #[derive(Clone)]
+    struct Message {
+        room: String,
+        text: String,
+    }
+
+    impl Resolver for Message {
+        fn resolve(&self, info: &ResolveInfo<'_>) -> FieldResult<Resolved> {
+            match info.field_name {
+                "room" => Ok(Resolved::value(self.room.clone())),
+                "text" => Ok(Resolved::value(self.text.clone())),
+                _ => Ok(Resolved::Null),
+            }
+        }
+    }
+
+    fn root(pubsub: Arc<PubSub<Message>>) -> impl Resolver {
+        move |info: &ResolveInfo<'_>| match info.field_name {
+            "messageAdded" => {
+                let room = match info.arguments.get("room") {
+                    Some(Value::String(room)) => room.clone(),
+                    _ => return Err("room must be a string".into()),
+                };
+                Ok(Resolved::stream(
+                    pubsub
+                        .subscribe()
+                        .filter(move |message| futures::future::ready(message.room == room))
+                        .map(|message| Ok(Resolved::object(message))),
+                ))
+            }
+            _ => Ok(Resolved::Null),
+        }
+    }
+
+    #[test]
+    fn stream_of_responses() {
+        let schema = Schema::build(&parse_type_system(SCHEMA).unwrap()).unwrap();
+        let document = parse_executable(
+            r#"
+subscription {
+  messageAdded(room: "general") {
+    text
+  }
+}
+"#,
+        )
+        .unwrap();
+        let pubsub = Arc::new(PubSub::new());
+        let root = root(pubsub.clone());
+
+        let stream = block_on(subscribe(
+            &schema,
+            &document,
+            None,
+            &Variables::new(),
+            &root,
+        ))
+        .unwrap();
+        assert_eq!(pubsub.subscriber_count(), 1);
+        for (room, text) in [
+            ("general", "hello"),
+            ("random", "skipped"),
+            ("general", "bye"),
+        ]
+        .iter()
+        {
+            pubsub.publish(Message {
+                room: room.to_string(),
+                text: text.to_string(),
+            });
+        }
+
+        let response_list: Vec<_> = block_on(stream.take(2).collect());
+        assert_eq!(
+            response_list
+                .into_iter()
+                .map(|response| serde_json::to_value(response).unwrap())
+                .collect::<Vec<_>>(),
+            vec![
+                json!({ "data": { "messageAdded": { "text": "hello" } } }),
+                json!({ "data": { "messageAdded": { "text": "bye" } } }),
+            ]
+        );
+    }
+
+    #[test]
+    fn single_root_field() {
+        let schema = Schema::build(&parse_type_system(SCHEMA).unwrap()).unwrap();
+        let document = parse_executable(
+            r#"
+subscription Both {
+  messageAdded(room: "general") {
+    text
+  }
+  userJoined
+}
+"#,
+        )
+        .unwrap();
+        let root = root(Arc::new(PubSub::new()));
+
+        let response = match block_on(subscribe(
+            &schema,
+            &document,
+            None,
+            &Variables::new(),
+            &root,
+        )) {
+            Ok(_) => panic!("expected an error"),
+            Err(response) => response,
+        };
+        assert_eq!(
+            serde_json::to_value(response).unwrap(),
+            json!({
+                "errors": [{
+                    "message": "Subscription \"Both\" must select only one top level field.",
+                    "locations": [{ "line": 6, "column": 3 }]
+                }]
+            })
+        );
+    }
+}
@@ -0,0 +1,1289 @@
+use crate::*;
+use graphql_parser::*;
+use std::collections::{HashMap, HashSet};
+
+/// Validates every operation and fragment of `document` against `schema`.
+///
+/// Errors found inside a fragment shared by several operations are reported once.
+pub fn validate(schema: &Schema, document: &ExecutableDocument) -> Vec<ServerError> {
+    let mut error_list = vec![];
+    let mut push = |error: ServerError| {
+        if !error_list.contains(&error) {
+            error_list.push(error);
+        }
+    };
+
+    let operation_list: Vec<&Positioned<OperationDefinition>> = document
+        .definition_list
+        .iter()
+        .filter_map(|definition| match &definition.node {
+            ExecutableDefinition::Operation(operation) => Some(operation),
+            ExecutableDefinition::Fragment(_) => None,
+        })
+        .collect();
+    let mut seen_name_set = HashSet::new();
+    for operation in operation_list.iter() {
+        match &operation.node.name {
+            Some(name) if !seen_name_set.insert(name.node.as_str()) => push(
+                ServerError::new(format!(
+                    "There can be only one operation named \"{}\".",
+                    name.node
+                ))
+                .at(&name.start),
+            ),
+            None if operation_list.len() > 1 => push(
+                ServerError::new("This anonymous operation must be the only defined operation.")
+                    .at(&operation.start),
+            ),
+            _ => {}
+        }
+    }
+
+    let mut used_fragment_set = HashSet::new();
+    for operation in operation_list.iter() {
+        let mut validator = Validator::new(schema, document);
+        validator.validate_operation(&operation.node, &operation.start);
+        used_fragment_set.extend(validator.visited_fragment_set);
+        validator.error_list.into_iter().for_each(&mut push);
+    }
+
+    fragment_cycle_list(document)
+        .into_iter()
+        .for_each(&mut push);
+
+    for definition in document.definition_list.iter() {
+        if let ExecutableDefinition::Fragment(fragment) = &definition.node {
+            let name = &fragment.node.name;
+            if !used_fragment_set.contains(name.node.as_str()) {
+                push(
+                    ServerError::new(format!("Fragment \"{}\" is never used.", name.node))
+                        .at(&fragment.start),
+                );
+            }
+        }
+    }
+
+    error_list
+}
+
+/// Validates a single operation of `document`, including the fragments it spreads.
+pub fn validate_operation(
+    schema: &Schema,
+    document: &ExecutableDocument,
+    operation: &Positioned<OperationDefinition>,
+) -> Vec<ServerError> {
+    let mut validator = Validator::new(schema, document);
+    validator.validate_operation(&operation.node, &operation.start);
+    validator.error_list
+}
+
+struct VariableUsage<'a> {
+    name: &'a str,
+    ty: Type,
+    has_location_default: bool,
+    position: Position,
+}
+
+/// A field of a selection set, flattened out of the fragments it was selected through.
+struct MergeField<'a> {
+    parent_type: &'a MetaType,
+    field: &'a Field,
+    definition: Option<&'a MetaField>,
+}
+
+impl<'a> MergeField<'a> {
+    fn response_key(&self) -> &'a str {
+        self.field
+            .alias
+            .as_ref()
+            .unwrap_or(&self.field.name)
+            .node
+            .as_str()
+    }
+}
+
+struct Validator<'a> {
+    schema: &'a Schema,
+    fragment_map: HashMap<&'a str, &'a Positioned<FragmentDefinition>>,
+    visited_fragment_set: HashSet<&'a str>,
+    variable_usage_list: Vec<VariableUsage<'a>>,
+    label_set: HashSet<&'a str>,
+    error_list: Vec<ServerError>,
+}
+
+impl<'a> Validator<'a> {
+    fn new(schema: &'a Schema, document: &'a ExecutableDocument) -> Self {
+        let fragment_map = document
+            .definition_list
+            .iter()
+            .filter_map(|definition| match &definition.node {
+                ExecutableDefinition::Fragment(fragment) => {
+                    Some((fragment.node.name.node.as_str(), fragment))
+                }
+                ExecutableDefinition::Operation(_) => None,
+            })
+            .collect();
+
+        Validator {
+            schema,
+            fragment_map,
+            visited_fragment_set: HashSet::new(),
+            variable_usage_list: vec![],
+            label_set: HashSet::new(),
+            error_list: vec![],
+        }
+    }
+
+    fn report(&mut self, message: String, position: &Position) {
+        self.error_list.push(ServerError::new(message).at(position));
+    }
+
+    fn validate_operation(&mut self, operation: &'a OperationDefinition, position: &Position) {
+        let (location, operation_name) = match operation.ty {
+            OperationType::Query => (ExecutableDirectiveLocation::Query, "query"),
+            OperationType::Mutation => (ExecutableDirectiveLocation::Mutation, "mutation"),
+            OperationType::Subscription => {
+                (ExecutableDirectiveLocation::Subscription, "subscription")
+            }
+        };
+        self.validate_directive_list(&operation.directive_list, location);
+
+        let mut variable_map = HashMap::new();
+        for definition in operation.variable_list.iter() {
+            let definition = &definition.node;
+            let name = definition.name.node.as_str();
+            if variable_map.insert(name, definition).is_some() {
+                self.report(
+                    format!("There can be only one variable named \"${}\".", name),
+                    &definition.name.start,
+                );
+            }
+            match self.schema.get_type(definition.ty.node.name()) {
+                None => self.report(
+                    format!("Unknown type \"{}\".", definition.ty.node.name()),
+                    &definition.ty.start,
+                ),
+                Some(ty) if !ty.is_input() => self.report(
+                    format!(
+                        "Variable \"${}\" cannot be non-input type \"{}\".",
+                        name, definition.ty.node
+                    ),
+                    &definition.ty.start,
+                ),
+                Some(_) => {
+                    if let Some(default_value) = &definition.default_value {
+                        if let Err(message) = self.check_literal(
+                            &definition.ty.node,
+                            &default_value.node,
+                            false,
+                            &default_value.start,
+                        ) {
+                            self.report(message, &default_value.start);
+                        }
+                    }
+                }
+            }
+        }
+
+        match self.schema.root_type(&operation.ty) {
+            Some(root_type) => self.validate_selection_list(root_type, &operation.selection_list),
+            None => {
+                self.report(
+                    format!(
+                        "Schema is not configured to execute {} operation.",
+                        operation_name
+                    ),
+                    position,
+                );
+                return;
+            }
+        }
+
+        let mut used_variable_set = HashSet::new();
+        for usage in std::mem::take(&mut self.variable_usage_list) {
+            used_variable_set.insert(usage.name);
+            let definition = match variable_map.get(usage.name) {
+                Some(definition) => definition,
+                None => {
+                    let message = match &operation.name {
+                        Some(operation_name) => format!(
+                            "Variable \"${}\" is not defined by operation \"{}\".",
+                            usage.name, operation_name.node
+                        ),
+                        None => format!("Variable \"${}\" is not defined.", usage.name),
+                    };
+                    self.report(message, &usage.position);
+                    continue;
+                }
+            };
+            let has_non_null_default = matches!(
+                &definition.default_value,
+                Some(default_value) if default_value.node != Value::Null
+            );
+            let location_ty = match &usage.ty {
+                Type::NonNull(ty)
+                    if !definition.ty.node.is_non_null()
+                        && (has_non_null_default || usage.has_location_default) =>
+                {
+                    ty
+                }
+                ty => ty,
+            };
+            if !is_sub_type(&definition.ty.node, location_ty) {
+                self.report(
+                    format!(
+                        "Variable \"${}\" of type \"{}\" used in position expecting type \"{}\".",
+                        usage.name, definition.ty.node, usage.ty
+                    ),
+                    &usage.position,
+                );
+            }
+        }
+        for definition in operation.variable_list.iter() {
+            let name = definition.node.name.node.as_str();
+            if !used_variable_set.contains(name) {
+                let message = match &operation.name {
+                    Some(operation_name) => format!(
+                        "Variable \"${}\" is never used in operation \"{}\".",
+                        name, operation_name.node
+                    ),
+                    None => format!("Variable \"${}\" is never used.", name),
+                };
+                self.report(message, &definition.start);
+            }
+        }
+    }
+
+    fn validate_selection_list(
+        &mut self,
+        parent_type: &'a MetaType,
+        selection_list: &'a [Positioned<Selection>],
+    ) {
+        self.validate_field_merge(parent_type, selection_list);
+        for selection in selection_list.iter() {
+            match &selection.node {
+                Selection::Field(field) => self.validate_field(parent_type, field),
+                Selection::FragmentSpread(spread) => {
+                    self.validate_directive_list(
+                        &spread.directive_list,
+                        ExecutableDirectiveLocation::FragmentSpread,
+                    );
+                    self.validate_incremental(parent_type, &spread.directive_list, None);
+                    let name = spread.name.node.as_str();
+                    let fragment = match self.fragment_map.get(name) {
+                        Some(fragment) => *fragment,
+                        None => {
+                            self.report(
+                                format!("Unknown fragment \"{}\".", name),
+                                &spread.name.start,
+                            );
+                            continue;
+                        }
+                    };
+                    let fragment_type = match self.type_condition(&fragment.node.on) {
+                        Some(fragment_type) => fragment_type,
+                        None => continue,
+                    };
+                    if !self.does_overlap(parent_type, fragment_type) {
+                        self.report(
+                            format!(
+                                "Fragment \"{}\" cannot be spread here as objects of type \"{}\" can never be of type \"{}\".",
+                                name, parent_type.name, fragment_type.name
+                            ),
+                            &selection.start,
+                        );
+                    }
+                    if self.visited_fragment_set.insert(name) {
+                        self.validate_directive_list(
+                            &fragment.node.directive_list,
+                            ExecutableDirectiveLocation::FragmentDefinition,
+                        );
+                        self.validate_selection_list(fragment_type, &fragment.node.selection_list);
+                    }
+                }
+                Selection::InlineFragment(fragment) => {
+                    self.validate_directive_list(
+                        &fragment.directive_list,
+                        ExecutableDirectiveLocation::InlineFragment,
+                    );
+                    self.validate_incremental(parent_type, &fragment.directive_list, None);
+                    let fragment_type = match &fragment.on {
+                        Some(on) => match self.type_condition(on) {
+                            Some(fragment_type) => fragment_type,
+                            None => continue,
+                        },
+                        None => parent_type,
+                    };
+                    if !self.does_overlap(parent_type, fragment_type) {
+                        self.report(
+                            format!(
+                                "Fragment cannot be spread here as objects of type \"{}\" can never be of type \"{}\".",
+                                parent_type.name, fragment_type.name
+                            ),
+                            &selection.start,
+                        );
+                    }
+                    self.validate_selection_list(fragment_type, &fragment.selection_list);
+                }
+            }
+        }
+    }
+
+    fn validate_field(&mut self, parent_type: &'a MetaType, field: &'a Field) {
+        self.validate_directive_list(&field.directive_list, ExecutableDirectiveLocation::Field);
+        let field_name = field.name.node.as_str();
+        if field_name == "__typename" {
+            self.validate_leaf(field, "String");
+            return;
+        }
+
+        let definition = match self.field_definition(parent_type, field_name) {
+            Some(definition) => definition,
+            None => {
+                self.report(
+                    format!(
+                        "Cannot query field \"{}\" on type \"{}\".",
+                        field_name, parent_type.name
+                    ),
+                    &field.name.start,
+                );
+                return;
+            }
+        };
+        self.validate_argument_list(
+            ("Field", &format!("{}.{}", parent_type.name, field_name)),
+            &definition.argument_list,
+            &field.argument_list,
+            &field.name.start,
+        );
+        self.validate_incremental(
+            parent_type,
+            &field.directive_list,
+            Some((field_name, &definition.ty)),
+        );
+
+        let field_type = match self.schema.get_type(definition.ty.name()) {
+            Some(field_type) => field_type,
+            None => return,
+        };
+        if field_type.is_composite() {
+            if field.selection_list.is_empty() {
+                self.report(
+                    format!(
+                        "Field \"{}\" of type \"{}\" must have a selection of subfields. Did you mean \"{} {{ ... }}\"?",
+                        field_name, definition.ty, field_name
+                    ),
+                    &field.name.start,
+                );
+            } else {
+                self.validate_selection_list(field_type, &field.selection_list);
+            }
+        } else {
+            self.validate_leaf(field, &definition.ty.to_string());
+        }
+    }
+
+    /// Finds the definition of a field, including the introspection fields of the query type.
+    fn field_definition(
+        &self,
+        parent_type: &'a MetaType,
+        field_name: &str,
+    ) -> Option<&'a MetaField> {
+        let introspection_field = if parent_type.name == self.schema.query_type().name {
+            self.schema.introspection_field(field_name)
+        } else {
+            None
+        };
+        introspection_field.or_else(|| parent_type.field(field_name))
+    }
+
+    /// Checks that the fields of a selection set sharing a response key can be merged
+    /// into a single field of the response.
+    fn validate_field_merge(
+        &mut self,
+        parent_type: &'a MetaType,
+        selection_list: &'a [Positioned<Selection>],
+    ) {
+        let mut field_list = vec![];
+        self.collect_merge_field_list(
+            parent_type,
+            selection_list,
+            &mut HashSet::new(),
+            &mut field_list,
+        );
+        let mut conflict_list = vec![];
+        for (index, field) in field_list.iter().enumerate() {
+            let response_key = field.response_key();
+            for other in field_list[index + 1..].iter() {
+                if other.response_key() != response_key {
+                    continue;
+                }
+                if let Some(reason) = self.find_conflict(false, field, other) {
+                    let name = field.field.alias.as_ref().unwrap_or(&field.field.name);
+                    conflict_list.push(
+                        ServerError::new(format!(
+                            "Fields \"{}\" conflict because {}. Use different aliases on the fields to fetch both if this was intentional.",
+                            response_key, reason
+                        ))
+                        .at(&name.start),
+                    );
+                }
+            }
+        }
+        for error in conflict_list {
+            if !self.error_list.contains(&error) {
+                self.error_list.push(error);
+            }
+        }
+    }
+
+    fn collect_merge_field_list(
+        &self,
+        parent_type: &'a MetaType,
+        selection_list: &'a [Positioned<Selection>],
+        visited_fragment_set: &mut HashSet<&'a str>,
+        field_list: &mut Vec<MergeField<'a>>,
+    ) {
+        for selection in selection_list.iter() {
+            let (fragment_type, selection_list) = match &selection.node {
+                Selection::Field(field) => {
+                    field_list.push(MergeField {
+                        parent_type,
+                        field,
+                        definition: self.field_definition(parent_type, &field.name.node),
+                    });
+                    continue;
+                }
+                Selection::FragmentSpread(spread) => {
+                    let name = spread.name.node.as_str();
+                    match self.fragment_map.get(name) {
+                        Some(fragment) if visited_fragment_set.insert(name) => (
+                            self.schema.get_type(&fragment.node.on.node),
+                            &fragment.node.selection_list,
+                        ),
+                        _ => continue,
+                    }
+                }
+                Selection::InlineFragment(fragment) => match &fragment.on {
+                    Some(on) => (self.schema.get_type(&on.node), &fragment.selection_list),
+                    None => (Some(parent_type), &fragment.selection_list),
+                },
+            };
+            if let Some(fragment_type) = fragment_type.filter(|ty| ty.is_composite()) {
+                self.collect_merge_field_list(
+                    fragment_type,
+                    selection_list,
+                    visited_fragment_set,
+                    field_list,
+                );
+            }
+        }
+    }
+
+    /// Explains why two fields with the same response key cannot be merged, if they cannot.
+    ///
+    /// Fields of two different object types are never both executed, so they may differ
+    /// in name and arguments, but must still return the same shape of response.
+    fn find_conflict(
+        &self,
+        is_exclusive: bool,
+        field: &MergeField<'a>,
+        other: &MergeField<'a>,
+    ) -> Option<String> {
+        let is_exclusive = is_exclusive
+            || (field.parent_type.name != other.parent_type.name
+                && field.parent_type.kind == TypeKind::Object
+                && other.parent_type.kind == TypeKind::Object);
+        if !is_exclusive {
+            if field.field.name.node != other.field.name.node {
+                return Some(format!(
+                    "\"{}\" and \"{}\" are different fields",
+                    field.field.name.node, other.field.name.node
+                ));
+            }
+            let is_same_argument_list = field.field.argument_list.len()
+                == other.field.argument_list.len()
+                && field.field.argument_list.iter().all(|argument| {
+                    other.field.argument_list.iter().any(|other_argument| {
+                        other_argument.node.name.node == argument.node.name.node
+                            && other_argument.node.value.node == argument.node.value.node
+                    })
+                });
+            if !is_same_argument_list {
+                return Some("they have differing arguments".to_owned());
+            }
+        }
+
+        let (definition, other_definition) = match (field.definition, other.definition) {
+            (Some(definition), Some(other_definition)) => (definition, other_definition),
+            _ => return None,
+        };
+        if self.do_types_conflict(&definition.ty, &other_definition.ty) {
+            return Some(format!(
+                "they return conflicting types \"{}\" and \"{}\"",
+                definition.ty, other_definition.ty
+            ));
+        }
+
+        let field_type = self.schema.get_type(definition.ty.name())?;
+        let other_type = self.schema.get_type(other_definition.ty.name())?;
+        if !field_type.is_composite() || !other_type.is_composite() {
+            return None;
+        }
+        let mut field_list = vec![];
+        self.collect_merge_field_list(
+            field_type,
+            &field.field.selection_list,
+            &mut HashSet::new(),
+            &mut field_list,
+        );
+        let mut other_list = vec![];
+        self.collect_merge_field_list(
+            other_type,
+            &other.field.selection_list,
+            &mut HashSet::new(),
+            &mut other_list,
+        );
+        let mut reason_list: Vec<String> = vec![];
+        for sub_field in field_list.iter() {
+            let response_key = sub_field.response_key();
+            for sub_other in other_list.iter() {
+                if sub_other.response_key() != response_key {
+                    continue;
+                }
+                if let Some(reason) = self.find_conflict(is_exclusive, sub_field, sub_other) {
+                    let reason =
+                        format!("subfields \"{}\" conflict because {}", response_key, reason);
+                    if !reason_list.contains(&reason) {
+                        reason_list.push(reason);
+                    }
+                }
+            }
+        }
+        if reason_list.is_empty() {
+            None
+        } else {
+            Some(reason_list.join(" and "))
+        }
+    }
+
+    /// Whether two fields return values that cannot be merged, leaving the comparison of
+    /// composite types to their subfields.
+    fn do_types_conflict(&self, ty: &Type, other: &Type) -> bool {
+        match (ty, other) {
+            (Type::List(ty), Type::List(other)) => self.do_types_conflict(ty, other),
+            (Type::List(_), _) | (_, Type::List(_)) => true,
+            (Type::NonNull(ty), Type::NonNull(other)) => self.do_types_conflict(ty, other),
+            (Type::NonNull(_), _) | (_, Type::NonNull(_)) => true,
+            (Type::Named(name), Type::Named(other_name)) => {
+                let is_leaf = |name: &str| {
+                    self.schema
+                        .get_type(name)
+                        .is_some_and(|ty| !ty.is_composite())
+                };
+                name != other_name && (is_leaf(name) || is_leaf(other_name))
+            }
+        }
+    }
+
+    fn validate_leaf(&mut self, field: &Field, ty: &str) {
+        if let Some(selection) = field.selection_list.first() {
+            self.report(
+                format!(
+                    "Field \"{}\" must not have a selection since type \"{}\" has no subfields.",
+                    field.name.node, ty
+                ),
+                &selection.start,
+            );
+        }
+    }
+
+    fn validate_directive_list(
+        &mut self,
+        directive_list: &'a [Positioned<Directive>],
+        location: ExecutableDirectiveLocation,
+    ) {
+        let location = DirectiveLocation::Executable(location);
+        for directive in directive_list.iter() {
+            let name = &directive.node.name;
+            let definition = match self.schema.get_directive(&name.node) {
+                Some(definition) => definition,
+                None => {
+                    self.report(
+                        format!("Unknown directive \"@{}\".", name.node),
+                        &name.start,
+                    );
+                    continue;
+                }
+            };
+            if !definition.location_list.contains(&location) {
+                self.report(
+                    format!(
+                        "Directive \"@{}\" may not be used on {}.",
+                        name.node, location
+                    ),
+                    &name.start,
+                );
+            }
+            self.validate_argument_list(
+                ("Directive", &format!("@{}", name.node)),
+                &definition.argument_list,
+                &directive.node.argument_list,
+                &name.start,
+            );
+        }
+    }
+
+    /// Checks the `@defer` and `@stream` directives of a selection, given the name and type
+    /// of the field when the selection is one.
+    ///
+    /// Neither may split the root fields of a mutation or subscription, which must be
+    /// delivered at once, and their labels must tell the payloads of an operation apart.
+    fn validate_incremental(
+        &mut self,
+        parent_type: &MetaType,
+        directive_list: &'a [Positioned<Directive>],
+        field: Option<(&str, &Type)>,
+    ) {
+        for directive in directive_list.iter() {
+            let name = &directive.node.name;
+            let kind = match name.node.as_str() {
+                "defer" => "Defer",
+                "stream" => "Stream",
+                _ => continue,
+            };
+            let is_root = |root_type: Option<&MetaType>| {
+                root_type.map(|root_type| root_type.name == parent_type.name) == Some(true)
+            };
+            let root_kind = if is_root(self.schema.mutation_type()) {
+                Some("mutation")
+            } else if is_root(self.schema.subscription_type()) {
+                Some("subscription")
+            } else {
+                None
+            };
+            if let Some(root_kind) = root_kind {
+                self.report(
+                    format!(
+                        "{} directive cannot be used on root {} type \"{}\".",
+                        kind, root_kind, parent_type.name
+                    ),
+                    &name.start,
+                );
+            }
+            if let (Some((field_name, ty)), "Stream") = (field, kind) {
+                let is_list = match ty {
+                    Type::NonNull(ty) => matches!(**ty, Type::List(_)),
+                    ty => matches!(ty, Type::List(_)),
+                };
+                if !is_list {
+                    self.report(
+                        format!(
+                            "Stream directive cannot be used on non-list field \"{}\" on type \"{}\".",
+                            field_name, parent_type.name
+                        ),
+                        &name.start,
+                    );
+                }
+            }
+            let label = directive
+                .node
+                .argument_list
+                .iter()
+                .find(|argument| argument.node.name.node == "label");
+            match label.map(|label| &label.node.value) {
+                Some(Positioned {
+                    node: Value::String(label),
+                    start,
+                    ..
+                }) if !self.label_set.insert(label) => self.report(
+                    "Defer/Stream directive label argument must be unique.".to_owned(),
+                    start,
+                ),
+                Some(Positioned {
+                    node: Value::Variable(_),
+                    start,
+                    ..
+                }) => self.report(
+                    "Defer/Stream directive label argument must be a static string.".to_owned(),
+                    start,
+                ),
+                _ => {}
+            }
+        }
+    }
+
+    fn validate_argument_list(
+        &mut self,
+        (kind, subject): (&str, &str),
+        definition_list: &'a [MetaInputValue],
+        argument_list: &'a [Positioned<Argument>],
+        position: &Position,
+    ) {
+        for argument in argument_list.iter() {
+            let name = &argument.node.name;
+            let definition = match definition_list
+                .iter()
+                .find(|definition| definition.name == name.node)
+            {
+                Some(definition) => definition,
+                None => {
+                    self.report(
+                        format!(
+                            "Unknown argument \"{}\" on {} \"{}\".",
+                            name.node,
+                            kind.to_lowercase(),
+                            subject
+                        ),
+                        &name.start,
+                    );
+                    continue;
+                }
+            };
+            let value = &argument.node.value;
+            if let Err(message) = self.check_literal(
+                &definition.ty,
+                &value.node,
+                definition.default_value.is_some(),
+                &value.start,
+            ) {
+                self.report(
+                    format!(
+                        "Argument \"{}\" has invalid value {}; {}",
+                        name.node, value.node, message
+                    ),
+                    &value.start,
+                );
+            }
+        }
+
+        for definition in definition_list.iter() {
+            let is_provided = argument_list
+                .iter()
+                .any(|argument| argument.node.name.node == definition.name);
+            if !is_provided && definition.ty.is_non_null() && definition.default_value.is_none() {
+                self.report(
+                    format!(
+                        "{} \"{}\" argument \"{}\" of type \"{}\" is required, but it was not provided.",
+                        kind, subject, definition.name, definition.ty
+                    ),
+                    position,
+                );
+            }
+        }
+    }
+
+    /// Checks a literal against an input type, recording every variable it refers to so
+    /// the variable can later be checked against the position it is used in.
+    fn check_literal(
+        &mut self,
+        ty: &Type,
+        value: &'a Value,
+        has_location_default: bool,
+        position: &Position,
+    ) -> Result<(), String> {
+        match (ty, value) {
+            (_, Value::Variable(name)) => {
+                self.variable_usage_list.push(VariableUsage {
+                    name,
+                    ty: ty.clone(),
+                    has_location_default,
+                    position: position.clone(),
+                });
+                Ok(())
+            }
+            (Type::NonNull(_), Value::Null) => {
+                Err(format!("Expected value of type \"{}\", found null.", ty))
+            }
+            (Type::NonNull(ty), value) => {
+                self.check_literal(ty, value, has_location_default, position)
+            }
+            (_, Value::Null) => Ok(()),
+            (Type::List(item_ty), Value::List(item_list)) => {
+                for item in item_list.iter() {
+                    self.check_literal(item_ty, item, false, position)?;
+                }
+                Ok(())
+            }
+            (Type::List(item_ty), value) => self.check_literal(item_ty, value, false, position),
+            (Type::Named(name), value) => {
+                let meta_type = match self.schema.get_type(name) {
+                    Some(meta_type) => meta_type,
+                    None => return Err(format!("Unknown type \"{}\".", name)),
+                };
+                match meta_type.kind {
+                    TypeKind::Scalar => match self.schema.scalar_codec(name) {
+                        Some(codec) => codec.parse_literal(value).map(|_| ()),
+                        None => Ok(()),
+                    },
+                    TypeKind::Enum => match value {
+                        Value::Enum(enum_value) if meta_type.enum_value(enum_value).is_some() => {
+                            Ok(())
+                        }
+                        Value::Enum(enum_value) => Err(format!(
+                            "Value \"{}\" does not exist in \"{}\" enum.",
+                            enum_value, name
+                        )),
+                        value => Err(format!(
+                            "Enum \"{}\" cannot represent non-enum value: {}.",
+                            name, value
+                        )),
+                    },
+                    TypeKind::InputObject => {
+                        let object = match value {
+                            Value::Object(object) => object,
+                            value => {
+                                return Err(format!(
+                                    "Expected value of type \"{}\", found {}.",
+                                    name, value
+                                ))
+                            }
+                        };
+                        for field_name in object.keys() {
+                            if meta_type.input_field(field_name).is_none() {
+                                return Err(format!(
+                                    "Field \"{}\" is not defined by type \"{}\".",
+                                    field_name, name
+                                ));
+                            }
+                        }
+                        for field in meta_type.input_field_list.iter() {
+                            match object.get(&field.name) {
+                                Some(value) => self.check_literal(
+                                    &field.ty,
+                                    value,
+                                    field.default_value.is_some(),
+                                    position,
+                                )?,
+                                None if field.ty.is_non_null() && field.default_value.is_none() => {
+                                    return Err(format!(
+                                        "Field \"{}.{}\" of required type \"{}\" was not provided.",
+                                        name, field.name, field.ty
+                                    ))
+                                }
+                                None => {}
+                            }
+                        }
+                        Ok(())
+                    }
+                    _ => Err(format!("Type \"{}\" is not an input type.", name)),
+                }
+            }
+        }
+    }
+
+    fn type_condition(&mut self, on: &Positioned<String>) -> Option<&'a MetaType> {
+        match self.schema.get_type(&on.node) {
+            Some(ty) if ty.is_composite() => Some(ty),
+            Some(_) => {
+                self.report(
+                    format!(
+                        "Fragment cannot condition on non composite type \"{}\".",
+                        on.node
+                    ),
+                    &on.start,
+                );
+                None
+            }
+            None => {
+                self.report(format!("Unknown type \"{}\".", on.node), &on.start);
+                None
+            }
+        }
+    }
+
+    fn does_overlap(&self, parent_type: &MetaType, fragment_type: &MetaType) -> bool {
+        let possible_type_list = |ty: &'a MetaType| -> Vec<&'a str> {
+            if ty.is_abstract() {
+                ty.possible_type_list.iter().map(String::as_str).collect()
+            } else {
+                vec![ty.name.as_str()]
+            }
+        };
+        let parent_list = possible_type_list(self.schema.get_type(&parent_type.name).unwrap());
+        possible_type_list(self.schema.get_type(&fragment_type.name).unwrap())
+            .iter()
+            .any(|name| parent_list.contains(name))
+    }
+}
+
+/// Reports every fragment that spreads itself, directly or through other fragments.
+fn fragment_cycle_list(document: &ExecutableDocument) -> Vec<ServerError> {
+    let fragment_map: HashMap<&str, &FragmentDefinition> = document
+        .definition_list
+        .iter()
+        .filter_map(|definition| match &definition.node {
+            ExecutableDefinition::Fragment(fragment) => {
+                Some((fragment.node.name.node.as_str(), &fragment.node))
+            }
+            ExecutableDefinition::Operation(_) => None,
+        })
+        .collect();
+    let mut error_list = vec![];
+    let mut visited_fragment_set = HashSet::new();
+    for definition in document.definition_list.iter() {
+        if let ExecutableDefinition::Fragment(fragment) = &definition.node {
+            find_fragment_cycle(
+                &fragment.node,
+                &fragment_map,
+                &mut visited_fragment_set,
+                &mut vec![],
+                &mut HashMap::new(),
+                &mut error_list,
+            );
+        }
+    }
+    error_list
+}
+
+fn find_fragment_cycle<'a>(
+    fragment: &'a FragmentDefinition,
+    fragment_map: &HashMap<&'a str, &'a FragmentDefinition>,
+    visited_fragment_set: &mut HashSet<&'a str>,
+    spread_path: &mut Vec<(&'a str, &'a Position)>,
+    path_index_map: &mut HashMap<&'a str, usize>,
+    error_list: &mut Vec<ServerError>,
+) {
+    let name = fragment.name.node.as_str();
+    if !visited_fragment_set.insert(name) {
+        return;
+    }
+    let mut spread_list = vec![];
+    collect_spread_list(&fragment.selection_list, &mut spread_list);
+    path_index_map.insert(name, spread_path.len());
+    for (spread_name, position) in spread_list {
+        spread_path.push((spread_name, position));
+        match path_index_map.get(spread_name) {
+            Some(&index) => {
+                let cycle_path = &spread_path[index..];
+                let via_list: Vec<String> = cycle_path[..cycle_path.len() - 1]
+                    .iter()
+                    .map(|(name, _)| format!("\"{}\"", name))
+                    .collect();
+                let message = if via_list.is_empty() {
+                    format!("Cannot spread fragment \"{}\" within itself.", spread_name)
+                } else {
+                    format!(
+                        "Cannot spread fragment \"{}\" within itself via {}.",
+                        spread_name,
+                        via_list.join(", ")
+                    )
+                };
+                error_list.push(ServerError::new(message).at(cycle_path[0].1));
+            }
+            None => {
+                if let Some(spread_fragment) = fragment_map.get(spread_name) {
+                    find_fragment_cycle(
+                        spread_fragment,
+                        fragment_map,
+                        visited_fragment_set,
+                        spread_path,
+                        path_index_map,
+                        error_list,
+                    );
+                }
+            }
+        }
+        spread_path.pop();
+    }
+    path_index_map.remove(name);
+}
+
+fn collect_spread_list<'a>(
+    selection_list: &'a [Positioned<Selection>],
+    spread_list: &mut Vec<(&'a str, &'a Position)>,
+) {
+    for selection in selection_list.iter() {
+        match &selection.node {
+            Selection::Field(field) => collect_spread_list(&field.selection_list, spread_list),
+            Selection::FragmentSpread(spread) => {
+                spread_list.push((spread.name.node.as_str(), &selection.start))
+            }
+            Selection::InlineFragment(fragment) => {
+                collect_spread_list(&fragment.selection_list, spread_list)
+            }
+        }
+    }
+}
+
+/// Whether a variable of type `variable` can be used where `location` is expected.
+fn is_sub_type(variable: &Type, location: &Type) -> bool {
+    match (variable, location) {
+        (Type::NonNull(variable), Type::NonNull(location)) => is_sub_type(variable, location),
+        (_, Type::NonNull(_)) => false,
+        (Type::NonNull(variable), location) => is_sub_type(variable, location),
+        (Type::List(variable), Type::List(location)) => is_sub_type(variable, location),
+        (Type::Named(variable), Type::Named(location)) => variable == location,
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SCHEMA: &str = r#"
+type Query {
+  user(id: ID!): User
+  search(role: Role, filter: Filter): [Result!]!
+}
+
+type User {
+  id: ID!
+  name: String
+  role: Role!
+}
+
+type Team {
+  name: String
+}
+
+union Result = User | Team
+
+enum Role {
+  ADMIN
+  MEMBER
+}
+
+input Filter {
+  text: String!
+  limit: Int = 10
+}
+"#;
+
+    fn validate_source(source: &str) -> Vec<(String, usize, usize)> {
+        validate_source_with(SCHEMA, source)
+    }
+
+    fn validate_source_with(schema: &str, source: &str) -> Vec<(String, usize, usize)> {
+        let schema = Schema::build(&parse_type_system(schema).unwrap()).unwrap();
+        let document = parse_executable(source).unwrap();
+        validate(&schema, &document)
+            .into_iter()
+            .map(|error| {
+                let location = error.locations[0];
+                (error.message, location.line, location.column)
+            })
+            .collect()
+    }
+
+    #[test]
+    fn valid() {
+        let source = r#"
+query Search($role: Role = ADMIN, $text: String!) {
+  __typename
+  search(role: $role, filter: {text: $text}) {
+    ...UserFields
+    ... on Team { name }
+  }
+  __type(name: "User") { name }
+}
+
+fragment UserFields on User {
+  id
+  name @include(if: true)
+}
+"#;
+        assert_eq!(validate_source(source), vec![]);
+    }
+
+    #[test]
+    fn invalid() {
+        let source = r#"
+query Search($id: ID, $unused: Int, $filter: User) {
+  user(id: $id) { email role { name } }
+  search(role: GUEST, filter: {limit: 1}) { ...TeamFields ...Missing }
+  other: user @skip
+}
+
+fragment TeamFields on Team {
+  name @deprecated
+}
+
+fragment Unused on Role { name }
+"#;
+        assert_eq!(
+            validate_source(source),
+            vec![
+                ("Variable \"$filter\" cannot be non-input type \"User\".".to_owned(), 2, 46),
+                ("Cannot query field \"email\" on type \"User\".".to_owned(), 3, 19),
+                (
+                    "Field \"role\" must not have a selection since type \"Role!\" has no subfields."
+                        .to_owned(),
+                    3,
+                    32
+                ),
+                (
+                    "Argument \"role\" has invalid value GUEST; Value \"GUEST\" does not exist in \"Role\" enum."
+                        .to_owned(),
+                    4,
+                    16
+                ),
+                (
+                    "Argument \"filter\" has invalid value {limit: 1}; Field \"Filter.text\" of required type \"String!\" was not provided."
+                        .to_owned(),
+                    4,
+                    31
+                ),
+                ("Directive \"@deprecated\" may not be used on FIELD.".to_owned(), 9, 9),
+                ("Unknown fragment \"Missing\".".to_owned(), 4, 62),
+                ("Directive \"@skip\" argument \"if\" of type \"Boolean!\" is required, but it was not provided.".to_owned(), 5, 16),
+                ("Field \"Query.user\" argument \"id\" of type \"ID!\" is required, but it was not provided.".to_owned(), 5, 10),
+                (
+                    "Field \"user\" of type \"User\" must have a selection of subfields. Did you mean \"user { ... }\"?"
+                        .to_owned(),
+                    5,
+                    10
+                ),
+                ("Variable \"$id\" of type \"ID\" used in position expecting type \"ID!\".".to_owned(), 3, 12),
+                ("Variable \"$unused\" is never used in operation \"Search\".".to_owned(), 2, 23),
+                ("Variable \"$filter\" is never used in operation \"Search\".".to_owned(), 2, 37),
+                ("Fragment \"Unused\" is never used.".to_owned(), 12, 1),
+            ]
+        );
+    }
+
+    #[test]
+    fn incremental() {
+        let schema = format!(
+            "{}\ntype Mutation {{\n  rename(name: String!): User\n}}\n",
+            SCHEMA
+        );
+        let source = r#"
+query Search {
+  search {
+    ...UserFields
+    ... on Team @defer(label: "team") { name }
+  }
+}
+
+fragment UserFields on User {
+  id
+  ... @defer { role }
+}
+"#;
+        assert_eq!(validate_source_with(&schema, source), vec![]);
+
+        let source = r#"
+query Search($label: String) {
+  search @stream(label: "search", initialCount: 1) {
+    ... on User @defer(label: "search") { id }
+  }
+  user(id: "1") @stream {
+    ...UserFields @defer(label: $label)
+  }
+}
+
+mutation Rename {
+  ... @defer { rename(name: "Ada") { id } }
+}
+
+fragment UserFields on User { name }
+"#;
+        assert_eq!(
+            validate_source_with(&schema, source),
+            vec![
+                (
+                    "Defer/Stream directive label argument must be unique.".to_owned(),
+                    4,
+                    31
+                ),
+                (
+                    "Stream directive cannot be used on non-list field \"user\" on type \"Query\"."
+                        .to_owned(),
+                    6,
+                    18
+                ),
+                (
+                    "Defer/Stream directive label argument must be a static string.".to_owned(),
+                    7,
+                    33
+                ),
+                (
+                    "Defer directive cannot be used on root mutation type \"Mutation\".".to_owned(),
+                    12,
+                    8
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn overlapping_fields() {
+        let source = r#"
+{
+  user(id: "1") { name: id name }
+  other: user(id: "1") { id }
+  other: user(id: "2") { id }
+  search {
+    ... on User { key: id title: name }
+    ... on Team { key: name title: name }
+  }
+  nested: user(id: "1") { info: name }
+  nested: user(id: "1") { ...Info }
+}
+
+fragment Info on User { info: role }
+"#;
+        assert_eq!(
+            validate_source(source),
+            vec![
+                (
+                    "Fields \"other\" conflict because they have differing arguments. Use different aliases on the fields to fetch both if this was intentional."
+                        .to_owned(),
+                    4,
+                    3
+                ),
+                (
+                    "Fields \"nested\" conflict because subfields \"info\" conflict because \"name\" and \"role\" are different fields. Use different aliases on the fields to fetch both if this was intentional."
+                        .to_owned(),
+                    10,
+                    3
+                ),
+                (
+                    "Fields \"name\" conflict because \"id\" and \"name\" are different fields. Use different aliases on the fields to fetch both if this was intentional."
+                        .to_owned(),
+                    3,
+                    19
+                ),
+                (
+                    "Fields \"key\" conflict because they return conflicting types \"ID!\" and \"String\". Use different aliases on the fields to fetch both if this was intentional."
+                        .to_owned(),
+                    7,
+                    19
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn fragment_cycle() {
+        let source = r#"
+{
+  user(id: "1") { ...First }
+}
+
+fragment First on User { name ...Second }
+
+fragment Second on User { id ... on User { ...First } }
+
+fragment Third on User { ...Third }
+"#;
+        assert_eq!(
+            validate_source(source),
+            vec![
+                (
+                    "Cannot spread fragment \"First\" within itself via \"Second\".".to_owned(),
+                    6,
+                    31
+                ),
+                (
+                    "Cannot spread fragment \"Third\" within itself.".to_owned(),
+                    10,
+                    26
+                ),
+                ("Fragment \"Third\" is never used.".to_owned(), 10, 1),
+            ]
+        );
+    }
+}
@@ -0,0 +1,38 @@
+use graphql_parser::Value;
+use serde_json::Value as JsonValue;
+
+pub fn json_to_value(json: &JsonValue) -> Value {
+    match json {
+        JsonValue::Null => Value::Null,
+        JsonValue::Bool(boolean) => Value::Boolean(*boolean),
+        JsonValue::Number(number) => match number.as_i64() {
+            Some(int) => Value::Int(int),
+            None => Value::Float(number.as_f64().unwrap_or(f64::NAN)),
+        },
+        JsonValue::String(string) => Value::String(string.clone()),
+        JsonValue::Array(list) => Value::List(list.iter().map(json_to_value).collect()),
+        JsonValue::Object(object) => Value::Object(
+            object
+                .iter()
+                .map(|(name, value)| (name.clone(), json_to_value(value)))
+                .collect(),
+        ),
+    }
+}
+
+pub fn value_to_json(value: &Value) -> JsonValue {
+    match value {
+        Value::Null | Value::Variable(_) => JsonValue::Null,
+        Value::Boolean(boolean) => JsonValue::Bool(*boolean),
+        Value::Int(int) => JsonValue::from(*int),
+        Value::Float(float) => JsonValue::from(*float),
+        Value::Enum(name) | Value::String(name) => JsonValue::String(name.clone()),
+        Value::List(list) => JsonValue::Array(list.iter().map(value_to_json).collect()),
+        Value::Object(object) => JsonValue::Object(
+            object
+                .iter()
+                .map(|(name, value)| (name.clone(), value_to_json(value)))
+                .collect(),
+        ),
+    }
+}
@@ -0,0 +1,206 @@
+use graphql_executor::Schema;
+use graphql_parser::OperationType;
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token<'a> {
+    Name(&'a str),
+    Punctuator(char),
+    Spread,
+    Value,
+}
+
+/// The type whose fields can be selected at `offset`, if it is inside a selection set.
+///
+/// Documents being edited rarely parse, so this scans the text before `offset`, keeping
+/// the type of every open selection set on a stack.
+pub fn parent_type(schema: &Schema, source: &str, offset: usize) -> Option<String> {
+    let mut stack: Vec<Option<String>> = vec![];
+    let mut head: Vec<Token> = vec![];
+    let mut paren_depth = 0usize;
+    for token in tokenize(&source[..offset.min(source.len())]) {
+        match token {
+            Token::Punctuator('{') if paren_depth == 0 => {
+                let ty = if stack.is_empty() {
+                    definition_type(schema, &head)
+                } else {
+                    selection_type(schema, stack.last().cloned().flatten(), &head)
+                };
+                stack.push(ty);
+                head.clear();
+            }
+            Token::Punctuator('}') if paren_depth == 0 => {
+                stack.pop();
+                head.clear();
+            }
+            token => {
+                match token {
+                    Token::Punctuator('(') => paren_depth += 1,
+                    Token::Punctuator(')') => paren_depth = paren_depth.saturating_sub(1),
+                    _ => {}
+                }
+                head.push(token);
+            }
+        }
+    }
+    if paren_depth > 0 {
+        return None;
+    }
+    stack.pop().flatten()
+}
+
+/// The type selected by a top-level `{`, from the tokens of the definition before it.
+fn definition_type(schema: &Schema, head: &[Token]) -> Option<String> {
+    let operation_type = match head.first() {
+        None => OperationType::Query,
+        Some(Token::Name("query")) => OperationType::Query,
+        Some(Token::Name("mutation")) => OperationType::Mutation,
+        Some(Token::Name("subscription")) => OperationType::Subscription,
+        Some(Token::Name("fragment")) => {
+            return match head {
+                [_, Token::Name(_), Token::Name("on"), Token::Name(ty), ..] => {
+                    Some((*ty).to_owned())
+                }
+                _ => None,
+            };
+        }
+        _ => return None,
+    };
+    schema.root_type(&operation_type).map(|ty| ty.name.clone())
+}
+
+/// The type selected by a nested `{`, from the selections before it in the enclosing set.
+fn selection_type(schema: &Schema, parent: Option<String>, head: &[Token]) -> Option<String> {
+    let mut head = head;
+    loop {
+        match head {
+            [rest @ .., Token::Punctuator(')')] => head = strip_group(rest),
+            [rest @ .., Token::Punctuator('@'), Token::Name(_)] => head = rest,
+            _ => break,
+        }
+    }
+    match head {
+        [.., Token::Spread, Token::Name("on"), Token::Name(ty)] => Some((*ty).to_owned()),
+        [.., Token::Spread] => parent,
+        [.., Token::Name(name)] => schema
+            .get_type(&parent?)?
+            .field(name)
+            .map(|field| field.ty.name().to_owned()),
+        _ => None,
+    }
+}
+
+/// Drops the tokens of a parenthesized group whose closing `)` was already removed.
+fn strip_group<'a, 'b>(head: &'b [Token<'a>]) -> &'b [Token<'a>] {
+    let mut depth = 1;
+    for (index, token) in head.iter().enumerate().rev() {
+        match token {
+            Token::Punctuator(')') => depth += 1,
+            Token::Punctuator('(') => {
+                depth -= 1;
+                if depth == 0 {
+                    return &head[..index];
+                }
+            }
+            _ => {}
+        }
+    }
+    &[]
+}
+
+fn tokenize(source: &str) -> Vec<Token<'_>> {
+    let mut token_list = vec![];
+    let mut index = 0;
+    let bytes = source.as_bytes();
+    while index < bytes.len() {
+        let rest = &source[index..];
+        let c = bytes[index];
+        if c == b'#' {
+            index += rest.find('\n').unwrap_or(rest.len());
+        } else if let Some(block) = rest.strip_prefix("\"\"\"") {
+            index += 3 + block
+                .find("\"\"\"")
+                .map(|end| end + 3)
+                .unwrap_or(block.len());
+            token_list.push(Token::Value);
+        } else if c == b'"' {
+            let mut end = 1;
+            let mut is_escaped = false;
+            for (position, c) in rest.char_indices().skip(1) {
+                end = position + c.len_utf8();
+                if is_escaped {
+                    is_escaped = false;
+                } else if c == '\\' {
+                    is_escaped = true;
+                } else if c == '"' || c == '\n' {
+                    break;
+                }
+            }
+            index += end;
+            token_list.push(Token::Value);
+        } else if rest.starts_with("...") {
+            index += 3;
+            token_list.push(Token::Spread);
+        } else if c == b'_' || c.is_ascii_alphabetic() {
+            let end = rest
+                .find(|c: char| c != '_' && !c.is_ascii_alphanumeric())
+                .unwrap_or(rest.len());
+            token_list.push(Token::Name(&rest[..end]));
+            index += end;
+        } else if c == b'-' || c.is_ascii_digit() {
+            let end = rest[1..]
+                .find(|c: char| !c.is_ascii_alphanumeric() && c != '.' && c != '+' && c != '-')
+                .map(|end| end + 1)
+                .unwrap_or(rest.len());
+            token_list.push(Token::Value);
+            index += end;
+        } else if c.is_ascii_whitespace() || c == b',' {
+            index += 1;
+        } else {
+            let c = rest.chars().next().unwrap();
+            token_list.push(Token::Punctuator(c));
+            index += c.len_utf8();
+        }
+    }
+    token_list
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use graphql_parser::parse_type_system;
+
+    #[test]
+    fn parent() {
+        let schema = Schema::build(
+            &parse_type_system(
+                "type Query { hero(id: ID): Hero }
+                type Hero { name: String, friend_list: [Hero!]! }
+                type Droid { model: String }",
+            )
+            .unwrap(),
+        )
+        .unwrap();
+        let parent = |source: &str| parent_type(&schema, source, source.len());
+
+        assert_eq!(parent("{ "), Some("Query".to_owned()));
+        assert_eq!(
+            parent("query Hero($id: ID = \"{\") { hero(id: $id) { name "),
+            Some("Hero".to_owned())
+        );
+        assert_eq!(
+            parent("{ best: hero @include(if: true) { friend_list { "),
+            Some("Hero".to_owned())
+        );
+        assert_eq!(
+            parent("{ hero { friend_list { name } ... { "),
+            Some("Hero".to_owned())
+        );
+        assert_eq!(
+            parent("fragment Model on Droid { ... on Hero { name } "),
+            Some("Droid".to_owned())
+        );
+        assert_eq!(parent("{ hero(id: "), None);
+        assert_eq!(parent("type Hero { "), None);
+        assert_eq!(parent("{ hero { name } } "), None);
+    }
+}
@@ -0,0 +1,14 @@
+#![warn(clippy::all)]
+
+mod completion;
+mod line_index;
+mod protocol;
+mod server;
+mod symbol;
+mod workspace;
+
+pub use line_index::*;
+pub use protocol::*;
+pub use server::*;
+pub use symbol::*;
+pub use workspace::*;
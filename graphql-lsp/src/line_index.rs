@@ -0,0 +1,104 @@
+use crate::*;
+
+/// Converts between byte offsets into a source and LSP positions, which count lines from
+/// zero and characters in UTF-16 code units.
+pub struct LineIndex<'a> {
+    source: &'a str,
+    line_start_list: Vec<usize>,
+}
+
+impl<'a> LineIndex<'a> {
+    pub fn new(source: &'a str) -> Self {
+        let mut line_start_list = vec![0];
+        line_start_list.extend(
+            source
+                .char_indices()
+                .filter(|(_, c)| *c == '\n')
+                .map(|(index, _)| index + 1),
+        );
+        LineIndex {
+            source,
+            line_start_list,
+        }
+    }
+
+    pub fn position(&self, offset: usize) -> Position {
+        let offset = offset.min(self.source.len());
+        let line = match self.line_start_list.binary_search(&offset) {
+            Ok(line) => line,
+            Err(line) => line - 1,
+        };
+        let line_start = self.line_start_list[line];
+        let character = self.source[line_start..offset]
+            .chars()
+            .map(char::len_utf16)
+            .sum();
+        Position { line, character }
+    }
+
+    /// The offset of `position`, clamped to the end of its line.
+    pub fn offset(&self, position: Position) -> usize {
+        let line_start = match self.line_start_list.get(position.line) {
+            Some(line_start) => *line_start,
+            None => return self.source.len(),
+        };
+        let mut character = 0;
+        for (index, c) in self.source[line_start..].char_indices() {
+            if character >= position.character || c == '\n' {
+                return line_start + index;
+            }
+            character += c.len_utf16();
+        }
+        self.source.len()
+    }
+
+    pub fn range(&self, start: usize, end: usize) -> Range {
+        Range {
+            start: self.position(start),
+            end: self.position(end),
+        }
+    }
+
+    /// The offset of a parser position, whose line and column count from one and whose
+    /// column counts bytes.
+    pub fn offset_of_location(&self, line: usize, column: usize) -> usize {
+        match self.line_start_list.get(line.saturating_sub(1)) {
+            Some(line_start) => (line_start + column.saturating_sub(1)).min(self.source.len()),
+            None => self.source.len(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn convert() {
+        let source = "type Query {\n  \u{1F600}: String\n}";
+        let index = LineIndex::new(source);
+        let name = source.find(": String").unwrap();
+        assert_eq!(
+            index.position(name),
+            Position {
+                line: 1,
+                character: 4
+            }
+        );
+        assert_eq!(
+            index.offset(Position {
+                line: 1,
+                character: 4
+            }),
+            name
+        );
+        assert_eq!(
+            index.offset(Position {
+                line: 0,
+                character: 99
+            }),
+            12
+        );
+        assert_eq!(index.offset_of_location(3, 1), source.len() - 1);
+    }
+}
@@ -0,0 +1,9 @@
+#![warn(clippy::all)]
+
+fn main() {
+    let stdin = std::io::stdin();
+    if let Err(error) = graphql_lsp::serve(stdin.lock(), std::io::stdout()) {
+        eprintln!("{}", error);
+        std::process::exit(1);
+    }
+}
@@ -0,0 +1,405 @@
+use crate::*;
+use serde::Serialize;
+use serde_json::{json, Value as JsonValue};
+use std::io::{self, BufRead, Write};
+use std::path::{Path, PathBuf};
+
+const METHOD_NOT_FOUND: i64 = -32601;
+const INVALID_PARAMS: i64 = -32602;
+
+/// Serves the Language Server Protocol over `reader` and `writer` until the client sends
+/// `exit` or closes the stream.
+///
+/// The schema is read from the files named by the `schema` initialization option, a path
+/// or a list of paths relative to the workspace root.
+pub fn serve<R: BufRead, W: Write>(mut reader: R, writer: W) -> io::Result<()> {
+    let mut server = Server {
+        workspace: Workspace::new(),
+        writer,
+    };
+    while let Some(message) = read_message(&mut reader)? {
+        if !server.handle(message)? {
+            break;
+        }
+    }
+    Ok(())
+}
+
+struct Server<W> {
+    workspace: Workspace,
+    writer: W,
+}
+
+impl<W: Write> Server<W> {
+    /// Handles a message, returning whether to keep serving.
+    fn handle(&mut self, message: JsonValue) -> io::Result<bool> {
+        let method = message["method"].as_str().unwrap_or_default();
+        let params = &message["params"];
+        let id = match message.get("id") {
+            Some(id) => id.clone(),
+            None => {
+                if method == "exit" {
+                    return Ok(false);
+                }
+                self.notification(method, params)?;
+                return Ok(true);
+            }
+        };
+
+        let result = match method {
+            "initialize" => Ok(self.initialize(params)),
+            "shutdown" => Ok(JsonValue::Null),
+            "textDocument/definition" => position_params(params)
+                .map(|(uri, position)| to_json(self.workspace.definition(&uri, position))),
+            "textDocument/references" => position_params(params).map(|(uri, position)| {
+                let include_declaration = params["context"]["includeDeclaration"]
+                    .as_bool()
+                    .unwrap_or(false);
+                to_json(
+                    self.workspace
+                        .references(&uri, position, include_declaration),
+                )
+            }),
+            "textDocument/hover" => position_params(params)
+                .map(|(uri, position)| to_json(self.workspace.hover(&uri, position))),
+            "textDocument/completion" => position_params(params)
+                .map(|(uri, position)| to_json(self.workspace.completion(&uri, position))),
+            "textDocument/documentSymbol" => {
+                document_uri(params).map(|uri| to_json(self.workspace.document_symbol_list(&uri)))
+            }
+            "textDocument/formatting" => {
+                document_uri(params).map(|uri| to_json(self.workspace.formatting(&uri)))
+            }
+            _ => Err((METHOD_NOT_FOUND, format!("Unknown method \"{}\".", method))),
+        };
+        let response = match result {
+            Ok(result) => json!({ "jsonrpc": "2.0", "id": id, "result": result }),
+            Err((code, message)) => json!({
+                "jsonrpc": "2.0",
+                "id": id,
+                "error": { "code": code, "message": message },
+            }),
+        };
+        self.send(&response)?;
+        Ok(true)
+    }
+
+    fn notification(&mut self, method: &str, params: &JsonValue) -> io::Result<()> {
+        let uri = match document_uri(params) {
+            Ok(uri) => uri,
+            Err(_) => return Ok(()),
+        };
+        match method {
+            "textDocument/didOpen" => {
+                let text = params["textDocument"]["text"].as_str().unwrap_or_default();
+                if self.workspace.is_schema(&uri) {
+                    self.workspace.open_schema(&uri, text.to_owned());
+                } else {
+                    self.workspace.open(&uri, text.to_owned());
+                }
+            }
+            "textDocument/didChange" => {
+                let change_list = match params["contentChanges"].as_array() {
+                    Some(change_list) => change_list,
+                    None => return Ok(()),
+                };
+                for change in change_list.iter() {
+                    let text = change["text"].as_str().unwrap_or_default();
+                    match serde_json::from_value::<Range>(change["range"].clone()) {
+                        Ok(range) => self.workspace.edit(&uri, range, text),
+                        Err(_) => self.workspace.open(&uri, text.to_owned()),
+                    }
+                }
+            }
+            "textDocument/didClose" => {
+                if !self.workspace.is_schema(&uri) {
+                    self.workspace.close(&uri);
+                    return self.publish_diagnostics(&uri, vec![]);
+                }
+                return Ok(());
+            }
+            _ => return Ok(()),
+        }
+
+        // A change to the schema can change the diagnostics of every document.
+        let uri_list: Vec<String> = if self.workspace.is_schema(&uri) {
+            self.workspace.uri_list().map(str::to_owned).collect()
+        } else {
+            vec![uri]
+        };
+        for uri in uri_list {
+            let diagnostic_list = self.workspace.diagnostic_list(&uri);
+            self.publish_diagnostics(&uri, diagnostic_list)?;
+        }
+        Ok(())
+    }
+
+    fn initialize(&mut self, params: &JsonValue) -> JsonValue {
+        let root = params["rootUri"]
+            .as_str()
+            .and_then(uri_to_path)
+            .or_else(|| params["rootPath"].as_str().map(PathBuf::from))
+            .unwrap_or_default();
+        let schema = &params["initializationOptions"]["schema"];
+        let path_list: Vec<&str> = match schema {
+            JsonValue::String(path) => vec![path.as_str()],
+            JsonValue::Array(path_list) => path_list.iter().filter_map(JsonValue::as_str).collect(),
+            _ => vec![],
+        };
+        for path in path_list {
+            let path = root.join(path);
+            match std::fs::read_to_string(&path) {
+                Ok(text) => self.workspace.open_schema(&path_to_uri(&path), text),
+                Err(error) => eprintln!("Cannot read {}: {}", path.display(), error),
+            }
+        }
+
+        json!({
+            "capabilities": {
+                "textDocumentSync": 2,
+                "definitionProvider": true,
+                "referencesProvider": true,
+                "hoverProvider": true,
+                "completionProvider": {},
+                "documentSymbolProvider": true,
+                "documentFormattingProvider": true,
+            },
+            "serverInfo": { "name": "graphql-lsp" },
+        })
+    }
+
+    fn publish_diagnostics(
+        &mut self,
+        uri: &str,
+        diagnostic_list: Vec<Diagnostic>,
+    ) -> io::Result<()> {
+        self.send(&json!({
+            "jsonrpc": "2.0",
+            "method": "textDocument/publishDiagnostics",
+            "params": { "uri": uri, "diagnostics": diagnostic_list },
+        }))
+    }
+
+    fn send(&mut self, message: &JsonValue) -> io::Result<()> {
+        let body = serde_json::to_string(message)?;
+        write!(
+            self.writer,
+            "Content-Length: {}\r\n\r\n{}",
+            body.len(),
+            body
+        )?;
+        self.writer.flush()
+    }
+}
+
+/// Reads one message framed by a `Content-Length` header, or `None` at the end of input.
+/// Messages that are not JSON are skipped.
+fn read_message<R: BufRead>(reader: &mut R) -> io::Result<Option<JsonValue>> {
+    loop {
+        let mut content_length = None;
+        loop {
+            let mut line = String::new();
+            if reader.read_line(&mut line)? == 0 {
+                return Ok(None);
+            }
+            let line = line.trim_end();
+            if line.is_empty() {
+                break;
+            }
+            if let Some((name, value)) = line.split_once(':') {
+                if name.eq_ignore_ascii_case("Content-Length") {
+                    content_length = value.trim().parse::<usize>().ok();
+                }
+            }
+        }
+        let content_length = match content_length {
+            Some(content_length) => content_length,
+            None => continue,
+        };
+        let mut body = vec![0; content_length];
+        reader.read_exact(&mut body)?;
+        if let Ok(message) = serde_json::from_slice(&body) {
+            return Ok(Some(message));
+        }
+    }
+}
+
+fn to_json<T: Serialize>(value: T) -> JsonValue {
+    serde_json::to_value(value).unwrap()
+}
+
+fn document_uri(params: &JsonValue) -> Result<String, (i64, String)> {
+    params["textDocument"]["uri"]
+        .as_str()
+        .map(str::to_owned)
+        .ok_or_else(|| (INVALID_PARAMS, "Missing text document.".to_owned()))
+}
+
+fn position_params(params: &JsonValue) -> Result<(String, Position), (i64, String)> {
+    let uri = document_uri(params)?;
+    let position = serde_json::from_value(params["position"].clone())
+        .map_err(|_| (INVALID_PARAMS, "Missing position.".to_owned()))?;
+    Ok((uri, position))
+}
+
+/// Converts a `file://` URI to a path, decoding percent escapes.
+pub fn uri_to_path(uri: &str) -> Option<PathBuf> {
+    let path = uri.strip_prefix("file://")?;
+    let bytes = path.as_bytes();
+    let mut decoded = vec![];
+    let mut index = 0;
+    while index < bytes.len() {
+        match (bytes[index], path.get(index + 1..index + 3)) {
+            (b'%', Some(hex)) => {
+                decoded.push(u8::from_str_radix(hex, 16).ok()?);
+                index += 3;
+            }
+            (byte, _) => {
+                decoded.push(byte);
+                index += 1;
+            }
+        }
+    }
+    String::from_utf8(decoded).ok().map(PathBuf::from)
+}
+
+/// Converts a path to a `file://` URI, percent-encoding what a URI path cannot hold.
+pub fn path_to_uri(path: &Path) -> String {
+    let mut uri = "file://".to_owned();
+    for byte in path.to_string_lossy().bytes() {
+        match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'/' | b'-' | b'_' | b'.' | b'~' => {
+                uri.push(byte as char)
+            }
+            byte => uri.push_str(&format!("%{:02X}", byte)),
+        }
+    }
+    uri
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn frame(message: JsonValue) -> String {
+        let body = message.to_string();
+        format!("Content-Length: {}\r\n\r\n{}", body.len(), body)
+    }
+
+    fn message_list(output: &[u8]) -> Vec<JsonValue> {
+        let mut reader = output;
+        let mut message_list = vec![];
+        while let Some(message) = read_message(&mut reader).unwrap() {
+            message_list.push(message);
+        }
+        message_list
+    }
+
+    #[test]
+    fn session() {
+        let root = std::env::temp_dir().join(format!("graphql-lsp-{}", std::process::id()));
+        std::fs::create_dir_all(&root).unwrap();
+        std::fs::write(
+            root.join("schema.graphql"),
+            "type Query { hero: Hero }\ntype Hero { name: String }\n",
+        )
+        .unwrap();
+        let uri = path_to_uri(&root.join("hero.graphql"));
+
+        let input = [
+            frame(json!({
+                "jsonrpc": "2.0",
+                "id": 1,
+                "method": "initialize",
+                "params": {
+                    "rootUri": path_to_uri(&root),
+                    "initializationOptions": { "schema": "schema.graphql" },
+                },
+            })),
+            frame(json!({ "jsonrpc": "2.0", "method": "initialized", "params": {} })),
+            frame(json!({
+                "jsonrpc": "2.0",
+                "method": "textDocument/didOpen",
+                "params": {
+                    "textDocument": { "uri": uri, "languageId": "graphql", "version": 1, "text": "{ hero { email } }" },
+                },
+            })),
+            frame(json!({
+                "jsonrpc": "2.0",
+                "method": "textDocument/didChange",
+                "params": {
+                    "textDocument": { "uri": uri, "version": 2 },
+                    "contentChanges": [{
+                        "range": {
+                            "start": { "line": 0, "character": 9 },
+                            "end": { "line": 0, "character": 14 },
+                        },
+                        "text": "name ",
+                    }],
+                },
+            })),
+            frame(json!({
+                "jsonrpc": "2.0",
+                "id": 2,
+                "method": "textDocument/completion",
+                "params": {
+                    "textDocument": { "uri": uri },
+                    "position": { "line": 0, "character": 9 },
+                },
+            })),
+            frame(json!({ "jsonrpc": "2.0", "id": 3, "method": "workspace/symbol", "params": {} })),
+            frame(json!({ "jsonrpc": "2.0", "id": 4, "method": "shutdown" })),
+            frame(json!({ "jsonrpc": "2.0", "method": "exit" })),
+        ]
+        .concat();
+        let mut output = vec![];
+        serve(input.as_bytes(), &mut output).unwrap();
+        std::fs::remove_dir_all(&root).unwrap();
+
+        let message_list = message_list(&output);
+        assert_eq!(message_list.len(), 6);
+        assert_eq!(
+            message_list[0]["result"]["capabilities"]["textDocumentSync"],
+            2
+        );
+        assert_eq!(
+            message_list[1]["params"],
+            json!({
+                "uri": uri,
+                "diagnostics": [{
+                    "range": {
+                        "start": { "line": 0, "character": 9 },
+                        "end": { "line": 0, "character": 14 },
+                    },
+                    "severity": 1,
+                    "source": "graphql",
+                    "message": "Cannot query field \"email\" on type \"Hero\".",
+                }],
+            })
+        );
+        assert_eq!(
+            message_list[2]["params"],
+            json!({ "uri": uri, "diagnostics": [] })
+        );
+        assert_eq!(
+            message_list[3]["result"],
+            json!([
+                { "label": "name", "kind": 5, "detail": "String" },
+                { "label": "__typename", "kind": 5, "detail": "String!" },
+            ])
+        );
+        assert_eq!(message_list[4]["error"]["code"], METHOD_NOT_FOUND);
+        assert_eq!(
+            message_list[5],
+            json!({ "jsonrpc": "2.0", "id": 4, "result": null })
+        );
+    }
+
+    #[test]
+    fn uri() {
+        let path = Path::new("/home/user/my project/schema.graphql");
+        let uri = path_to_uri(path);
+        assert_eq!(uri, "file:///home/user/my%20project/schema.graphql");
+        assert_eq!(uri_to_path(&uri).unwrap(), path);
+    }
+}
@@ -0,0 +1,298 @@
+use graphql_executor::Schema;
+use graphql_parser::*;
+
+/// Something a name in a document can stand for.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Symbol {
+    Type(String),
+    Fragment(String),
+    Field { parent: String, name: String },
+    Directive(String),
+}
+
+/// A name in a document, as the byte range it covers and what it stands for.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Occurrence {
+    pub symbol: Symbol,
+    pub start: usize,
+    pub end: usize,
+    pub is_definition: bool,
+}
+
+/// Lists every name in `document` that stands for a symbol. Fields of selections are only
+/// resolved when a schema tells what type they are selected on.
+pub fn occurrence_list(
+    document: &Document,
+    source: &str,
+    schema: Option<&Schema>,
+) -> Vec<Occurrence> {
+    let mut collector = Collector {
+        source,
+        schema,
+        occurrence_list: vec![],
+    };
+    for definition in document.definition_list.iter() {
+        collector.collect_definition(&definition.node);
+    }
+    collector.occurrence_list
+}
+
+struct Collector<'a> {
+    source: &'a str,
+    schema: Option<&'a Schema>,
+    occurrence_list: Vec<Occurrence>,
+}
+
+impl<'a> Collector<'a> {
+    fn push(&mut self, symbol: Symbol, name: &Positioned<String>, is_definition: bool) {
+        self.occurrence_list.push(Occurrence {
+            symbol,
+            start: name.start.offset,
+            end: name.end.offset,
+            is_definition,
+        });
+    }
+
+    fn push_type(&mut self, name: &Positioned<String>, is_definition: bool) {
+        self.push(Symbol::Type(name.node.clone()), name, is_definition);
+    }
+
+    /// Types such as `[Hero!]!` are positioned as a whole, so the named type is found in
+    /// their source.
+    fn push_type_reference(&mut self, ty: &Positioned<Type>) {
+        let name = ty.node.name();
+        let text = match self.source.get(ty.start.offset..ty.end.offset) {
+            Some(text) => text,
+            None => return,
+        };
+        if let Some(index) = text.find(name) {
+            let start = ty.start.offset + index;
+            self.occurrence_list.push(Occurrence {
+                symbol: Symbol::Type(name.to_owned()),
+                start,
+                end: start + name.len(),
+                is_definition: false,
+            });
+        }
+    }
+
+    fn collect_definition(&mut self, definition: &DocumentDefinition) {
+        match definition {
+            DocumentDefinition::Operation(operation) => {
+                let operation = &operation.node;
+                for variable in operation.variable_list.iter() {
+                    self.push_type_reference(&variable.node.ty);
+                }
+                self.collect_directive_list(&operation.directive_list);
+                let parent = self
+                    .schema
+                    .and_then(|schema| schema.root_type(&operation.ty))
+                    .map(|ty| ty.name.clone());
+                self.collect_selection_list(parent.as_deref(), &operation.selection_list);
+            }
+            DocumentDefinition::Fragment(fragment) => {
+                let fragment = &fragment.node;
+                self.push(
+                    Symbol::Fragment(fragment.name.node.clone()),
+                    &fragment.name,
+                    true,
+                );
+                self.push_type(&fragment.on, false);
+                self.collect_directive_list(&fragment.directive_list);
+                self.collect_selection_list(Some(&fragment.on.node), &fragment.selection_list);
+            }
+            DocumentDefinition::Schema(schema) => {
+                let schema = &schema.node;
+                self.collect_directive_list(&schema.directive_list);
+                for field in schema.field_list.iter() {
+                    self.push_type(&field.name, false);
+                }
+            }
+            DocumentDefinition::Type(ty) => self.collect_type_definition(&ty.node),
+            DocumentDefinition::Directive(directive) => {
+                let directive = &directive.node;
+                self.push(
+                    Symbol::Directive(directive.name.node.clone()),
+                    &directive.name,
+                    true,
+                );
+                for argument in directive.argument_list.iter() {
+                    self.push_type_reference(&argument.node.ty);
+                    self.collect_directive_list(&argument.node.directive_list);
+                }
+            }
+        }
+    }
+
+    fn collect_type_definition(&mut self, definition: &TypeDefinition) {
+        match definition {
+            TypeDefinition::Scalar(ty) => {
+                self.push_type(&ty.node.name, !ty.node.is_extend);
+                self.collect_directive_list(&ty.node.directive_list);
+            }
+            TypeDefinition::Object(ty) => {
+                self.push_type(&ty.node.name, !ty.node.is_extend);
+                for interface in ty.node.interface_list.iter() {
+                    self.push_type(interface, false);
+                }
+                self.collect_directive_list(&ty.node.directive_list);
+                self.collect_field_definition_list(&ty.node.name.node, &ty.node.field_list);
+            }
+            TypeDefinition::Interface(ty) => {
+                self.push_type(&ty.node.name, !ty.node.is_extend);
+                self.collect_directive_list(&ty.node.directive_list);
+                self.collect_field_definition_list(&ty.node.name.node, &ty.node.field_list);
+            }
+            TypeDefinition::Union(ty) => {
+                self.push_type(&ty.node.name, !ty.node.is_extend);
+                self.collect_directive_list(&ty.node.directive_list);
+                for member in ty.node.member_list.iter() {
+                    self.push_type(member, false);
+                }
+            }
+            TypeDefinition::Enum(ty) => {
+                self.push_type(&ty.node.name, !ty.node.is_extend);
+                self.collect_directive_list(&ty.node.directive_list);
+                for member in ty.node.member_list.iter() {
+                    self.collect_directive_list(&member.node.directive_list);
+                }
+            }
+            TypeDefinition::InputObject(ty) => {
+                self.push_type(&ty.node.name, !ty.node.is_extend);
+                self.collect_directive_list(&ty.node.directive_list);
+                for field in ty.node.field_list.iter() {
+                    self.push_type_reference(&field.node.ty);
+                    self.collect_directive_list(&field.node.directive_list);
+                }
+            }
+        }
+    }
+
+    fn collect_field_definition_list(
+        &mut self,
+        parent: &str,
+        field_list: &[Positioned<FieldDefinition>],
+    ) {
+        for field in field_list.iter() {
+            let field = &field.node;
+            self.push(
+                Symbol::Field {
+                    parent: parent.to_owned(),
+                    name: field.name.node.clone(),
+                },
+                &field.name,
+                true,
+            );
+            for argument in field.argument_list.iter() {
+                self.push_type_reference(&argument.node.ty);
+                self.collect_directive_list(&argument.node.directive_list);
+            }
+            self.push_type_reference(&field.ty);
+            self.collect_directive_list(&field.directive_list);
+        }
+    }
+
+    fn collect_selection_list(
+        &mut self,
+        parent: Option<&str>,
+        selection_list: &[Positioned<Selection>],
+    ) {
+        for selection in selection_list.iter() {
+            match &selection.node {
+                Selection::Field(field) => {
+                    self.collect_directive_list(&field.directive_list);
+                    let parent = match parent {
+                        Some(parent) => parent,
+                        None => {
+                            self.collect_selection_list(None, &field.selection_list);
+                            continue;
+                        }
+                    };
+                    self.push(
+                        Symbol::Field {
+                            parent: parent.to_owned(),
+                            name: field.name.node.clone(),
+                        },
+                        &field.name,
+                        false,
+                    );
+                    let child = self
+                        .schema
+                        .and_then(|schema| schema.get_type(parent))
+                        .and_then(|ty| ty.field(&field.name.node))
+                        .map(|field| field.ty.name().to_owned());
+                    self.collect_selection_list(child.as_deref(), &field.selection_list);
+                }
+                Selection::FragmentSpread(spread) => {
+                    self.push(
+                        Symbol::Fragment(spread.name.node.clone()),
+                        &spread.name,
+                        false,
+                    );
+                    self.collect_directive_list(&spread.directive_list);
+                }
+                Selection::InlineFragment(fragment) => {
+                    self.collect_directive_list(&fragment.directive_list);
+                    match &fragment.on {
+                        Some(on) => {
+                            self.push_type(on, false);
+                            self.collect_selection_list(Some(&on.node), &fragment.selection_list);
+                        }
+                        None => self.collect_selection_list(parent, &fragment.selection_list),
+                    }
+                }
+            }
+        }
+    }
+
+    fn collect_directive_list(&mut self, directive_list: &[Positioned<Directive>]) {
+        for directive in directive_list.iter() {
+            let name = &directive.node.name;
+            self.push(Symbol::Directive(name.node.clone()), name, false);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn occurrence() {
+        let schema_source =
+            "type Query { hero: Hero }\ntype Hero { name: String, friend_list: [Hero!]! }";
+        let schema = Schema::build(&parse_type_system(schema_source).unwrap()).unwrap();
+        let source = "query { hero { ...HeroName friend_list @skip(if: true) { name } } }\nfragment HeroName on Hero { name }";
+        let document = parse_document(source).unwrap();
+        let name_list: Vec<(&str, bool)> = occurrence_list(&document, source, Some(&schema))
+            .iter()
+            .map(|occurrence| {
+                (
+                    &source[occurrence.start..occurrence.end],
+                    occurrence.is_definition,
+                )
+            })
+            .collect();
+        assert_eq!(
+            name_list,
+            vec![
+                ("hero", false),
+                ("HeroName", false),
+                ("skip", false),
+                ("friend_list", false),
+                ("name", false),
+                ("HeroName", true),
+                ("Hero", false),
+                ("name", false),
+            ]
+        );
+
+        let document = parse_document(schema_source).unwrap();
+        let occurrence = occurrence_list(&document, schema_source, None)
+            .into_iter()
+            .filter(|occurrence| occurrence.symbol == Symbol::Type("Hero".to_owned()))
+            .map(|occurrence| (occurrence.start, occurrence.is_definition))
+            .collect::<Vec<_>>();
+        assert_eq!(occurrence, vec![(19, false), (31, true), (66, false)]);
+    }
+}
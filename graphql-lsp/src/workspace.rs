@@ -0,0 +1,831 @@
+use crate::*;
+use graphql_executor::{validate, MetaField, Schema, SchemaError, TypeKind};
+use graphql_parser::{
+    has_comment, parse_document, parse_executable, parse_type_system, print_document,
+    reparse_document, Document, DocumentDefinition, Edit, ExecutableDefinition, ExecutableDocument,
+    FieldDefinition, OperationType, Positioned, TypeDefinition, TypeSystemDefinition,
+    TypeSystemDocument,
+};
+use std::cell::OnceCell;
+use std::collections::BTreeMap;
+
+/// The documents the server knows about, split into the schema they are checked against
+/// and the documents written against it.
+#[derive(Debug, Default)]
+pub struct Workspace {
+    schema_uri_list: Vec<String>,
+    document_map: BTreeMap<String, SourceFile>,
+    /// The schema built from the schema documents, cleared whenever one of them changes.
+    schema: OnceCell<Option<Result<Schema, SchemaError>>>,
+}
+
+#[derive(Debug)]
+struct SourceFile {
+    text: String,
+    /// The parsed text, kept up to date edit by edit, or `None` when it does not parse.
+    document: Option<Document>,
+}
+
+impl SourceFile {
+    fn new(text: String) -> Self {
+        let document = parse_document(&text).ok();
+        SourceFile { text, document }
+    }
+}
+
+impl Workspace {
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    /// Adds a document to the schema. Schema documents stay known after they are closed.
+    pub fn open_schema(&mut self, uri: &str, text: String) {
+        if !self.is_schema(uri) {
+            self.schema_uri_list.push(uri.to_owned());
+        }
+        self.schema.take();
+        self.document_map
+            .insert(uri.to_owned(), SourceFile::new(text));
+    }
+
+    pub fn open(&mut self, uri: &str, text: String) {
+        if self.is_schema(uri) {
+            self.schema.take();
+        }
+        self.document_map
+            .insert(uri.to_owned(), SourceFile::new(text));
+    }
+
+    /// Replaces `range` of an open document with `text`, parsing again only the
+    /// definitions the edit touches.
+    pub fn edit(&mut self, uri: &str, range: Range, text: &str) {
+        if self.is_schema(uri) {
+            self.schema.take();
+        }
+        let file = match self.document_map.get_mut(uri) {
+            Some(file) => file,
+            None => return,
+        };
+        let index = LineIndex::new(&file.text);
+        let start = index.offset(range.start);
+        let old_end = index.offset(range.end).max(start);
+        file.text.replace_range(start..old_end, text);
+        let edit = Edit {
+            start,
+            old_end,
+            new_end: start + text.len(),
+        };
+        file.document = match file.document.take() {
+            Some(mut document) => reparse_document(&mut document, &file.text, &edit)
+                .ok()
+                .map(|_| document),
+            None => parse_document(&file.text).ok(),
+        };
+    }
+
+    pub fn text(&self, uri: &str) -> Option<&str> {
+        self.document_map.get(uri).map(|file| file.text.as_str())
+    }
+
+    pub fn close(&mut self, uri: &str) {
+        if !self.is_schema(uri) {
+            self.document_map.remove(uri);
+        }
+    }
+
+    pub fn is_schema(&self, uri: &str) -> bool {
+        self.schema_uri_list
+            .iter()
+            .any(|schema_uri| schema_uri == uri)
+    }
+
+    pub fn uri_list(&self) -> impl Iterator<Item = &str> {
+        self.document_map.keys().map(String::as_str)
+    }
+
+    /// The schema built from every schema document, which all need to parse.
+    ///
+    /// It is built once and kept until a schema document is opened or edited.
+    pub fn schema(&self) -> Option<Result<Schema, SchemaError>> {
+        self.schema.get_or_init(|| self.build_schema()).clone()
+    }
+
+    fn build_schema(&self) -> Option<Result<Schema, SchemaError>> {
+        if self.schema_uri_list.is_empty() {
+            return None;
+        }
+        let mut definition_list = vec![];
+        for uri in self.schema_uri_list.iter() {
+            let document = self.document_map.get(uri)?.document.as_ref()?;
+            definition_list.extend(type_system_definition_list(document)?);
+        }
+        Some(Schema::build(&TypeSystemDocument { definition_list }))
+    }
+
+    pub fn diagnostic_list(&self, uri: &str) -> Vec<Diagnostic> {
+        let file = match self.document_map.get(uri) {
+            Some(file) => file,
+            None => return vec![],
+        };
+        let source = &file.text;
+        let index = LineIndex::new(source);
+        if self.is_schema(uri) {
+            // The document is only parsed again to locate the error when it is not a schema.
+            let is_schema = file
+                .document
+                .as_ref()
+                .and_then(type_system_definition_list)
+                .is_some();
+            if !is_schema {
+                if let Err(error) = parse_type_system(source) {
+                    return vec![diagnostic(
+                        &index,
+                        source,
+                        error.position().offset,
+                        "Syntax Error",
+                    )];
+                }
+            }
+            return match self.schema() {
+                Some(Err(error)) => vec![diagnostic(&index, source, 0, &error.to_string())],
+                _ => vec![],
+            };
+        }
+
+        let document = match file.document.as_ref().and_then(executable_document) {
+            Some(document) => Ok(document),
+            None => parse_executable(source),
+        };
+        let document = match document {
+            Ok(document) => document,
+            Err(error) => {
+                return vec![diagnostic(
+                    &index,
+                    source,
+                    error.position().offset,
+                    "Syntax Error",
+                )]
+            }
+        };
+        let schema = match self.schema() {
+            Some(Ok(schema)) => schema,
+            _ => return vec![],
+        };
+        validate(&schema, &document)
+            .into_iter()
+            .map(|error| {
+                let offset = error
+                    .locations
+                    .first()
+                    .map(|location| index.offset_of_location(location.line, location.column))
+                    .unwrap_or(0);
+                diagnostic(&index, source, offset, &error.message)
+            })
+            .collect()
+    }
+
+    pub fn definition(&self, uri: &str, position: Position) -> Vec<Location> {
+        match self.symbol_at(uri, position) {
+            Some((symbol, _)) => self.location_list(&symbol, true),
+            None => vec![],
+        }
+    }
+
+    pub fn references(
+        &self,
+        uri: &str,
+        position: Position,
+        include_declaration: bool,
+    ) -> Vec<Location> {
+        match self.symbol_at(uri, position) {
+            Some((symbol, _)) => {
+                let mut location_list = self.location_list(&symbol, false);
+                if include_declaration {
+                    location_list.extend(self.location_list(&symbol, true));
+                }
+                location_list
+            }
+            None => vec![],
+        }
+    }
+
+    pub fn hover(&self, uri: &str, position: Position) -> Option<Hover> {
+        let (symbol, range) = self.symbol_at(uri, position)?;
+        let value = match &symbol {
+            Symbol::Type(name) => {
+                let schema = self.schema()?.ok()?;
+                let ty = schema.get_type(name)?;
+                with_description(
+                    format!("{} {}", kind_keyword(ty.kind), ty.name),
+                    ty.description.as_deref(),
+                )
+            }
+            Symbol::Field { parent, name } => {
+                let schema = self.schema()?.ok()?;
+                let field = schema.get_type(parent)?.field(name)?;
+                let mut value = with_description(
+                    format!("{}.{}{}", parent, field.name, field_signature(field)),
+                    field.description.as_deref(),
+                );
+                if let Some(reason) = &field.deprecation {
+                    value.push_str(&format!("\n\n_Deprecated: {}_", reason));
+                }
+                value
+            }
+            Symbol::Directive(name) => {
+                let schema = self.schema()?.ok()?;
+                let directive = schema.get_directive(name)?;
+                with_description(
+                    format!("directive @{}", directive.name),
+                    directive.description.as_deref(),
+                )
+            }
+            Symbol::Fragment(name) => {
+                let location = self.location_list(&symbol, true).into_iter().next()?;
+                let document = self.document_map[&location.uri].document.as_ref()?;
+                let on =
+                    document.definition_list.iter().find_map(|definition| {
+                        match &definition.node {
+                            DocumentDefinition::Fragment(fragment)
+                                if &fragment.node.name.node == name =>
+                            {
+                                Some(fragment.node.on.node.clone())
+                            }
+                            _ => None,
+                        }
+                    })?;
+                with_description(format!("fragment {} on {}", name, on), None)
+            }
+        };
+        Some(Hover {
+            contents: MarkupContent::markdown(value),
+            range,
+        })
+    }
+
+    pub fn completion(&self, uri: &str, position: Position) -> Vec<CompletionItem> {
+        let source = match self.text(uri) {
+            Some(source) if !self.is_schema(uri) => source,
+            _ => return vec![],
+        };
+        let schema = match self.schema() {
+            Some(Ok(schema)) => schema,
+            _ => return vec![],
+        };
+        let offset = LineIndex::new(source).offset(position);
+        let ty = match completion::parent_type(&schema, source, offset)
+            .and_then(|name| schema.get_type(&name))
+        {
+            Some(ty) if ty.is_composite() => ty,
+            _ => return vec![],
+        };
+        let mut item_list: Vec<CompletionItem> = ty
+            .field_list
+            .iter()
+            .map(|field| CompletionItem {
+                label: field.name.clone(),
+                kind: CompletionItemKind::Field,
+                detail: Some(field.ty.to_string()),
+                documentation: field.description.clone().map(MarkupContent::markdown),
+                deprecated: field.deprecation.is_some(),
+            })
+            .collect();
+        item_list.push(CompletionItem {
+            label: "__typename".to_owned(),
+            kind: CompletionItemKind::Field,
+            detail: Some("String!".to_owned()),
+            documentation: None,
+            deprecated: false,
+        });
+        item_list
+    }
+
+    pub fn document_symbol_list(&self, uri: &str) -> Vec<DocumentSymbol> {
+        let (source, document) = match self.document_map.get(uri) {
+            Some(SourceFile {
+                text,
+                document: Some(document),
+            }) => (text, document),
+            _ => return vec![],
+        };
+        let index = LineIndex::new(source);
+        document
+            .definition_list
+            .iter()
+            .map(|definition| definition_symbol(&index, definition))
+            .collect()
+    }
+
+    /// Replaces the whole document with its canonical form, unless it has comments the
+    /// printer would drop.
+    pub fn formatting(&self, uri: &str) -> Vec<TextEdit> {
+        let (source, document) = match self.document_map.get(uri) {
+            Some(SourceFile {
+                text,
+                document: Some(document),
+            }) if !has_comment(text) => (text, document),
+            _ => return vec![],
+        };
+        let formatted = print_document(document);
+        if &formatted == source {
+            return vec![];
+        }
+        vec![TextEdit {
+            range: LineIndex::new(source).range(0, source.len()),
+            new_text: formatted,
+        }]
+    }
+
+    fn occurrence_list(&self, uri: &str, schema: Option<&Schema>) -> Vec<Occurrence> {
+        match self.document_map.get(uri) {
+            Some(SourceFile {
+                text,
+                document: Some(document),
+            }) => symbol::occurrence_list(document, text, schema),
+            _ => vec![],
+        }
+    }
+
+    fn symbol_at(&self, uri: &str, position: Position) -> Option<(Symbol, Range)> {
+        let source = self.text(uri)?;
+        let schema = self.schema().and_then(Result::ok);
+        let index = LineIndex::new(source);
+        let offset = index.offset(position);
+        self.occurrence_list(uri, schema.as_ref())
+            .into_iter()
+            .find(|occurrence| occurrence.start <= offset && offset <= occurrence.end)
+            .map(|occurrence| {
+                let range = index.range(occurrence.start, occurrence.end);
+                (occurrence.symbol, range)
+            })
+    }
+
+    fn location_list(&self, symbol: &Symbol, is_definition: bool) -> Vec<Location> {
+        let schema = self.schema().and_then(Result::ok);
+        let mut location_list = vec![];
+        for (uri, file) in self.document_map.iter() {
+            let index = LineIndex::new(&file.text);
+            location_list.extend(
+                self.occurrence_list(uri, schema.as_ref())
+                    .into_iter()
+                    .filter(|occurrence| {
+                        &occurrence.symbol == symbol && occurrence.is_definition == is_definition
+                    })
+                    .map(|occurrence| Location {
+                        uri: uri.clone(),
+                        range: index.range(occurrence.start, occurrence.end),
+                    }),
+            );
+        }
+        location_list
+    }
+}
+
+/// The definitions of a document that only describes a schema.
+fn type_system_definition_list(
+    document: &Document,
+) -> Option<Vec<Positioned<TypeSystemDefinition>>> {
+    document
+        .definition_list
+        .iter()
+        .map(|definition| {
+            let node = match &definition.node {
+                DocumentDefinition::Schema(schema) => TypeSystemDefinition::Schema(schema.clone()),
+                DocumentDefinition::Type(ty) => TypeSystemDefinition::Type(ty.clone()),
+                DocumentDefinition::Directive(directive) => {
+                    TypeSystemDefinition::Directive(directive.clone())
+                }
+                _ => return None,
+            };
+            Some(Positioned {
+                start: definition.start.clone(),
+                end: definition.end.clone(),
+                node,
+            })
+        })
+        .collect()
+}
+
+/// A document that only holds operations and fragments.
+fn executable_document(document: &Document) -> Option<ExecutableDocument> {
+    let definition_list = document
+        .definition_list
+        .iter()
+        .map(|definition| {
+            let node = match &definition.node {
+                DocumentDefinition::Operation(operation) => {
+                    ExecutableDefinition::Operation(operation.clone())
+                }
+                DocumentDefinition::Fragment(fragment) => {
+                    ExecutableDefinition::Fragment(fragment.clone())
+                }
+                _ => return None,
+            };
+            Some(Positioned {
+                start: definition.start.clone(),
+                end: definition.end.clone(),
+                node,
+            })
+        })
+        .collect::<Option<_>>()?;
+    Some(ExecutableDocument { definition_list })
+}
+
+/// A diagnostic covering the name or punctuator at `offset`.
+fn diagnostic(index: &LineIndex, source: &str, offset: usize, message: &str) -> Diagnostic {
+    let rest = &source[offset.min(source.len())..];
+    let length = match rest.find(|c: char| c != '_' && !c.is_ascii_alphanumeric()) {
+        Some(0) => rest.chars().next().map(char::len_utf8).unwrap_or(0),
+        Some(length) => length,
+        None => rest.len(),
+    };
+    Diagnostic {
+        range: index.range(offset, offset + length),
+        severity: DiagnosticSeverity::Error,
+        source: "graphql".to_owned(),
+        message: message.to_owned(),
+    }
+}
+
+fn with_description(signature: String, description: Option<&str>) -> String {
+    match description {
+        Some(description) => format!("```graphql\n{}\n```\n\n{}", signature, description),
+        None => format!("```graphql\n{}\n```", signature),
+    }
+}
+
+fn kind_keyword(kind: TypeKind) -> &'static str {
+    match kind {
+        TypeKind::Scalar => "scalar",
+        TypeKind::Object => "type",
+        TypeKind::Interface => "interface",
+        TypeKind::Union => "union",
+        TypeKind::Enum => "enum",
+        TypeKind::InputObject => "input",
+    }
+}
+
+fn field_signature(field: &MetaField) -> String {
+    let argument_list: Vec<String> = field
+        .argument_list
+        .iter()
+        .map(|argument| format!("{}: {}", argument.name, argument.ty))
+        .collect();
+    if argument_list.is_empty() {
+        format!(": {}", field.ty)
+    } else {
+        format!("({}): {}", argument_list.join(", "), field.ty)
+    }
+}
+
+fn symbol<T>(
+    index: &LineIndex,
+    positioned: &Positioned<T>,
+    name: &Positioned<String>,
+    detail: Option<String>,
+    kind: SymbolKind,
+    children: Vec<DocumentSymbol>,
+) -> DocumentSymbol {
+    DocumentSymbol {
+        name: name.node.clone(),
+        detail,
+        kind,
+        range: index.range(positioned.start.offset, positioned.end.offset),
+        selection_range: index.range(name.start.offset, name.end.offset),
+        children,
+    }
+}
+
+fn definition_symbol(
+    index: &LineIndex,
+    definition: &Positioned<DocumentDefinition>,
+) -> DocumentSymbol {
+    let whole = index.range(definition.start.offset, definition.end.offset);
+    match &definition.node {
+        DocumentDefinition::Operation(operation) => {
+            let keyword = match operation.node.ty {
+                OperationType::Query => "query",
+                OperationType::Mutation => "mutation",
+                OperationType::Subscription => "subscription",
+            };
+            match &operation.node.name {
+                Some(name) => symbol(
+                    index,
+                    operation,
+                    name,
+                    Some(keyword.to_owned()),
+                    SymbolKind::Function,
+                    vec![],
+                ),
+                None => DocumentSymbol {
+                    name: format!("anonymous {}", keyword),
+                    detail: None,
+                    kind: SymbolKind::Function,
+                    range: whole,
+                    selection_range: whole,
+                    children: vec![],
+                },
+            }
+        }
+        DocumentDefinition::Fragment(fragment) => symbol(
+            index,
+            fragment,
+            &fragment.node.name,
+            Some(format!("on {}", fragment.node.on.node)),
+            SymbolKind::Function,
+            vec![],
+        ),
+        DocumentDefinition::Schema(_) => DocumentSymbol {
+            name: "schema".to_owned(),
+            detail: None,
+            kind: SymbolKind::Module,
+            range: whole,
+            selection_range: whole,
+            children: vec![],
+        },
+        DocumentDefinition::Directive(directive) => symbol(
+            index,
+            directive,
+            &directive.node.name,
+            None,
+            SymbolKind::Operator,
+            vec![],
+        ),
+        DocumentDefinition::Type(ty) => {
+            let field_symbol_list = |field_list: &[Positioned<FieldDefinition>]| {
+                field_list
+                    .iter()
+                    .map(|field| {
+                        symbol(
+                            index,
+                            field,
+                            &field.node.name,
+                            Some(field.node.ty.node.to_string()),
+                            SymbolKind::Field,
+                            vec![],
+                        )
+                    })
+                    .collect()
+            };
+            match &ty.node {
+                TypeDefinition::Scalar(scalar) => symbol(
+                    index,
+                    ty,
+                    &scalar.node.name,
+                    None,
+                    SymbolKind::TypeParameter,
+                    vec![],
+                ),
+                TypeDefinition::Object(object) => symbol(
+                    index,
+                    ty,
+                    &object.node.name,
+                    None,
+                    SymbolKind::Class,
+                    field_symbol_list(&object.node.field_list),
+                ),
+                TypeDefinition::Interface(interface) => symbol(
+                    index,
+                    ty,
+                    &interface.node.name,
+                    None,
+                    SymbolKind::Interface,
+                    field_symbol_list(&interface.node.field_list),
+                ),
+                TypeDefinition::Union(union) => {
+                    symbol(index, ty, &union.node.name, None, SymbolKind::Enum, vec![])
+                }
+                TypeDefinition::Enum(enum_type) => symbol(
+                    index,
+                    ty,
+                    &enum_type.node.name,
+                    None,
+                    SymbolKind::Enum,
+                    enum_type
+                        .node
+                        .member_list
+                        .iter()
+                        .map(|member| {
+                            symbol(
+                                index,
+                                member,
+                                &member.node.name,
+                                None,
+                                SymbolKind::EnumMember,
+                                vec![],
+                            )
+                        })
+                        .collect(),
+                ),
+                TypeDefinition::InputObject(input) => symbol(
+                    index,
+                    ty,
+                    &input.node.name,
+                    None,
+                    SymbolKind::Struct,
+                    field_symbol_list(&input.node.field_list),
+                ),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SCHEMA: &str = r#"type Query {
+  """The main character."""
+  hero: Hero
+}
+
+type Hero {
+  name: String
+  friend_list: [Hero!]!
+}
+"#;
+
+    fn open(document: &str) -> Workspace {
+        let mut workspace = Workspace::new();
+        workspace.open_schema("file:///schema.graphql", SCHEMA.to_owned());
+        workspace.open("file:///hero.graphql", document.to_owned());
+        workspace
+    }
+
+    fn position(line: usize, character: usize) -> Position {
+        Position { line, character }
+    }
+
+    #[test]
+    fn navigation() {
+        let workspace =
+            open("query {\n  hero { ...HeroName }\n}\n\nfragment HeroName on Hero {\n  name\n}\n");
+        let uri = "file:///hero.graphql";
+
+        let definition = workspace.definition(uri, position(1, 4));
+        assert_eq!(definition.len(), 1);
+        assert_eq!(definition[0].uri, "file:///schema.graphql");
+        assert_eq!(definition[0].range.start, position(2, 2));
+
+        let definition = workspace.definition(uri, position(1, 14));
+        assert_eq!(definition[0].uri, uri);
+        assert_eq!(definition[0].range.start, position(4, 9));
+
+        let reference_list = workspace.references("file:///schema.graphql", position(5, 6), true);
+        assert_eq!(
+            reference_list
+                .iter()
+                .map(|location| (location.uri.as_str(), location.range.start))
+                .collect::<Vec<_>>(),
+            vec![
+                ("file:///hero.graphql", position(4, 21)),
+                ("file:///schema.graphql", position(2, 8)),
+                ("file:///schema.graphql", position(7, 16)),
+                ("file:///schema.graphql", position(5, 5)),
+            ]
+        );
+
+        let hover = workspace.hover(uri, position(1, 3)).unwrap();
+        assert_eq!(
+            hover.contents.value,
+            "```graphql\nQuery.hero: Hero\n```\n\nThe main character."
+        );
+        assert_eq!(
+            hover.range,
+            Range {
+                start: position(1, 2),
+                end: position(1, 6)
+            }
+        );
+    }
+
+    #[test]
+    fn diagnostic() {
+        let workspace = open("{\n  hero { email }\n}\n");
+        let diagnostic_list = workspace.diagnostic_list("file:///hero.graphql");
+        assert_eq!(diagnostic_list.len(), 1);
+        assert_eq!(
+            diagnostic_list[0].message,
+            "Cannot query field \"email\" on type \"Hero\"."
+        );
+        assert_eq!(
+            diagnostic_list[0].range,
+            Range {
+                start: position(1, 9),
+                end: position(1, 14)
+            }
+        );
+
+        let workspace = open("{\n  hero {\n}\n");
+        let diagnostic_list = workspace.diagnostic_list("file:///hero.graphql");
+        assert_eq!(diagnostic_list[0].message, "Syntax Error");
+        assert!(workspace
+            .diagnostic_list("file:///schema.graphql")
+            .is_empty());
+    }
+
+    #[test]
+    fn edit() {
+        let mut workspace =
+            open("{\n  hero { email }\n}\n\nfragment HeroName on Hero {\n  name\n}\n");
+        let uri = "file:///hero.graphql";
+        let email = Range {
+            start: position(1, 9),
+            end: position(1, 14),
+        };
+        workspace.edit(uri, email, "...HeroName\n    name");
+        assert_eq!(
+            workspace.text(uri).unwrap(),
+            "{\n  hero { ...HeroName\n    name }\n}\n\nfragment HeroName on Hero {\n  name\n}\n"
+        );
+        assert!(workspace.diagnostic_list(uri).is_empty());
+        let file = &workspace.document_map[uri];
+        assert_eq!(file.document, parse_document(&file.text).ok());
+        let definition = workspace.definition(uri, position(1, 14));
+        assert_eq!(definition[0].range.start, position(5, 9));
+
+        workspace.edit(
+            uri,
+            Range {
+                start: position(2, 9),
+                end: position(2, 9),
+            },
+            "{",
+        );
+        assert!(workspace.document_map[uri].document.is_none());
+        assert_eq!(workspace.diagnostic_list(uri)[0].message, "Syntax Error");
+    }
+
+    #[test]
+    fn completion() {
+        let workspace = open("{\n  hero {\n    \n  }\n}\n");
+        let label_list: Vec<String> = workspace
+            .completion("file:///hero.graphql", position(2, 4))
+            .into_iter()
+            .map(|item| item.label)
+            .collect();
+        assert_eq!(label_list, vec!["name", "friend_list", "__typename"]);
+        assert!(workspace
+            .completion("file:///schema.graphql", position(2, 2))
+            .is_empty());
+    }
+
+    #[test]
+    fn symbol_and_format() {
+        let workspace = open("query Hero { hero { name } }\nfragment HeroName on Hero { name }");
+        let symbol_list = workspace.document_symbol_list("file:///hero.graphql");
+        assert_eq!(
+            symbol_list
+                .iter()
+                .map(|symbol| (symbol.name.as_str(), symbol.detail.as_deref()))
+                .collect::<Vec<_>>(),
+            vec![("Hero", Some("query")), ("HeroName", Some("on Hero"))]
+        );
+        let symbol_list = workspace.document_symbol_list("file:///schema.graphql");
+        assert_eq!(symbol_list[1].name, "Hero");
+        assert_eq!(
+            symbol_list[1].children[1].detail.as_deref(),
+            Some("[Hero!]!")
+        );
+
+        let edit_list = workspace.formatting("file:///hero.graphql");
+        assert_eq!(edit_list.len(), 1);
+        assert_eq!(
+            edit_list[0].new_text,
+            "query Hero {\n  hero {\n    name\n  }\n}\n\nfragment HeroName on Hero {\n  name\n}\n"
+        );
+
+        let workspace = open("# Kept as written.\nquery Hero { hero { name } }");
+        assert!(workspace.formatting("file:///hero.graphql").is_empty());
+    }
+
+    #[test]
+    fn schema_cache() {
+        let mut workspace = open("{\n  hero {\n    \n  }\n}\n");
+        let uri = "file:///hero.graphql";
+        let schema = workspace.schema().unwrap().unwrap();
+        assert!(schema.get_type("Hero").unwrap().field("age").is_none());
+
+        workspace.edit(
+            uri,
+            Range {
+                start: position(2, 4),
+                end: position(2, 4),
+            },
+            "name",
+        );
+        assert!(workspace.schema.get().is_some());
+
+        workspace.edit(
+            "file:///schema.graphql",
+            Range {
+                start: position(7, 0),
+                end: position(7, 0),
+            },
+            "  age: Int\n",
+        );
+        assert!(workspace.schema.get().is_none());
+        let schema = workspace.schema().unwrap().unwrap();
+        assert!(schema.get_type("Hero").unwrap().field("age").is_some());
+    }
+}
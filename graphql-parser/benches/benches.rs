@@ -5,7 +5,7 @@ fn load_graphql(path_list: &[&str]) -> Vec<String> {
     path_list
         .iter()
         .filter_map(|path| std::fs::read_dir(path).ok())
-        .flat_map(|dir| dir)
+        .flatten()
         .flat_map(|path| path.into_iter())
         .map(|entry| entry.path())
         .filter_map(|path| std::fs::read_to_string(path).ok())
@@ -1,14 +1,37 @@
 use crate::*;
 use std::collections::BTreeMap;
 
-#[derive(Debug, PartialEq)]
+#[derive(Debug, Clone, PartialEq)]
 pub enum Type {
     NonNull(Box<Type>),
     List(Box<Type>),
     Named(String),
 }
 
-#[derive(Debug, PartialEq)]
+impl Type {
+    pub fn name(&self) -> &str {
+        match self {
+            Type::NonNull(ty) | Type::List(ty) => ty.name(),
+            Type::Named(name) => name,
+        }
+    }
+
+    pub fn is_non_null(&self) -> bool {
+        matches!(self, Type::NonNull(_))
+    }
+}
+
+impl std::fmt::Display for Type {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Type::NonNull(ty) => write!(f, "{}!", ty),
+            Type::List(ty) => write!(f, "[{}]", ty),
+            Type::Named(name) => write!(f, "{}", name),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
 pub enum Value {
     Null,
     Boolean(bool),
@@ -16,16 +39,65 @@ pub enum Value {
     Float(f64),
     Enum(String),
     String(String),
+    Variable(String),
     Object(BTreeMap<String, Value>),
     List(Vec<Value>),
 }
 
-#[derive(Debug, PartialEq)]
+impl std::fmt::Display for Value {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Value::Null => write!(f, "null"),
+            Value::Boolean(boolean) => write!(f, "{}", boolean),
+            Value::Int(int) => write!(f, "{}", int),
+            Value::Float(float) => write!(f, "{:?}", float),
+            Value::Enum(name) => write!(f, "{}", name),
+            Value::String(string) => {
+                write!(f, "\"")?;
+                for c in string.chars() {
+                    match c {
+                        '"' => write!(f, "\\\"")?,
+                        '\\' => write!(f, "\\\\")?,
+                        '\n' => write!(f, "\\n")?,
+                        '\r' => write!(f, "\\r")?,
+                        '\t' => write!(f, "\\t")?,
+                        c if c.is_control() => write!(f, "\\u{:04X}", c as u32)?,
+                        c => write!(f, "{}", c)?,
+                    }
+                }
+                write!(f, "\"")
+            }
+            Value::Variable(name) => write!(f, "${}", name),
+            Value::Object(map) => {
+                write!(f, "{{")?;
+                for (index, (name, value)) in map.iter().enumerate() {
+                    if index > 0 {
+                        write!(f, ", ")?;
+                    }
+                    write!(f, "{}: {}", name, value)?;
+                }
+                write!(f, "}}")
+            }
+            Value::List(list) => {
+                write!(f, "[")?;
+                for (index, value) in list.iter().enumerate() {
+                    if index > 0 {
+                        write!(f, ", ")?;
+                    }
+                    write!(f, "{}", value)?;
+                }
+                write!(f, "]")
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
 pub struct Document {
     pub definition_list: Vec<Positioned<DocumentDefinition>>,
 }
 
-#[derive(Debug, PartialEq)]
+#[derive(Debug, Clone, PartialEq)]
 pub enum DocumentDefinition {
     Operation(Positioned<OperationDefinition>),
     Fragment(Positioned<FragmentDefinition>),
@@ -34,30 +106,30 @@ pub enum DocumentDefinition {
     Directive(Positioned<DirectiveDefinition>),
 }
 
-#[derive(Debug, PartialEq)]
+#[derive(Debug, Clone, PartialEq)]
 pub struct TypeSystemDocument {
     pub definition_list: Vec<Positioned<TypeSystemDefinition>>,
 }
 
-#[derive(Debug, PartialEq)]
+#[derive(Debug, Clone, PartialEq)]
 pub enum TypeSystemDefinition {
     Schema(Positioned<SchemaDefinition>),
     Type(Positioned<TypeDefinition>),
     Directive(Positioned<DirectiveDefinition>),
 }
 
-#[derive(Debug, PartialEq)]
+#[derive(Debug, Clone, PartialEq)]
 pub struct ExecutableDocument {
     pub definition_list: Vec<Positioned<ExecutableDefinition>>,
 }
 
-#[derive(Debug, PartialEq)]
+#[derive(Debug, Clone, PartialEq)]
 pub enum ExecutableDefinition {
     Operation(Positioned<OperationDefinition>),
     Fragment(Positioned<FragmentDefinition>),
 }
 
-#[derive(Debug, PartialEq)]
+#[derive(Debug, Clone, PartialEq)]
 pub struct OperationDefinition {
     pub ty: OperationType,
     pub name: Option<Positioned<String>>,
@@ -66,7 +138,7 @@ pub struct OperationDefinition {
     pub directive_list: Vec<Positioned<Directive>>,
 }
 
-#[derive(Debug, PartialEq)]
+#[derive(Debug, Clone, PartialEq)]
 pub struct FragmentDefinition {
     pub name: Positioned<String>,
     pub on: Positioned<String>,
@@ -74,7 +146,7 @@ pub struct FragmentDefinition {
     pub directive_list: Vec<Positioned<Directive>>,
 }
 
-#[derive(Debug, PartialEq)]
+#[derive(Debug, Clone, PartialEq)]
 pub struct SchemaDefinition {
     pub is_extend: bool,
     pub description: Option<Positioned<String>>,
@@ -82,27 +154,27 @@ pub struct SchemaDefinition {
     pub field_list: Vec<OperationField>,
 }
 
-#[derive(Debug, PartialEq)]
+#[derive(Debug, Clone, PartialEq)]
 pub struct VariableDefinition {
     pub name: Positioned<String>,
     pub ty: Positioned<Type>,
     pub default_value: Option<Positioned<Value>>,
 }
 
-#[derive(Debug, PartialEq)]
+#[derive(Debug, Clone, PartialEq)]
 pub struct OperationField {
     pub ty: OperationType,
     pub name: Positioned<String>,
 }
 
-#[derive(Debug, PartialEq)]
+#[derive(Debug, Clone, PartialEq)]
 pub enum OperationType {
     Query,
     Mutation,
     Subscription,
 }
 
-#[derive(Debug, PartialEq)]
+#[derive(Debug, Clone, PartialEq)]
 pub enum TypeDefinition {
     Scalar(Positioned<ScalarType>),
     Object(Positioned<ObjectType>),
@@ -112,7 +184,7 @@ pub enum TypeDefinition {
     InputObject(Positioned<InputObjectType>),
 }
 
-#[derive(Debug, PartialEq)]
+#[derive(Debug, Clone, PartialEq)]
 pub struct ScalarType {
     pub is_extend: bool,
     pub description: Option<Positioned<String>>,
@@ -120,7 +192,7 @@ pub struct ScalarType {
     pub directive_list: Vec<Positioned<Directive>>,
 }
 
-#[derive(Debug, PartialEq)]
+#[derive(Debug, Clone, PartialEq)]
 pub struct ObjectType {
     pub is_extend: bool,
     pub description: Option<Positioned<String>>,
@@ -130,7 +202,7 @@ pub struct ObjectType {
     pub directive_list: Vec<Positioned<Directive>>,
 }
 
-#[derive(Debug, PartialEq)]
+#[derive(Debug, Clone, PartialEq)]
 pub struct InterfaceType {
     pub is_extend: bool,
     pub description: Option<Positioned<String>>,
@@ -139,7 +211,7 @@ pub struct InterfaceType {
     pub directive_list: Vec<Positioned<Directive>>,
 }
 
-#[derive(Debug, PartialEq)]
+#[derive(Debug, Clone, PartialEq)]
 pub struct UnionType {
     pub is_extend: bool,
     pub description: Option<Positioned<String>>,
@@ -148,7 +220,7 @@ pub struct UnionType {
     pub directive_list: Vec<Positioned<Directive>>,
 }
 
-#[derive(Debug, PartialEq)]
+#[derive(Debug, Clone, PartialEq)]
 pub struct EnumType {
     pub is_extend: bool,
     pub description: Option<Positioned<String>>,
@@ -157,7 +229,7 @@ pub struct EnumType {
     pub directive_list: Vec<Positioned<Directive>>,
 }
 
-#[derive(Debug, PartialEq)]
+#[derive(Debug, Clone, PartialEq)]
 pub struct InputObjectType {
     pub is_extend: bool,
     pub description: Option<Positioned<String>>,
@@ -166,53 +238,55 @@ pub struct InputObjectType {
     pub directive_list: Vec<Positioned<Directive>>,
 }
 
-#[derive(Debug, PartialEq)]
+#[derive(Debug, Clone, PartialEq)]
 pub struct DirectiveDefinition {
     pub description: Option<Positioned<String>>,
     pub name: Positioned<String>,
     pub argument_list: Vec<Positioned<FieldArgument>>,
+    pub is_repeatable: bool,
     pub location_list: Vec<Positioned<DirectiveLocation>>,
 }
 
-#[derive(Debug, PartialEq)]
+#[derive(Debug, Clone, PartialEq)]
 pub enum Selection {
     Field(Field),
     FragmentSpread(FragmentSpread),
     InlineFragment(InlineFragment),
 }
 
-#[derive(Debug, PartialEq)]
+#[derive(Debug, Clone, PartialEq)]
 pub struct Field {
     pub name: Positioned<String>,
     pub alias: Option<Positioned<String>>,
-    pub argument_list: Vec<Positioned<FieldArgument>>,
+    pub argument_list: Vec<Positioned<Argument>>,
     pub selection_list: Vec<Positioned<Selection>>,
     pub directive_list: Vec<Positioned<Directive>>,
 }
 
-#[derive(Debug, PartialEq)]
+#[derive(Debug, Clone, PartialEq)]
 pub struct FragmentSpread {
     pub name: Positioned<String>,
     pub directive_list: Vec<Positioned<Directive>>,
 }
 
-#[derive(Debug, PartialEq)]
+#[derive(Debug, Clone, PartialEq)]
 pub struct InlineFragment {
     pub on: Option<Positioned<String>>,
     pub selection_list: Vec<Positioned<Selection>>,
     pub directive_list: Vec<Positioned<Directive>>,
 }
 
-#[derive(Debug, PartialEq)]
+#[derive(Debug, Clone, PartialEq)]
 pub struct FieldDefinition {
     pub description: Option<Positioned<String>>,
     pub name: Positioned<String>,
     pub argument_list: Vec<Positioned<FieldArgument>>,
     pub ty: Positioned<Type>,
+    pub default_value: Option<Positioned<Value>>,
     pub directive_list: Vec<Positioned<Directive>>,
 }
 
-#[derive(Debug, PartialEq)]
+#[derive(Debug, Clone, PartialEq)]
 pub struct FieldArgument {
     pub description: Option<Positioned<String>>,
     pub name: Positioned<String>,
@@ -221,20 +295,94 @@ pub struct FieldArgument {
     pub directive_list: Vec<Positioned<Directive>>,
 }
 
-#[derive(Debug, PartialEq)]
+#[derive(Debug, Clone, PartialEq)]
 pub struct EnumMember {
     pub description: Option<Positioned<String>>,
     pub name: Positioned<String>,
     pub directive_list: Vec<Positioned<Directive>>,
 }
 
-#[derive(Debug, PartialEq)]
+#[derive(Debug, Clone, PartialEq)]
 pub enum DirectiveLocation {
     Executable(ExecutableDirectiveLocation),
     TypeSystem(TypeSystemDirectiveLocation),
 }
 
-#[derive(Debug, PartialEq)]
+impl std::fmt::Display for DirectiveLocation {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let name = match self {
+            DirectiveLocation::Executable(location) => match location {
+                ExecutableDirectiveLocation::Query => "QUERY",
+                ExecutableDirectiveLocation::Mutation => "MUTATION",
+                ExecutableDirectiveLocation::Subscription => "SUBSCRIPTION",
+                ExecutableDirectiveLocation::Field => "FIELD",
+                ExecutableDirectiveLocation::FragmentDefinition => "FRAGMENT_DEFINITION",
+                ExecutableDirectiveLocation::FragmentSpread => "FRAGMENT_SPREAD",
+                ExecutableDirectiveLocation::InlineFragment => "INLINE_FRAGMENT",
+            },
+            DirectiveLocation::TypeSystem(location) => match location {
+                TypeSystemDirectiveLocation::Schema => "SCHEMA",
+                TypeSystemDirectiveLocation::Scalar => "SCALAR",
+                TypeSystemDirectiveLocation::Object => "OBJECT",
+                TypeSystemDirectiveLocation::FieldDefinition => "FIELD_DEFINITION",
+                TypeSystemDirectiveLocation::ArgumentDefinition => "ARGUMENT_DEFINITION",
+                TypeSystemDirectiveLocation::Interface => "INTERFACE",
+                TypeSystemDirectiveLocation::Union => "UNION",
+                TypeSystemDirectiveLocation::Enum => "ENUM",
+                TypeSystemDirectiveLocation::EnumValue => "ENUM_VALUE",
+                TypeSystemDirectiveLocation::InputObject => "INPUT_OBJECT",
+                TypeSystemDirectiveLocation::InputFieldDefinition => "INPUT_FIELD_DEFINITION",
+            },
+        };
+        write!(f, "{}", name)
+    }
+}
+
+impl std::str::FromStr for DirectiveLocation {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        Ok(match s {
+            "QUERY" => DirectiveLocation::Executable(ExecutableDirectiveLocation::Query),
+            "MUTATION" => DirectiveLocation::Executable(ExecutableDirectiveLocation::Mutation),
+            "SUBSCRIPTION" => {
+                DirectiveLocation::Executable(ExecutableDirectiveLocation::Subscription)
+            }
+            "FIELD" => DirectiveLocation::Executable(ExecutableDirectiveLocation::Field),
+            "FRAGMENT_DEFINITION" => {
+                DirectiveLocation::Executable(ExecutableDirectiveLocation::FragmentDefinition)
+            }
+            "FRAGMENT_SPREAD" => {
+                DirectiveLocation::Executable(ExecutableDirectiveLocation::FragmentSpread)
+            }
+            "INLINE_FRAGMENT" => {
+                DirectiveLocation::Executable(ExecutableDirectiveLocation::InlineFragment)
+            }
+            "SCHEMA" => DirectiveLocation::TypeSystem(TypeSystemDirectiveLocation::Schema),
+            "SCALAR" => DirectiveLocation::TypeSystem(TypeSystemDirectiveLocation::Scalar),
+            "OBJECT" => DirectiveLocation::TypeSystem(TypeSystemDirectiveLocation::Object),
+            "FIELD_DEFINITION" => {
+                DirectiveLocation::TypeSystem(TypeSystemDirectiveLocation::FieldDefinition)
+            }
+            "ARGUMENT_DEFINITION" => {
+                DirectiveLocation::TypeSystem(TypeSystemDirectiveLocation::ArgumentDefinition)
+            }
+            "INTERFACE" => DirectiveLocation::TypeSystem(TypeSystemDirectiveLocation::Interface),
+            "UNION" => DirectiveLocation::TypeSystem(TypeSystemDirectiveLocation::Union),
+            "ENUM" => DirectiveLocation::TypeSystem(TypeSystemDirectiveLocation::Enum),
+            "ENUM_VALUE" => DirectiveLocation::TypeSystem(TypeSystemDirectiveLocation::EnumValue),
+            "INPUT_OBJECT" => {
+                DirectiveLocation::TypeSystem(TypeSystemDirectiveLocation::InputObject)
+            }
+            "INPUT_FIELD_DEFINITION" => {
+                DirectiveLocation::TypeSystem(TypeSystemDirectiveLocation::InputFieldDefinition)
+            }
+            _ => return Err(format!("Unknown directive location \"{}\".", s)),
+        })
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
 pub enum ExecutableDirectiveLocation {
     Query,
     Mutation,
@@ -245,7 +393,7 @@ pub enum ExecutableDirectiveLocation {
     InlineFragment,
 }
 
-#[derive(Debug, PartialEq)]
+#[derive(Debug, Clone, PartialEq)]
 pub enum TypeSystemDirectiveLocation {
     Schema,
     Scalar,
@@ -260,8 +408,14 @@ pub enum TypeSystemDirectiveLocation {
     InputFieldDefinition,
 }
 
-#[derive(Debug, PartialEq)]
+#[derive(Debug, Clone, PartialEq)]
 pub struct Directive {
     pub name: Positioned<String>,
-    pub argument_list: Vec<Positioned<FieldArgument>>,
+    pub argument_list: Vec<Positioned<Argument>>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Argument {
+    pub name: Positioned<String>,
+    pub value: Positioned<Value>,
 }
@@ -1,4 +1,4 @@
-use crate::{Result, Span};
+use crate::{Position, Result, Span};
 use nom::{
     error::{ErrorKind, ParseError},
     Err::Error,
@@ -19,6 +19,19 @@ impl<'a> ParseError<Span<'a>> for ParsingError<'a> {
     }
 }
 
+impl<'a> ParsingError<'a> {
+    /// Where in the source parsing failed.
+    pub fn position(&self) -> Position {
+        match self {
+            ParsingError::Nom(span, _) => Position {
+                line: span.location_line() as usize,
+                column: span.get_column(),
+                offset: span.location_offset(),
+            },
+        }
+    }
+}
+
 #[allow(dead_code)]
 pub(crate) fn err<'a, O, F, E>(f: F, e: E) -> impl Fn(Span<'a>) -> Result<O>
 where
@@ -4,6 +4,8 @@ mod ast;
 mod error;
 mod parse;
 mod position;
+mod print;
+mod reparse;
 mod utils;
 
 pub use ast::*;
@@ -12,6 +14,8 @@ use nom::IResult;
 use nom_locate::LocatedSpan;
 pub use parse::*;
 pub use position::*;
+pub use print::*;
+pub use reparse::*;
 pub(crate) use utils::*;
 
 type Result<'a, T> = IResult<Span<'a>, T, ParsingError<'a>>;
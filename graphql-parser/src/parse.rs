@@ -2,8 +2,8 @@ use crate::*;
 use nom::{
     branch::alt,
     bytes::complete::{tag, take, take_while, take_while1},
-    character::complete::char,
-    combinator::{all_consuming, map, opt},
+    character::complete::{char, one_of},
+    combinator::{all_consuming, cond, map, opt, peek, verify},
     error::ErrorKind,
     multi::{many0, many1, separated_list},
     sequence::tuple,
@@ -13,7 +13,7 @@ use std::collections::BTreeMap;
 
 const UNEXTEND: bool = false;
 
-pub fn parse_document(source: &str) -> std::result::Result<Document, ParsingError> {
+pub fn parse_document(source: &str) -> std::result::Result<Document, ParsingError<'_>> {
     match all_consuming(map(
         tuple((
             many0(map(
@@ -44,7 +44,9 @@ fn document_definition(s: Span) -> Result<DocumentDefinition> {
     ))(s)
 }
 
-pub fn parse_type_system(source: &str) -> std::result::Result<TypeSystemDocument, ParsingError> {
+pub fn parse_type_system(
+    source: &str,
+) -> std::result::Result<TypeSystemDocument, ParsingError<'_>> {
     match all_consuming(map(
         tuple((
             many0(map(
@@ -62,6 +64,41 @@ pub fn parse_type_system(source: &str) -> std::result::Result<TypeSystemDocument
     }
 }
 
+/// Parses a single constant or variable value, such as the `defaultValue` of an introspection result.
+pub fn parse_value(source: &str) -> std::result::Result<Value, ParsingError<'_>> {
+    match all_consuming(map(
+        tuple((ignore_token0, value, ignore_token0)),
+        |(_, value, _)| value,
+    ))(LocatedSpan::new(source))
+    {
+        Ok((_, value)) => Ok(value),
+        Err(Error(error)) => Err(error),
+        _ => std::unreachable!(),
+    }
+}
+
+/// Parses a selection set written without its surrounding braces, such as the `fields`
+/// argument of a federation `@key`.
+pub fn parse_selection_list(
+    source: &str,
+) -> std::result::Result<Vec<Positioned<Selection>>, ParsingError<'_>> {
+    match all_consuming(map(
+        tuple((
+            many1(map(
+                tuple((ignore_token0, positioned(selection))),
+                |(_, selection)| selection,
+            )),
+            ignore_token0,
+        )),
+        |(selection_list, _)| selection_list,
+    ))(LocatedSpan::new(source))
+    {
+        Ok((_, selection_list)) => Ok(selection_list),
+        Err(Error(error)) => Err(error),
+        _ => std::unreachable!(),
+    }
+}
+
 fn type_system_definition(s: Span) -> Result<TypeSystemDefinition> {
     alt((
         map(positioned(schema_definition), TypeSystemDefinition::Schema),
@@ -73,7 +110,7 @@ fn type_system_definition(s: Span) -> Result<TypeSystemDefinition> {
     ))(s)
 }
 
-pub fn parse_executable(source: &str) -> std::result::Result<ExecutableDocument, ParsingError> {
+pub fn parse_executable(source: &str) -> std::result::Result<ExecutableDocument, ParsingError<'_>> {
     match all_consuming(map(
         tuple((
             many0(map(
@@ -164,6 +201,10 @@ fn directive_definition(s: Span) -> Result<DirectiveDefinition> {
             directive_name,
             ignore_token0,
             field_argument_list,
+            map(
+                opt(tuple((tag("repeatable"), ignore_token1))),
+                |repeatable| repeatable.is_some(),
+            ),
             tag("on"),
             alt((
                 map(tuple((ignore_token1, pipeline, ignore_token0)), |_| ()),
@@ -177,11 +218,14 @@ fn directive_definition(s: Span) -> Result<DirectiveDefinition> {
                 ),
             ),
         )),
-        |(description, _, _, name, _, argument_list, _, _, location_list)| DirectiveDefinition {
-            description,
-            name,
-            argument_list,
-            location_list,
+        |(description, _, _, name, _, argument_list, is_repeatable, _, _, location_list)| {
+            DirectiveDefinition {
+                description,
+                name,
+                argument_list,
+                is_repeatable,
+                location_list,
+            }
         },
     )(s)
 }
@@ -192,7 +236,7 @@ fn operation(s: Span) -> Result<OperationDefinition> {
             map(
                 opt(tuple((
                     operation_type,
-                    ignore_token1,
+                    alt((ignore_token1, map(peek(one_of("({@")), |_| ()))),
                     opt(map(
                         tuple((positioned(name), ignore_token0)),
                         |(name, _)| name,
@@ -290,6 +334,7 @@ fn object_type(s: Span) -> Result<ObjectType> {
     ))(s)?;
     let (s, field_list) = field_definition_list(
         is_extend && directive_list.is_empty() && interface_list.is_empty(),
+        false,
     )(s)?;
 
     Ok((
@@ -312,7 +357,7 @@ fn interface_type(s: Span) -> Result<InterfaceType> {
         definition_type("interface"),
         directive_list(UNEXTEND),
     ))(s)?;
-    let (s, field_list) = field_definition_list(is_extend && directive_list.is_empty())(s)?;
+    let (s, field_list) = field_definition_list(is_extend && directive_list.is_empty(), false)(s)?;
 
     Ok((
         s,
@@ -408,7 +453,7 @@ fn input_object_type(s: Span) -> Result<InputObjectType> {
         definition_type("input"),
         directive_list(UNEXTEND),
     ))(s)?;
-    let (s, field_list) = field_definition_list(is_extend && directive_list.is_empty())(s)?;
+    let (s, field_list) = field_definition_list(is_extend && directive_list.is_empty(), true)(s)?;
 
     Ok((
         s,
@@ -463,8 +508,8 @@ fn variable_definition(s: Span) -> Result<VariableDefinition> {
             ignore_token0,
             positioned(ty),
             opt(map(
-                tuple((ignore_token0, positioned(value))),
-                |(_, value)| value,
+                tuple((ignore_token0, equal, ignore_token0, positioned(value))),
+                |(_, _, _, value)| value,
             )),
         )),
         |(_, _, name, _, _, _, ty, default_value)| VariableDefinition {
@@ -507,7 +552,7 @@ fn field(s: Span) -> Result<Field> {
             )),
             positioned(name),
             map(
-                opt(tuple((ignore_token0, field_argument_list))),
+                opt(tuple((ignore_token0, argument_list))),
                 |argument_list| {
                     argument_list
                         .map(|(_, argument_list)| argument_list)
@@ -534,7 +579,7 @@ fn fragment_spread(s: Span) -> Result<FragmentSpread> {
         tuple((
             tag("..."),
             ignore_token0,
-            positioned(name),
+            positioned(fragment_name),
             directive_list(UNEXTEND),
         )),
         |(_, _, name, directive_list)| FragmentSpread {
@@ -544,6 +589,10 @@ fn fragment_spread(s: Span) -> Result<FragmentSpread> {
     )(s)
 }
 
+fn fragment_name(s: Span) -> Result<String> {
+    verify(name, |name: &str| name != "on")(s)
+}
+
 fn inline_fragment(s: Span) -> Result<InlineFragment> {
     map(
         tuple((
@@ -568,7 +617,7 @@ fn extend(s: Span) -> Result<bool> {
     })(s)
 }
 
-fn definition_type<'a>(identifier: &'a str) -> impl Fn(Span<'a>) -> Result<Positioned<String>> {
+fn definition_type<'a>(identifier: &'a str) -> impl Fn(Span<'a>) -> Result<'a, Positioned<String>> {
     move |s: Span<'a>| {
         map(
             tuple((tag(identifier), ignore_token1, positioned(name))),
@@ -594,18 +643,21 @@ fn enum_member(s: Span) -> Result<EnumMember> {
 
 fn field_definition_list(
     should_exists: bool,
+    is_input: bool,
 ) -> impl Fn(Span) -> Result<Vec<Positioned<FieldDefinition>>> {
     move |s| {
         let (s, field_definition_list) = match opt(tuple((
             ignore_token0,
             left_brace,
-            ignore_token0,
-            separated_list(ignore_token1, positioned(field_definition)),
+            many0(map(
+                tuple((ignore_token0, positioned(field_definition(is_input)))),
+                |(_, field_definition)| field_definition,
+            )),
             ignore_token0,
             right_brace,
         )))(s)?
         {
-            (s, Some((_, _, _, field_list, _, _))) => (s, field_list),
+            (s, Some((_, _, field_list, _, _))) => (s, field_list),
             (s, None) if should_exists => return Err(Error(ParsingError::Nom(s, ErrorKind::Char))),
             (s, _) => (s, vec![]),
         };
@@ -614,46 +666,93 @@ fn field_definition_list(
     }
 }
 
-fn field_definition(s: Span) -> Result<FieldDefinition> {
+/// Parses a field definition, with a default value only when it is a field of an input
+/// object.
+fn field_definition(is_input: bool) -> impl Fn(Span) -> Result<FieldDefinition> + Copy {
+    move |s| {
+        map(
+            tuple((
+                description(UNEXTEND),
+                positioned(name),
+                ignore_token0,
+                field_argument_list,
+                colon,
+                ignore_token0,
+                positioned(ty),
+                cond(
+                    is_input,
+                    opt(map(
+                        tuple((ignore_token0, equal, ignore_token0, positioned(value))),
+                        |(_, _, _, default_value)| default_value,
+                    )),
+                ),
+                directive_list(UNEXTEND),
+            )),
+            |(description, name, _, argument_list, _, _, ty, default_value, directive_list)| {
+                FieldDefinition {
+                    description,
+                    name,
+                    argument_list,
+                    ty,
+                    default_value: default_value.flatten(),
+                    directive_list,
+                }
+            },
+        )(s)
+    }
+}
+
+fn field_argument_list(s: Span) -> Result<Vec<Positioned<FieldArgument>>> {
     map(
-        tuple((
-            description(UNEXTEND),
-            positioned(name),
+        opt(tuple((
+            left_parens,
+            many0(map(
+                tuple((ignore_token0, positioned(field_argument))),
+                |(_, field_argument)| field_argument,
+            )),
             ignore_token0,
-            field_argument_list,
-            colon,
+            right_parens,
             ignore_token0,
-            positioned(ty),
-            directive_list(UNEXTEND),
-        )),
-        |(description, name, _, argument_list, _, _, ty, directive_list)| FieldDefinition {
-            description,
-            name,
-            argument_list,
-            ty,
-            directive_list,
+        ))),
+        |field_argument_list| {
+            field_argument_list
+                .map(|(_, field_argument, _, _, _)| field_argument)
+                .unwrap_or_else(Vec::new)
         },
     )(s)
 }
 
-fn field_argument_list(s: Span) -> Result<Vec<Positioned<FieldArgument>>> {
+fn argument_list(s: Span) -> Result<Vec<Positioned<Argument>>> {
     map(
         opt(tuple((
             left_parens,
             ignore_token0,
-            separated_list(ignore_token1, positioned(field_argument)),
+            separated_list(ignore_token1, positioned(argument)),
             ignore_token0,
             right_parens,
             ignore_token0,
         ))),
-        |field_argument_list| {
-            field_argument_list
-                .map(|(_, _, field_argument, _, _, _)| field_argument)
+        |argument_list| {
+            argument_list
+                .map(|(_, _, argument_list, _, _, _)| argument_list)
                 .unwrap_or_else(Vec::new)
         },
     )(s)
 }
 
+fn argument(s: Span) -> Result<Argument> {
+    map(
+        tuple((
+            positioned(name),
+            ignore_token0,
+            colon,
+            ignore_token0,
+            positioned(value),
+        )),
+        |(name, _, _, _, value)| Argument { name, value },
+    )(s)
+}
+
 fn field_argument(s: Span) -> Result<FieldArgument> {
     map(
         tuple((
@@ -702,7 +801,7 @@ fn directive(s: Span) -> Result<Directive> {
             ignore_token0,
             positioned(name),
             ignore_token0,
-            field_argument_list,
+            argument_list,
         )),
         |(_, _, name, _, argument_list)| Directive {
             name,
@@ -746,6 +845,7 @@ fn value(s: Span) -> Result<Value> {
         value_numeric,
         value_enum,
         value_string,
+        value_variable,
         value_object,
         value_list,
     ))(s)
@@ -800,7 +900,10 @@ fn value_numeric(s: Span) -> Result<Value> {
     {
         (_, Numeric::Int(size)) => {
             let (s, numeric) = take(size)(s)?;
-            Ok((s, Value::Int(numeric.fragment().parse().unwrap())))
+            match numeric.fragment().parse() {
+                Ok(int) => Ok((s, Value::Int(int))),
+                Err(_) => Err(Error(ParsingError::Nom(numeric, ErrorKind::Digit))),
+            }
         }
         (ss, Numeric::Float(minority)) => {
             let (_, exponential) = alt((
@@ -816,6 +919,13 @@ fn value_numeric(s: Span) -> Result<Value> {
             ))(ss)?;
 
             let (s, numeric) = take(sign + majority + minority + exponential)(s)?;
+            // A number with neither a fraction nor an exponent is an integer.
+            if minority + exponential == 0 {
+                return match numeric.fragment().parse() {
+                    Ok(int) => Ok((s, Value::Int(int))),
+                    Err(_) => Err(Error(ParsingError::Nom(numeric, ErrorKind::Digit))),
+                };
+            }
 
             Ok((s, Value::Float(numeric.fragment().parse().unwrap())))
         }
@@ -830,13 +940,23 @@ fn value_string(s: Span) -> Result<Value> {
     map(string, Value::String)(s)
 }
 
+fn value_variable(s: Span) -> Result<Value> {
+    map(tuple((dollar, name)), |(_, name)| Value::Variable(name))(s)
+}
+
 fn value_object(s: Span) -> Result<Value> {
     map(
-        separated_list(
-            ignore_token1,
-            tuple((name, ignore_token0, colon, ignore_token0, value)),
-        ),
-        |pair_list| {
+        tuple((
+            left_brace,
+            ignore_token0,
+            separated_list(
+                ignore_token1,
+                tuple((name, ignore_token0, colon, ignore_token0, value)),
+            ),
+            ignore_token0,
+            right_brace,
+        )),
+        |(_, _, pair_list, _, _)| {
             let object: BTreeMap<String, Value> = pair_list
                 .into_iter()
                 .map(|(name, _, _, _, value)| (name, value))
@@ -847,7 +967,16 @@ fn value_object(s: Span) -> Result<Value> {
 }
 
 fn value_list(s: Span) -> Result<Value> {
-    map(separated_list(ignore_token1, value), Value::List)(s)
+    map(
+        tuple((
+            left_bracket,
+            ignore_token0,
+            separated_list(ignore_token1, value),
+            ignore_token0,
+            right_bracket,
+        )),
+        |(_, _, value_list, _, _)| Value::List(value_list),
+    )(s)
 }
 
 fn directive_location(s: Span) -> Result<DirectiveLocation> {
@@ -908,16 +1037,14 @@ mod tests {
         ];
 
         for path in path_list.iter() {
-            for entry in std::fs::read_dir(path).unwrap() {
-                if let Ok(entry) = entry {
-                    let path = entry.path();
-                    let source = std::fs::read_to_string(&path).unwrap();
-                    if let Err(error) = parse_type_system(source.as_str()) {
-                        dbg!(path);
-                        dbg!(error);
-                        panic!();
-                    };
-                }
+            for entry in std::fs::read_dir(path).unwrap().flatten() {
+                let path = entry.path();
+                let source = std::fs::read_to_string(&path).unwrap();
+                if let Err(error) = parse_type_system(source.as_str()) {
+                    dbg!(path);
+                    dbg!(error);
+                    panic!();
+                };
             }
         }
     }
@@ -932,17 +1059,56 @@ mod tests {
         ];
 
         for path in path_list.iter() {
-            for entry in std::fs::read_dir(path).unwrap() {
-                if let Ok(entry) = entry {
-                    let path = entry.path();
-                    let source = std::fs::read_to_string(&path).unwrap();
-                    if let Err(error) = parse_executable(source.as_str()) {
-                        dbg!(path);
-                        dbg!(error);
-                        panic!();
-                    };
-                }
+            for entry in std::fs::read_dir(path).unwrap().flatten() {
+                let path = entry.path();
+                let source = std::fs::read_to_string(&path).unwrap();
+                if let Err(error) = parse_executable(source.as_str()) {
+                    dbg!(path);
+                    dbg!(error);
+                    panic!();
+                };
             }
         }
     }
+
+    #[test]
+    fn operation_type() {
+        let operation_name =
+            |source| match &parse_executable(source).unwrap().definition_list[0].node {
+                ExecutableDefinition::Operation(operation) => {
+                    operation.node.name.as_ref().map(|name| name.node.clone())
+                }
+                _ => unreachable!(),
+            };
+
+        assert_eq!(operation_name("query($a: Int) { a }"), None);
+        assert_eq!(operation_name("mutation@foo { a }"), None);
+        assert_eq!(operation_name("query{ a }"), None);
+        assert_eq!(
+            operation_name("query A($a: Int) { a }"),
+            Some("A".to_owned())
+        );
+        assert!(parse_executable("queryA { a }").is_err());
+    }
+
+    #[test]
+    fn field_default_value() {
+        assert!(parse_type_system("input Foo { bar: Int = 1 }").is_ok());
+        assert!(parse_type_system("type Foo { bar: Int = 1 }").is_err());
+        assert!(parse_type_system("interface Foo { bar: Int = 1 }").is_err());
+    }
+
+    #[test]
+    fn numeric() {
+        let numeric = |source| value(LocatedSpan::new(source)).unwrap().1;
+
+        assert_eq!(numeric("0"), Value::Int(0));
+        assert_eq!(numeric("-10"), Value::Int(-10));
+        assert_eq!(numeric("10.5"), Value::Float(10.5));
+        assert_eq!(numeric("-1e3"), Value::Float(-1000.0));
+        assert_eq!(numeric("1.5E-1"), Value::Float(0.15));
+        assert!(value(LocatedSpan::new("99999999999999999999")).is_err());
+        assert!(value(LocatedSpan::new("99999999999999999999.")).is_err());
+        assert!(parse_executable("{ a(x: 99999999999999999999.) }").is_err());
+    }
 }
@@ -1,25 +1,37 @@
 use crate::{Result, Span};
 use nom::{combinator::map, sequence::tuple};
 
-#[derive(Debug, PartialEq)]
+#[derive(Debug, Clone, Default, PartialEq)]
 pub struct Position {
     pub line: usize,
     pub column: usize,
     pub offset: usize,
 }
 
-#[derive(Debug, PartialEq)]
+#[derive(Debug, Clone, PartialEq)]
 pub struct Positioned<T: Sized> {
     pub start: Position,
     pub end: Position,
     pub node: T,
 }
 
+impl<T> Positioned<T> {
+    /// Wraps a node that does not come from source text, such as one built from an
+    /// introspection result, at the default position.
+    pub fn new(node: T) -> Self {
+        Positioned {
+            start: Position::default(),
+            end: Position::default(),
+            node,
+        }
+    }
+}
+
 pub(crate) fn position(s: Span) -> Result<Position> {
     map(nom_locate::position, |s: Span| Position {
         line: s.location_line() as usize,
         column: s.get_column(),
-        offset: s.location_offset() as usize,
+        offset: s.location_offset(),
     })(s)
 }
 
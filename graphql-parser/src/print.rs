@@ -0,0 +1,596 @@
+use crate::*;
+use std::fmt::Write;
+
+const INDENT: &str = "  ";
+
+/// Prints a type system document as SDL, one definition per paragraph.
+pub fn print_type_system(document: &TypeSystemDocument) -> String {
+    let definition_list: Vec<String> = document
+        .definition_list
+        .iter()
+        .map(|definition| match &definition.node {
+            TypeSystemDefinition::Schema(schema) => print_schema_definition(&schema.node),
+            TypeSystemDefinition::Type(ty) => print_type_definition(&ty.node),
+            TypeSystemDefinition::Directive(directive) => {
+                print_directive_definition(&directive.node)
+            }
+        })
+        .collect();
+
+    definition_list.join("\n")
+}
+
+/// Prints an executable document, one definition per paragraph.
+pub fn print_executable(document: &ExecutableDocument) -> String {
+    let definition_list: Vec<String> = document
+        .definition_list
+        .iter()
+        .map(|definition| match &definition.node {
+            ExecutableDefinition::Operation(operation) => print_operation(&operation.node),
+            ExecutableDefinition::Fragment(fragment) => print_fragment(&fragment.node),
+        })
+        .collect();
+
+    definition_list.join("\n")
+}
+
+/// Prints a document mixing type system and executable definitions, one definition per
+/// paragraph.
+pub fn print_document(document: &Document) -> String {
+    let definition_list: Vec<String> = document
+        .definition_list
+        .iter()
+        .map(|definition| match &definition.node {
+            DocumentDefinition::Operation(operation) => print_operation(&operation.node),
+            DocumentDefinition::Fragment(fragment) => print_fragment(&fragment.node),
+            DocumentDefinition::Schema(schema) => print_schema_definition(&schema.node),
+            DocumentDefinition::Type(ty) => print_type_definition(&ty.node),
+            DocumentDefinition::Directive(directive) => print_directive_definition(&directive.node),
+        })
+        .collect();
+
+    definition_list.join("\n")
+}
+
+/// Whether `source` has a comment, which parsing does not keep and printing a parsed
+/// document would therefore drop.
+pub fn has_comment(source: &str) -> bool {
+    let mut rest = source;
+    while let Some(index) = rest.find(['#', '"']) {
+        if rest[index..].starts_with('#') {
+            return true;
+        }
+        rest = &rest[index..];
+        rest = if let Some(block) = rest.strip_prefix("\"\"\"") {
+            match block.find("\"\"\"") {
+                Some(end) => &block[end + 3..],
+                None => return false,
+            }
+        } else {
+            let mut char_list = rest[1..].char_indices();
+            let mut end = None;
+            while let Some((index, c)) = char_list.next() {
+                match c {
+                    '\\' => {
+                        char_list.next();
+                    }
+                    '"' | '\n' => {
+                        end = Some(index + 2);
+                        break;
+                    }
+                    _ => {}
+                }
+            }
+            match end {
+                Some(end) => &rest[end..],
+                None => return false,
+            }
+        };
+    }
+    false
+}
+
+/// An anonymous query without variables or directives is printed in its shorthand form.
+fn print_operation(operation: &OperationDefinition) -> String {
+    let mut output = String::new();
+    let is_shorthand = operation.ty == OperationType::Query
+        && operation.name.is_none()
+        && operation.variable_list.is_empty()
+        && operation.directive_list.is_empty();
+    if !is_shorthand {
+        output.push_str(match operation.ty {
+            OperationType::Query => "query",
+            OperationType::Mutation => "mutation",
+            OperationType::Subscription => "subscription",
+        });
+        if let Some(name) = &operation.name {
+            write!(output, " {}", name.node).unwrap();
+        }
+        if !operation.variable_list.is_empty() {
+            let variable_list: Vec<String> = operation
+                .variable_list
+                .iter()
+                .map(|variable| {
+                    let variable = &variable.node;
+                    match &variable.default_value {
+                        Some(default_value) => format!(
+                            "${}: {} = {}",
+                            variable.name.node, variable.ty.node, default_value.node
+                        ),
+                        None => format!("${}: {}", variable.name.node, variable.ty.node),
+                    }
+                })
+                .collect();
+            write!(output, "({})", variable_list.join(", ")).unwrap();
+        }
+        print_directive_list(&mut output, &operation.directive_list);
+        output.push(' ');
+    }
+    print_selection_list(&mut output, &operation.selection_list, "");
+    output.push('\n');
+    output
+}
+
+fn print_fragment(fragment: &FragmentDefinition) -> String {
+    let mut output = String::new();
+    write!(
+        output,
+        "fragment {} on {}",
+        fragment.name.node, fragment.on.node
+    )
+    .unwrap();
+    print_directive_list(&mut output, &fragment.directive_list);
+    output.push(' ');
+    print_selection_list(&mut output, &fragment.selection_list, "");
+    output.push('\n');
+    output
+}
+
+fn print_selection_list(
+    output: &mut String,
+    selection_list: &[Positioned<Selection>],
+    indent: &str,
+) {
+    let selection_indent = format!("{}{}", indent, INDENT);
+    output.push_str("{\n");
+    for selection in selection_list.iter() {
+        output.push_str(&selection_indent);
+        let selection_list = match &selection.node {
+            Selection::Field(field) => {
+                if let Some(alias) = &field.alias {
+                    write!(output, "{}: ", alias.node).unwrap();
+                }
+                output.push_str(&field.name.node);
+                print_argument_list(output, &field.argument_list);
+                print_directive_list(output, &field.directive_list);
+                &field.selection_list
+            }
+            Selection::FragmentSpread(spread) => {
+                write!(output, "...{}", spread.name.node).unwrap();
+                print_directive_list(output, &spread.directive_list);
+                output.push('\n');
+                continue;
+            }
+            Selection::InlineFragment(fragment) => {
+                output.push_str("...");
+                if let Some(on) = &fragment.on {
+                    write!(output, " on {}", on.node).unwrap();
+                }
+                print_directive_list(output, &fragment.directive_list);
+                &fragment.selection_list
+            }
+        };
+        if !selection_list.is_empty() {
+            output.push(' ');
+            print_selection_list(output, selection_list, &selection_indent);
+        }
+        output.push('\n');
+    }
+    output.push_str(indent);
+    output.push('}');
+}
+
+fn print_schema_definition(schema: &SchemaDefinition) -> String {
+    let mut output = String::new();
+    print_description(&mut output, &schema.description, "");
+    print_extend(&mut output, schema.is_extend);
+    output.push_str("schema");
+    print_directive_list(&mut output, &schema.directive_list);
+    if !schema.field_list.is_empty() {
+        output.push_str(" {\n");
+        for field in schema.field_list.iter() {
+            let operation = match field.ty {
+                OperationType::Query => "query",
+                OperationType::Mutation => "mutation",
+                OperationType::Subscription => "subscription",
+            };
+            writeln!(output, "{}{}: {}", INDENT, operation, field.name.node).unwrap();
+        }
+        output.push('}');
+    }
+    output.push('\n');
+    output
+}
+
+fn print_type_definition(definition: &TypeDefinition) -> String {
+    let mut output = String::new();
+    match definition {
+        TypeDefinition::Scalar(ty) => {
+            let ty = &ty.node;
+            print_description(&mut output, &ty.description, "");
+            print_extend(&mut output, ty.is_extend);
+            write!(output, "scalar {}", ty.name.node).unwrap();
+            print_directive_list(&mut output, &ty.directive_list);
+        }
+        TypeDefinition::Object(ty) => {
+            let ty = &ty.node;
+            print_description(&mut output, &ty.description, "");
+            print_extend(&mut output, ty.is_extend);
+            write!(output, "type {}", ty.name.node).unwrap();
+            if !ty.interface_list.is_empty() {
+                let interface_list: Vec<&str> = ty
+                    .interface_list
+                    .iter()
+                    .map(|interface| interface.node.as_str())
+                    .collect();
+                write!(output, " implements {}", interface_list.join(" & ")).unwrap();
+            }
+            print_directive_list(&mut output, &ty.directive_list);
+            print_field_definition_list(&mut output, &ty.field_list);
+        }
+        TypeDefinition::Interface(ty) => {
+            let ty = &ty.node;
+            print_description(&mut output, &ty.description, "");
+            print_extend(&mut output, ty.is_extend);
+            write!(output, "interface {}", ty.name.node).unwrap();
+            print_directive_list(&mut output, &ty.directive_list);
+            print_field_definition_list(&mut output, &ty.field_list);
+        }
+        TypeDefinition::Union(ty) => {
+            let ty = &ty.node;
+            print_description(&mut output, &ty.description, "");
+            print_extend(&mut output, ty.is_extend);
+            write!(output, "union {}", ty.name.node).unwrap();
+            print_directive_list(&mut output, &ty.directive_list);
+            if !ty.member_list.is_empty() {
+                let member_list: Vec<&str> = ty
+                    .member_list
+                    .iter()
+                    .map(|member| member.node.as_str())
+                    .collect();
+                write!(output, " = {}", member_list.join(" | ")).unwrap();
+            }
+        }
+        TypeDefinition::Enum(ty) => {
+            let ty = &ty.node;
+            print_description(&mut output, &ty.description, "");
+            print_extend(&mut output, ty.is_extend);
+            write!(output, "enum {}", ty.name.node).unwrap();
+            print_directive_list(&mut output, &ty.directive_list);
+            if !ty.member_list.is_empty() {
+                output.push_str(" {\n");
+                for member in ty.member_list.iter() {
+                    print_description(&mut output, &member.node.description, INDENT);
+                    write!(output, "{}{}", INDENT, member.node.name.node).unwrap();
+                    print_directive_list(&mut output, &member.node.directive_list);
+                    output.push('\n');
+                }
+                output.push('}');
+            }
+        }
+        TypeDefinition::InputObject(ty) => {
+            let ty = &ty.node;
+            print_description(&mut output, &ty.description, "");
+            print_extend(&mut output, ty.is_extend);
+            write!(output, "input {}", ty.name.node).unwrap();
+            print_directive_list(&mut output, &ty.directive_list);
+            print_field_definition_list(&mut output, &ty.field_list);
+        }
+    }
+    output.push('\n');
+    output
+}
+
+fn print_directive_definition(directive: &DirectiveDefinition) -> String {
+    let mut output = String::new();
+    print_description(&mut output, &directive.description, "");
+    write!(output, "directive @{}", directive.name.node).unwrap();
+    print_field_argument_list(&mut output, &directive.argument_list, "");
+    if directive.is_repeatable {
+        output.push_str(" repeatable");
+    }
+    let location_list: Vec<String> = directive
+        .location_list
+        .iter()
+        .map(|location| location.node.to_string())
+        .collect();
+    writeln!(output, " on {}", location_list.join(" | ")).unwrap();
+    output
+}
+
+fn print_field_definition_list(output: &mut String, field_list: &[Positioned<FieldDefinition>]) {
+    if field_list.is_empty() {
+        return;
+    }
+
+    output.push_str(" {\n");
+    for field in field_list.iter() {
+        let field = &field.node;
+        print_description(output, &field.description, INDENT);
+        write!(output, "{}{}", INDENT, field.name.node).unwrap();
+        print_field_argument_list(output, &field.argument_list, INDENT);
+        write!(output, ": {}", field.ty.node).unwrap();
+        if let Some(default_value) = &field.default_value {
+            write!(output, " = {}", default_value.node).unwrap();
+        }
+        print_directive_list(output, &field.directive_list);
+        output.push('\n');
+    }
+    output.push('}');
+}
+
+/// Arguments are printed on a single line unless one of them has a description.
+fn print_field_argument_list(
+    output: &mut String,
+    argument_list: &[Positioned<FieldArgument>],
+    indent: &str,
+) {
+    if argument_list.is_empty() {
+        return;
+    }
+
+    let print_argument = |output: &mut String, argument: &FieldArgument| {
+        write!(output, "{}: {}", argument.name.node, argument.ty.node).unwrap();
+        if let Some(default_value) = &argument.default_value {
+            write!(output, " = {}", default_value.node).unwrap();
+        }
+        print_directive_list(output, &argument.directive_list);
+    };
+
+    output.push('(');
+    if argument_list
+        .iter()
+        .any(|argument| argument.node.description.is_some())
+    {
+        let argument_indent = format!("{}{}", indent, INDENT);
+        output.push('\n');
+        for argument in argument_list.iter() {
+            print_description(output, &argument.node.description, &argument_indent);
+            output.push_str(&argument_indent);
+            print_argument(output, &argument.node);
+            output.push('\n');
+        }
+        output.push_str(indent);
+    } else {
+        for (index, argument) in argument_list.iter().enumerate() {
+            if index > 0 {
+                output.push_str(", ");
+            }
+            print_argument(output, &argument.node);
+        }
+    }
+    output.push(')');
+}
+
+fn print_directive_list(output: &mut String, directive_list: &[Positioned<Directive>]) {
+    for directive in directive_list.iter() {
+        write!(output, " @{}", directive.node.name.node).unwrap();
+        print_argument_list(output, &directive.node.argument_list);
+    }
+}
+
+fn print_argument_list(output: &mut String, argument_list: &[Positioned<Argument>]) {
+    if !argument_list.is_empty() {
+        let argument_list: Vec<String> = argument_list
+            .iter()
+            .map(|argument| format!("{}: {}", argument.node.name.node, argument.node.value.node))
+            .collect();
+        write!(output, "({})", argument_list.join(", ")).unwrap();
+    }
+}
+
+fn print_description(output: &mut String, description: &Option<Positioned<String>>, indent: &str) {
+    if let Some(description) = description {
+        writeln!(output, "{}\"\"\"", indent).unwrap();
+        for line in block_string_value(&description.node).lines() {
+            if line.is_empty() {
+                output.push('\n');
+            } else {
+                writeln!(output, "{}{}", indent, line.replace("\"\"\"", "\\\"\"\"")).unwrap();
+            }
+        }
+        writeln!(output, "{}\"\"\"", indent).unwrap();
+    }
+}
+
+/// The value of a block string as the spec defines it: the indentation common to every
+/// line but the first is removed, as are the blank lines at the start and the end.
+///
+/// Descriptions are kept in the document as written between the quotes; this is the
+/// text they stand for.
+pub fn block_string_value(raw: &str) -> String {
+    let line_list: Vec<&str> = raw.lines().collect();
+    let is_blank = |line: &str| line.trim_start_matches([' ', '\t']).is_empty();
+    let common_indent = line_list
+        .iter()
+        .skip(1)
+        .filter(|line| !is_blank(line))
+        .map(|line| line.len() - line.trim_start_matches([' ', '\t']).len())
+        .min()
+        .unwrap_or(0);
+    let line_list: Vec<&str> = line_list
+        .iter()
+        .enumerate()
+        .map(|(index, line)| match index {
+            0 => line,
+            _ => line.get(common_indent..).unwrap_or(""),
+        })
+        .collect();
+    let start = line_list
+        .iter()
+        .position(|line| !is_blank(line))
+        .unwrap_or(line_list.len());
+    let end = line_list
+        .iter()
+        .rposition(|line| !is_blank(line))
+        .map_or(start, |end| end + 1);
+    line_list[start..end].join("\n")
+}
+
+fn print_extend(output: &mut String, is_extend: bool) {
+    if is_extend {
+        output.push_str("extend ");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn round_trip() {
+        let source = r#""""
+The query root.
+"""
+type Query implements Node & Entity @key(fields: "id") {
+  id: ID!
+  search(
+    """
+    Text to look for.
+    """
+    text: String = "\"any\""
+    first: Int = 10
+  ): [Result!]! @deprecated(reason: "Use `find`.")
+  find(filter: Filter = {limit: 1, tags: ["a", "b"]}): Result
+}
+
+union Result = Query | Other
+
+enum Color {
+  RED
+  GREEN @deprecated
+}
+
+input Filter {
+  limit: Int = 20
+  tags: [String!]
+}
+
+extend scalar Date @specifiedBy(url: "https://example.com/date")
+
+directive @key(fields: String!) repeatable on OBJECT | INTERFACE
+"#;
+        let document = parse_type_system(source).unwrap();
+        let printed = print_type_system(&document);
+        assert_eq!(printed, source);
+        assert_eq!(
+            print_type_system(&parse_type_system(&printed).unwrap()),
+            printed
+        );
+    }
+
+    #[test]
+    fn multiline_description() {
+        let source = r#"type Query {
+  """
+  The hero of a film.
+
+  Written as:
+      hero(episode: JEDI)
+  and quoted with \""".
+  """
+  hero(
+        """
+        The film.
+          Defaults to all of them.
+        """
+        episode: Episode
+  ): String
+}
+
+enum Episode {
+  """
+  A new hope.
+  Released in 1977.
+  """
+  NEW_HOPE
+}
+"#;
+        let printed = print_type_system(&parse_type_system(source).unwrap());
+        assert_eq!(
+            printed,
+            r#"type Query {
+  """
+  The hero of a film.
+
+  Written as:
+      hero(episode: JEDI)
+  and quoted with \""".
+  """
+  hero(
+    """
+    The film.
+      Defaults to all of them.
+    """
+    episode: Episode
+  ): String
+}
+
+enum Episode {
+  """
+  A new hope.
+  Released in 1977.
+  """
+  NEW_HOPE
+}
+"#
+        );
+        assert_eq!(
+            print_type_system(&parse_type_system(&printed).unwrap()),
+            printed
+        );
+    }
+
+    #[test]
+    fn round_trip_executable() {
+        let source = r#"query Hero($episode: Episode = JEDI, $withFriends: Boolean!) @live {
+  hero(episode: $episode) {
+    __typename
+    ...Common @include(if: $withFriends)
+    ... on Droid {
+      primaryFunction
+    }
+    ... @defer(label: "slow") {
+      friendList(first: 2, filter: {tags: ["a"]}) {
+        name
+      }
+    }
+  }
+  hello: greeting
+}
+
+{
+  greeting
+}
+
+fragment Common on Character @skip(if: false) {
+  id
+  name
+}
+"#;
+        let document = parse_executable(source).unwrap();
+        let printed = print_executable(&document);
+        assert_eq!(printed, source);
+        assert_eq!(print_document(&parse_document(source).unwrap()), printed);
+    }
+
+    #[test]
+    fn comment() {
+        assert!(has_comment("# Heroes\n{ hero }"));
+        assert!(has_comment("{ hero(name: \"#1\") # first\n}"));
+        assert!(!has_comment("{ hero(name: \"#1\\\"\") }"));
+        assert!(!has_comment(
+            "\"\"\"\nA \"#\" sign.\n\"\"\"\ntype Query { a: Int }"
+        ));
+    }
+}
@@ -0,0 +1,360 @@
+use crate::*;
+
+/// A change to a source: the bytes `start..old_end` were replaced by text that now ends at
+/// `new_end`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Edit {
+    pub start: usize,
+    pub old_end: usize,
+    pub new_end: usize,
+}
+
+/// Brings `document` up to date with `source` after `edit`, parsing only the definitions
+/// the edit touches and moving the positions of the ones after it.
+///
+/// The definition on either side of the touched ones is parsed again too, since an edit
+/// can change where a neighbour ends. When that part does not parse on its own the whole
+/// source is parsed, so the error, if any, is located in `source` and `document` is only
+/// replaced on success.
+pub fn reparse_document<'a>(
+    document: &mut Document,
+    source: &'a str,
+    edit: &Edit,
+) -> std::result::Result<(), ParsingError<'a>> {
+    let definition_list = &document.definition_list;
+    let old_length = (source.len() + edit.old_end).checked_sub(edit.new_end);
+    let is_valid = edit.start <= edit.old_end
+        && edit.start <= edit.new_end
+        && edit.new_end <= source.len()
+        && matches!(old_length, Some(old_length) if edit.old_end <= old_length)
+        && source.is_char_boundary(edit.start)
+        && source.is_char_boundary(edit.new_end);
+    if !is_valid {
+        *document = parse_document(source)?;
+        return Ok(());
+    }
+
+    let first = definition_list
+        .iter()
+        .position(|definition| definition.end.offset >= edit.start)
+        .unwrap_or(definition_list.len())
+        .saturating_sub(1);
+    let last = definition_list
+        .iter()
+        .position(|definition| definition.start.offset > edit.old_end)
+        .map_or(definition_list.len(), |last| {
+            (last + 1).min(definition_list.len())
+        });
+    let region_start = match first {
+        0 => 0,
+        first => definition_list[first - 1].end.offset,
+    };
+    let region_end = match definition_list.get(last) {
+        Some(definition) => definition.start.offset + edit.new_end - edit.old_end,
+        None => source.len(),
+    };
+
+    let region = match source
+        .get(region_start..region_end)
+        .and_then(|region| parse_document(region).ok())
+    {
+        Some(region) => region,
+        None => {
+            *document = parse_document(source)?;
+            return Ok(());
+        }
+    };
+
+    let base = position_at(source, region_start);
+    let mut region_list = region.definition_list;
+    for definition in region_list.iter_mut() {
+        definition.shift(&|position| {
+            if position.line == 1 {
+                position.column += base.column - 1;
+            }
+            position.line += base.line - 1;
+            position.offset += base.offset;
+        });
+    }
+
+    let after = document.definition_list.get(last).map(|definition| {
+        let old = definition.start.clone();
+        (position_at(source, region_end), old)
+    });
+    let region_length = region_list.len();
+    document.definition_list.splice(first..last, region_list);
+    if let Some((new, old)) = after {
+        for definition in document.definition_list[first + region_length..].iter_mut() {
+            definition.shift(&|position| {
+                if position.line == old.line {
+                    position.column = position.column - old.column + new.column;
+                }
+                position.line = position.line - old.line + new.line;
+                position.offset = position.offset - old.offset + new.offset;
+            });
+        }
+    }
+    Ok(())
+}
+
+/// The position of `offset` in `source`, counted the way the parser counts.
+fn position_at(source: &str, offset: usize) -> Position {
+    let before = &source[..offset];
+    let line_start = before.rfind('\n').map_or(0, |index| index + 1);
+    Position {
+        line: before.matches('\n').count() + 1,
+        column: offset - line_start + 1,
+        offset,
+    }
+}
+
+/// Moves every position in a node.
+pub(crate) trait Shift {
+    fn shift(&mut self, f: &dyn Fn(&mut Position));
+}
+
+impl<T: Shift> Shift for Positioned<T> {
+    fn shift(&mut self, f: &dyn Fn(&mut Position)) {
+        f(&mut self.start);
+        f(&mut self.end);
+        self.node.shift(f);
+    }
+}
+
+impl<T: Shift> Shift for Option<T> {
+    fn shift(&mut self, f: &dyn Fn(&mut Position)) {
+        if let Some(node) = self {
+            node.shift(f);
+        }
+    }
+}
+
+impl<T: Shift> Shift for Vec<T> {
+    fn shift(&mut self, f: &dyn Fn(&mut Position)) {
+        for node in self.iter_mut() {
+            node.shift(f);
+        }
+    }
+}
+
+macro_rules! shift_leaf {
+    ($($ty:ty),*) => {
+        $(
+            impl Shift for $ty {
+                fn shift(&mut self, _: &dyn Fn(&mut Position)) {}
+            }
+        )*
+    };
+}
+
+shift_leaf!(String, Type, Value, DirectiveLocation);
+
+macro_rules! shift_struct {
+    ($ty:ident { $($field:ident),* }) => {
+        impl Shift for $ty {
+            fn shift(&mut self, f: &dyn Fn(&mut Position)) {
+                $(self.$field.shift(f);)*
+            }
+        }
+    };
+}
+
+shift_struct!(OperationDefinition {
+    name,
+    variable_list,
+    selection_list,
+    directive_list
+});
+shift_struct!(FragmentDefinition {
+    name,
+    on,
+    selection_list,
+    directive_list
+});
+shift_struct!(SchemaDefinition {
+    description,
+    directive_list,
+    field_list
+});
+shift_struct!(VariableDefinition {
+    name,
+    ty,
+    default_value
+});
+shift_struct!(OperationField { name });
+shift_struct!(ScalarType {
+    description,
+    name,
+    directive_list
+});
+shift_struct!(ObjectType {
+    description,
+    interface_list,
+    name,
+    field_list,
+    directive_list
+});
+shift_struct!(InterfaceType {
+    description,
+    name,
+    field_list,
+    directive_list
+});
+shift_struct!(UnionType {
+    description,
+    name,
+    member_list,
+    directive_list
+});
+shift_struct!(EnumType {
+    description,
+    name,
+    member_list,
+    directive_list
+});
+shift_struct!(InputObjectType {
+    description,
+    name,
+    field_list,
+    directive_list
+});
+shift_struct!(DirectiveDefinition {
+    description,
+    name,
+    argument_list,
+    location_list
+});
+shift_struct!(Field {
+    name,
+    alias,
+    argument_list,
+    selection_list,
+    directive_list
+});
+shift_struct!(FragmentSpread {
+    name,
+    directive_list
+});
+shift_struct!(InlineFragment {
+    on,
+    selection_list,
+    directive_list
+});
+shift_struct!(FieldDefinition {
+    description,
+    name,
+    argument_list,
+    ty,
+    default_value,
+    directive_list
+});
+shift_struct!(FieldArgument {
+    description,
+    name,
+    ty,
+    default_value,
+    directive_list
+});
+shift_struct!(EnumMember {
+    description,
+    name,
+    directive_list
+});
+shift_struct!(Directive {
+    name,
+    argument_list
+});
+shift_struct!(Argument { name, value });
+
+impl Shift for DocumentDefinition {
+    fn shift(&mut self, f: &dyn Fn(&mut Position)) {
+        match self {
+            DocumentDefinition::Operation(node) => node.shift(f),
+            DocumentDefinition::Fragment(node) => node.shift(f),
+            DocumentDefinition::Schema(node) => node.shift(f),
+            DocumentDefinition::Type(node) => node.shift(f),
+            DocumentDefinition::Directive(node) => node.shift(f),
+        }
+    }
+}
+
+impl Shift for TypeDefinition {
+    fn shift(&mut self, f: &dyn Fn(&mut Position)) {
+        match self {
+            TypeDefinition::Scalar(node) => node.shift(f),
+            TypeDefinition::Object(node) => node.shift(f),
+            TypeDefinition::Interface(node) => node.shift(f),
+            TypeDefinition::Union(node) => node.shift(f),
+            TypeDefinition::Enum(node) => node.shift(f),
+            TypeDefinition::InputObject(node) => node.shift(f),
+        }
+    }
+}
+
+impl Shift for Selection {
+    fn shift(&mut self, f: &dyn Fn(&mut Position)) {
+        match self {
+            Selection::Field(node) => node.shift(f),
+            Selection::FragmentSpread(node) => node.shift(f),
+            Selection::InlineFragment(node) => node.shift(f),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn edit(source: &str, start: usize, old_end: usize, text: &str) -> (String, Edit) {
+        let edited = format!("{}{}{}", &source[..start], text, &source[old_end..]);
+        let edit = Edit {
+            start,
+            old_end,
+            new_end: start + text.len(),
+        };
+        (edited, edit)
+    }
+
+    #[test]
+    fn reparse() {
+        let source = "type Query {\n  hero: Hero\n}\n\ntype Hero {\n  name: String\n}\n\n\"\"\"\nA droid.\n\"\"\"\ntype Droid { model: String }\n";
+        let mut document = parse_document(source).unwrap();
+
+        // Renames a field, growing its line.
+        let start = source.find("name").unwrap();
+        let (source, change) = edit(source, start, start + 4, "full_name");
+        reparse_document(&mut document, &source, &change).unwrap();
+        assert_eq!(document, parse_document(&source).unwrap());
+
+        // Adds lines and a definition in the gap between two definitions.
+        let start = source.find("\"\"\"").unwrap() - 1;
+        let (source, change) = edit(&source, start, start, "\nscalar Date\n# A comment.\n");
+        reparse_document(&mut document, &source, &change).unwrap();
+        assert_eq!(document, parse_document(&source).unwrap());
+        assert_eq!(document.definition_list.len(), 4);
+
+        // Removes a whole definition, joining two lines.
+        let start = source.find("type Hero").unwrap();
+        let end = source.find("scalar").unwrap();
+        let (source, change) = edit(&source, start, end, "");
+        reparse_document(&mut document, &source, &change).unwrap();
+        assert_eq!(document, parse_document(&source).unwrap());
+
+        // Edits on the line a later definition starts on.
+        let source = "scalar A scalar B type C { c: A }";
+        let mut document = parse_document(source).unwrap();
+        let (source, change) = edit(source, 7, 8, "Long");
+        reparse_document(&mut document, &source, &change).unwrap();
+        assert_eq!(document, parse_document(&source).unwrap());
+
+        // A broken document is reported where the full parse fails.
+        let start = source.find('}').unwrap();
+        let (broken, change) = edit(&source, start, start + 1, "");
+        let error = reparse_document(&mut document, &broken, &change).unwrap_err();
+        assert_eq!(
+            error.position(),
+            parse_document(&broken).unwrap_err().position()
+        );
+        assert_eq!(document, parse_document(&source).unwrap());
+    }
+}
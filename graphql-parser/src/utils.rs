@@ -1,9 +1,9 @@
 use crate::*;
 use nom::{
     branch::alt,
-    bytes::complete::{is_a, is_not, tag, take_till},
-    character::complete::char,
-    combinator::{map, opt},
+    bytes::complete::{is_a, is_not, tag, take, take_till, take_until},
+    character::complete::{anychar, char},
+    combinator::{map, map_opt, opt},
     multi::{many0, many1},
     sequence::tuple,
 };
@@ -41,11 +41,25 @@ pub(crate) fn description(is_extend: bool) -> impl Fn(Span) -> Result<Option<Pos
     }
 }
 
+/// A block string, with `\"""` read as `"""` and its lines left as written.
 pub(crate) fn multiline_description(s: Span) -> Result<String> {
-    map(
-        tuple((tag(r#"""""#), is_not(r#"""""#), tag(r#"""""#))),
-        |(_, string, _): (Span, Span, Span)| string.fragment().to_string(),
-    )(s)
+    let (mut s, _) = tag(r#"""""#)(s)?;
+    let mut description = String::new();
+    loop {
+        let (ss, chunk) = take_until(r#"""""#)(s)?;
+        let (ss, _) = tag(r#"""""#)(ss)?;
+        match chunk.fragment().strip_suffix('\\') {
+            Some(chunk) => {
+                description.push_str(chunk);
+                description.push_str(r#"""""#);
+            }
+            None => {
+                description.push_str(chunk.fragment());
+                return Ok((ss, description));
+            }
+        }
+        s = ss;
+    }
 }
 
 pub(crate) fn hashtag_description(s: Span) -> Result<()> {
@@ -65,14 +79,43 @@ pub(crate) fn hashtag_description(s: Span) -> Result<()> {
 }
 
 pub(crate) fn string(s: Span) -> Result<String> {
-    map(
-        tuple((double_quote, opt(is_not("\"\r\n")), double_quote)),
-        |(_, string, _)| {
-            string
-                .map(|s| s.fragment().to_string())
-                .unwrap_or_else(|| "".to_owned())
-        },
-    )(s)
+    let (mut s, _) = double_quote(s)?;
+    let mut string = String::new();
+    loop {
+        let (ss, chunk) = opt(is_not("\\\"\r\n"))(s)?;
+        if let Some(chunk) = chunk {
+            string.push_str(chunk.fragment());
+        }
+        if let Ok((ss, _)) = double_quote(ss) {
+            return Ok((ss, string));
+        }
+        let (ss, c) = escaped_character(ss)?;
+        string.push(c);
+        s = ss;
+    }
+}
+
+fn escaped_character(s: Span) -> Result<char> {
+    let (s, _) = char('\\')(s)?;
+    alt((
+        map_opt(anychar, |c| match c {
+            '"' | '\\' | '/' => Some(c),
+            'b' => Some('\u{8}'),
+            'f' => Some('\u{c}'),
+            'n' => Some('\n'),
+            'r' => Some('\r'),
+            't' => Some('\t'),
+            _ => None,
+        }),
+        map_opt(
+            tuple((char('u'), take(4usize))),
+            |(_, hex): (char, Span)| {
+                u32::from_str_radix(hex.fragment(), 16)
+                    .ok()
+                    .and_then(std::char::from_u32)
+            },
+        ),
+    ))(s)
 }
 
 pub(crate) fn is_nonzero_digit(c: char) -> bool {
@@ -80,7 +123,7 @@ pub(crate) fn is_nonzero_digit(c: char) -> bool {
 }
 
 pub(crate) fn is_digit(c: char) -> bool {
-    matches!(c, '0'..='9')
+    c.is_ascii_digit()
 }
 
 pub(crate) fn digit(s: Span) -> Result<Span> {
@@ -0,0 +1,223 @@
+use crate::*;
+use futures::stream::{self, StreamExt};
+use graphql_executor::{get_operation, Response, ServerError};
+use graphql_parser::*;
+use std::collections::HashMap;
+use std::sync::Arc;
+
+/// The GraphQL requests carried by an HTTP request, which legacy clients may batch into
+/// a JSON array.
+#[derive(Debug, Clone, PartialEq)]
+pub enum BatchRequest {
+    Single(Request),
+    Batch(Vec<Request>),
+}
+
+impl HttpHandler {
+    /// Executes every request of a batch, up to the configured concurrency at a time, and
+    /// answers with their responses in the order of the requests.
+    ///
+    /// A mutation runs alone: the requests before it finish first, and the requests after
+    /// it wait for it, as if the batch was sent one request at a time.
+    ///
+    /// Identical query strings are parsed once for the whole batch. With persisted queries,
+    /// documents are shared through the store instead.
+    pub async fn execute_batch(&self, request_list: &[Request]) -> Vec<Response> {
+        let prepared_list = self.prepare_batch(request_list);
+        let mut entry_list = request_list.iter().zip(prepared_list).peekable();
+        let mut response_list = Vec::with_capacity(request_list.len());
+        while let Some(entry) = entry_list.next() {
+            if is_mutation(&entry) {
+                response_list.push(self.execute_entry(entry).await);
+                continue;
+            }
+            let mut group = vec![entry];
+            while let Some(entry) = entry_list.next_if(|entry| !is_mutation(entry)) {
+                group.push(entry);
+            }
+            let group_response_list: Vec<Response> = stream::iter(group)
+                .map(|entry| self.execute_entry(entry))
+                .buffered(self.batch_concurrency)
+                .collect()
+                .await;
+            response_list.extend(group_response_list);
+        }
+        response_list
+    }
+
+    async fn execute_entry(&self, (request, document): BatchEntry<'_>) -> Response {
+        let document = match document {
+            Ok(document) => document,
+            Err(error_list) => return Response::from_errors(error_list),
+        };
+        self.execute_document(request, &document, false)
+            .await
+            .unwrap_or_else(|error| {
+                Response::from_errors(vec![ServerError::new(error.to_string())])
+            })
+    }
+
+    fn prepare_batch(
+        &self,
+        request_list: &[Request],
+    ) -> Vec<Result<Arc<ExecutableDocument>, Vec<ServerError>>> {
+        let mut document_map = HashMap::new();
+        request_list
+            .iter()
+            .map(|request| match &request.query {
+                Some(query) if self.persisted_queries.is_none() => document_map
+                    .entry(query.as_str())
+                    .or_insert_with(|| self.prepare(request))
+                    .clone(),
+                _ => self.prepare(request),
+            })
+            .collect()
+    }
+}
+
+type BatchEntry<'a> = (
+    &'a Request,
+    Result<Arc<ExecutableDocument>, Vec<ServerError>>,
+);
+
+fn is_mutation((request, document): &BatchEntry<'_>) -> bool {
+    document.as_ref().is_ok_and(|document| {
+        get_operation(document, request.operation_name.as_deref())
+            .is_ok_and(|operation| operation.ty == OperationType::Mutation)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use futures::executor::block_on;
+    use graphql_executor::*;
+    use serde_json::{json, Value as JsonValue};
+
+    const SCHEMA: &str = r#"
+type Query {
+  hello(name: String): String
+}
+"#;
+
+    fn post(body: &str) -> HttpRequest {
+        HttpRequest {
+            method: "POST".to_owned(),
+            query_string: String::new(),
+            header_list: vec![("Content-Type".to_owned(), "application/json".to_owned())],
+            body: body.as_bytes().to_vec(),
+        }
+    }
+
+    #[test]
+    fn batch() {
+        let schema = Schema::build(&parse_type_system(SCHEMA).unwrap()).unwrap();
+        let handler = HttpHandler::new(schema, |info: &ResolveInfo<'_>| {
+            let name = match info.arguments.get("name") {
+                Some(Value::String(name)) => name.clone(),
+                _ => "world".to_owned(),
+            };
+            Ok(Resolved::value(format!("Hello, {}!", name)))
+        })
+        .with_batch_concurrency(2);
+
+        let response = block_on(handler.handle(&post(
+            r#"[
+                {"query": "query Hello($name: String) { hello(name: $name) }", "variables": {"name": "Ada"}},
+                {"query": "{ unknown }"},
+                {"query": "{"},
+                {"query": "query Hello($name: String) { hello(name: $name) }", "variables": {"name": "Alan"}}
+            ]"#,
+        )));
+        assert_eq!(response.status, 200);
+        assert_eq!(
+            serde_json::from_slice::<JsonValue>(&block_on(response.body.into_bytes())).unwrap(),
+            json!([
+                {"data": {"hello": "Hello, Ada!"}},
+                {"errors": [{
+                    "message": "Cannot query field \"unknown\" on type \"Query\".",
+                    "locations": [{"line": 1, "column": 3}],
+                }]},
+                {"errors": [{
+                    "message": "Syntax Error",
+                    "locations": [{"line": 1, "column": 1}],
+                }]},
+                {"data": {"hello": "Hello, Alan!"}},
+            ])
+        );
+
+        let response = block_on(handler.handle(&post("[]")));
+        assert_eq!(response.status, 400);
+    }
+
+    struct YieldNow(bool);
+
+    impl std::future::Future for YieldNow {
+        type Output = ();
+
+        fn poll(
+            mut self: std::pin::Pin<&mut Self>,
+            cx: &mut std::task::Context<'_>,
+        ) -> std::task::Poll<()> {
+            if self.0 {
+                std::task::Poll::Ready(())
+            } else {
+                self.0 = true;
+                cx.waker().wake_by_ref();
+                std::task::Poll::Pending
+            }
+        }
+    }
+
+    #[test]
+    fn serial_mutation() {
+        let schema = Schema::build(
+            &parse_type_system(
+                "type Query { a: String b: String d: String }\ntype Mutation { c: String }",
+            )
+            .unwrap(),
+        )
+        .unwrap();
+        let log = Arc::new(std::sync::Mutex::new(vec![]));
+        let root = {
+            let log = log.clone();
+            move |info: &ResolveInfo<'_>| {
+                let log = log.clone();
+                let name = info.field_name.to_owned();
+                Ok(Resolved::future(async move {
+                    log.lock().unwrap().push(format!("start {}", name));
+                    YieldNow(false).await;
+                    log.lock().unwrap().push(format!("end {}", name));
+                    Ok(Resolved::value(name))
+                }))
+            }
+        };
+        let handler = HttpHandler::new(schema, root).with_batch_concurrency(4);
+
+        let response_list = block_on(handler.execute_batch(&[
+            Request::new("{ a }"),
+            Request::new("{ b }"),
+            Request::new("mutation { c }"),
+            Request::new("{ d }"),
+        ]));
+        assert_eq!(response_list[2].data, Some(json!({ "c": "c" })));
+        assert_eq!(
+            *log.lock().unwrap(),
+            ["start a", "start b", "end a", "end b", "start c", "end c", "start d", "end d"]
+        );
+    }
+
+    #[test]
+    fn deduplicate() {
+        let schema = Schema::build(&parse_type_system(SCHEMA).unwrap()).unwrap();
+        let handler = HttpHandler::new(schema, |_: &ResolveInfo<'_>| Ok(Resolved::value(true)));
+        let prepared_list = handler.prepare_batch(&[
+            Request::new("{ hello }"),
+            Request::new("{ hello(name: \"Ada\") }"),
+            Request::new("{ hello }"),
+        ]);
+        let document_list: Vec<_> = prepared_list.into_iter().map(Result::unwrap).collect();
+        assert!(Arc::ptr_eq(&document_list[0], &document_list[2]));
+        assert!(!Arc::ptr_eq(&document_list[0], &document_list[1]));
+    }
+}
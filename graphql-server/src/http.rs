@@ -0,0 +1,714 @@
+use crate::*;
+use futures::channel::mpsc;
+use futures::future::{self, FutureExt};
+use futures::stream::{self, BoxStream, StreamExt};
+use graphql_executor::*;
+use graphql_parser::*;
+use serde::Serialize;
+use serde_json::{Map, Value as JsonValue};
+use std::sync::Arc;
+use std::task::Poll;
+
+const GRAPHQL_RESPONSE_JSON: &str = "application/graphql-response+json";
+const JSON: &str = "application/json";
+const MULTIPART_MIXED: &str = "multipart/mixed";
+
+/// An HTTP request, as handed over by whichever server received it.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct HttpRequest {
+    pub method: String,
+    /// The part of the URI after `?`, still percent-encoded.
+    pub query_string: String,
+    pub header_list: Vec<(String, String)>,
+    pub body: Vec<u8>,
+}
+
+impl HttpRequest {
+    /// The value of the first header named `name`, compared case-insensitively.
+    pub fn header(&self, name: &str) -> Option<&str> {
+        find_header(&self.header_list, name)
+    }
+}
+
+#[derive(Debug)]
+pub struct HttpResponse {
+    pub status: u16,
+    pub header_list: Vec<(String, String)>,
+    pub body: HttpBody,
+}
+
+pub enum HttpBody {
+    Full(Vec<u8>),
+    /// Chunks to send as soon as each of them is produced, such as the parts of a
+    /// `multipart/mixed` response.
+    Stream(BoxStream<'static, Vec<u8>>),
+}
+
+impl HttpBody {
+    /// Waits for every chunk of the body.
+    pub async fn into_bytes(self) -> Vec<u8> {
+        match self {
+            HttpBody::Full(bytes) => bytes,
+            HttpBody::Stream(stream) => stream.concat().await,
+        }
+    }
+}
+
+impl std::fmt::Debug for HttpBody {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            HttpBody::Full(bytes) => f.debug_tuple("Full").field(bytes).finish(),
+            HttpBody::Stream(_) => f.debug_tuple("Stream").finish(),
+        }
+    }
+}
+
+impl HttpResponse {
+    pub fn header(&self, name: &str) -> Option<&str> {
+        find_header(&self.header_list, name)
+    }
+}
+
+fn find_header<'a>(header_list: &'a [(String, String)], name: &str) -> Option<&'a str> {
+    header_list
+        .iter()
+        .find(|(header, _)| header.eq_ignore_ascii_case(name))
+        .map(|(_, value)| value.as_str())
+}
+
+/// Why an HTTP request could not be turned into a GraphQL request.
+#[derive(Debug, Clone, PartialEq)]
+pub enum HttpError {
+    MethodNotAllowed(String),
+    /// Only queries may be sent with `GET`, as it must be free of side effects.
+    MutationViaGet(OperationType),
+    UnsupportedMediaType(String),
+    NotAcceptable(String),
+    InvalidRequest(String),
+    /// The body is longer than the limit of the handler, in bytes.
+    PayloadTooLarge(usize),
+}
+
+impl HttpError {
+    pub fn status(&self) -> u16 {
+        match self {
+            HttpError::MethodNotAllowed(_) | HttpError::MutationViaGet(_) => 405,
+            HttpError::UnsupportedMediaType(_) => 415,
+            HttpError::NotAcceptable(_) => 406,
+            HttpError::InvalidRequest(_) => 400,
+            HttpError::PayloadTooLarge(_) => 413,
+        }
+    }
+}
+
+impl std::fmt::Display for HttpError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            HttpError::MethodNotAllowed(method) => {
+                write!(
+                    f,
+                    "GraphQL only supports GET and POST requests, not {}.",
+                    method
+                )
+            }
+            HttpError::MutationViaGet(ty) => {
+                let name = match ty {
+                    OperationType::Query => "query",
+                    OperationType::Mutation => "mutation",
+                    OperationType::Subscription => "subscription",
+                };
+                write!(
+                    f,
+                    "Can only perform a {} operation from a POST request.",
+                    name
+                )
+            }
+            HttpError::UnsupportedMediaType(media_type) => {
+                write!(f, "Unsupported content type \"{}\".", media_type)
+            }
+            HttpError::NotAcceptable(accept) => {
+                write!(
+                    f,
+                    "None of the accepted media types \"{}\" is supported.",
+                    accept
+                )
+            }
+            HttpError::InvalidRequest(message) => write!(f, "{}", message),
+            HttpError::PayloadTooLarge(limit) => {
+                write!(f, "Request body is larger than {} bytes.", limit)
+            }
+        }
+    }
+}
+
+impl std::error::Error for HttpError {}
+
+/// Extracts the GraphQL request carried by a `GET` query string or a JSON `POST` body,
+/// which may hold a batch of requests.
+pub fn parse_http_request(request: &HttpRequest) -> Result<BatchRequest, HttpError> {
+    if request.method.eq_ignore_ascii_case("GET") {
+        let mut graphql_request = Request::default();
+        for pair in request
+            .query_string
+            .split('&')
+            .filter(|pair| !pair.is_empty())
+        {
+            let mut split = pair.splitn(2, '=');
+            let name = percent_decode(split.next().unwrap_or_default())?;
+            let value = percent_decode(split.next().unwrap_or_default())?;
+            match name.as_str() {
+                "query" => graphql_request.query = Some(value),
+                "operationName" if !value.is_empty() => {
+                    graphql_request.operation_name = Some(value)
+                }
+                "variables" if !value.is_empty() => {
+                    graphql_request.variables = parse_json_object("variables", &value)?
+                }
+                "extensions" if !value.is_empty() => {
+                    graphql_request.extensions = parse_json_object("extensions", &value)?
+                }
+                _ => {}
+            }
+        }
+        Ok(BatchRequest::Single(graphql_request))
+    } else if request.method.eq_ignore_ascii_case("POST") {
+        let content_type = request.header("content-type").map(media_type);
+        match content_type.as_deref() {
+            None | Some(JSON) | Some(GRAPHQL_RESPONSE_JSON) => {}
+            Some(content_type) => {
+                return Err(HttpError::UnsupportedMediaType(content_type.to_owned()))
+            }
+        }
+        let invalid = |error: serde_json::Error| {
+            HttpError::InvalidRequest(format!("Invalid JSON body: {}", error))
+        };
+        match serde_json::from_slice(&request.body).map_err(invalid)? {
+            JsonValue::Array(list) if list.is_empty() => Err(HttpError::InvalidRequest(
+                "A batch must contain at least one request.".to_owned(),
+            )),
+            JsonValue::Array(list) => list
+                .into_iter()
+                .map(serde_json::from_value)
+                .collect::<Result<_, _>>()
+                .map(BatchRequest::Batch)
+                .map_err(invalid),
+            body => serde_json::from_value(body)
+                .map(BatchRequest::Single)
+                .map_err(invalid),
+        }
+    } else {
+        Err(HttpError::MethodNotAllowed(request.method.clone()))
+    }
+}
+
+fn media_type(value: &str) -> String {
+    value
+        .split(';')
+        .next()
+        .unwrap_or_default()
+        .trim()
+        .to_ascii_lowercase()
+}
+
+fn parse_json_object(name: &str, value: &str) -> Result<Map<String, JsonValue>, HttpError> {
+    match serde_json::from_str(value) {
+        Ok(JsonValue::Object(map)) => Ok(map),
+        Ok(JsonValue::Null) => Ok(Map::new()),
+        _ => Err(HttpError::InvalidRequest(format!(
+            "The \"{}\" parameter must be a JSON object.",
+            name
+        ))),
+    }
+}
+
+fn percent_decode(value: &str) -> Result<String, HttpError> {
+    let invalid = || HttpError::InvalidRequest("Malformed query string.".to_owned());
+    let mut byte_list = Vec::with_capacity(value.len());
+    let mut bytes = value.bytes();
+    while let Some(byte) = bytes.next() {
+        match byte {
+            b'+' => byte_list.push(b' '),
+            b'%' => {
+                let high = bytes.next().and_then(|byte| (byte as char).to_digit(16));
+                let low = bytes.next().and_then(|byte| (byte as char).to_digit(16));
+                match (high, low) {
+                    (Some(high), Some(low)) => byte_list.push((high * 16 + low) as u8),
+                    _ => return Err(invalid()),
+                }
+            }
+            byte => byte_list.push(byte),
+        }
+    }
+    String::from_utf8(byte_list).map_err(|_| invalid())
+}
+
+/// Picks the media type of the response from the `Accept` header of the request.
+///
+/// A request without `Accept` is answered with `application/json`, as expected by
+/// clients predating `application/graphql-response+json`.
+fn negotiate(request: &HttpRequest) -> Result<&'static str, HttpError> {
+    let accept = match request.header("accept") {
+        Some(accept) => accept,
+        None => return Ok(JSON),
+    };
+    accept
+        .split(',')
+        .map(media_type)
+        .find_map(|media_type| match media_type.as_str() {
+            GRAPHQL_RESPONSE_JSON => Some(GRAPHQL_RESPONSE_JSON),
+            MULTIPART_MIXED => Some(MULTIPART_MIXED),
+            JSON | "application/*" | "*/*" => Some(JSON),
+            _ => None,
+        })
+        .ok_or_else(|| HttpError::NotAcceptable(accept.to_owned()))
+}
+
+/// Answers GraphQL-over-HTTP requests by executing them against a schema.
+pub struct HttpHandler {
+    schema: Schema,
+    root: Arc<dyn Resolver>,
+    pub(crate) persisted_queries: Option<PersistedQueries>,
+    pub(crate) batch_concurrency: usize,
+    pub(crate) max_body_size: usize,
+}
+
+impl HttpHandler {
+    pub fn new<R: Resolver + 'static>(schema: Schema, root: R) -> Self {
+        HttpHandler {
+            schema,
+            root: Arc::new(root),
+            persisted_queries: None,
+            batch_concurrency: 1,
+            max_body_size: 1 << 20,
+        }
+    }
+
+    /// Resolves the documents of requests through `persisted_queries` instead of parsing
+    /// every query.
+    pub fn with_persisted_queries(mut self, persisted_queries: PersistedQueries) -> Self {
+        self.persisted_queries = Some(persisted_queries);
+        self
+    }
+
+    /// Executes up to `limit` requests of a batch at the same time. Mutations always run
+    /// alone.
+    pub fn with_batch_concurrency(mut self, limit: usize) -> Self {
+        self.batch_concurrency = limit.max(1);
+        self
+    }
+
+    /// Refuses request bodies longer than `limit` bytes with 413, instead of 1 MiB.
+    pub fn with_max_body_size(mut self, limit: usize) -> Self {
+        self.max_body_size = limit;
+        self
+    }
+
+    pub fn schema(&self) -> &Schema {
+        &self.schema
+    }
+
+    /// Parses, validates and executes the GraphQL request carried by `request`.
+    ///
+    /// With `application/graphql-response+json`, a request that fails before execution
+    /// is answered with 400; with `application/json`, every well-formed request is
+    /// answered with 200. With `multipart/mixed`, `@defer` and `@stream` are honored and
+    /// the body is streamed, with every payload written as a part as soon as it is ready.
+    pub async fn handle(&self, request: &HttpRequest) -> HttpResponse {
+        let media_type = match negotiate(request) {
+            Ok(media_type) => media_type,
+            Err(error) => return error_response(&error, JSON),
+        };
+        if request.body.len() > self.max_body_size {
+            return error_response(&HttpError::PayloadTooLarge(self.max_body_size), media_type);
+        }
+        let graphql_request = match parse_http_request(request) {
+            Ok(BatchRequest::Single(graphql_request)) => graphql_request,
+            Ok(BatchRequest::Batch(request_list)) => {
+                let response_list = self.execute_batch(&request_list).await;
+                return json_response(200, &response_list, single_media_type(media_type));
+            }
+            Err(error) => return error_response(&error, media_type),
+        };
+        let is_get = request.method.eq_ignore_ascii_case("GET");
+
+        let document = match self.prepare(&graphql_request) {
+            Ok(document) => document,
+            Err(error_list) => {
+                return graphql_response(&Response::from_errors(error_list), media_type)
+            }
+        };
+        let error_list = match self.check_document(&graphql_request, &document, is_get) {
+            Ok(error_list) => error_list,
+            Err(error) => return error_response(&error, media_type),
+        };
+        if !error_list.is_empty() {
+            return graphql_response(&Response::from_errors(error_list), media_type);
+        }
+
+        if media_type == MULTIPART_MIXED {
+            return multipart_response(self.execute_incremental(graphql_request, document));
+        }
+        let response = execute_async(
+            &self.schema,
+            &document,
+            graphql_request.operation_name.as_deref(),
+            &graphql_request.variables,
+            self.root.as_ref(),
+        )
+        .await;
+        graphql_response(&response, media_type)
+    }
+
+    /// The payloads of `document`, owning everything the execution borrows.
+    ///
+    /// The execution runs inside the returned stream: a payload is handed over as soon as
+    /// it is produced, before the work postponed by `@defer` and `@stream` goes on.
+    fn execute_incremental(
+        &self,
+        request: Request,
+        document: Arc<ExecutableDocument>,
+    ) -> BoxStream<'static, IncrementalResponse> {
+        let (sender, mut receiver) = mpsc::unbounded();
+        let schema = self.schema.clone();
+        let root = self.root.clone();
+        let mut execution = Some(
+            async move {
+                let mut response_stream = execute_incremental(
+                    &schema,
+                    &document,
+                    request.operation_name.as_deref(),
+                    &request.variables,
+                    root,
+                );
+                while let Some(response) = response_stream.next().await {
+                    if sender.unbounded_send(response).is_err() {
+                        return;
+                    }
+                    // Gives the payload a chance to be written before any more work runs.
+                    let mut is_yielded = false;
+                    future::poll_fn(|cx| {
+                        if is_yielded {
+                            Poll::Ready(())
+                        } else {
+                            is_yielded = true;
+                            cx.waker().wake_by_ref();
+                            Poll::Pending
+                        }
+                    })
+                    .await;
+                }
+            }
+            .boxed(),
+        );
+        stream::poll_fn(move |cx| {
+            // A payload already produced goes out before the execution is resumed.
+            if let Poll::Ready(Some(response)) = receiver.poll_next_unpin(cx) {
+                return Poll::Ready(Some(response));
+            }
+            if let Some(future) = &mut execution {
+                if future.poll_unpin(cx).is_ready() {
+                    execution = None;
+                }
+            }
+            receiver.poll_next_unpin(cx)
+        })
+        .boxed()
+    }
+
+    /// Validates and executes `document`, refusing anything but a query when the request
+    /// came with `GET`.
+    pub(crate) async fn execute_document(
+        &self,
+        request: &Request,
+        document: &ExecutableDocument,
+        is_get: bool,
+    ) -> Result<Response, HttpError> {
+        let error_list = self.check_document(request, document, is_get)?;
+        if !error_list.is_empty() {
+            return Ok(Response::from_errors(error_list));
+        }
+
+        Ok(execute_async(
+            &self.schema,
+            document,
+            request.operation_name.as_deref(),
+            &request.variables,
+            self.root.as_ref(),
+        )
+        .await)
+    }
+
+    /// Validates `document`, refusing anything but a query when the request came with
+    /// `GET`.
+    fn check_document(
+        &self,
+        request: &Request,
+        document: &ExecutableDocument,
+        is_get: bool,
+    ) -> Result<Vec<ServerError>, HttpError> {
+        let operation_name = request.operation_name.as_deref();
+        if let Ok(operation) = get_operation(document, operation_name) {
+            if is_get && operation.ty != OperationType::Query {
+                return Err(HttpError::MutationViaGet(operation.ty.clone()));
+            }
+        }
+        Ok(validate(&self.schema, document))
+    }
+
+    pub(crate) fn prepare(
+        &self,
+        request: &Request,
+    ) -> Result<Arc<ExecutableDocument>, Vec<ServerError>> {
+        let document = match &self.persisted_queries {
+            Some(persisted_queries) => persisted_queries.resolve(request),
+            None => match &request.query {
+                Some(query) => parse_executable(query)
+                    .map(Arc::new)
+                    .map_err(|error| PersistedQueryError::Syntax(error.position())),
+                None => Err(PersistedQueryError::MissingQuery),
+            },
+        };
+        document.map_err(|error| vec![error.into()])
+    }
+}
+
+fn graphql_response(response: &Response, media_type: &str) -> HttpResponse {
+    if media_type == MULTIPART_MIXED {
+        return multipart_response(stream::iter(vec![response.clone().into()]).boxed());
+    }
+    let status = if response.data.is_none() && media_type == GRAPHQL_RESPONSE_JSON {
+        400
+    } else {
+        200
+    };
+    json_response(status, response, media_type)
+}
+
+fn json_response<T: Serialize>(status: u16, body: &T, media_type: &str) -> HttpResponse {
+    HttpResponse {
+        status,
+        header_list: vec![(
+            "Content-Type".to_owned(),
+            format!("{}; charset=utf-8", media_type),
+        )],
+        body: HttpBody::Full(serde_json::to_vec(body).unwrap()),
+    }
+}
+
+/// Streams every payload as a part of a `multipart/mixed` body, following the incremental
+/// delivery over HTTP proposal.
+fn multipart_response(response_stream: BoxStream<'static, IncrementalResponse>) -> HttpResponse {
+    let body = response_stream
+        .map(|response| {
+            let mut part =
+                b"\r\n---\r\nContent-Type: application/json; charset=utf-8\r\n\r\n".to_vec();
+            part.extend(serde_json::to_vec(&response).unwrap());
+            part
+        })
+        .chain(stream::once(future::ready(b"\r\n-----\r\n".to_vec())));
+    HttpResponse {
+        status: 200,
+        header_list: vec![(
+            "Content-Type".to_owned(),
+            format!("{}; boundary=\"-\"; deferSpec=20220824", MULTIPART_MIXED),
+        )],
+        body: HttpBody::Stream(body.boxed()),
+    }
+}
+
+/// The media type to answer with when the response cannot be split into payloads.
+fn single_media_type(media_type: &str) -> &str {
+    if media_type == MULTIPART_MIXED {
+        JSON
+    } else {
+        media_type
+    }
+}
+
+fn error_response(error: &HttpError, media_type: &str) -> HttpResponse {
+    let mut response = graphql_response(
+        &Response::from_errors(vec![ServerError::new(error.to_string())]),
+        single_media_type(media_type),
+    );
+    response.status = error.status();
+    match error {
+        HttpError::MethodNotAllowed(_) => response
+            .header_list
+            .push(("Allow".to_owned(), "GET, POST".to_owned())),
+        HttpError::MutationViaGet(_) => response
+            .header_list
+            .push(("Allow".to_owned(), "POST".to_owned())),
+        _ => {}
+    }
+    response
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use futures::executor::block_on;
+    use serde_json::json;
+
+    const SCHEMA: &str = r#"
+type Query {
+  hello(name: String): String
+}
+
+type Mutation {
+  reset: Boolean
+}
+"#;
+
+    fn handler() -> HttpHandler {
+        let schema = Schema::build(&parse_type_system(SCHEMA).unwrap()).unwrap();
+        HttpHandler::new(schema, |info: &ResolveInfo<'_>| match info.field_name {
+            "hello" => {
+                let name = match info.arguments.get("name") {
+                    Some(Value::String(name)) => name.clone(),
+                    _ => "world".to_owned(),
+                };
+                Ok(Resolved::value(format!("Hello, {}!", name)))
+            }
+            _ => Ok(Resolved::value(true)),
+        })
+    }
+
+    fn get(query_string: &str, accept: Option<&str>) -> HttpRequest {
+        HttpRequest {
+            method: "GET".to_owned(),
+            query_string: query_string.to_owned(),
+            header_list: accept
+                .map(|accept| vec![("Accept".to_owned(), accept.to_owned())])
+                .unwrap_or_default(),
+            body: vec![],
+        }
+    }
+
+    fn post(content_type: &str, body: &str) -> HttpRequest {
+        HttpRequest {
+            method: "POST".to_owned(),
+            query_string: String::new(),
+            header_list: vec![
+                ("content-type".to_owned(), content_type.to_owned()),
+                ("accept".to_owned(), GRAPHQL_RESPONSE_JSON.to_owned()),
+            ],
+            body: body.as_bytes().to_vec(),
+        }
+    }
+
+    fn body(response: HttpResponse) -> JsonValue {
+        serde_json::from_slice(&block_on(response.body.into_bytes())).unwrap()
+    }
+
+    #[test]
+    fn parse_request() {
+        let request = match parse_http_request(&get(
+            "query=query%20Hello(%24name%3A%20String)%7Bhello(name%3A%24name)%7D&operationName=Hello&variables=%7B%22name%22%3A%22Ada%22%7D",
+            None,
+        )) {
+            Ok(BatchRequest::Single(request)) => request,
+            request => panic!("{:?}", request),
+        };
+        assert_eq!(
+            serde_json::to_value(request).unwrap(),
+            json!({
+                "query": "query Hello($name: String){hello(name:$name)}",
+                "operationName": "Hello",
+                "variables": {"name": "Ada"},
+                "extensions": {},
+            })
+        );
+        assert_eq!(
+            parse_http_request(&get("query=%7Bhello%7D&variables=%5B%5D", None)),
+            Err(HttpError::InvalidRequest(
+                "The \"variables\" parameter must be a JSON object.".to_owned()
+            ))
+        );
+        assert_eq!(
+            parse_http_request(&post("text/plain", "{}")),
+            Err(HttpError::UnsupportedMediaType("text/plain".to_owned()))
+        );
+    }
+
+    #[test]
+    fn handle() {
+        let handler = handler();
+
+        let response = block_on(handler.handle(&get("query=%7Bhello%7D", None)));
+        assert_eq!(response.status, 200);
+        assert_eq!(
+            response.header("content-type"),
+            Some("application/json; charset=utf-8")
+        );
+        assert_eq!(body(response), json!({"data": {"hello": "Hello, world!"}}));
+
+        let response = block_on(handler.handle(&post(
+            "application/json; charset=utf-8",
+            r#"{"query": "query Hello($name: String) { hello(name: $name) }", "variables": {"name": "Ada"}}"#,
+        )));
+        assert_eq!(response.status, 200);
+        assert_eq!(body(response), json!({"data": {"hello": "Hello, Ada!"}}));
+
+        let response = block_on(handler.handle(&get(
+            "query=mutation%7Breset%7D",
+            Some(GRAPHQL_RESPONSE_JSON),
+        )));
+        assert_eq!(response.status, 405);
+        assert_eq!(response.header("allow"), Some("POST"));
+        let response = block_on(handler.handle(&post(JSON, r#"{"query": "mutation { reset }"}"#)));
+        assert_eq!(body(response), json!({"data": {"reset": true}}));
+
+        let response = block_on(handler.handle(&post(JSON, r#"{"query": "{ unknown }"}"#)));
+        assert_eq!(response.status, 400);
+        assert_eq!(
+            body(response),
+            json!({"errors": [{
+                "message": "Cannot query field \"unknown\" on type \"Query\".",
+                "locations": [{"line": 1, "column": 3}],
+            }]})
+        );
+        let response = block_on(handler.handle(&get("query=%7Bunknown%7D", Some("*/*"))));
+        assert_eq!(response.status, 200);
+
+        let response = block_on(handler.handle(&post(JSON, "{")));
+        assert_eq!(response.status, 400);
+        let response = block_on(handler.handle(&get("query=%7Bhello%7D", Some("text/html"))));
+        assert_eq!(response.status, 406);
+        let mut request = get("", None);
+        request.method = "PUT".to_owned();
+        let response = block_on(handler.handle(&request));
+        assert_eq!(response.status, 405);
+        assert_eq!(response.header("allow"), Some("GET, POST"));
+        let limited = self::handler().with_max_body_size(8);
+        let response = block_on(limited.handle(&post(JSON, r#"{"query": "{ hello }"}"#)));
+        assert_eq!(response.status, 413);
+        assert_eq!(
+            body(response),
+            json!({"errors": [{"message": "Request body is larger than 8 bytes."}]})
+        );
+    }
+
+    #[test]
+    fn multipart() {
+        let handler = handler();
+        let response = block_on(handler.handle(&get(
+            "query=%7Bhello%20...%20%40defer(label%3A%22ada%22)%7Bada%3A%20hello(name%3A%22Ada%22)%7D%7D",
+            Some("multipart/mixed; deferSpec=20220824, application/json"),
+        )));
+        assert_eq!(response.status, 200);
+        assert_eq!(
+            response.header("content-type"),
+            Some("multipart/mixed; boundary=\"-\"; deferSpec=20220824")
+        );
+        assert_eq!(
+            String::from_utf8(block_on(response.body.into_bytes())).unwrap(),
+            concat!(
+                "\r\n---\r\nContent-Type: application/json; charset=utf-8\r\n\r\n",
+                r#"{"data":{"hello":"Hello, world!"},"hasNext":true}"#,
+                "\r\n---\r\nContent-Type: application/json; charset=utf-8\r\n\r\n",
+                r#"{"incremental":[{"data":{"ada":"Hello, Ada!"},"path":[],"label":"ada"}],"hasNext":false}"#,
+                "\r\n-----\r\n",
+            )
+        );
+    }
+}
@@ -0,0 +1,15 @@
+#![warn(clippy::all)]
+
+mod batch;
+mod http;
+mod local;
+mod persisted;
+mod request;
+mod ws;
+
+pub use batch::*;
+pub use http::*;
+pub use local::*;
+pub use persisted::*;
+pub use request::*;
+pub use ws::*;
@@ -0,0 +1,258 @@
+use crate::*;
+use futures::executor::{block_on, block_on_stream};
+use std::io::{BufRead, BufReader, Write};
+use std::net::{TcpListener, TcpStream, ToSocketAddrs};
+use std::sync::Arc;
+use std::thread;
+
+/// Serves `handler` over HTTP/1.1 on `address`, one thread per connection, until
+/// accepting a connection fails.
+///
+/// This is a minimal server for development and tests; production services are expected
+/// to mount `HttpHandler::handle` in their own server.
+pub fn serve_local<A: ToSocketAddrs>(handler: Arc<HttpHandler>, address: A) -> std::io::Result<()> {
+    let listener = TcpListener::bind(address)?;
+    for stream in listener.incoming() {
+        let stream = stream?;
+        let handler = handler.clone();
+        thread::spawn(move || {
+            let _ = handle_connection(&handler, stream);
+        });
+    }
+    Ok(())
+}
+
+fn handle_connection(handler: &HttpHandler, stream: TcpStream) -> std::io::Result<()> {
+    let mut reader = BufReader::new(stream.try_clone()?);
+    let request = match read_request(&mut reader, handler.max_body_size)? {
+        Ok(request) => request,
+        Err(status) => {
+            return write_response(
+                stream,
+                HttpResponse {
+                    status,
+                    header_list: vec![],
+                    body: HttpBody::Full(vec![]),
+                },
+            )
+        }
+    };
+    let response = block_on(handler.handle(&request));
+    write_response(stream, response)
+}
+
+/// Reads one request, or the status to answer with when it is not well-formed HTTP/1.1 or
+/// its body is longer than `max_body_size`. Such a body is not read at all.
+fn read_request<R: BufRead>(
+    reader: &mut R,
+    max_body_size: usize,
+) -> std::io::Result<Result<HttpRequest, u16>> {
+    let mut line = String::new();
+    reader.read_line(&mut line)?;
+    let mut part_list = line.split_whitespace();
+    let (method, target) = match (part_list.next(), part_list.next()) {
+        (Some(method), Some(target)) => (method.to_owned(), target.to_owned()),
+        _ => return Ok(Err(400)),
+    };
+    let query_string = match target.find('?') {
+        Some(index) => target[index + 1..].to_owned(),
+        None => String::new(),
+    };
+
+    let mut header_list = vec![];
+    loop {
+        line.clear();
+        if reader.read_line(&mut line)? == 0 {
+            return Ok(Err(400));
+        }
+        let header = line.trim_end();
+        if header.is_empty() {
+            break;
+        }
+        match header.find(':') {
+            Some(index) => header_list.push((
+                header[..index].trim().to_owned(),
+                header[index + 1..].trim().to_owned(),
+            )),
+            None => return Ok(Err(400)),
+        }
+    }
+
+    let mut request = HttpRequest {
+        method,
+        query_string,
+        header_list,
+        body: vec![],
+    };
+    let content_length = match request.header("content-length") {
+        Some(length) => match length.parse::<usize>() {
+            Ok(length) => length,
+            Err(_) => return Ok(Err(400)),
+        },
+        None => 0,
+    };
+    if content_length > max_body_size {
+        return Ok(Err(413));
+    }
+    request.body.resize(content_length, 0);
+    reader.read_exact(&mut request.body)?;
+    Ok(Ok(request))
+}
+
+/// Writes a streamed body with chunked encoding, flushing every chunk as it comes.
+fn write_response(mut stream: TcpStream, response: HttpResponse) -> std::io::Result<()> {
+    let reason = match response.status {
+        200 => "OK",
+        400 => "Bad Request",
+        405 => "Method Not Allowed",
+        406 => "Not Acceptable",
+        413 => "Payload Too Large",
+        415 => "Unsupported Media Type",
+        _ => "",
+    };
+    let mut head = format!("HTTP/1.1 {} {}\r\n", response.status, reason);
+    for (name, value) in response.header_list.iter() {
+        head.push_str(&format!("{}: {}\r\n", name, value));
+    }
+    match response.body {
+        HttpBody::Full(body) => {
+            head.push_str(&format!(
+                "Content-Length: {}\r\nConnection: close\r\n\r\n",
+                body.len()
+            ));
+            stream.write_all(head.as_bytes())?;
+            stream.write_all(&body)?;
+        }
+        HttpBody::Stream(body) => {
+            head.push_str("Transfer-Encoding: chunked\r\nConnection: close\r\n\r\n");
+            stream.write_all(head.as_bytes())?;
+            for chunk in block_on_stream(body).filter(|chunk| !chunk.is_empty()) {
+                write!(stream, "{:x}\r\n", chunk.len())?;
+                stream.write_all(&chunk)?;
+                stream.write_all(b"\r\n")?;
+                stream.flush()?;
+            }
+            stream.write_all(b"0\r\n\r\n")?;
+        }
+    }
+    stream.flush()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn read() {
+        let raw = "POST /graphql?debug=1 HTTP/1.1\r\nHost: localhost\r\nContent-Type: application/json\r\nContent-Length: 16\r\n\r\n{\"query\":\"{a}\"}\n";
+        let request = read_request(&mut raw.as_bytes(), 16).unwrap().unwrap();
+        assert_eq!(request.method, "POST");
+        assert_eq!(request.query_string, "debug=1");
+        assert_eq!(request.header("content-type"), Some("application/json"));
+        assert_eq!(request.body, b"{\"query\":\"{a}\"}\n");
+
+        assert_eq!(read_request(&mut raw.as_bytes(), 15).unwrap(), Err(413));
+        assert_eq!(
+            read_request(&mut "GET\r\n\r\n".as_bytes(), 16).unwrap(),
+            Err(400)
+        );
+    }
+
+    #[test]
+    fn payload_too_large() {
+        use graphql_executor::*;
+        use graphql_parser::parse_type_system;
+        use std::io::Read;
+
+        let schema =
+            Schema::build(&parse_type_system("type Query { a: String }").unwrap()).unwrap();
+        let handler = HttpHandler::new(schema, |_: &ResolveInfo<'_>| Ok(Resolved::value("a")))
+            .with_max_body_size(64);
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let address = listener.local_addr().unwrap();
+        let server = thread::spawn(move || {
+            for _ in 0..2 {
+                let (stream, _) = listener.accept().unwrap();
+                handle_connection(&handler, stream).unwrap();
+            }
+        });
+        let send = |raw: &str| {
+            let mut stream = TcpStream::connect(address).unwrap();
+            stream.write_all(raw.as_bytes()).unwrap();
+            let mut response = String::new();
+            stream.read_to_string(&mut response).unwrap();
+            response
+        };
+
+        // The length alone is enough to refuse the request, before any byte of the body.
+        let response = send("POST / HTTP/1.1\r\nContent-Length: 18446744073709551615\r\n\r\n");
+        assert!(response.starts_with("HTTP/1.1 413 Payload Too Large\r\n"));
+        let response = send(
+            "POST / HTTP/1.1\r\nContent-Type: application/json\r\nContent-Length: 15\r\n\r\n{\"query\":\"{a}\"}",
+        );
+        assert!(response.starts_with("HTTP/1.1 200 OK\r\n"));
+        assert!(response.ends_with("{\"data\":{\"a\":\"a\"}}"));
+        server.join().unwrap();
+    }
+
+    #[test]
+    fn streamed_multipart() {
+        use graphql_executor::*;
+        use graphql_parser::parse_type_system;
+        use std::io::Read;
+        use std::sync::{mpsc, Mutex};
+        use std::time::Duration;
+
+        let schema =
+            Schema::build(&parse_type_system("type Query { fast: String slow: String }").unwrap())
+                .unwrap();
+        let (release, released) = mpsc::channel::<()>();
+        let released = Mutex::new(released);
+        let handler = HttpHandler::new(schema, move |info: &ResolveInfo<'_>| {
+            if info.field_name == "slow" {
+                released.lock().unwrap().recv().unwrap();
+            }
+            Ok(Resolved::value(info.field_name))
+        });
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let address = listener.local_addr().unwrap();
+        let server = thread::spawn(move || {
+            let (stream, _) = listener.accept().unwrap();
+            handle_connection(&handler, stream).unwrap();
+        });
+
+        let body = r#"{"query":"{ fast ... @defer { slow } }"}"#;
+        let mut stream = TcpStream::connect(address).unwrap();
+        stream
+            .set_read_timeout(Some(Duration::from_secs(10)))
+            .unwrap();
+        write!(
+            stream,
+            "POST / HTTP/1.1\r\nContent-Type: application/json\r\nAccept: multipart/mixed\r\nContent-Length: {}\r\n\r\n{}",
+            body.len(),
+            body
+        )
+        .unwrap();
+
+        // The deferred resolver is still waiting while the initial part is read.
+        let mut response = vec![];
+        let mut buffer = [0; 1024];
+        while !String::from_utf8_lossy(&response).contains(r#""hasNext":true}"#) {
+            let length = stream.read(&mut buffer).unwrap();
+            assert_ne!(length, 0);
+            response.extend_from_slice(&buffer[..length]);
+        }
+        let head = String::from_utf8_lossy(&response).into_owned();
+        assert!(head.starts_with("HTTP/1.1 200 OK\r\n"));
+        assert!(head.contains("Transfer-Encoding: chunked\r\n"));
+        assert!(head.contains(r#"{"data":{"fast":"fast"},"hasNext":true}"#));
+
+        release.send(()).unwrap();
+        stream.read_to_end(&mut response).unwrap();
+        let response = String::from_utf8(response).unwrap();
+        assert!(response
+            .contains(r#"{"incremental":[{"data":{"slow":"slow"},"path":[]}],"hasNext":false}"#));
+        assert!(response.ends_with("\r\n-----\r\n\r\n0\r\n\r\n"));
+        server.join().unwrap();
+    }
+}
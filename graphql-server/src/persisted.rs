@@ -0,0 +1,292 @@
+use crate::*;
+use graphql_executor::ServerError;
+use graphql_parser::*;
+use serde_json::{json, Value as JsonValue};
+use sha2::{Digest, Sha256};
+use std::collections::{BTreeMap, HashMap};
+use std::fmt::Write;
+use std::sync::{Arc, Mutex};
+
+/// Where persisted queries are kept, keyed by the SHA-256 hash of their source.
+pub trait PersistedQueryStore: Send + Sync {
+    fn get(&self, hash: &str) -> Option<Arc<ExecutableDocument>>;
+
+    fn insert(&self, hash: &str, document: Arc<ExecutableDocument>);
+}
+
+/// An in-memory store evicting the least recently used query once it holds `capacity`.
+pub struct LruQueryStore {
+    capacity: usize,
+    inner: Mutex<LruInner>,
+}
+
+#[derive(Default)]
+struct LruInner {
+    tick: u64,
+    entry_map: HashMap<String, (u64, Arc<ExecutableDocument>)>,
+    /// The hash of every entry, by the tick it was last used at.
+    recency_map: BTreeMap<u64, String>,
+}
+
+impl LruQueryStore {
+    pub fn new(capacity: usize) -> Self {
+        LruQueryStore {
+            capacity,
+            inner: Mutex::new(LruInner::default()),
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.inner.lock().unwrap().entry_map.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+impl PersistedQueryStore for LruQueryStore {
+    fn get(&self, hash: &str) -> Option<Arc<ExecutableDocument>> {
+        let mut inner = self.inner.lock().unwrap();
+        inner.tick += 1;
+        let tick = inner.tick;
+        let (last_used, document) = inner.entry_map.get_mut(hash)?;
+        let previous = std::mem::replace(last_used, tick);
+        let document = document.clone();
+        inner.recency_map.remove(&previous);
+        inner.recency_map.insert(tick, hash.to_owned());
+        Some(document)
+    }
+
+    fn insert(&self, hash: &str, document: Arc<ExecutableDocument>) {
+        if self.capacity == 0 {
+            return;
+        }
+        let mut inner = self.inner.lock().unwrap();
+        inner.tick += 1;
+        let tick = inner.tick;
+        if let Some((previous, _)) = inner.entry_map.insert(hash.to_owned(), (tick, document)) {
+            inner.recency_map.remove(&previous);
+        }
+        inner.recency_map.insert(tick, hash.to_owned());
+        while inner.entry_map.len() > self.capacity {
+            let oldest = *inner.recency_map.keys().next().unwrap();
+            let hash = inner.recency_map.remove(&oldest).unwrap();
+            inner.entry_map.remove(&hash);
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum PersistedQueryError {
+    /// The hash is not registered; the client is expected to retry with the full query.
+    NotFound,
+    UnsupportedVersion,
+    /// The extension does not carry a `sha256Hash` string.
+    MissingHash,
+    HashMismatch,
+    MissingQuery,
+    Syntax(Position),
+}
+
+impl std::fmt::Display for PersistedQueryError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            PersistedQueryError::NotFound => write!(f, "PersistedQueryNotFound"),
+            PersistedQueryError::UnsupportedVersion => {
+                write!(f, "Unsupported persisted query version.")
+            }
+            PersistedQueryError::MissingHash => {
+                write!(
+                    f,
+                    "Must provide sha256Hash in the persistedQuery extension."
+                )
+            }
+            PersistedQueryError::HashMismatch => {
+                write!(f, "Provided sha256Hash does not match query.")
+            }
+            PersistedQueryError::MissingQuery => write!(f, "Must provide query string."),
+            PersistedQueryError::Syntax(_) => write!(f, "Syntax Error"),
+        }
+    }
+}
+
+impl std::error::Error for PersistedQueryError {}
+
+impl From<PersistedQueryError> for ServerError {
+    fn from(error: PersistedQueryError) -> Self {
+        let mut server_error = ServerError::new(error.to_string());
+        match &error {
+            PersistedQueryError::NotFound => {
+                server_error.extensions = Some(json!({"code": "PERSISTED_QUERY_NOT_FOUND"}));
+            }
+            PersistedQueryError::Syntax(position) => server_error = server_error.at(position),
+            _ => {}
+        }
+        server_error
+    }
+}
+
+/// Resolves requests to parsed documents following the automatic persisted queries
+/// protocol, so that a query is parsed once and then only referred to by its hash.
+pub struct PersistedQueries {
+    store: Box<dyn PersistedQueryStore>,
+}
+
+impl PersistedQueries {
+    pub fn new<S: PersistedQueryStore + 'static>(store: S) -> Self {
+        PersistedQueries {
+            store: Box::new(store),
+        }
+    }
+
+    /// Keeps up to `capacity` queries in an `LruQueryStore`.
+    pub fn with_capacity(capacity: usize) -> Self {
+        PersistedQueries::new(LruQueryStore::new(capacity))
+    }
+
+    pub fn store(&self) -> &dyn PersistedQueryStore {
+        self.store.as_ref()
+    }
+
+    /// Finds the document of `request`.
+    ///
+    /// A request carrying `extensions.persistedQuery.sha256Hash` without a query is looked
+    /// up in the store. With a query, the hash is verified and the parsed query registered
+    /// under it. Requests without the extension are parsed as usual.
+    pub fn resolve(
+        &self,
+        request: &Request,
+    ) -> Result<Arc<ExecutableDocument>, PersistedQueryError> {
+        let persisted_query = match request.extensions.get("persistedQuery") {
+            Some(persisted_query) => persisted_query,
+            None => {
+                let query = request
+                    .query
+                    .as_deref()
+                    .ok_or(PersistedQueryError::MissingQuery)?;
+                return parse(query);
+            }
+        };
+        if persisted_query.get("version") != Some(&JsonValue::from(1)) {
+            return Err(PersistedQueryError::UnsupportedVersion);
+        }
+        let hash = match persisted_query.get("sha256Hash") {
+            Some(JsonValue::String(hash)) => hash.to_ascii_lowercase(),
+            _ => return Err(PersistedQueryError::MissingHash),
+        };
+
+        match &request.query {
+            Some(query) => {
+                if sha256_hex(query) != hash {
+                    return Err(PersistedQueryError::HashMismatch);
+                }
+                let document = parse(query)?;
+                self.store.insert(&hash, document.clone());
+                Ok(document)
+            }
+            None => self.store.get(&hash).ok_or(PersistedQueryError::NotFound),
+        }
+    }
+}
+
+fn parse(query: &str) -> Result<Arc<ExecutableDocument>, PersistedQueryError> {
+    parse_executable(query)
+        .map(Arc::new)
+        .map_err(|error| PersistedQueryError::Syntax(error.position()))
+}
+
+fn sha256_hex(text: &str) -> String {
+    Sha256::digest(text.as_bytes())
+        .iter()
+        .fold(String::with_capacity(64), |mut output, byte| {
+            write!(output, "{:02x}", byte).unwrap();
+            output
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const QUERY: &str = "{ me { name } }";
+
+    fn request(query: Option<&str>, hash: &str) -> Request {
+        serde_json::from_value(json!({
+            "query": query,
+            "extensions": {"persistedQuery": {"version": 1, "sha256Hash": hash}},
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn persisted_query() {
+        let persisted_queries = PersistedQueries::with_capacity(1);
+        let hash = sha256_hex(QUERY);
+
+        let error = persisted_queries
+            .resolve(&request(None, &hash))
+            .unwrap_err();
+        assert_eq!(error, PersistedQueryError::NotFound);
+        assert_eq!(
+            serde_json::to_value(ServerError::from(error)).unwrap(),
+            json!({
+                "message": "PersistedQueryNotFound",
+                "extensions": {"code": "PERSISTED_QUERY_NOT_FOUND"},
+            })
+        );
+
+        assert_eq!(
+            persisted_queries.resolve(&request(Some("{ me { id } }"), &hash)),
+            Err(PersistedQueryError::HashMismatch)
+        );
+        let document = persisted_queries
+            .resolve(&request(Some(QUERY), &hash))
+            .unwrap();
+        assert_eq!(document, parse(QUERY).unwrap());
+        let cached = persisted_queries.resolve(&request(None, &hash)).unwrap();
+        assert!(Arc::ptr_eq(&document, &cached));
+
+        let other = "{ me { id } }";
+        persisted_queries
+            .resolve(&request(Some(other), &sha256_hex(other)))
+            .unwrap();
+        assert_eq!(
+            persisted_queries.resolve(&request(None, &hash)),
+            Err(PersistedQueryError::NotFound)
+        );
+        let request: Request = serde_json::from_value(json!({
+            "query": QUERY,
+            "extensions": {"persistedQuery": {"version": 1}},
+        }))
+        .unwrap();
+        let error = persisted_queries.resolve(&request).unwrap_err();
+        assert_eq!(error, PersistedQueryError::MissingHash);
+        assert_eq!(
+            serde_json::to_value(ServerError::from(error)).unwrap(),
+            json!({"message": "Must provide sha256Hash in the persistedQuery extension."})
+        );
+        assert_eq!(
+            persisted_queries.resolve(&Request::new("{ me ")),
+            Err(PersistedQueryError::Syntax(Position {
+                line: 1,
+                column: 1,
+                offset: 0
+            }))
+        );
+    }
+
+    #[test]
+    fn lru() {
+        let store = LruQueryStore::new(2);
+        let document = parse(QUERY).unwrap();
+        store.insert("a", document.clone());
+        store.insert("b", document.clone());
+        assert!(store.get("a").is_some());
+        store.insert("c", document);
+        assert_eq!(store.len(), 2);
+        assert!(store.get("a").is_some());
+        assert!(store.get("b").is_none());
+        assert!(store.get("c").is_some());
+    }
+}
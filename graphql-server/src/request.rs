@@ -0,0 +1,35 @@
+use graphql_executor::Variables;
+use serde::{Deserialize, Deserializer, Serialize};
+use serde_json::{Map, Value as JsonValue};
+
+/// A GraphQL request as sent by clients, independent of the transport carrying it.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Request {
+    /// Missing when the request only names a persisted query in its `extensions`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub query: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub operation_name: Option<String>,
+    #[serde(default, deserialize_with = "null_as_default")]
+    pub variables: Variables,
+    #[serde(default, deserialize_with = "null_as_default")]
+    pub extensions: Map<String, JsonValue>,
+}
+
+impl Request {
+    pub fn new<T: Into<String>>(query: T) -> Self {
+        Request {
+            query: Some(query.into()),
+            ..Request::default()
+        }
+    }
+}
+
+fn null_as_default<'de, D, T>(deserializer: D) -> Result<T, D::Error>
+where
+    D: Deserializer<'de>,
+    T: Deserialize<'de> + Default,
+{
+    Option::<T>::deserialize(deserializer).map(Option::unwrap_or_default)
+}
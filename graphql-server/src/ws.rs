@@ -0,0 +1,625 @@
+use crate::*;
+use futures::{
+    channel::mpsc::{self, UnboundedSender},
+    future::{abortable, AbortHandle, BoxFuture, Fuse, FutureExt},
+    select_biased,
+    sink::{Sink, SinkExt},
+    stream::{FuturesUnordered, Stream, StreamExt},
+};
+use graphql_executor::*;
+use graphql_parser::*;
+use serde_json::{json, Value as JsonValue};
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Duration;
+
+/// The WebSocket subprotocols GraphQL operations can be carried over.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum WsProtocol {
+    /// `graphql-transport-ws`, from the `graphql-ws` library.
+    TransportWs,
+    /// `graphql-ws`, from the legacy `subscriptions-transport-ws` library.
+    Legacy,
+}
+
+impl WsProtocol {
+    /// Picks the protocol named by a `Sec-WebSocket-Protocol` value.
+    pub fn from_subprotocol(name: &str) -> Option<Self> {
+        match name.trim() {
+            "graphql-transport-ws" => Some(WsProtocol::TransportWs),
+            "graphql-ws" => Some(WsProtocol::Legacy),
+            _ => None,
+        }
+    }
+
+    pub fn subprotocol(&self) -> &'static str {
+        match self {
+            WsProtocol::TransportWs => "graphql-transport-ws",
+            WsProtocol::Legacy => "graphql-ws",
+        }
+    }
+}
+
+/// A frame sent to the client.
+#[derive(Debug, Clone, PartialEq)]
+pub enum WsMessage {
+    Text(String),
+    /// Closes the socket with a code and a reason; nothing is sent after it.
+    Close(u16, String),
+}
+
+type Sleep = Arc<dyn Fn(Duration) -> BoxFuture<'static, ()> + Send + Sync>;
+
+type ConnectionInit = Arc<dyn Fn(&JsonValue) -> Result<(), String> + Send + Sync>;
+
+/// Runs GraphQL operations sent over a WebSocket, independent of the socket library.
+///
+/// Timers are created through the `sleep` function, so that any async runtime can be used.
+pub struct WsHandler {
+    schema: Schema,
+    root: Arc<dyn Resolver>,
+    sleep: Sleep,
+    connection_init_timeout: Duration,
+    keep_alive_interval: Option<Duration>,
+    connection_init: Option<ConnectionInit>,
+}
+
+impl WsHandler {
+    pub fn new<R, F>(schema: Schema, root: R, sleep: F) -> Self
+    where
+        R: Resolver + 'static,
+        F: Fn(Duration) -> BoxFuture<'static, ()> + Send + Sync + 'static,
+    {
+        WsHandler {
+            schema,
+            root: Arc::new(root),
+            sleep: Arc::new(sleep),
+            connection_init_timeout: Duration::from_secs(3),
+            keep_alive_interval: None,
+            connection_init: None,
+        }
+    }
+
+    /// Closes connections that have not sent `connection_init` within `timeout`.
+    pub fn with_connection_init_timeout(mut self, timeout: Duration) -> Self {
+        self.connection_init_timeout = timeout;
+        self
+    }
+
+    /// Sends `ping` (or `ka` with the legacy protocol) every `interval` once acknowledged.
+    pub fn with_keep_alive(mut self, interval: Duration) -> Self {
+        self.keep_alive_interval = Some(interval);
+        self
+    }
+
+    /// Checks the payload of `connection_init`, rejecting the connection on error.
+    pub fn with_connection_init<F>(mut self, connection_init: F) -> Self
+    where
+        F: Fn(&JsonValue) -> Result<(), String> + Send + Sync + 'static,
+    {
+        self.connection_init = Some(Arc::new(connection_init));
+        self
+    }
+
+    /// Serves one connection until `incoming` ends, the client goes away or the socket is
+    /// closed by the protocol.
+    pub async fn serve<I, O>(&self, protocol: WsProtocol, incoming: I, mut outgoing: O)
+    where
+        I: Stream<Item = String> + Unpin,
+        O: Sink<WsMessage> + Unpin,
+    {
+        let (event_sender, event_receiver) = mpsc::unbounded();
+        let mut session = Session {
+            handler: self,
+            protocol,
+            state: SessionState::Waiting,
+            operation_map: HashMap::new(),
+            generation: 0,
+            event_sender,
+            spawn_list: vec![],
+        };
+        let mut incoming = incoming.fuse();
+        let mut event_receiver = event_receiver.fuse();
+        let mut task_list = FuturesUnordered::new();
+        let mut init_timeout = (self.sleep)(self.connection_init_timeout).fuse();
+        let mut keep_alive = Fuse::terminated();
+
+        loop {
+            let message_list = select_biased! {
+                event = event_receiver.select_next_some() => session.event(event),
+                () = task_list.select_next_some() => vec![],
+                text = incoming.next() => match text {
+                    Some(text) => {
+                        let was_waiting = session.state == SessionState::Waiting;
+                        let message_list = session.receive(&text);
+                        if was_waiting && session.state == SessionState::Acknowledged {
+                            if let Some(interval) = self.keep_alive_interval {
+                                keep_alive = (self.sleep)(interval).fuse();
+                            }
+                        }
+                        message_list
+                    }
+                    None => return,
+                },
+                () = init_timeout => match session.state {
+                    SessionState::Waiting => vec![WsMessage::Close(
+                        4408,
+                        "Connection initialisation timeout".to_owned(),
+                    )],
+                    SessionState::Acknowledged => vec![],
+                },
+                () = keep_alive => {
+                    if let Some(interval) = self.keep_alive_interval {
+                        keep_alive = (self.sleep)(interval).fuse();
+                    }
+                    vec![session.keep_alive()]
+                },
+            };
+            task_list.extend(session.spawn_list.drain(..));
+
+            for message in message_list {
+                let is_close = matches!(message, WsMessage::Close(..));
+                if outgoing.send(message).await.is_err() || is_close {
+                    return;
+                }
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum SessionState {
+    Waiting,
+    Acknowledged,
+}
+
+enum Event {
+    Next(Response),
+    /// The operation failed before producing any result.
+    Error(Vec<ServerError>),
+    Complete,
+}
+
+struct Session<'a> {
+    handler: &'a WsHandler,
+    protocol: WsProtocol,
+    state: SessionState,
+    /// The running operations by id, with the generation telling apart operations that
+    /// reuse the id of a finished one.
+    operation_map: HashMap<String, (u64, AbortHandle)>,
+    generation: u64,
+    event_sender: UnboundedSender<(String, u64, Event)>,
+    spawn_list: Vec<BoxFuture<'static, ()>>,
+}
+
+impl<'a> Session<'a> {
+    fn receive(&mut self, text: &str) -> Vec<WsMessage> {
+        let message: JsonValue = match serde_json::from_str(text) {
+            Ok(message @ JsonValue::Object(_)) => message,
+            _ => return self.invalid_message("Message must be a JSON object."),
+        };
+        let id = message["id"].as_str();
+        match (self.protocol, message["type"].as_str().unwrap_or_default()) {
+            (_, "connection_init") => self.connection_init(&message["payload"]),
+            (WsProtocol::TransportWs, "ping") => {
+                vec![self.message("pong", None, message.get("payload").cloned())]
+            }
+            (WsProtocol::TransportWs, "pong") => vec![],
+            (WsProtocol::TransportWs, "subscribe") | (WsProtocol::Legacy, "start") => match id {
+                Some(id) => self.start(id, &message["payload"]),
+                None => self.invalid_message("Message is missing an id."),
+            },
+            (WsProtocol::TransportWs, "complete") | (WsProtocol::Legacy, "stop") => {
+                if let Some((_, handle)) = id.and_then(|id| self.operation_map.remove(id)) {
+                    handle.abort();
+                }
+                vec![]
+            }
+            (WsProtocol::Legacy, "connection_terminate") => {
+                vec![WsMessage::Close(1000, "Normal Closure".to_owned())]
+            }
+            (_, ty) => self.invalid_message(&format!("Invalid message type \"{}\".", ty)),
+        }
+    }
+
+    fn connection_init(&mut self, payload: &JsonValue) -> Vec<WsMessage> {
+        if self.state == SessionState::Acknowledged {
+            return vec![WsMessage::Close(
+                4429,
+                "Too many initialisation requests".to_owned(),
+            )];
+        }
+        let result = match &self.handler.connection_init {
+            Some(connection_init) => connection_init(payload),
+            None => Ok(()),
+        };
+        match (self.protocol, result) {
+            (_, Ok(())) => {
+                self.state = SessionState::Acknowledged;
+                let mut message_list = vec![self.message("connection_ack", None, None)];
+                if self.protocol == WsProtocol::Legacy && self.handler.keep_alive_interval.is_some()
+                {
+                    message_list.push(self.keep_alive());
+                }
+                message_list
+            }
+            (WsProtocol::TransportWs, Err(_)) => {
+                vec![WsMessage::Close(4403, "Forbidden".to_owned())]
+            }
+            (WsProtocol::Legacy, Err(message)) => vec![
+                self.message(
+                    "connection_error",
+                    None,
+                    Some(json!({ "message": message })),
+                ),
+                WsMessage::Close(1011, message),
+            ],
+        }
+    }
+
+    fn start(&mut self, id: &str, payload: &JsonValue) -> Vec<WsMessage> {
+        if self.state == SessionState::Waiting {
+            return match self.protocol {
+                WsProtocol::TransportWs => vec![WsMessage::Close(4401, "Unauthorized".to_owned())],
+                WsProtocol::Legacy => self.error_message(
+                    id,
+                    vec![ServerError::new("Connection has not been initialized.")],
+                ),
+            };
+        }
+        if self.operation_map.contains_key(id) {
+            return match self.protocol {
+                WsProtocol::TransportWs => vec![WsMessage::Close(
+                    4409,
+                    format!("Subscriber for {} already exists", id),
+                )],
+                WsProtocol::Legacy => self.error_message(
+                    id,
+                    vec![ServerError::new(format!(
+                        "Operation with id \"{}\" is already running.",
+                        id
+                    ))],
+                ),
+            };
+        }
+        let request: Request = match serde_json::from_value(payload.clone()) {
+            Ok(request) => request,
+            Err(error) => return self.invalid_message(&format!("Invalid payload: {}", error)),
+        };
+        let document = match request.query.as_deref().map(parse_executable) {
+            Some(Ok(document)) => document,
+            Some(Err(error)) => {
+                return self.error_message(
+                    id,
+                    vec![ServerError::new("Syntax Error").at(&error.position())],
+                )
+            }
+            None => {
+                return self.error_message(id, vec![ServerError::new("Must provide query string.")])
+            }
+        };
+        let error_list = validate(&self.handler.schema, &document);
+        if !error_list.is_empty() {
+            return self.error_message(id, error_list);
+        }
+
+        self.generation += 1;
+        let generation = self.generation;
+        let schema = self.handler.schema.clone();
+        let root = self.handler.root.clone();
+        let sender = self.event_sender.clone();
+        let owned_id = id.to_owned();
+        let send = move |event: Event| {
+            let _ = sender.unbounded_send((owned_id.clone(), generation, event));
+        };
+        let (task, handle) = abortable(async move {
+            let operation_name = request.operation_name.as_deref();
+            let is_subscription = matches!(
+                get_operation(&document, operation_name),
+                Ok(operation) if operation.ty == OperationType::Subscription
+            );
+            if is_subscription {
+                match subscribe(
+                    &schema,
+                    &document,
+                    operation_name,
+                    &request.variables,
+                    root.as_ref(),
+                )
+                .await
+                {
+                    Ok(mut stream) => {
+                        while let Some(response) = stream.next().await {
+                            send(Event::Next(response));
+                        }
+                    }
+                    Err(response) => return send(Event::Error(response.errors)),
+                }
+            } else {
+                let response = execute_async(
+                    &schema,
+                    &document,
+                    operation_name,
+                    &request.variables,
+                    root.as_ref(),
+                )
+                .await;
+                if response.data.is_none() {
+                    return send(Event::Error(response.errors));
+                }
+                send(Event::Next(response));
+            }
+            send(Event::Complete);
+        });
+        self.operation_map
+            .insert(id.to_owned(), (generation, handle));
+        self.spawn_list.push(task.map(|_| ()).boxed());
+        vec![]
+    }
+
+    fn event(&mut self, (id, generation, event): (String, u64, Event)) -> Vec<WsMessage> {
+        match self.operation_map.get(&id) {
+            Some((running, _)) if *running == generation => {}
+            _ => return vec![],
+        }
+        match event {
+            Event::Next(response) => {
+                let ty = match self.protocol {
+                    WsProtocol::TransportWs => "next",
+                    WsProtocol::Legacy => "data",
+                };
+                vec![self.message(ty, Some(&id), Some(json!(response)))]
+            }
+            Event::Error(error_list) => {
+                self.operation_map.remove(&id);
+                self.error_message(&id, error_list)
+            }
+            Event::Complete => {
+                self.operation_map.remove(&id);
+                vec![self.message("complete", Some(&id), None)]
+            }
+        }
+    }
+
+    /// Reports an operation that failed before producing a result. The legacy protocol
+    /// carries such errors in a `data` message followed by `complete`.
+    fn error_message(&self, id: &str, error_list: Vec<ServerError>) -> Vec<WsMessage> {
+        match self.protocol {
+            WsProtocol::TransportWs => {
+                vec![self.message("error", Some(id), Some(json!(error_list)))]
+            }
+            WsProtocol::Legacy => vec![
+                self.message(
+                    "data",
+                    Some(id),
+                    Some(json!(Response::from_errors(error_list))),
+                ),
+                self.message("complete", Some(id), None),
+            ],
+        }
+    }
+
+    fn invalid_message(&self, message: &str) -> Vec<WsMessage> {
+        match self.protocol {
+            WsProtocol::TransportWs => vec![WsMessage::Close(4400, message.to_owned())],
+            WsProtocol::Legacy => {
+                vec![self.message("error", None, Some(json!({ "message": message })))]
+            }
+        }
+    }
+
+    fn keep_alive(&self) -> WsMessage {
+        match self.protocol {
+            WsProtocol::TransportWs => self.message("ping", None, None),
+            WsProtocol::Legacy => self.message("ka", None, None),
+        }
+    }
+
+    fn message(&self, ty: &str, id: Option<&str>, payload: Option<JsonValue>) -> WsMessage {
+        let mut message = json!({ "type": ty });
+        if let Some(id) = id {
+            message["id"] = json!(id);
+        }
+        if let Some(payload) = payload {
+            message["payload"] = payload;
+        }
+        WsMessage::Text(message.to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use futures::{executor::block_on, future};
+
+    const SCHEMA: &str = r#"
+type Query {
+  hello: String
+}
+
+type Subscription {
+  count(to: Int!): Int
+}
+"#;
+
+    fn handler(sleep: fn(Duration) -> BoxFuture<'static, ()>) -> WsHandler {
+        let schema = Schema::build(&parse_type_system(SCHEMA).unwrap()).unwrap();
+        let root = |info: &ResolveInfo<'_>| match info.field_name {
+            "count" => {
+                let to = match info.arguments.get("to") {
+                    Some(Value::Int(to)) => *to,
+                    _ => 0,
+                };
+                Ok(Resolved::stream(futures::stream::iter(
+                    (1..=to).map(|count| Ok(Resolved::value(count))),
+                )))
+            }
+            _ => Ok(Resolved::value("world")),
+        };
+        WsHandler::new(schema, root, sleep).with_connection_init(|payload| {
+            match payload["token"].as_str() {
+                Some("secret") => Ok(()),
+                _ => Err("Invalid token.".to_owned()),
+            }
+        })
+    }
+
+    fn never(_: Duration) -> BoxFuture<'static, ()> {
+        future::pending().boxed()
+    }
+
+    /// Sends every client message in turn, collecting the server messages until `until`
+    /// of them have arrived, then hangs up.
+    fn exchange(
+        handler: &WsHandler,
+        protocol: WsProtocol,
+        client_list: &[JsonValue],
+        until: usize,
+    ) -> Vec<JsonValue> {
+        let (client, incoming) = mpsc::unbounded();
+        let (outgoing, mut server) = mpsc::unbounded();
+        for message in client_list {
+            client.unbounded_send(message.to_string()).unwrap();
+        }
+        block_on(async {
+            let serve = handler.serve(protocol, incoming, outgoing).fuse();
+            let collect = async {
+                let mut message_list = vec![];
+                while message_list.len() < until {
+                    match server.next().await {
+                        Some(WsMessage::Text(text)) => {
+                            message_list.push(serde_json::from_str(&text).unwrap())
+                        }
+                        Some(WsMessage::Close(code, reason)) => {
+                            message_list.push(json!({ "close": code, "reason": reason }))
+                        }
+                        None => break,
+                    }
+                }
+                drop(client);
+                message_list
+            };
+            future::join(serve, collect).await.1
+        })
+    }
+
+    #[test]
+    fn transport_ws() {
+        let handler = handler(never);
+        let message_list = exchange(
+            &handler,
+            WsProtocol::TransportWs,
+            &[
+                json!({"type": "connection_init", "payload": {"token": "secret"}}),
+                json!({"type": "ping", "payload": {"at": 1}}),
+                json!({"id": "1", "type": "subscribe", "payload": {"query": "subscription { count(to: 2) }"}}),
+            ],
+            5,
+        );
+        assert_eq!(
+            message_list,
+            vec![
+                json!({"type": "connection_ack"}),
+                json!({"type": "pong", "payload": {"at": 1}}),
+                json!({"type": "next", "id": "1", "payload": {"data": {"count": 1}}}),
+                json!({"type": "next", "id": "1", "payload": {"data": {"count": 2}}}),
+                json!({"type": "complete", "id": "1"}),
+            ]
+        );
+
+        let message_list = exchange(
+            &handler,
+            WsProtocol::TransportWs,
+            &[
+                json!({"type": "connection_init", "payload": {"token": "secret"}}),
+                json!({"id": "1", "type": "subscribe", "payload": {"query": "{ nope }"}}),
+                json!({"id": "2", "type": "subscribe", "payload": {"query": "{ hello }"}}),
+                json!({"type": "connection_init"}),
+            ],
+            5,
+        );
+        assert_eq!(
+            message_list,
+            vec![
+                json!({"type": "connection_ack"}),
+                json!({"type": "error", "id": "1", "payload": [{
+                    "message": "Cannot query field \"nope\" on type \"Query\".",
+                    "locations": [{"line": 1, "column": 3}],
+                }]}),
+                json!({"type": "next", "id": "2", "payload": {"data": {"hello": "world"}}}),
+                json!({"type": "complete", "id": "2"}),
+                json!({"close": 4429, "reason": "Too many initialisation requests"}),
+            ]
+        );
+
+        let message_list = exchange(
+            &handler,
+            WsProtocol::TransportWs,
+            &[json!({"id": "1", "type": "subscribe", "payload": {"query": "{ hello }"}})],
+            1,
+        );
+        assert_eq!(
+            message_list,
+            vec![json!({"close": 4401, "reason": "Unauthorized"})]
+        );
+        let message_list = exchange(
+            &handler,
+            WsProtocol::TransportWs,
+            &[json!({"type": "connection_init", "payload": {"token": "guess"}})],
+            1,
+        );
+        assert_eq!(
+            message_list,
+            vec![json!({"close": 4403, "reason": "Forbidden"})]
+        );
+    }
+
+    #[test]
+    fn legacy() {
+        let handler = handler(never).with_keep_alive(Duration::from_secs(30));
+        let message_list = exchange(
+            &handler,
+            WsProtocol::Legacy,
+            &[
+                json!({"type": "connection_init", "payload": {"token": "secret"}}),
+                json!({"id": "a", "type": "start", "payload": {"query": "{ hello }"}}),
+                json!({"type": "unknown"}),
+            ],
+            5,
+        );
+        assert_eq!(
+            message_list,
+            vec![
+                json!({"type": "connection_ack"}),
+                json!({"type": "ka"}),
+                json!({"type": "data", "id": "a", "payload": {"data": {"hello": "world"}}}),
+                json!({"type": "complete", "id": "a"}),
+                json!({"type": "error", "payload": {"message": "Invalid message type \"unknown\"."}}),
+            ]
+        );
+
+        let message_list = exchange(
+            &handler,
+            WsProtocol::Legacy,
+            &[json!({"type": "connection_init", "payload": {}})],
+            2,
+        );
+        assert_eq!(
+            message_list,
+            vec![
+                json!({"type": "connection_error", "payload": {"message": "Invalid token."}}),
+                json!({"close": 1011, "reason": "Invalid token."}),
+            ]
+        );
+    }
+
+    #[test]
+    fn init_timeout() {
+        let handler = handler(|_| future::ready(()).boxed());
+        let message_list = exchange(&handler, WsProtocol::TransportWs, &[], 1);
+        assert_eq!(
+            message_list,
+            vec![json!({"close": 4408, "reason": "Connection initialisation timeout"})]
+        );
+    }
+}
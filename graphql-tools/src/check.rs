@@ -0,0 +1,179 @@
+use graphql_executor::*;
+use graphql_parser::*;
+
+/// The validation errors of one operation of the corpus against the checked schema.
+#[derive(Debug, Clone, PartialEq)]
+pub struct OperationReport {
+    /// The name the document was registered with in the corpus.
+    pub document: String,
+    pub operation_name: Option<String>,
+    /// Where the operation starts in its document.
+    pub position: Position,
+    pub error_list: Vec<ServerError>,
+}
+
+impl std::fmt::Display for OperationReport {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let operation_name = self.operation_name.as_deref().unwrap_or("<anonymous>");
+        for (index, error) in self.error_list.iter().enumerate() {
+            if index > 0 {
+                writeln!(f)?;
+            }
+            match error.locations.first() {
+                Some(location) => write!(
+                    f,
+                    "{}:{}:{}: {}: {}",
+                    self.document, location.line, location.column, operation_name, error.message
+                )?,
+                None => write!(
+                    f,
+                    "{}: {}: {}",
+                    self.document, operation_name, error.message
+                )?,
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Validates every operation of a corpus of named documents against `schema`, and reports
+/// the operations that would be rejected, in corpus order.
+///
+/// Each operation is checked on its own with the fragments it spreads, so an error inside
+/// a shared fragment is reported for every operation that uses it.
+pub fn check_operations<'a, I>(schema: &Schema, corpus: I) -> Vec<OperationReport>
+where
+    I: IntoIterator<Item = (&'a str, &'a ExecutableDocument)>,
+{
+    let mut report_list = vec![];
+    for (name, document) in corpus {
+        for definition in document.definition_list.iter() {
+            let operation = match &definition.node {
+                ExecutableDefinition::Operation(operation) => operation,
+                ExecutableDefinition::Fragment(_) => continue,
+            };
+            let error_list = validate_operation(schema, document, operation);
+            if !error_list.is_empty() {
+                report_list.push(OperationReport {
+                    document: name.to_owned(),
+                    operation_name: operation.node.name.as_ref().map(|name| name.node.clone()),
+                    position: operation.start.clone(),
+                    error_list,
+                });
+            }
+        }
+    }
+    report_list
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const OLD_SCHEMA: &str = r#"
+type Query {
+  user(id: ID!): User
+  search(text: String!): [Result!]!
+  users(role: Role): [User!]!
+}
+
+type User {
+  id: ID!
+  name: String
+  email: String
+  role: Role!
+}
+
+type Team {
+  name: String
+}
+
+union Result = User | Team
+
+enum Role {
+  ADMIN
+  GUEST
+}
+"#;
+
+    const NEW_SCHEMA: &str = r#"
+type Query {
+  user(id: ID!): User
+  search(text: String!): [Result!]!
+  users(role: Role): [User!]!
+}
+
+type User {
+  id: ID!
+  name: String
+  role: Role!
+}
+
+type Team {
+  name: String
+}
+
+union Result = User
+
+enum Role {
+  ADMIN
+}
+"#;
+
+    const PROFILE: &str = r#"query Profile($id: ID!) {
+  user(id: $id) { ...UserFields }
+}
+
+query Guest {
+  user(id: "guest") @include(if: false) { role }
+}
+
+fragment UserFields on User {
+  name
+  email
+}
+"#;
+
+    const SEARCH: &str = r#"query Search {
+  search(text: "a") {
+    ... on User { name }
+    ... on Team { name }
+  }
+}
+"#;
+
+    const ADMIN: &str = r#"{
+  user(id: "1") { role }
+  guests: users(role: GUEST) { id }
+}
+"#;
+
+    #[test]
+    fn check_corpus() {
+        let profile = parse_executable(PROFILE).unwrap();
+        let search = parse_executable(SEARCH).unwrap();
+        let admin = parse_executable(ADMIN).unwrap();
+        let corpus = vec![
+            ("profile.graphql", &profile),
+            ("search.graphql", &search),
+            ("admin.graphql", &admin),
+        ];
+
+        let old_schema = Schema::build(&parse_type_system(OLD_SCHEMA).unwrap()).unwrap();
+        assert_eq!(check_operations(&old_schema, corpus.clone()), vec![]);
+
+        let new_schema = Schema::build(&parse_type_system(NEW_SCHEMA).unwrap()).unwrap();
+        let report_list = check_operations(&new_schema, corpus);
+        let output: Vec<String> = report_list.iter().map(ToString::to_string).collect();
+        assert_eq!(
+            output,
+            vec![
+                "profile.graphql:11:3: Profile: Cannot query field \"email\" on type \"User\".",
+                "search.graphql:4:5: Search: Fragment cannot be spread here as objects of type \"Result\" can never be of type \"Team\".",
+                "admin.graphql:3:23: <anonymous>: Argument \"role\" has invalid value GUEST; Value \"GUEST\" does not exist in \"Role\" enum.",
+            ]
+        );
+        assert_eq!(report_list[0].position.line, 1);
+        assert_eq!(report_list[1].operation_name.as_deref(), Some("Search"));
+    }
+}
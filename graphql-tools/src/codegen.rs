@@ -0,0 +1,879 @@
+use crate::lint::word_list;
+use graphql_executor::*;
+use graphql_parser::*;
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt::Write;
+
+const DERIVE: &str = "#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]";
+
+const KEYWORD_LIST: [&str; 51] = [
+    "abstract", "as", "async", "await", "become", "box", "break", "const", "continue", "crate",
+    "do", "dyn", "else", "enum", "extern", "false", "final", "fn", "for", "if", "impl", "in",
+    "let", "loop", "macro", "match", "mod", "move", "mut", "override", "priv", "pub", "ref",
+    "return", "self", "Self", "static", "struct", "super", "trait", "true", "try", "type",
+    "typeof", "unsafe", "unsized", "use", "virtual", "where", "while", "yield",
+];
+
+/// How `generate_client` maps the schema to Rust.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct CodegenConfig {
+    scalar_map: BTreeMap<String, String>,
+}
+
+impl CodegenConfig {
+    /// Maps a custom scalar to a Rust type implementing `Serialize` and `Deserialize`,
+    /// such as `chrono::DateTime<chrono::Utc>`. Custom scalars are `String` otherwise.
+    ///
+    /// The type is written inside the module of each operation, so paths into the crate
+    /// the code is included in start with `crate::`.
+    pub fn with_scalar<N: Into<String>, T: Into<String>>(mut self, name: N, rust_type: T) -> Self {
+        self.scalar_map.insert(name.into(), rust_type.into());
+        self
+    }
+}
+
+/// Generates Rust types for the operations of `document`, to be written to a file by a
+/// build script and included with `include!`.
+///
+/// Every operation becomes a module named after it in snake_case, holding the operation
+/// text with the fragments it uses as `QUERY`, a `Variables` struct and a `ResponseData`
+/// struct for the `data` of its response, along with a struct for every selection set
+/// nested in it. Fragments are merged into the selection sets spreading them. A selection
+/// set on an interface or union that selects fields of some of its types becomes an enum
+/// tagged by `__typename`, with a variant for every possible type, and `__typename` is
+/// added to `QUERY` wherever it is needed. Such enums, like the enums of the schema, end
+/// with an `Unknown` variant for types and values added to the schema later. Fields under
+/// `@skip` or `@include` are optional. The enums and input objects of the schema the
+/// operations use are generated once, before the modules.
+///
+/// The code derives `serde::Serialize` and `serde::Deserialize`. `document` is validated
+/// first, and every operation needs a name.
+pub fn generate_client(
+    schema: &Schema,
+    document: &ExecutableDocument,
+    config: &CodegenConfig,
+) -> Result<String, Vec<ServerError>> {
+    let mut error_list = validate(schema, document);
+    for definition in document.definition_list.iter() {
+        if let ExecutableDefinition::Operation(operation) = &definition.node {
+            if operation.node.name.is_none() {
+                let error = ServerError::new("Operation needs a name to generate types for it.")
+                    .at(&operation.start);
+                error_list.push(error);
+            }
+        }
+    }
+    if !error_list.is_empty() {
+        return Err(error_list);
+    }
+
+    let mut fragment_map = BTreeMap::new();
+    for definition in document.definition_list.iter() {
+        if let ExecutableDefinition::Fragment(fragment) = &definition.node {
+            fragment_map.insert(fragment.node.name.node.as_str(), &fragment.node);
+        }
+    }
+    let mut generator = Generator {
+        schema,
+        config,
+        fragment_map,
+        enum_set: BTreeSet::new(),
+        input_set: BTreeSet::new(),
+        error_list: vec![],
+    };
+    let mut module_list = vec![];
+    for definition in document.definition_list.iter() {
+        if let ExecutableDefinition::Operation(operation) = &definition.node {
+            module_list.push(generator.generate_operation(document, &operation.node));
+        }
+    }
+    if !generator.error_list.is_empty() {
+        return Err(generator.error_list);
+    }
+
+    let mut item_list = vec![];
+    let mut generated_set = BTreeSet::new();
+    // Input objects can name more input objects and enums.
+    while let Some(name) = generator
+        .input_set
+        .iter()
+        .find(|name| !generated_set.contains(*name))
+        .copied()
+    {
+        generated_set.insert(name);
+        item_list.push((name, generator.generate_input_object(name)));
+    }
+    for name in generator.enum_set.iter() {
+        item_list.push((name, generate_enum(schema.get_type(name).unwrap())));
+    }
+    item_list.sort_by_key(|(name, _)| *name);
+
+    let mut output = String::from("// Generated from GraphQL operations. Do not edit.\n");
+    if !item_list.is_empty() {
+        output.push_str("\nuse serde::{Deserialize, Serialize};\n");
+    }
+    for (_, item) in item_list {
+        output.push('\n');
+        output.push_str(&item);
+    }
+    for module in module_list {
+        output.push('\n');
+        output.push_str(&module);
+    }
+    Ok(output)
+}
+
+struct Generator<'a> {
+    schema: &'a Schema,
+    config: &'a CodegenConfig,
+    fragment_map: BTreeMap<&'a str, &'a FragmentDefinition>,
+    enum_set: BTreeSet<&'a str>,
+    input_set: BTreeSet<&'a str>,
+    error_list: Vec<ServerError>,
+}
+
+/// A field of a response struct, merged from every field selected under its response key.
+struct ResponseField<'a> {
+    key: &'a str,
+    field_list: Vec<&'a Field>,
+    /// Whether every selection of the field sits under `@skip` or `@include`.
+    is_conditional: bool,
+}
+
+/// A struct or enum waiting to be generated for a nested selection set.
+struct Pending<'a> {
+    name: String,
+    ty: &'a MetaType,
+    selection_set_list: Vec<&'a [Positioned<Selection>]>,
+}
+
+impl<'a> Generator<'a> {
+    fn generate_operation(
+        &mut self,
+        document: &'a ExecutableDocument,
+        operation: &'a OperationDefinition,
+    ) -> String {
+        let operation_name = operation.name.as_ref().unwrap();
+        let root_type = self.schema.root_type(&operation.ty).unwrap();
+
+        let mut item_list = vec![
+            format!(
+                "pub const OPERATION_NAME: &str = {:?};\n",
+                operation_name.node
+            ),
+            format!(
+                "pub const QUERY: &str = {:?};\n",
+                self.query(document, operation)
+            ),
+            self.generate_variables(&operation.variable_list),
+        ];
+        let mut name_set = BTreeSet::new();
+        name_set.insert("Variables".to_owned());
+        name_set.insert("ResponseData".to_owned());
+        let mut pending_list = vec![Pending {
+            name: "ResponseData".to_owned(),
+            ty: root_type,
+            selection_set_list: vec![&operation.selection_list],
+        }];
+        while !pending_list.is_empty() {
+            let pending = pending_list.remove(0);
+            let (item, child_list) = self.generate_selection(pending, &mut name_set);
+            item_list.push(item);
+            pending_list.extend(child_list);
+        }
+
+        let mut module = format!(
+            "pub mod {} {{\n    use serde::{{Deserialize, Serialize}};\n",
+            ident(&to_snake_case(&operation_name.node))
+        );
+        for item in item_list {
+            module.push('\n');
+            for line in item.lines() {
+                if !line.is_empty() {
+                    module.push_str("    ");
+                }
+                module.push_str(line);
+                module.push('\n');
+            }
+        }
+        module.push_str("}\n");
+        module
+    }
+
+    /// The operation with the fragments it spreads, asking for `__typename` on every field
+    /// of an abstract type so responses can be told apart.
+    fn query(&self, document: &ExecutableDocument, operation: &OperationDefinition) -> String {
+        let mut fragment_set = BTreeSet::new();
+        self.collect_fragment_set(&operation.selection_list, &mut fragment_set);
+        let root_type = self.schema.root_type(&operation.ty).unwrap();
+
+        let mut operation = operation.clone();
+        self.add_typename(&mut operation.selection_list, root_type);
+        let mut definition_list = vec![Positioned::new(ExecutableDefinition::Operation(
+            Positioned::new(operation),
+        ))];
+        for definition in document.definition_list.iter() {
+            if let ExecutableDefinition::Fragment(fragment) = &definition.node {
+                if fragment_set.contains(fragment.node.name.node.as_str()) {
+                    let mut fragment = fragment.node.clone();
+                    if let Some(ty) = self.schema.get_type(&fragment.on.node) {
+                        self.add_typename(&mut fragment.selection_list, ty);
+                    }
+                    definition_list.push(Positioned::new(ExecutableDefinition::Fragment(
+                        Positioned::new(fragment),
+                    )));
+                }
+            }
+        }
+        print_executable(&ExecutableDocument { definition_list })
+    }
+
+    fn collect_fragment_set(
+        &self,
+        selection_list: &'a [Positioned<Selection>],
+        fragment_set: &mut BTreeSet<&'a str>,
+    ) {
+        for selection in selection_list.iter() {
+            match &selection.node {
+                Selection::Field(field) => {
+                    self.collect_fragment_set(&field.selection_list, fragment_set)
+                }
+                Selection::FragmentSpread(spread) => {
+                    let name = spread.name.node.as_str();
+                    if fragment_set.insert(name) {
+                        let fragment = self.fragment_map[name];
+                        self.collect_fragment_set(&fragment.selection_list, fragment_set);
+                    }
+                }
+                Selection::InlineFragment(fragment) => {
+                    self.collect_fragment_set(&fragment.selection_list, fragment_set)
+                }
+            }
+        }
+    }
+
+    /// Selects `__typename` on the fields of an abstract type that do not select it.
+    fn add_typename(&self, selection_list: &mut [Positioned<Selection>], ty: &MetaType) {
+        for selection in selection_list.iter_mut() {
+            match &mut selection.node {
+                Selection::Field(field) => {
+                    let child = match ty
+                        .field(&field.name.node)
+                        .and_then(|child| self.schema.get_type(child.ty.name()))
+                    {
+                        Some(child) => child,
+                        None => continue,
+                    };
+                    let has_typename = field.selection_list.iter().any(|selection| {
+                        matches!(&selection.node, Selection::Field(field)
+                            if field.name.node == "__typename" && field.alias.is_none())
+                    });
+                    if child.is_abstract() && !has_typename {
+                        let typename = Field {
+                            name: Positioned::new("__typename".to_owned()),
+                            alias: None,
+                            argument_list: vec![],
+                            selection_list: vec![],
+                            directive_list: vec![],
+                        };
+                        field
+                            .selection_list
+                            .insert(0, Positioned::new(Selection::Field(typename)));
+                    }
+                    self.add_typename(&mut field.selection_list, child);
+                }
+                Selection::FragmentSpread(_) => {}
+                Selection::InlineFragment(fragment) => {
+                    let on = fragment
+                        .on
+                        .as_ref()
+                        .and_then(|on| self.schema.get_type(&on.node));
+                    self.add_typename(&mut fragment.selection_list, on.unwrap_or(ty));
+                }
+            }
+        }
+    }
+
+    fn generate_variables(
+        &mut self,
+        variable_list: &'a [Positioned<VariableDefinition>],
+    ) -> String {
+        let mut output = format!("{}\npub struct Variables {{\n", DERIVE);
+        for variable in variable_list.iter() {
+            let variable = &variable.node;
+            let is_optional = !variable.ty.node.is_non_null() || variable.default_value.is_some();
+            let rust_type = self.input_type(&variable.ty.node, "super::", None, is_optional);
+            write_field(
+                &mut output,
+                None,
+                &variable.name.node,
+                &rust_type,
+                is_optional,
+            );
+        }
+        output.push_str("}\n");
+        output
+    }
+
+    /// The Rust type of an input value, boxing the input objects that contain `parent`.
+    /// Enums and input objects are named through `scope`.
+    fn input_type(
+        &mut self,
+        ty: &'a Type,
+        scope: &str,
+        parent: Option<&str>,
+        is_optional: bool,
+    ) -> String {
+        let inner = match ty {
+            Type::NonNull(ty) => return self.input_type(ty, scope, parent, is_optional),
+            Type::List(item) => format!(
+                "Vec<{}>",
+                self.input_type(item, scope, None, !item.is_non_null())
+            ),
+            Type::Named(name) => {
+                let named = self.named_type(self.schema.get_type(name).unwrap(), scope);
+                match parent {
+                    Some(parent) if self.contains_input(name, parent, &mut BTreeSet::new()) => {
+                        format!("Box<{}>", named)
+                    }
+                    _ => named,
+                }
+            }
+        };
+        if is_optional {
+            format!("Option<{}>", inner)
+        } else {
+            inner
+        }
+    }
+
+    /// Whether the input object `name` holds `target` without a list in between, so that
+    /// holding it inline would make `target` infinitely large.
+    fn contains_input(&self, name: &'a str, target: &str, visited: &mut BTreeSet<&'a str>) -> bool {
+        let ty = match self.schema.get_type(name) {
+            Some(ty) if ty.kind == TypeKind::InputObject => ty,
+            _ => return false,
+        };
+        if name == target {
+            return true;
+        }
+        visited.insert(name)
+            && ty.input_field_list.iter().any(|field| {
+                unlisted_name(&field.ty)
+                    .is_some_and(|child| self.contains_input(child, target, visited))
+            })
+    }
+
+    /// The Rust type of a named type, remembering the enums and input objects to generate.
+    fn named_type(&mut self, ty: &'a MetaType, scope: &str) -> String {
+        match ty.kind {
+            TypeKind::Enum => {
+                self.enum_set.insert(&ty.name);
+                format!("{}{}", scope, ty.name)
+            }
+            TypeKind::InputObject => {
+                self.input_set.insert(&ty.name);
+                format!("{}{}", scope, ty.name)
+            }
+            _ => match ty.name.as_str() {
+                "Int" => "i32".to_owned(),
+                "Float" => "f64".to_owned(),
+                "Boolean" => "bool".to_owned(),
+                "String" | "ID" => "String".to_owned(),
+                name => self
+                    .config
+                    .scalar_map
+                    .get(name)
+                    .cloned()
+                    .unwrap_or_else(|| "String".to_owned()),
+            },
+        }
+    }
+
+    fn generate_input_object(&mut self, name: &'a str) -> String {
+        let ty = self.schema.get_type(name).unwrap();
+        let mut output = String::new();
+        write_description(&mut output, ty.description.as_deref());
+        let _ = writeln!(output, "{}\npub struct {} {{", DERIVE, name);
+        for field in ty.input_field_list.iter() {
+            let is_optional = !field.ty.is_non_null();
+            let rust_type = self.input_type(&field.ty, "", Some(name), is_optional);
+            write_field(
+                &mut output,
+                field.description.as_deref(),
+                &field.name,
+                &rust_type,
+                is_optional,
+            );
+        }
+        output.push_str("}\n");
+        output
+    }
+
+    /// Generates the struct or enum for a selection set, returning the ones for the
+    /// selection sets nested in it.
+    fn generate_selection(
+        &mut self,
+        pending: Pending<'a>,
+        name_set: &mut BTreeSet<String>,
+    ) -> (String, Vec<Pending<'a>>) {
+        let is_narrowed = pending.ty.is_abstract()
+            && self.is_narrowed(&pending.selection_set_list, &pending.ty.name);
+        if !is_narrowed {
+            let concrete = if pending.ty.is_abstract() {
+                None
+            } else {
+                Some(pending.ty.name.as_str())
+            };
+            let field_list =
+                self.collect_field_list(&pending.selection_set_list, &pending.ty.name, concrete);
+            return self.generate_struct(&pending.name, pending.ty, field_list, false, name_set);
+        }
+
+        let mut output = format!(
+            "{}\n#[serde(tag = \"__typename\")]\npub enum {} {{\n",
+            DERIVE, pending.name
+        );
+        let mut item_list = vec![];
+        let mut child_list = vec![];
+        for possible in pending.ty.possible_type_list.iter() {
+            let variant_type = self.schema.get_type(possible).unwrap();
+            let variant_name = unique_name(format!("{}On{}", pending.name, possible), name_set);
+            let _ = writeln!(output, "    {}({}),", possible, variant_name);
+            let field_list = self.collect_field_list(
+                &pending.selection_set_list,
+                &pending.ty.name,
+                Some(possible),
+            );
+            let (item, children) =
+                self.generate_struct(&variant_name, variant_type, field_list, true, name_set);
+            item_list.push(item);
+            child_list.extend(children);
+        }
+        let variant_set = pending.ty.possible_type_list.iter().cloned().collect();
+        write_unknown_variant(&mut output, variant_set);
+        output.push_str("}\n");
+        for item in item_list {
+            output.push('\n');
+            output.push_str(&item);
+        }
+        (output, child_list)
+    }
+
+    /// Whether some fragment in the selection sets only applies to some types of `parent`.
+    fn is_narrowed(
+        &self,
+        selection_set_list: &[&'a [Positioned<Selection>]],
+        parent: &str,
+    ) -> bool {
+        selection_set_list.iter().any(|selection_list| {
+            selection_list
+                .iter()
+                .any(|selection| match &selection.node {
+                    Selection::Field(_) => false,
+                    Selection::FragmentSpread(spread) => {
+                        let fragment = self.fragment_map[spread.name.node.as_str()];
+                        fragment.on.node != parent
+                            || self.is_narrowed(&[&fragment.selection_list], parent)
+                    }
+                    Selection::InlineFragment(fragment) => match &fragment.on {
+                        Some(on) if on.node != parent => true,
+                        _ => self.is_narrowed(&[&fragment.selection_list], parent),
+                    },
+                })
+        })
+    }
+
+    /// Lists the fields selected on `concrete`, or on the abstract `parent` itself when
+    /// there is no concrete type, merging fragments and fields with the same response key.
+    fn collect_field_list(
+        &self,
+        selection_set_list: &[&'a [Positioned<Selection>]],
+        parent: &str,
+        concrete: Option<&str>,
+    ) -> Vec<ResponseField<'a>> {
+        let mut field_list: Vec<ResponseField<'a>> = vec![];
+        for selection_list in selection_set_list.iter() {
+            self.collect_selection_list(selection_list, parent, concrete, false, &mut field_list);
+        }
+        field_list
+    }
+
+    fn collect_selection_list(
+        &self,
+        selection_list: &'a [Positioned<Selection>],
+        parent: &str,
+        concrete: Option<&str>,
+        is_conditional: bool,
+        field_list: &mut Vec<ResponseField<'a>>,
+    ) {
+        let applies = |condition: &str| match concrete {
+            Some(concrete) => self.schema.is_possible_type(condition, concrete),
+            None => condition == parent,
+        };
+        for selection in selection_list.iter() {
+            match &selection.node {
+                Selection::Field(field) => {
+                    let key = field.alias.as_ref().unwrap_or(&field.name).node.as_str();
+                    let is_conditional = is_conditional || has_condition(&field.directive_list);
+                    match field_list.iter_mut().find(|entry| entry.key == key) {
+                        Some(entry) => {
+                            entry.field_list.push(field);
+                            entry.is_conditional &= is_conditional;
+                        }
+                        None => field_list.push(ResponseField {
+                            key,
+                            field_list: vec![field],
+                            is_conditional,
+                        }),
+                    }
+                }
+                Selection::FragmentSpread(spread) => {
+                    let fragment = self.fragment_map[spread.name.node.as_str()];
+                    if applies(&fragment.on.node) {
+                        self.collect_selection_list(
+                            &fragment.selection_list,
+                            parent,
+                            concrete,
+                            is_conditional || has_condition(&spread.directive_list),
+                            field_list,
+                        );
+                    }
+                }
+                Selection::InlineFragment(fragment) => {
+                    if fragment.on.as_ref().is_none_or(|on| applies(&on.node)) {
+                        self.collect_selection_list(
+                            &fragment.selection_list,
+                            parent,
+                            concrete,
+                            is_conditional || has_condition(&fragment.directive_list),
+                            field_list,
+                        );
+                    }
+                }
+            }
+        }
+    }
+
+    fn generate_struct(
+        &mut self,
+        name: &str,
+        ty: &'a MetaType,
+        field_list: Vec<ResponseField<'a>>,
+        is_tagged: bool,
+        name_set: &mut BTreeSet<String>,
+    ) -> (String, Vec<Pending<'a>>) {
+        let mut output = format!("{}\npub struct {} {{\n", DERIVE, name);
+        let mut child_list = vec![];
+        for response_field in field_list {
+            let field = response_field.field_list[0];
+            if field.name.node == "__typename" {
+                if !is_tagged {
+                    write_field(&mut output, None, response_field.key, "String", false);
+                }
+                continue;
+            }
+            let meta_field = match ty.field(&field.name.node) {
+                Some(meta_field) => meta_field,
+                None => {
+                    let message = format!(
+                        "Cannot generate a type for \"{}\" on \"{}\".",
+                        field.name.node, ty.name
+                    );
+                    self.error_list
+                        .push(ServerError::new(message).at(&field.name.start));
+                    continue;
+                }
+            };
+            let field_type = self.schema.get_type(meta_field.ty.name()).unwrap();
+            let named = if field_type.is_composite() {
+                let prefix = match name {
+                    "ResponseData" => "",
+                    name => name,
+                };
+                let child_name = unique_name(
+                    format!("{}{}", prefix, to_pascal_case(response_field.key)),
+                    name_set,
+                );
+                child_list.push(Pending {
+                    name: child_name.clone(),
+                    ty: field_type,
+                    selection_set_list: response_field
+                        .field_list
+                        .iter()
+                        .map(|field| &field.selection_list[..])
+                        .collect(),
+                });
+                child_name
+            } else {
+                self.named_type(field_type, "super::")
+            };
+            // A skipped field is missing from the response, whatever its type.
+            let is_optional = response_field.is_conditional || !meta_field.ty.is_non_null();
+            let rust_type = output_type(&meta_field.ty, &named, is_optional);
+            write_field(
+                &mut output,
+                meta_field.description.as_deref(),
+                response_field.key,
+                &rust_type,
+                false,
+            );
+        }
+        output.push_str("}\n");
+        (output, child_list)
+    }
+}
+
+/// Whether a selection is under `@skip` or `@include`, so it may be left out of the response.
+fn has_condition(directive_list: &[Positioned<Directive>]) -> bool {
+    directive_list
+        .iter()
+        .any(|directive| matches!(directive.node.name.node.as_str(), "skip" | "include"))
+}
+
+/// The name of a type that is not a list, perhaps non-null.
+fn unlisted_name(ty: &Type) -> Option<&str> {
+    match ty {
+        Type::Named(name) => Some(name),
+        Type::NonNull(ty) => unlisted_name(ty),
+        Type::List(_) => None,
+    }
+}
+
+fn output_type(ty: &Type, named: &str, is_optional: bool) -> String {
+    let inner = match ty {
+        Type::NonNull(ty) => return output_type(ty, named, is_optional),
+        Type::List(item) => format!("Vec<{}>", output_type(item, named, !item.is_non_null())),
+        Type::Named(_) => named.to_owned(),
+    };
+    if is_optional {
+        format!("Option<{}>", inner)
+    } else {
+        inner
+    }
+}
+
+fn generate_enum(ty: &MetaType) -> String {
+    let mut output = String::new();
+    let mut variant_set = BTreeSet::new();
+    write_description(&mut output, ty.description.as_deref());
+    let _ = writeln!(
+        output,
+        "#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]\npub enum {} {{",
+        ty.name
+    );
+    for value in ty.enum_value_list.iter() {
+        let variant = to_pascal_case(&value.name);
+        let mut description = String::new();
+        write_description(&mut description, value.description.as_deref());
+        for line in description.lines() {
+            let _ = writeln!(output, "    {}", line);
+        }
+        if variant != value.name {
+            let _ = writeln!(output, "    #[serde(rename = {:?})]", value.name);
+        }
+        let _ = writeln!(output, "    {},", variant);
+        variant_set.insert(variant);
+    }
+    write_unknown_variant(&mut output, variant_set);
+    output.push_str("}\n");
+    output
+}
+
+/// Writes the variant that values added to the schema later deserialize to.
+fn write_unknown_variant(output: &mut String, mut variant_set: BTreeSet<String>) {
+    let variant = unique_name("Unknown".to_owned(), &mut variant_set);
+    output.push_str("    /// A value this code was generated without.\n");
+    let _ = writeln!(output, "    #[serde(other)]\n    {},", variant);
+}
+
+fn write_description(output: &mut String, description: Option<&str>) {
+    if let Some(description) = description {
+        for line in description.lines() {
+            if line.is_empty() {
+                output.push_str("///\n");
+            } else {
+                let _ = writeln!(output, "/// {}", line);
+            }
+        }
+    }
+}
+
+/// Writes a struct field named after the GraphQL `name`, renamed for serde when the Rust
+/// name differs.
+fn write_field(
+    output: &mut String,
+    description: Option<&str>,
+    name: &str,
+    rust_type: &str,
+    is_skipped_when_none: bool,
+) {
+    let mut attribute = String::new();
+    write_description(&mut attribute, description);
+    let field_name = ident(&to_snake_case(name));
+    let mut serde_list = vec![];
+    if field_name.trim_start_matches("r#") != name {
+        serde_list.push(format!("rename = {:?}", name));
+    }
+    if is_skipped_when_none {
+        serde_list.push("skip_serializing_if = \"Option::is_none\"".to_owned());
+    }
+    if !serde_list.is_empty() {
+        let _ = writeln!(attribute, "#[serde({})]", serde_list.join(", "));
+    }
+    for line in attribute.lines() {
+        let _ = writeln!(output, "    {}", line);
+    }
+    let _ = writeln!(output, "    pub {}: {},", field_name, rust_type);
+}
+
+/// Makes `name` unique within a module by numbering it.
+fn unique_name(name: String, name_set: &mut BTreeSet<String>) -> String {
+    let mut unique = name.clone();
+    let mut index = 2;
+    while name_set.contains(&unique) {
+        unique = format!("{}{}", name, index);
+        index += 1;
+    }
+    name_set.insert(unique.clone());
+    unique
+}
+
+/// Escapes a keyword used as an identifier.
+fn ident(name: &str) -> String {
+    match name {
+        "crate" | "self" | "Self" | "super" => format!("{}_", name),
+        name if KEYWORD_LIST.contains(&name) => format!("r#{}", name),
+        name => name.to_owned(),
+    }
+}
+
+fn to_pascal_case(name: &str) -> String {
+    word_list(name)
+        .iter()
+        .map(|word| {
+            let word = word.to_ascii_lowercase();
+            let mut chars = word.chars();
+            match chars.next() {
+                Some(first) => first.to_ascii_uppercase().to_string() + chars.as_str(),
+                None => String::new(),
+            }
+        })
+        .collect()
+}
+
+fn to_snake_case(name: &str) -> String {
+    word_list(name)
+        .iter()
+        .map(|word| word.to_ascii_lowercase())
+        .collect::<Vec<_>>()
+        .join("_")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn generate() {
+        let schema_source = std::fs::read_to_string("tests/codegen/schema.graphql").unwrap();
+        let schema = Schema::build(&parse_type_system(&schema_source).unwrap()).unwrap();
+        let source = std::fs::read_to_string("tests/codegen/operations.graphql").unwrap();
+        let document = parse_executable(&source).unwrap();
+        let config = CodegenConfig::default().with_scalar("DateTime", "serde_json::Value");
+        assert_eq!(
+            generate_client(&schema, &document, &config).unwrap(),
+            include_str!("../tests/codegen/generated.rs")
+        );
+
+        let document = parse_executable("{ hero { name } }").unwrap();
+        let error_list = generate_client(&schema, &document, &config).unwrap_err();
+        assert_eq!(
+            error_list[0].message,
+            "Operation needs a name to generate types for it."
+        );
+        let document = parse_executable("query A { hero { title } }").unwrap();
+        assert!(generate_client(&schema, &document, &config).is_err());
+    }
+
+    #[allow(dead_code)]
+    mod generated {
+        include!("../tests/codegen/generated.rs");
+    }
+
+    #[test]
+    fn round_trip() {
+        use generated::*;
+        use serde_json::json;
+
+        let data: hero_and_friends::ResponseData = serde_json::from_value(json!({
+            "hero": {
+                "__typename": "Droid",
+                "name": "R2-D2",
+                "id": "2001",
+                "primaryFunction": "Astromech",
+                "friends": [{ "__typename": "Human", "name": "Luke Skywalker" }, null],
+            }
+        }))
+        .unwrap();
+        match data.hero {
+            Some(hero_and_friends::Hero::Droid(droid)) => {
+                assert_eq!(droid.primary_function.as_deref(), Some("Astromech"));
+                assert_eq!(droid.friends.unwrap().len(), 2);
+            }
+            hero => panic!("Unexpected hero {:?}", hero),
+        }
+
+        // Skipped fragments leave fields out, and types added later still deserialize.
+        let data: hero_and_friends::ResponseData = serde_json::from_value(json!({
+            "hero": { "__typename": "Human", "name": "Luke Skywalker" }
+        }))
+        .unwrap();
+        match data.hero {
+            Some(hero_and_friends::Hero::Human(human)) => assert_eq!(human.id, None),
+            hero => panic!("Unexpected hero {:?}", hero),
+        }
+        let data: hero_and_friends::ResponseData = serde_json::from_value(json!({
+            "hero": { "__typename": "Wookiee", "name": "Chewbacca" }
+        }))
+        .unwrap();
+        assert_eq!(data.hero, Some(hero_and_friends::Hero::Unknown));
+        let episode: Episode = serde_json::from_value(json!("PHANTOM_MENACE")).unwrap();
+        assert_eq!(episode, Episode::Unknown);
+
+        let data: search::ResponseData = serde_json::from_value(json!({
+            "results": [
+                { "__typename": "Starship", "name": "Falcon", "length": 34.37 },
+                { "__typename": "Human", "id": "1000", "name": "Luke Skywalker" },
+            ]
+        }))
+        .unwrap();
+        assert!(matches!(data.results[0], search::Results::Starship(_)));
+        assert!(matches!(data.results[1], search::Results::Human(_)));
+
+        let variables = create_review::Variables {
+            episode: Episode::NewHope,
+            review: ReviewInput {
+                stars: 5,
+                commentary: None,
+                favorite_color: Some(ColorInput {
+                    red: 255,
+                    green: 0,
+                    blue: 0,
+                    blend: None,
+                    next: None,
+                }),
+            },
+        };
+        assert_eq!(
+            serde_json::to_value(&variables).unwrap(),
+            json!({
+                "episode": "NEW_HOPE",
+                "review": {
+                    "stars": 5,
+                    "favoriteColor": { "red": 255, "green": 0, "blue": 0 },
+                },
+            })
+        );
+    }
+}
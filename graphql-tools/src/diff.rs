@@ -0,0 +1,1018 @@
+use graphql_parser::*;
+use std::collections::BTreeMap;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Criticality {
+    /// Existing clients may stop working.
+    Breaking,
+    /// Existing clients keep working but may observe different behaviour.
+    Dangerous,
+    Safe,
+}
+
+impl std::fmt::Display for Criticality {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Criticality::Breaking => write!(f, "BREAKING"),
+            Criticality::Dangerous => write!(f, "DANGEROUS"),
+            Criticality::Safe => write!(f, "SAFE"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ChangeKind {
+    TypeAdded,
+    TypeRemoved,
+    TypeKindChanged,
+    FieldAdded,
+    FieldRemoved,
+    FieldTypeChanged,
+    FieldDeprecationAdded,
+    FieldDeprecationRemoved,
+    ArgumentAdded,
+    ArgumentRemoved,
+    ArgumentTypeChanged,
+    ArgumentDefaultValueChanged,
+    ArgumentDeprecationAdded,
+    ArgumentDeprecationRemoved,
+    InputFieldAdded,
+    InputFieldRemoved,
+    InputFieldTypeChanged,
+    InputFieldDefaultValueChanged,
+    EnumValueAdded,
+    EnumValueRemoved,
+    EnumValueDeprecationAdded,
+    EnumValueDeprecationRemoved,
+    UnionMemberAdded,
+    UnionMemberRemoved,
+    InterfaceAdded,
+    InterfaceRemoved,
+    DirectiveAdded,
+    DirectiveRemoved,
+    DirectiveLocationAdded,
+    DirectiveLocationRemoved,
+    DirectiveRepeatableAdded,
+    DirectiveRepeatableRemoved,
+}
+
+/// One difference between two schemas.
+///
+/// `old_position` and `new_position` point at the changed node in the old and the new
+/// document, and are missing when the node only exists on one side.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SchemaChange {
+    pub kind: ChangeKind,
+    pub criticality: Criticality,
+    pub path: String,
+    pub message: String,
+    pub old_position: Option<Position>,
+    pub new_position: Option<Position>,
+}
+
+impl std::fmt::Display for SchemaChange {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}: {}", self.criticality, self.message)
+    }
+}
+
+/// Lists every change from `old` to `new`, type by type in alphabetical order and then
+/// directive by directive.
+///
+/// Type extensions are folded into the type they extend before comparing.
+pub fn diff_schema(old: &TypeSystemDocument, new: &TypeSystemDocument) -> Vec<SchemaChange> {
+    let old = DefinitionMap::new(old);
+    let new = DefinitionMap::new(new);
+    let mut diff = Diff {
+        change_list: vec![],
+    };
+
+    for (name, old_type) in old.type_map.iter() {
+        match new.type_map.get(name) {
+            None => diff.push(
+                ChangeKind::TypeRemoved,
+                Criticality::Breaking,
+                name,
+                format!("Type \"{}\" was removed.", name),
+                Some(&old_type.name.start),
+                None,
+            ),
+            Some(new_type) if new_type.kind != old_type.kind => diff.push(
+                ChangeKind::TypeKindChanged,
+                Criticality::Breaking,
+                name,
+                format!(
+                    "Type \"{}\" changed from {} to {}.",
+                    name,
+                    old_type.kind.article_name(),
+                    new_type.kind.article_name()
+                ),
+                Some(&old_type.name.start),
+                Some(&new_type.name.start),
+            ),
+            Some(new_type) => diff.diff_type(old_type, new_type),
+        }
+    }
+    for (name, new_type) in new.type_map.iter() {
+        if !old.type_map.contains_key(name) {
+            diff.push(
+                ChangeKind::TypeAdded,
+                Criticality::Safe,
+                name,
+                format!("Type \"{}\" was added.", name),
+                None,
+                Some(&new_type.name.start),
+            );
+        }
+    }
+
+    for (name, old_directive) in old.directive_map.iter() {
+        let path = format!("@{}", name);
+        match new.directive_map.get(name) {
+            None => diff.push(
+                ChangeKind::DirectiveRemoved,
+                Criticality::Breaking,
+                &path,
+                format!("Directive \"{}\" was removed.", path),
+                Some(&old_directive.name.start),
+                None,
+            ),
+            Some(new_directive) => diff.diff_directive(&path, old_directive, new_directive),
+        }
+    }
+    for (name, new_directive) in new.directive_map.iter() {
+        if !old.directive_map.contains_key(name) {
+            let path = format!("@{}", name);
+            diff.push(
+                ChangeKind::DirectiveAdded,
+                Criticality::Safe,
+                &path,
+                format!("Directive \"{}\" was added.", path),
+                None,
+                Some(&new_directive.name.start),
+            );
+        }
+    }
+
+    diff.change_list
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum Kind {
+    Scalar,
+    Object,
+    Interface,
+    Union,
+    Enum,
+    InputObject,
+}
+
+impl Kind {
+    fn article_name(self) -> &'static str {
+        match self {
+            Kind::Scalar => "a scalar type",
+            Kind::Object => "an object type",
+            Kind::Interface => "an interface type",
+            Kind::Union => "a union type",
+            Kind::Enum => "an enum type",
+            Kind::InputObject => "an input object type",
+        }
+    }
+}
+
+struct TypeEntry<'a> {
+    kind: Kind,
+    name: &'a Positioned<String>,
+    field_list: Vec<&'a FieldDefinition>,
+    interface_list: Vec<&'a Positioned<String>>,
+    member_list: Vec<&'a Positioned<String>>,
+    enum_value_list: Vec<&'a EnumMember>,
+}
+
+struct DefinitionMap<'a> {
+    type_map: BTreeMap<&'a str, TypeEntry<'a>>,
+    directive_map: BTreeMap<&'a str, &'a DirectiveDefinition>,
+}
+
+impl<'a> DefinitionMap<'a> {
+    fn new(document: &'a TypeSystemDocument) -> Self {
+        let mut type_map = BTreeMap::new();
+        let mut directive_map = BTreeMap::new();
+
+        for definition in document.definition_list.iter() {
+            let ty = match &definition.node {
+                TypeSystemDefinition::Type(ty) => &ty.node,
+                TypeSystemDefinition::Directive(directive) => {
+                    directive_map.insert(directive.node.name.node.as_str(), &directive.node);
+                    continue;
+                }
+                TypeSystemDefinition::Schema(_) => continue,
+            };
+            let (kind, name) = match ty {
+                TypeDefinition::Scalar(ty) => (Kind::Scalar, &ty.node.name),
+                TypeDefinition::Object(ty) => (Kind::Object, &ty.node.name),
+                TypeDefinition::Interface(ty) => (Kind::Interface, &ty.node.name),
+                TypeDefinition::Union(ty) => (Kind::Union, &ty.node.name),
+                TypeDefinition::Enum(ty) => (Kind::Enum, &ty.node.name),
+                TypeDefinition::InputObject(ty) => (Kind::InputObject, &ty.node.name),
+            };
+            let entry = type_map
+                .entry(name.node.as_str())
+                .or_insert_with(|| TypeEntry {
+                    kind,
+                    name,
+                    field_list: vec![],
+                    interface_list: vec![],
+                    member_list: vec![],
+                    enum_value_list: vec![],
+                });
+            match ty {
+                TypeDefinition::Scalar(_) => {}
+                TypeDefinition::Object(ty) => {
+                    entry
+                        .field_list
+                        .extend(ty.node.field_list.iter().map(|field| &field.node));
+                    entry.interface_list.extend(ty.node.interface_list.iter());
+                }
+                TypeDefinition::Interface(ty) => entry
+                    .field_list
+                    .extend(ty.node.field_list.iter().map(|field| &field.node)),
+                TypeDefinition::Union(ty) => entry.member_list.extend(ty.node.member_list.iter()),
+                TypeDefinition::Enum(ty) => entry
+                    .enum_value_list
+                    .extend(ty.node.member_list.iter().map(|member| &member.node)),
+                TypeDefinition::InputObject(ty) => entry
+                    .field_list
+                    .extend(ty.node.field_list.iter().map(|field| &field.node)),
+            }
+        }
+
+        DefinitionMap {
+            type_map,
+            directive_map,
+        }
+    }
+}
+
+struct Diff {
+    change_list: Vec<SchemaChange>,
+}
+
+impl Diff {
+    fn push(
+        &mut self,
+        kind: ChangeKind,
+        criticality: Criticality,
+        path: &str,
+        message: String,
+        old_position: Option<&Position>,
+        new_position: Option<&Position>,
+    ) {
+        self.change_list.push(SchemaChange {
+            kind,
+            criticality,
+            path: path.to_owned(),
+            message,
+            old_position: old_position.cloned(),
+            new_position: new_position.cloned(),
+        });
+    }
+
+    fn diff_type(&mut self, old: &TypeEntry<'_>, new: &TypeEntry<'_>) {
+        let type_name = &old.name.node;
+        match old.kind {
+            Kind::Object | Kind::Interface => {
+                self.diff_field_list(type_name, &old.field_list, &new.field_list);
+                self.diff_name_list(
+                    type_name,
+                    &old.interface_list,
+                    &new.interface_list,
+                    |interface, is_added| {
+                        if is_added {
+                            (
+                                ChangeKind::InterfaceAdded,
+                                Criticality::Dangerous,
+                                format!(
+                                    "Type \"{}\" now implements interface \"{}\".",
+                                    type_name, interface
+                                ),
+                            )
+                        } else {
+                            (
+                                ChangeKind::InterfaceRemoved,
+                                Criticality::Breaking,
+                                format!(
+                                    "Type \"{}\" no longer implements interface \"{}\".",
+                                    type_name, interface
+                                ),
+                            )
+                        }
+                    },
+                );
+            }
+            Kind::InputObject => {
+                self.diff_input_field_list(type_name, &old.field_list, &new.field_list)
+            }
+            Kind::Union => self.diff_name_list(
+                type_name,
+                &old.member_list,
+                &new.member_list,
+                |member, is_added| {
+                    if is_added {
+                        (
+                            ChangeKind::UnionMemberAdded,
+                            Criticality::Dangerous,
+                            format!(
+                                "Member \"{}\" was added to union type \"{}\".",
+                                member, type_name
+                            ),
+                        )
+                    } else {
+                        (
+                            ChangeKind::UnionMemberRemoved,
+                            Criticality::Breaking,
+                            format!(
+                                "Member \"{}\" was removed from union type \"{}\".",
+                                member, type_name
+                            ),
+                        )
+                    }
+                },
+            ),
+            Kind::Enum => self.diff_enum_value_list(type_name, old, new),
+            Kind::Scalar => {}
+        }
+    }
+
+    fn diff_field_list(
+        &mut self,
+        type_name: &str,
+        old_list: &[&FieldDefinition],
+        new_list: &[&FieldDefinition],
+    ) {
+        for old in old_list.iter() {
+            let path = format!("{}.{}", type_name, old.name.node);
+            let new = match find_field(new_list, &old.name.node) {
+                Some(new) => new,
+                None => {
+                    self.push(
+                        ChangeKind::FieldRemoved,
+                        Criticality::Breaking,
+                        &path,
+                        format!("Field \"{}\" was removed.", path),
+                        Some(&old.name.start),
+                        None,
+                    );
+                    continue;
+                }
+            };
+
+            if old.ty.node != new.ty.node {
+                let criticality = if is_safe_output_change(&old.ty.node, &new.ty.node) {
+                    Criticality::Safe
+                } else {
+                    Criticality::Breaking
+                };
+                self.push(
+                    ChangeKind::FieldTypeChanged,
+                    criticality,
+                    &path,
+                    format!(
+                        "Field \"{}\" changed type from \"{}\" to \"{}\".",
+                        path, old.ty.node, new.ty.node
+                    ),
+                    Some(&old.ty.start),
+                    Some(&new.ty.start),
+                );
+            }
+            match (
+                is_deprecated(&old.directive_list),
+                is_deprecated(&new.directive_list),
+            ) {
+                (false, true) => self.push(
+                    ChangeKind::FieldDeprecationAdded,
+                    Criticality::Safe,
+                    &path,
+                    format!("Field \"{}\" was deprecated.", path),
+                    Some(&old.name.start),
+                    Some(&new.name.start),
+                ),
+                (true, false) => self.push(
+                    ChangeKind::FieldDeprecationRemoved,
+                    Criticality::Safe,
+                    &path,
+                    format!("Field \"{}\" is no longer deprecated.", path),
+                    Some(&old.name.start),
+                    Some(&new.name.start),
+                ),
+                _ => {}
+            }
+            self.diff_argument_list(
+                &path,
+                &old.argument_list,
+                &new.argument_list,
+                Criticality::Dangerous,
+            );
+        }
+
+        for new in new_list.iter() {
+            if find_field(old_list, &new.name.node).is_none() {
+                let path = format!("{}.{}", type_name, new.name.node);
+                self.push(
+                    ChangeKind::FieldAdded,
+                    Criticality::Safe,
+                    &path,
+                    format!("Field \"{}\" was added.", path),
+                    None,
+                    Some(&new.name.start),
+                );
+            }
+        }
+    }
+
+    /// `optional_added` is how adding an optional argument is classified: it changes the
+    /// arguments a field resolver receives, but not those a directive is applied with.
+    fn diff_argument_list(
+        &mut self,
+        parent_path: &str,
+        old_list: &[Positioned<FieldArgument>],
+        new_list: &[Positioned<FieldArgument>],
+        optional_added: Criticality,
+    ) {
+        for old in old_list.iter().map(|argument| &argument.node) {
+            let path = format!("{}({}:)", parent_path, old.name.node);
+            match find_argument(new_list, &old.name.node) {
+                None => self.push(
+                    ChangeKind::ArgumentRemoved,
+                    Criticality::Breaking,
+                    &path,
+                    format!("Argument \"{}\" was removed.", path),
+                    Some(&old.name.start),
+                    None,
+                ),
+                Some(new) => {
+                    self.diff_input_value(
+                        &path,
+                        "Argument",
+                        (
+                            ChangeKind::ArgumentTypeChanged,
+                            ChangeKind::ArgumentDefaultValueChanged,
+                        ),
+                        (&old.ty, &old.default_value, &old.name.start),
+                        (&new.ty, &new.default_value, &new.name.start),
+                    );
+                    match (
+                        is_deprecated(&old.directive_list),
+                        is_deprecated(&new.directive_list),
+                    ) {
+                        (false, true) => self.push(
+                            ChangeKind::ArgumentDeprecationAdded,
+                            Criticality::Safe,
+                            &path,
+                            format!("Argument \"{}\" was deprecated.", path),
+                            Some(&old.name.start),
+                            Some(&new.name.start),
+                        ),
+                        (true, false) => self.push(
+                            ChangeKind::ArgumentDeprecationRemoved,
+                            Criticality::Safe,
+                            &path,
+                            format!("Argument \"{}\" is no longer deprecated.", path),
+                            Some(&old.name.start),
+                            Some(&new.name.start),
+                        ),
+                        _ => {}
+                    }
+                }
+            }
+        }
+
+        for new in new_list.iter().map(|argument| &argument.node) {
+            if find_argument(old_list, &new.name.node).is_none() {
+                let path = format!("{}({}:)", parent_path, new.name.node);
+                let (criticality, adjective) = if is_required(&new.ty.node, &new.default_value) {
+                    (Criticality::Breaking, "Required")
+                } else {
+                    (optional_added, "Optional")
+                };
+                self.push(
+                    ChangeKind::ArgumentAdded,
+                    criticality,
+                    &path,
+                    format!("{} argument \"{}\" was added.", adjective, path),
+                    None,
+                    Some(&new.name.start),
+                );
+            }
+        }
+    }
+
+    fn diff_input_field_list(
+        &mut self,
+        type_name: &str,
+        old_list: &[&FieldDefinition],
+        new_list: &[&FieldDefinition],
+    ) {
+        for old in old_list.iter() {
+            let path = format!("{}.{}", type_name, old.name.node);
+            match find_field(new_list, &old.name.node) {
+                None => self.push(
+                    ChangeKind::InputFieldRemoved,
+                    Criticality::Breaking,
+                    &path,
+                    format!("Input field \"{}\" was removed.", path),
+                    Some(&old.name.start),
+                    None,
+                ),
+                Some(new) => self.diff_input_value(
+                    &path,
+                    "Input field",
+                    (
+                        ChangeKind::InputFieldTypeChanged,
+                        ChangeKind::InputFieldDefaultValueChanged,
+                    ),
+                    (&old.ty, &old.default_value, &old.name.start),
+                    (&new.ty, &new.default_value, &new.name.start),
+                ),
+            }
+        }
+
+        for new in new_list.iter() {
+            if find_field(old_list, &new.name.node).is_none() {
+                let path = format!("{}.{}", type_name, new.name.node);
+                let (criticality, adjective) = if is_required(&new.ty.node, &new.default_value) {
+                    (Criticality::Breaking, "Required")
+                } else {
+                    (Criticality::Dangerous, "Optional")
+                };
+                self.push(
+                    ChangeKind::InputFieldAdded,
+                    criticality,
+                    &path,
+                    format!("{} input field \"{}\" was added.", adjective, path),
+                    None,
+                    Some(&new.name.start),
+                );
+            }
+        }
+    }
+
+    fn diff_input_value(
+        &mut self,
+        path: &str,
+        subject: &str,
+        (type_changed, default_value_changed): (ChangeKind, ChangeKind),
+        (old_ty, old_default_value, old_position): (
+            &Positioned<Type>,
+            &Option<Positioned<Value>>,
+            &Position,
+        ),
+        (new_ty, new_default_value, new_position): (
+            &Positioned<Type>,
+            &Option<Positioned<Value>>,
+            &Position,
+        ),
+    ) {
+        if old_ty.node != new_ty.node {
+            let criticality = if is_safe_input_change(&old_ty.node, &new_ty.node) {
+                Criticality::Safe
+            } else {
+                Criticality::Breaking
+            };
+            self.push(
+                type_changed,
+                criticality,
+                path,
+                format!(
+                    "{} \"{}\" changed type from \"{}\" to \"{}\".",
+                    subject, path, old_ty.node, new_ty.node
+                ),
+                Some(&old_ty.start),
+                Some(&new_ty.start),
+            );
+        }
+
+        let old_default_value = old_default_value.as_ref().map(|value| &value.node);
+        let new_default_value = new_default_value.as_ref().map(|value| &value.node);
+        if old_default_value != new_default_value {
+            let print = |value: Option<&Value>| {
+                value
+                    .map(|value| value.to_string())
+                    .unwrap_or_else(|| "none".to_owned())
+            };
+            self.push(
+                default_value_changed,
+                Criticality::Dangerous,
+                path,
+                format!(
+                    "{} \"{}\" changed default value from {} to {}.",
+                    subject,
+                    path,
+                    print(old_default_value),
+                    print(new_default_value)
+                ),
+                Some(old_position),
+                Some(new_position),
+            );
+        }
+    }
+
+    fn diff_enum_value_list(&mut self, type_name: &str, old: &TypeEntry<'_>, new: &TypeEntry<'_>) {
+        for old_value in old.enum_value_list.iter() {
+            let path = format!("{}.{}", type_name, old_value.name.node);
+            let new_value = new
+                .enum_value_list
+                .iter()
+                .find(|value| value.name.node == old_value.name.node);
+            match new_value {
+                None => self.push(
+                    ChangeKind::EnumValueRemoved,
+                    Criticality::Breaking,
+                    &path,
+                    format!("Enum value \"{}\" was removed.", path),
+                    Some(&old_value.name.start),
+                    None,
+                ),
+                Some(new_value) => match (
+                    is_deprecated(&old_value.directive_list),
+                    is_deprecated(&new_value.directive_list),
+                ) {
+                    (false, true) => self.push(
+                        ChangeKind::EnumValueDeprecationAdded,
+                        Criticality::Safe,
+                        &path,
+                        format!("Enum value \"{}\" was deprecated.", path),
+                        Some(&old_value.name.start),
+                        Some(&new_value.name.start),
+                    ),
+                    (true, false) => self.push(
+                        ChangeKind::EnumValueDeprecationRemoved,
+                        Criticality::Safe,
+                        &path,
+                        format!("Enum value \"{}\" is no longer deprecated.", path),
+                        Some(&old_value.name.start),
+                        Some(&new_value.name.start),
+                    ),
+                    _ => {}
+                },
+            }
+        }
+
+        for new_value in new.enum_value_list.iter() {
+            if !old
+                .enum_value_list
+                .iter()
+                .any(|value| value.name.node == new_value.name.node)
+            {
+                let path = format!("{}.{}", type_name, new_value.name.node);
+                self.push(
+                    ChangeKind::EnumValueAdded,
+                    Criticality::Dangerous,
+                    &path,
+                    format!("Enum value \"{}\" was added.", path),
+                    None,
+                    Some(&new_value.name.start),
+                );
+            }
+        }
+    }
+
+    /// Compares interface implementations or union members, which are plain type names.
+    fn diff_name_list<F>(
+        &mut self,
+        type_name: &str,
+        old_list: &[&Positioned<String>],
+        new_list: &[&Positioned<String>],
+        describe: F,
+    ) where
+        F: Fn(&str, bool) -> (ChangeKind, Criticality, String),
+    {
+        for old in old_list.iter() {
+            if !new_list.iter().any(|new| new.node == old.node) {
+                let (kind, criticality, message) = describe(&old.node, false);
+                self.push(
+                    kind,
+                    criticality,
+                    type_name,
+                    message,
+                    Some(&old.start),
+                    None,
+                );
+            }
+        }
+        for new in new_list.iter() {
+            if !old_list.iter().any(|old| old.node == new.node) {
+                let (kind, criticality, message) = describe(&new.node, true);
+                self.push(
+                    kind,
+                    criticality,
+                    type_name,
+                    message,
+                    None,
+                    Some(&new.start),
+                );
+            }
+        }
+    }
+
+    fn diff_directive(&mut self, path: &str, old: &DirectiveDefinition, new: &DirectiveDefinition) {
+        match (old.is_repeatable, new.is_repeatable) {
+            (false, true) => self.push(
+                ChangeKind::DirectiveRepeatableAdded,
+                Criticality::Safe,
+                path,
+                format!("Directive \"{}\" became repeatable.", path),
+                Some(&old.name.start),
+                Some(&new.name.start),
+            ),
+            (true, false) => self.push(
+                ChangeKind::DirectiveRepeatableRemoved,
+                Criticality::Breaking,
+                path,
+                format!("Directive \"{}\" is no longer repeatable.", path),
+                Some(&old.name.start),
+                Some(&new.name.start),
+            ),
+            _ => {}
+        }
+        for old_location in old.location_list.iter() {
+            if !new
+                .location_list
+                .iter()
+                .any(|location| location.node == old_location.node)
+            {
+                self.push(
+                    ChangeKind::DirectiveLocationRemoved,
+                    Criticality::Breaking,
+                    path,
+                    format!(
+                        "Location \"{}\" was removed from directive \"{}\".",
+                        old_location.node, path
+                    ),
+                    Some(&old_location.start),
+                    Some(&new.name.start),
+                );
+            }
+        }
+        for new_location in new.location_list.iter() {
+            if !old
+                .location_list
+                .iter()
+                .any(|location| location.node == new_location.node)
+            {
+                self.push(
+                    ChangeKind::DirectiveLocationAdded,
+                    Criticality::Safe,
+                    path,
+                    format!(
+                        "Location \"{}\" was added to directive \"{}\".",
+                        new_location.node, path
+                    ),
+                    Some(&old.name.start),
+                    Some(&new_location.start),
+                );
+            }
+        }
+
+        self.diff_argument_list(
+            path,
+            &old.argument_list,
+            &new.argument_list,
+            Criticality::Safe,
+        );
+    }
+}
+
+fn find_field<'a>(list: &[&'a FieldDefinition], name: &str) -> Option<&'a FieldDefinition> {
+    list.iter().find(|field| field.name.node == name).copied()
+}
+
+fn find_argument<'a>(
+    list: &'a [Positioned<FieldArgument>],
+    name: &str,
+) -> Option<&'a FieldArgument> {
+    list.iter()
+        .map(|argument| &argument.node)
+        .find(|argument| argument.name.node == name)
+}
+
+fn is_deprecated(directive_list: &[Positioned<Directive>]) -> bool {
+    directive_list
+        .iter()
+        .any(|directive| directive.node.name.node == "deprecated")
+}
+
+fn is_required(ty: &Type, default_value: &Option<Positioned<Value>>) -> bool {
+    ty.is_non_null() && default_value.is_none()
+}
+
+/// Whether every value of the old output type is still a valid value of the new one,
+/// such as a nullable field becoming non-null.
+fn is_safe_output_change(old: &Type, new: &Type) -> bool {
+    match (old, new) {
+        (Type::List(old), Type::List(new)) => is_safe_output_change(old, new),
+        (Type::NonNull(old), Type::NonNull(new)) => is_safe_output_change(old, new),
+        (Type::NonNull(_), _) => false,
+        (_, Type::NonNull(new)) => is_safe_output_change(old, new),
+        (Type::Named(old), Type::Named(new)) => old == new,
+        _ => false,
+    }
+}
+
+/// Whether every value accepted by the old input type is still accepted by the new one,
+/// such as a required argument becoming nullable.
+fn is_safe_input_change(old: &Type, new: &Type) -> bool {
+    match (old, new) {
+        (Type::List(old), Type::List(new)) => is_safe_input_change(old, new),
+        (Type::NonNull(old), Type::NonNull(new)) => is_safe_input_change(old, new),
+        (Type::NonNull(old), new) => is_safe_input_change(old, new),
+        (Type::Named(old), Type::Named(new)) => old == new,
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const OLD: &str = r#"
+type Query {
+  user(id: ID!): User
+  users(first: Int = 10): [User!]!
+  legacy: String
+}
+
+type User implements Node {
+  id: ID!
+  name: String
+  email: String!
+}
+
+interface Node {
+  id: ID!
+}
+
+union SearchResult = User | Post
+
+type Post {
+  title: String
+}
+
+enum Role {
+  ADMIN
+  GUEST
+}
+
+input UserFilter {
+  role: Role!
+}
+
+scalar Date
+
+directive @cached(ttl: Int) on FIELD_DEFINITION | OBJECT
+"#;
+
+    const NEW: &str = r#"
+type Query {
+  user(id: ID, locale: String!): User
+  users(first: Int = 20, after: String): [User!]!
+  legacy: String @deprecated
+}
+
+type User {
+  id: ID!
+  name: String!
+  email: String
+}
+
+extend type User implements Node
+
+interface Node {
+  id: ID!
+}
+
+union SearchResult = User
+
+type Post {
+  title: String
+}
+
+enum Role {
+  ADMIN
+  MEMBER
+}
+
+input UserFilter {
+  role: Role
+  name: String!
+}
+
+type Date {
+  iso: String
+}
+
+directive @cached(ttl: Int, scope: String) on FIELD_DEFINITION
+"#;
+
+    #[test]
+    fn classification() {
+        let old = parse_type_system(OLD).unwrap();
+        let new = parse_type_system(NEW).unwrap();
+        let change_list = diff_schema(&old, &new);
+
+        assert_eq!(
+            change_list
+                .iter()
+                .map(|change| change.to_string())
+                .collect::<Vec<_>>(),
+            vec![
+                "BREAKING: Type \"Date\" changed from a scalar type to an object type.",
+                "SAFE: Argument \"Query.user(id:)\" changed type from \"ID!\" to \"ID\".",
+                "BREAKING: Required argument \"Query.user(locale:)\" was added.",
+                "DANGEROUS: Argument \"Query.users(first:)\" changed default value from 10 to 20.",
+                "DANGEROUS: Optional argument \"Query.users(after:)\" was added.",
+                "SAFE: Field \"Query.legacy\" was deprecated.",
+                "BREAKING: Enum value \"Role.GUEST\" was removed.",
+                "DANGEROUS: Enum value \"Role.MEMBER\" was added.",
+                "BREAKING: Member \"Post\" was removed from union type \"SearchResult\".",
+                "SAFE: Field \"User.name\" changed type from \"String\" to \"String!\".",
+                "BREAKING: Field \"User.email\" changed type from \"String!\" to \"String\".",
+                "SAFE: Input field \"UserFilter.role\" changed type from \"Role!\" to \"Role\".",
+                "BREAKING: Required input field \"UserFilter.name\" was added.",
+                "BREAKING: Location \"OBJECT\" was removed from directive \"@cached\".",
+                "SAFE: Optional argument \"@cached(scope:)\" was added.",
+            ]
+        );
+
+        let position = |position: &Option<Position>| {
+            position
+                .as_ref()
+                .map(|position| (position.line, position.column))
+        };
+        let removed_member = &change_list[8];
+        assert_eq!(removed_member.kind, ChangeKind::UnionMemberRemoved);
+        assert_eq!(removed_member.path, "SearchResult");
+        assert_eq!(position(&removed_member.old_position), Some((18, 29)));
+        assert_eq!(position(&removed_member.new_position), None);
+        let required_argument = &change_list[2];
+        assert_eq!(position(&required_argument.old_position), None);
+        assert_eq!(position(&required_argument.new_position), Some((3, 16)));
+        let email = &change_list[10];
+        assert_eq!(position(&email.old_position), Some((11, 10)));
+        assert_eq!(position(&email.new_position), Some((11, 10)));
+    }
+
+    #[test]
+    fn deprecation_and_repeatable() {
+        let old = parse_type_system(
+            r#"
+type Query {
+  posts(sort: String, order: String @deprecated): [String!]!
+}
+
+directive @tag(name: String!) repeatable on FIELD_DEFINITION
+
+directive @live on FIELD_DEFINITION
+"#,
+        )
+        .unwrap();
+        let new = parse_type_system(
+            r#"
+type Query {
+  posts(sort: String @deprecated, order: String): [String!]!
+}
+
+directive @tag(name: String!) on FIELD_DEFINITION
+
+directive @live repeatable on FIELD_DEFINITION
+"#,
+        )
+        .unwrap();
+        let change_list = diff_schema(&old, &new);
+
+        assert_eq!(
+            change_list
+                .iter()
+                .map(|change| (change.kind, change.to_string()))
+                .collect::<Vec<_>>(),
+            vec![
+                (
+                    ChangeKind::ArgumentDeprecationAdded,
+                    "SAFE: Argument \"Query.posts(sort:)\" was deprecated.".to_owned()
+                ),
+                (
+                    ChangeKind::ArgumentDeprecationRemoved,
+                    "SAFE: Argument \"Query.posts(order:)\" is no longer deprecated.".to_owned()
+                ),
+                (
+                    ChangeKind::DirectiveRepeatableAdded,
+                    "SAFE: Directive \"@live\" became repeatable.".to_owned()
+                ),
+                (
+                    ChangeKind::DirectiveRepeatableRemoved,
+                    "BREAKING: Directive \"@tag\" is no longer repeatable.".to_owned()
+                ),
+            ]
+        );
+    }
+}
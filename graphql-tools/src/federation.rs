@@ -0,0 +1,1212 @@
+use crate::*;
+use graphql_parser::*;
+use std::collections::HashMap;
+
+/// Definitions of the federation v2 directives a subgraph may apply.
+pub const FEDERATION_DEFINITION_LIST: &str = r#"directive @key(fields: FieldSet!, resolvable: Boolean = true) repeatable on OBJECT | INTERFACE
+
+directive @requires(fields: FieldSet!) on FIELD_DEFINITION
+
+directive @provides(fields: FieldSet!) on FIELD_DEFINITION
+
+directive @external on OBJECT | FIELD_DEFINITION
+
+directive @shareable repeatable on OBJECT | FIELD_DEFINITION
+
+directive @override(from: String!) on FIELD_DEFINITION
+
+directive @link(url: String!, import: [link__Import]) repeatable on SCHEMA
+
+scalar FieldSet
+
+scalar link__Import
+"#;
+
+const JOIN_DEFINITION_LIST: &str = r#"directive @link(url: String, as: String, for: link__Purpose, import: [link__Import]) repeatable on SCHEMA
+
+directive @join__graph(name: String!, url: String!) on ENUM_VALUE
+
+directive @join__type(graph: join__Graph!, key: join__FieldSet, extension: Boolean! = false, resolvable: Boolean! = true) repeatable on OBJECT | INTERFACE | UNION | ENUM | INPUT_OBJECT | SCALAR
+
+directive @join__field(graph: join__Graph, requires: join__FieldSet, provides: join__FieldSet, type: String, external: Boolean, override: String) repeatable on FIELD_DEFINITION | INPUT_FIELD_DEFINITION
+
+directive @join__implements(graph: join__Graph!, interface: String!) repeatable on OBJECT | INTERFACE
+
+directive @join__unionMember(graph: join__Graph!, member: String!) repeatable on UNION
+
+scalar join__FieldSet
+
+scalar link__Import
+
+enum link__Purpose {
+  SECURITY
+  EXECUTION
+}
+"#;
+
+const FEDERATION_DIRECTIVE_NAME_LIST: &[&str] = &[
+    "key",
+    "requires",
+    "provides",
+    "external",
+    "shareable",
+    "override",
+    "link",
+];
+
+/// Types a subgraph declares for the federation runtime, which are not part of the graph.
+const FEDERATION_TYPE_NAME_LIST: &[&str] = &[
+    "_Any",
+    "_Entity",
+    "_Service",
+    "FieldSet",
+    "_FieldSet",
+    "link__Import",
+    "link__Purpose",
+];
+
+const FEDERATION_LINK_PREFIX: &str = "https://specs.apollo.dev/federation/v2.";
+
+/// One schema of a federated graph, along with the name and URL it is served under.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Subgraph {
+    pub name: String,
+    pub url: String,
+    pub document: TypeSystemDocument,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct FederationError {
+    /// The name of the subgraph the error was found in.
+    pub subgraph: String,
+    pub message: String,
+    pub position: Position,
+}
+
+impl std::fmt::Display for FederationError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "[{}] {}:{}: {}",
+            self.subgraph, self.position.line, self.position.column, self.message
+        )
+    }
+}
+
+impl std::error::Error for FederationError {}
+
+/// The composition of several subgraphs, where every type and field is annotated with the
+/// `@join__*` directives telling which subgraphs can resolve it.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Supergraph {
+    pub document: TypeSystemDocument,
+}
+
+impl Supergraph {
+    pub fn sdl(&self) -> String {
+        print_type_system(&self.document)
+    }
+}
+
+/// Checks the placement of the federation directives of a subgraph, and that every
+/// `fields` argument is a selection set valid against the type it applies to.
+pub fn validate_subgraph(subgraph: &Subgraph) -> Vec<FederationError> {
+    let index = SubgraphIndex::new(&subgraph.document);
+    let mut validator = SubgraphValidator {
+        subgraph,
+        index: &index,
+        error_list: vec![],
+    };
+
+    for definition in subgraph.document.definition_list.iter() {
+        match &definition.node {
+            TypeSystemDefinition::Schema(schema) => {
+                for directive in schema.node.directive_list.iter() {
+                    validator.validate_schema_directive(directive);
+                }
+            }
+            TypeSystemDefinition::Type(ty) => validator.validate_type(&ty.node),
+            TypeSystemDefinition::Directive(_) => {}
+        }
+    }
+
+    validator.error_list
+}
+
+/// Composes subgraphs into a supergraph.
+///
+/// Every subgraph is validated first. Types are then merged by name, and must agree on the
+/// type of every field they share. A field of an object type that several subgraphs
+/// resolve must be `@shareable` in each of them, unless it is part of a `@key` there or
+/// one definition takes it over with `@override`.
+pub fn compose(subgraph_list: &[Subgraph]) -> Result<Supergraph, Vec<FederationError>> {
+    let mut error_list: Vec<FederationError> =
+        subgraph_list.iter().flat_map(validate_subgraph).collect();
+    if !error_list.is_empty() {
+        return Err(error_list);
+    }
+
+    let stripped_list: Vec<TypeSystemDocument> = subgraph_list
+        .iter()
+        .map(|subgraph| strip_federation(&subgraph.document))
+        .collect();
+    let mut document = match merge_schema(&stripped_list, ConflictPolicy::Error) {
+        Ok(merged) => merged.document,
+        Err(conflict_list) => {
+            return Err(conflict_list
+                .into_iter()
+                .map(|conflict| FederationError {
+                    subgraph: subgraph_list[conflict.document].name.clone(),
+                    message: conflict.message,
+                    position: conflict.incoming,
+                })
+                .collect())
+        }
+    };
+
+    let index_list: Vec<SubgraphIndex<'_>> = subgraph_list
+        .iter()
+        .map(|subgraph| SubgraphIndex::new(&subgraph.document))
+        .collect();
+    let graph_list: Vec<(&Subgraph, String, &SubgraphIndex<'_>)> = subgraph_list
+        .iter()
+        .zip(index_list.iter())
+        .map(|(subgraph, index)| (subgraph, graph_name(&subgraph.name), index))
+        .collect();
+
+    for definition in document.definition_list.iter_mut() {
+        if let TypeSystemDefinition::Type(ty) = &mut definition.node {
+            error_list.extend(annotate_type(&mut ty.node, &graph_list));
+        }
+    }
+    if !error_list.is_empty() {
+        return Err(error_list);
+    }
+
+    let mut definition_list = vec![supergraph_schema_definition(&document)];
+    definition_list.extend(
+        parse_type_system(JOIN_DEFINITION_LIST)
+            .unwrap()
+            .definition_list,
+    );
+    definition_list.push(Positioned::new(TypeSystemDefinition::Type(
+        Positioned::new(TypeDefinition::Enum(Positioned::new(EnumType {
+            is_extend: false,
+            description: None,
+            name: Positioned::new("join__Graph".to_owned()),
+            member_list: graph_list
+                .iter()
+                .map(|(subgraph, graph, _)| {
+                    Positioned::new(EnumMember {
+                        description: None,
+                        name: Positioned::new(graph.clone()),
+                        directive_list: vec![directive(
+                            "join__graph",
+                            vec![
+                                ("name", Value::String(subgraph.name.clone())),
+                                ("url", Value::String(subgraph.url.clone())),
+                            ],
+                        )],
+                    })
+                })
+                .collect(),
+            directive_list: vec![],
+        }))),
+    )));
+    definition_list.extend(
+        document
+            .definition_list
+            .into_iter()
+            .filter(|definition| !matches!(definition.node, TypeSystemDefinition::Schema(_))),
+    );
+
+    Ok(Supergraph {
+        document: TypeSystemDocument { definition_list },
+    })
+}
+
+/// The types of a subgraph by name, with their extensions. A `Query` type with nothing but
+/// `_entities` and `_service` is left out, as the subgraph resolves no root field.
+pub(crate) struct SubgraphIndex<'a> {
+    type_map: HashMap<&'a str, Vec<&'a TypeDefinition>>,
+}
+
+impl<'a> SubgraphIndex<'a> {
+    pub(crate) fn new(document: &'a TypeSystemDocument) -> Self {
+        let mut type_map: HashMap<&str, Vec<&TypeDefinition>> = HashMap::new();
+        for definition in document.definition_list.iter() {
+            if let TypeSystemDefinition::Type(ty) = &definition.node {
+                let parts = type_parts(&ty.node);
+                if parts.name == "Query" && parts.field_list.iter().all(is_federation_field) {
+                    continue;
+                }
+                type_map.entry(parts.name).or_default().push(&ty.node);
+            }
+        }
+        SubgraphIndex { type_map }
+    }
+
+    pub(crate) fn contains(&self, name: &str) -> bool {
+        self.type_map.contains_key(name)
+    }
+
+    pub(crate) fn is_composite(&self, name: &str) -> bool {
+        self.type_map.get(name).is_some_and(|definition_list| {
+            definition_list.iter().any(|definition| {
+                matches!(
+                    definition,
+                    TypeDefinition::Object(_)
+                        | TypeDefinition::Interface(_)
+                        | TypeDefinition::Union(_)
+                )
+            })
+        })
+    }
+
+    pub(crate) fn field(&self, type_name: &str, field_name: &str) -> Option<&'a FieldDefinition> {
+        self.type_map
+            .get(type_name)?
+            .iter()
+            .flat_map(|definition| type_parts(definition).field_list.iter())
+            .map(|field| &field.node)
+            .find(|field| field.name.node == field_name)
+    }
+
+    pub(crate) fn directive_list(&self, type_name: &str) -> Vec<&'a Positioned<Directive>> {
+        self.type_map
+            .get(type_name)
+            .into_iter()
+            .flatten()
+            .flat_map(|definition| type_parts(definition).directive_list.iter())
+            .collect()
+    }
+
+    /// The `fields` of every `@key` of a type, with whether the key is resolvable.
+    pub(crate) fn key_list(&self, type_name: &str) -> Vec<(&'a str, bool)> {
+        self.directive_list(type_name)
+            .into_iter()
+            .filter(|directive| directive.node.name.node == "key")
+            .filter_map(|directive| {
+                let fields = match argument(&directive.node, "fields") {
+                    Some(Value::String(fields)) => fields.as_str(),
+                    _ => return None,
+                };
+                let resolvable = !matches!(
+                    argument(&directive.node, "resolvable"),
+                    Some(Value::Boolean(false))
+                );
+                Some((fields, resolvable))
+            })
+            .collect()
+    }
+}
+
+struct SubgraphValidator<'a> {
+    subgraph: &'a Subgraph,
+    index: &'a SubgraphIndex<'a>,
+    error_list: Vec<FederationError>,
+}
+
+impl<'a> SubgraphValidator<'a> {
+    fn report(&mut self, message: String, position: &Position) {
+        self.error_list.push(FederationError {
+            subgraph: self.subgraph.name.clone(),
+            message,
+            position: position.clone(),
+        });
+    }
+
+    fn report_location(&mut self, directive: &Positioned<Directive>, location: &str) {
+        self.report(
+            format!(
+                "Directive \"@{}\" may not be used on {}.",
+                directive.node.name.node, location
+            ),
+            &directive.start,
+        );
+    }
+
+    fn validate_schema_directive(&mut self, directive: &Positioned<Directive>) {
+        match directive.node.name.node.as_str() {
+            "link" => match argument(&directive.node, "url") {
+                Some(Value::String(url)) => {
+                    if url.contains("/federation/") && !url.starts_with(FEDERATION_LINK_PREFIX) {
+                        self.report(
+                            format!("Unsupported federation version \"{}\".", url),
+                            &directive.start,
+                        );
+                    }
+                }
+                _ => self.report(
+                    "Directive \"@link\" requires a string \"url\" argument.".to_owned(),
+                    &directive.start,
+                ),
+            },
+            name if FEDERATION_DIRECTIVE_NAME_LIST.contains(&name) => {
+                self.report_location(directive, "SCHEMA")
+            }
+            _ => {}
+        }
+    }
+
+    fn validate_type(&mut self, ty: &TypeDefinition) {
+        let parts = type_parts(ty);
+        for directive in parts.directive_list.iter() {
+            match directive.node.name.node.as_str() {
+                "key" if parts.is_object_like => {
+                    self.validate_field_set(
+                        &format!("On type \"{}\"", parts.name),
+                        parts.name,
+                        directive,
+                    );
+                }
+                "external" | "shareable" if matches!(ty, TypeDefinition::Object(_)) => {}
+                name if FEDERATION_DIRECTIVE_NAME_LIST.contains(&name) => {
+                    self.report_location(directive, parts.location)
+                }
+                _ => {}
+            }
+        }
+        if !parts.is_object_like {
+            return;
+        }
+
+        for field in parts.field_list.iter() {
+            let field = &field.node;
+            let subject = format!("On field \"{}.{}\"", parts.name, field.name.node);
+            for directive in field.directive_list.iter() {
+                match directive.node.name.node.as_str() {
+                    "requires" => {
+                        if let Some(selection_list) =
+                            self.validate_field_set(&subject, parts.name, directive)
+                        {
+                            self.validate_external(&subject, parts.name, &selection_list, directive);
+                        }
+                    }
+                    "provides" => {
+                        let return_type = field.ty.node.name();
+                        if !self.index.is_composite(return_type) {
+                            self.report(
+                                format!(
+                                    "{}, for @provides: type \"{}\" has no fields to provide.",
+                                    subject, return_type
+                                ),
+                                &directive.start,
+                            );
+                        } else if let Some(selection_list) =
+                            self.validate_field_set(&subject, return_type, directive)
+                        {
+                            self.validate_external(&subject, return_type, &selection_list, directive);
+                        }
+                    }
+                    "override" => match argument(&directive.node, "from") {
+                        Some(Value::String(from)) if *from == self.subgraph.name => self.report(
+                            format!(
+                                "Source and destination subgraphs \"{}\" are the same for overridden field \"{}.{}\".",
+                                from, parts.name, field.name.node
+                            ),
+                            &directive.start,
+                        ),
+                        Some(Value::String(_)) => {}
+                        _ => self.report(
+                            "Directive \"@override\" requires a string \"from\" argument.".to_owned(),
+                            &directive.start,
+                        ),
+                    },
+                    "external" | "shareable" => {}
+                    name if FEDERATION_DIRECTIVE_NAME_LIST.contains(&name) => {
+                        self.report_location(directive, "FIELD_DEFINITION")
+                    }
+                    _ => {}
+                }
+            }
+        }
+    }
+
+    /// Parses the `fields` argument of `directive` with the executable grammar and checks
+    /// it against `type_name`.
+    fn validate_field_set(
+        &mut self,
+        subject: &str,
+        type_name: &str,
+        directive: &Positioned<Directive>,
+    ) -> Option<Vec<Positioned<Selection>>> {
+        let name = &directive.node.name.node;
+        let fields = match directive
+            .node
+            .argument_list
+            .iter()
+            .find(|argument| argument.node.name.node == "fields")
+        {
+            Some(argument) => argument,
+            None => {
+                self.report(
+                    format!("Directive \"@{}\" requires a \"fields\" argument.", name),
+                    &directive.start,
+                );
+                return None;
+            }
+        };
+        let source = match &fields.node.value.node {
+            Value::String(source) => source,
+            value => {
+                self.report(
+                    format!(
+                        "{}, for @{}: the \"fields\" argument must be a string, found {}.",
+                        subject, name, value
+                    ),
+                    &fields.node.value.start,
+                );
+                return None;
+            }
+        };
+        let prefix = format!("{}, for @{}(fields: \"{}\")", subject, name, source);
+        let selection_list = match parse_selection_list(source) {
+            Ok(selection_list) => selection_list,
+            Err(error) => {
+                self.report(
+                    format!(
+                        "{}: syntax error at column {}.",
+                        prefix,
+                        error.position().column
+                    ),
+                    &fields.node.value.start,
+                );
+                return None;
+            }
+        };
+        if let Err(message) = self.check_selection_list(type_name, &selection_list) {
+            self.report(format!("{}: {}", prefix, message), &fields.node.value.start);
+            return None;
+        }
+        Some(selection_list)
+    }
+
+    fn check_selection_list(
+        &self,
+        type_name: &str,
+        selection_list: &[Positioned<Selection>],
+    ) -> Result<(), String> {
+        for selection in selection_list.iter() {
+            match &selection.node {
+                Selection::Field(field) => {
+                    let name = &field.name.node;
+                    if !field.argument_list.is_empty() {
+                        return Err(format!("field \"{}\" may not have arguments.", name));
+                    }
+                    if name == "__typename" {
+                        continue;
+                    }
+                    let definition = self.index.field(type_name, name).ok_or_else(|| {
+                        format!("Cannot query field \"{}\" on type \"{}\".", name, type_name)
+                    })?;
+                    let field_type = definition.ty.node.name();
+                    match (self.index.is_composite(field_type), field.selection_list.is_empty()) {
+                        (true, true) => {
+                            return Err(format!(
+                                "Field \"{}\" of type \"{}\" must have a selection of subfields.",
+                                name, definition.ty.node
+                            ))
+                        }
+                        (false, false) => {
+                            return Err(format!(
+                                "Field \"{}\" must not have a selection since type \"{}\" has no subfields.",
+                                name, definition.ty.node
+                            ))
+                        }
+                        (true, false) => {
+                            self.check_selection_list(field_type, &field.selection_list)?
+                        }
+                        (false, true) => {}
+                    }
+                }
+                Selection::InlineFragment(fragment) => {
+                    let on = match &fragment.on {
+                        Some(on) if !self.index.contains(&on.node) => {
+                            return Err(format!("Unknown type \"{}\".", on.node))
+                        }
+                        Some(on) => on.node.as_str(),
+                        None => type_name,
+                    };
+                    self.check_selection_list(on, &fragment.selection_list)?;
+                }
+                Selection::FragmentSpread(spread) => {
+                    return Err(format!(
+                        "cannot spread fragment \"{}\" in a field set.",
+                        spread.name.node
+                    ))
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// The fields a subgraph requires or provides are resolved by another subgraph.
+    fn validate_external(
+        &mut self,
+        subject: &str,
+        type_name: &str,
+        selection_list: &[Positioned<Selection>],
+        directive: &Positioned<Directive>,
+    ) {
+        for selection in selection_list.iter() {
+            if let Selection::Field(field) = &selection.node {
+                let is_external = self
+                    .index
+                    .field(type_name, &field.name.node)
+                    .is_none_or(|definition| has_directive(&definition.directive_list, "external"));
+                if !is_external {
+                    self.report(
+                        format!(
+                            "{}, for @{}: field \"{}.{}\" must be marked @external.",
+                            subject, directive.node.name.node, type_name, field.name.node
+                        ),
+                        &directive.start,
+                    );
+                }
+            }
+        }
+    }
+}
+
+pub(crate) struct TypeParts<'a> {
+    pub(crate) name: &'a str,
+    pub(crate) location: &'static str,
+    pub(crate) is_object_like: bool,
+    pub(crate) directive_list: &'a [Positioned<Directive>],
+    pub(crate) field_list: &'a [Positioned<FieldDefinition>],
+    pub(crate) interface_list: &'a [Positioned<String>],
+    pub(crate) member_list: &'a [Positioned<String>],
+}
+
+pub(crate) fn type_parts(ty: &TypeDefinition) -> TypeParts<'_> {
+    let mut parts = TypeParts {
+        name: "",
+        location: "",
+        is_object_like: false,
+        directive_list: &[],
+        field_list: &[],
+        interface_list: &[],
+        member_list: &[],
+    };
+    match ty {
+        TypeDefinition::Scalar(ty) => {
+            parts.name = &ty.node.name.node;
+            parts.location = "SCALAR";
+            parts.directive_list = &ty.node.directive_list;
+        }
+        TypeDefinition::Object(ty) => {
+            parts.name = &ty.node.name.node;
+            parts.location = "OBJECT";
+            parts.is_object_like = true;
+            parts.directive_list = &ty.node.directive_list;
+            parts.field_list = &ty.node.field_list;
+            parts.interface_list = &ty.node.interface_list;
+        }
+        TypeDefinition::Interface(ty) => {
+            parts.name = &ty.node.name.node;
+            parts.location = "INTERFACE";
+            parts.is_object_like = true;
+            parts.directive_list = &ty.node.directive_list;
+            parts.field_list = &ty.node.field_list;
+        }
+        TypeDefinition::Union(ty) => {
+            parts.name = &ty.node.name.node;
+            parts.location = "UNION";
+            parts.directive_list = &ty.node.directive_list;
+            parts.member_list = &ty.node.member_list;
+        }
+        TypeDefinition::Enum(ty) => {
+            parts.name = &ty.node.name.node;
+            parts.location = "ENUM";
+            parts.directive_list = &ty.node.directive_list;
+        }
+        TypeDefinition::InputObject(ty) => {
+            parts.name = &ty.node.name.node;
+            parts.location = "INPUT_OBJECT";
+            parts.directive_list = &ty.node.directive_list;
+            parts.field_list = &ty.node.field_list;
+        }
+    }
+    parts
+}
+
+/// The parts of a type composition rewrites.
+struct TypePartsMut<'a> {
+    name: &'a str,
+    is_extend: &'a mut bool,
+    directive_list: &'a mut Vec<Positioned<Directive>>,
+    field_list: Option<&'a mut Vec<Positioned<FieldDefinition>>>,
+}
+
+fn type_parts_mut(ty: &mut TypeDefinition) -> TypePartsMut<'_> {
+    match ty {
+        TypeDefinition::Scalar(ty) => TypePartsMut {
+            name: &ty.node.name.node,
+            is_extend: &mut ty.node.is_extend,
+            directive_list: &mut ty.node.directive_list,
+            field_list: None,
+        },
+        TypeDefinition::Object(ty) => TypePartsMut {
+            name: &ty.node.name.node,
+            is_extend: &mut ty.node.is_extend,
+            directive_list: &mut ty.node.directive_list,
+            field_list: Some(&mut ty.node.field_list),
+        },
+        TypeDefinition::Interface(ty) => TypePartsMut {
+            name: &ty.node.name.node,
+            is_extend: &mut ty.node.is_extend,
+            directive_list: &mut ty.node.directive_list,
+            field_list: Some(&mut ty.node.field_list),
+        },
+        TypeDefinition::Union(ty) => TypePartsMut {
+            name: &ty.node.name.node,
+            is_extend: &mut ty.node.is_extend,
+            directive_list: &mut ty.node.directive_list,
+            field_list: None,
+        },
+        TypeDefinition::Enum(ty) => TypePartsMut {
+            name: &ty.node.name.node,
+            is_extend: &mut ty.node.is_extend,
+            directive_list: &mut ty.node.directive_list,
+            field_list: None,
+        },
+        TypeDefinition::InputObject(ty) => TypePartsMut {
+            name: &ty.node.name.node,
+            is_extend: &mut ty.node.is_extend,
+            directive_list: &mut ty.node.directive_list,
+            field_list: Some(&mut ty.node.field_list),
+        },
+    }
+}
+
+/// Removes everything a subgraph declares for the federation runtime, so the remaining
+/// types can be merged as plain SDL. Subgraph type extensions become definitions.
+fn strip_federation(document: &TypeSystemDocument) -> TypeSystemDocument {
+    let is_federation_directive = |directive: &Positioned<Directive>| {
+        FEDERATION_DIRECTIVE_NAME_LIST.contains(&directive.node.name.node.as_str())
+    };
+    let mut definition_list = vec![];
+    for definition in document.definition_list.iter() {
+        let mut definition = definition.clone();
+        match &mut definition.node {
+            TypeSystemDefinition::Schema(schema) => {
+                let schema = &mut schema.node;
+                schema
+                    .directive_list
+                    .retain(|directive| !is_federation_directive(directive));
+                if schema.field_list.is_empty() {
+                    continue;
+                }
+                schema.is_extend = false;
+            }
+            TypeSystemDefinition::Type(ty) => {
+                let parts = type_parts_mut(&mut ty.node);
+                if FEDERATION_TYPE_NAME_LIST.contains(&parts.name) {
+                    continue;
+                }
+                let is_query = parts.name == "Query";
+                *parts.is_extend = false;
+                parts
+                    .directive_list
+                    .retain(|directive| !is_federation_directive(directive));
+                if let Some(field_list) = parts.field_list {
+                    if is_query {
+                        field_list.retain(|field| !is_federation_field(field));
+                        if field_list.is_empty() {
+                            continue;
+                        }
+                    }
+                    for field in field_list.iter_mut() {
+                        field
+                            .node
+                            .directive_list
+                            .retain(|directive| !is_federation_directive(directive));
+                    }
+                }
+            }
+            TypeSystemDefinition::Directive(directive) => {
+                if FEDERATION_DIRECTIVE_NAME_LIST.contains(&directive.node.name.node.as_str()) {
+                    continue;
+                }
+            }
+        }
+        definition_list.push(definition);
+    }
+    TypeSystemDocument { definition_list }
+}
+
+/// Whether a field of `Query` only exists for the router, as `_entities` and `_service` do.
+fn is_federation_field(field: &Positioned<FieldDefinition>) -> bool {
+    matches!(field.node.name.node.as_str(), "_entities" | "_service")
+}
+
+/// Adds the `@join__*` directives to a merged type and checks that its fields are shareable.
+fn annotate_type(
+    ty: &mut TypeDefinition,
+    graph_list: &[(&Subgraph, String, &SubgraphIndex<'_>)],
+) -> Vec<FederationError> {
+    let mut error_list = vec![];
+    let is_object = matches!(ty, TypeDefinition::Object(_));
+    let type_name = type_parts(ty).name.to_owned();
+    let owner_list: Vec<&(&Subgraph, String, &SubgraphIndex<'_>)> = graph_list
+        .iter()
+        .filter(|(_, _, index)| index.contains(&type_name))
+        .collect();
+
+    let mut join_list = vec![];
+    for (_, graph, index) in owner_list.iter() {
+        let graph = Value::Enum(graph.clone());
+        let key_list = index.key_list(&type_name);
+        if key_list.is_empty() {
+            join_list.push(directive("join__type", vec![("graph", graph.clone())]));
+        }
+        for (fields, resolvable) in key_list {
+            let mut argument_list = vec![
+                ("graph", graph.clone()),
+                ("key", Value::String(fields.to_owned())),
+            ];
+            if !resolvable {
+                argument_list.push(("resolvable", Value::Boolean(false)));
+            }
+            join_list.push(directive("join__type", argument_list));
+        }
+        for definition in index.type_map[type_name.as_str()].iter() {
+            let parts = type_parts(definition);
+            for interface in parts.interface_list.iter() {
+                join_list.push(directive(
+                    "join__implements",
+                    vec![
+                        ("graph", graph.clone()),
+                        ("interface", Value::String(interface.node.clone())),
+                    ],
+                ));
+            }
+            for member in parts.member_list.iter() {
+                join_list.push(directive(
+                    "join__unionMember",
+                    vec![
+                        ("graph", graph.clone()),
+                        ("member", Value::String(member.node.clone())),
+                    ],
+                ));
+            }
+        }
+    }
+
+    let parts = type_parts_mut(ty);
+    parts.directive_list.extend(join_list);
+    let field_list = match parts.field_list {
+        Some(field_list) => field_list,
+        None => return error_list,
+    };
+
+    for field in field_list.iter_mut() {
+        let field_name = field.node.name.node.clone();
+        let mut definition_list: Vec<(&Subgraph, &String, &SubgraphIndex<'_>, &FieldDefinition)> =
+            owner_list
+                .iter()
+                .filter_map(|(subgraph, graph, index)| {
+                    index
+                        .field(&type_name, &field_name)
+                        .map(|definition| (*subgraph, graph, *index, definition))
+                })
+                .collect();
+        let overridden_list: Vec<String> = definition_list
+            .iter()
+            .filter_map(|(_, _, _, definition)| {
+                find_directive(&definition.directive_list, "override").and_then(|directive| {
+                    match argument(directive, "from") {
+                        Some(Value::String(from)) => Some(from.clone()),
+                        _ => None,
+                    }
+                })
+            })
+            .collect();
+        definition_list.retain(|(subgraph, _, _, _)| !overridden_list.contains(&subgraph.name));
+
+        if is_object {
+            let resolver_list: Vec<&(&Subgraph, &String, &SubgraphIndex<'_>, &FieldDefinition)> =
+                definition_list
+                    .iter()
+                    .filter(|(_, _, _, definition)| {
+                        !has_directive(&definition.directive_list, "external")
+                    })
+                    .collect();
+            if resolver_list.len() > 1 {
+                let non_shareable_list: Vec<&str> = resolver_list
+                    .iter()
+                    .filter(|(_, _, index, definition)| {
+                        !is_shareable(index, &type_name, definition)
+                    })
+                    .map(|(subgraph, _, _, _)| subgraph.name.as_str())
+                    .collect();
+                if !non_shareable_list.is_empty() {
+                    let subgraph_list: Vec<String> = resolver_list
+                        .iter()
+                        .map(|(subgraph, _, _, _)| format!("\"{}\"", subgraph.name))
+                        .collect();
+                    let (subgraph, _, _, definition) = resolver_list[resolver_list.len() - 1];
+                    error_list.push(FederationError {
+                        subgraph: subgraph.name.clone(),
+                        message: format!(
+                            "Non-shareable field \"{}.{}\" is resolved from multiple subgraphs: it is resolved from subgraphs {} and defined as non-shareable in subgraph \"{}\".",
+                            type_name,
+                            field_name,
+                            subgraph_list.join(" and "),
+                            non_shareable_list.join("\", \"")
+                        ),
+                        position: definition.name.start.clone(),
+                    });
+                }
+            }
+        }
+
+        let is_plain = overridden_list.is_empty()
+            && definition_list.len() == owner_list.len()
+            && definition_list.iter().all(|(_, _, _, definition)| {
+                ["requires", "provides", "external"]
+                    .iter()
+                    .all(|name| !has_directive(&definition.directive_list, name))
+            });
+        if is_plain {
+            continue;
+        }
+        for (_, graph, _, definition) in definition_list.iter() {
+            let mut argument_list = vec![("graph", Value::Enum((*graph).clone()))];
+            for name in ["requires", "provides"].iter() {
+                if let Some(Value::String(fields)) =
+                    find_directive(&definition.directive_list, name)
+                        .and_then(|directive| argument(directive, "fields"))
+                {
+                    argument_list.push((*name, Value::String(fields.clone())));
+                }
+            }
+            if has_directive(&definition.directive_list, "external") {
+                argument_list.push(("external", Value::Boolean(true)));
+            }
+            if let Some(Value::String(from)) =
+                find_directive(&definition.directive_list, "override")
+                    .and_then(|directive| argument(directive, "from"))
+            {
+                argument_list.push(("override", Value::String(from.clone())));
+            }
+            field
+                .node
+                .directive_list
+                .push(directive("join__field", argument_list));
+        }
+    }
+
+    error_list
+}
+
+/// A field can be resolved by several subgraphs when each marks it or its type
+/// `@shareable`, or uses it in a `@key`.
+fn is_shareable(index: &SubgraphIndex<'_>, type_name: &str, definition: &FieldDefinition) -> bool {
+    if has_directive(&definition.directive_list, "shareable")
+        || index
+            .directive_list(type_name)
+            .iter()
+            .any(|directive| directive.node.name.node == "shareable")
+    {
+        return true;
+    }
+    index.key_list(type_name).iter().any(|(fields, _)| {
+        parse_selection_list(fields)
+            .map(|selection_list| {
+                selection_list.iter().any(|selection| {
+                    matches!(&selection.node, Selection::Field(field) if field.name.node == definition.name.node)
+                })
+            })
+            .unwrap_or(false)
+    })
+}
+
+fn supergraph_schema_definition(document: &TypeSystemDocument) -> Positioned<TypeSystemDefinition> {
+    let mut field_list = vec![];
+    for definition in document.definition_list.iter() {
+        if let TypeSystemDefinition::Schema(schema) = &definition.node {
+            field_list = schema.node.field_list.clone();
+        }
+    }
+    if field_list.is_empty() {
+        let index = SubgraphIndex::new(document);
+        for (ty, name) in [
+            (OperationType::Query, "Query"),
+            (OperationType::Mutation, "Mutation"),
+            (OperationType::Subscription, "Subscription"),
+        ]
+        .iter()
+        {
+            if index.contains(name) {
+                field_list.push(OperationField {
+                    ty: ty.clone(),
+                    name: Positioned::new((*name).to_owned()),
+                });
+            }
+        }
+    }
+
+    Positioned::new(TypeSystemDefinition::Schema(Positioned::new(
+        SchemaDefinition {
+            is_extend: false,
+            description: None,
+            directive_list: vec![
+                directive(
+                    "link",
+                    vec![(
+                        "url",
+                        Value::String("https://specs.apollo.dev/link/v1.0".to_owned()),
+                    )],
+                ),
+                directive(
+                    "link",
+                    vec![
+                        (
+                            "url",
+                            Value::String("https://specs.apollo.dev/join/v0.3".to_owned()),
+                        ),
+                        ("for", Value::Enum("EXECUTION".to_owned())),
+                    ],
+                ),
+            ],
+            field_list,
+        },
+    )))
+}
+
+/// The `join__Graph` enum value naming a subgraph, such as `INVENTORY_V2` for `inventory-v2`.
+pub(crate) fn graph_name(subgraph: &str) -> String {
+    subgraph
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() {
+                c.to_ascii_uppercase()
+            } else {
+                '_'
+            }
+        })
+        .collect()
+}
+
+fn directive(name: &str, argument_list: Vec<(&str, Value)>) -> Positioned<Directive> {
+    Positioned::new(Directive {
+        name: Positioned::new(name.to_owned()),
+        argument_list: argument_list
+            .into_iter()
+            .map(|(name, value)| {
+                Positioned::new(Argument {
+                    name: Positioned::new(name.to_owned()),
+                    value: Positioned::new(value),
+                })
+            })
+            .collect(),
+    })
+}
+
+pub(crate) fn find_directive<'a>(
+    directive_list: &'a [Positioned<Directive>],
+    name: &str,
+) -> Option<&'a Directive> {
+    directive_list
+        .iter()
+        .map(|directive| &directive.node)
+        .find(|directive| directive.name.node == name)
+}
+
+pub(crate) fn has_directive(directive_list: &[Positioned<Directive>], name: &str) -> bool {
+    find_directive(directive_list, name).is_some()
+}
+
+pub(crate) fn argument<'a>(directive: &'a Directive, name: &str) -> Option<&'a Value> {
+    directive
+        .argument_list
+        .iter()
+        .find(|argument| argument.node.name.node == name)
+        .map(|argument| &argument.node.value.node)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn subgraph(name: &str, source: &str) -> Subgraph {
+        Subgraph {
+            name: name.to_owned(),
+            url: format!("http://{}.local/graphql", name),
+            document: parse_type_system(source).unwrap(),
+        }
+    }
+
+    const ACCOUNTS: &str = r#"extend schema @link(url: "https://specs.apollo.dev/federation/v2.3", import: ["@key", "@shareable"])
+
+type Query {
+  me: User
+}
+
+type User @key(fields: "id") {
+  id: ID!
+  name: String @shareable
+}
+"#;
+
+    const REVIEWS: &str = r#"type Review {
+  body: String
+  author: User @provides(fields: "name")
+  product: Product
+}
+
+type User @key(fields: "id") {
+  id: ID!
+  name: String @external
+  reviewList: [Review!]!
+}
+
+type Product @key(fields: "upc", resolvable: false) {
+  upc: String!
+}
+"#;
+
+    const PRODUCTS: &str = r#"type Query {
+  topProductList(first: Int = 5): [Product!]!
+}
+
+type Product @key(fields: "upc") {
+  upc: String!
+  name: String
+  weight: Int @external
+  shippingEstimate: Int @requires(fields: "weight")
+}
+"#;
+
+    #[test]
+    fn compose_supergraph() {
+        let supergraph = compose(&[
+            subgraph("accounts", ACCOUNTS),
+            subgraph("reviews", REVIEWS),
+            subgraph("products", PRODUCTS),
+        ])
+        .unwrap();
+        let sdl = supergraph.sdl();
+        assert!(sdl.starts_with(
+            "schema @link(url: \"https://specs.apollo.dev/link/v1.0\") @link(url: \"https://specs.apollo.dev/join/v0.3\", for: EXECUTION) {\n  query: Query\n}\n"
+        ));
+        assert!(sdl.ends_with(
+            r#"enum join__Graph {
+  ACCOUNTS @join__graph(name: "accounts", url: "http://accounts.local/graphql")
+  REVIEWS @join__graph(name: "reviews", url: "http://reviews.local/graphql")
+  PRODUCTS @join__graph(name: "products", url: "http://products.local/graphql")
+}
+
+type Query @join__type(graph: ACCOUNTS) @join__type(graph: PRODUCTS) {
+  me: User @join__field(graph: ACCOUNTS)
+  topProductList(first: Int = 5): [Product!]! @join__field(graph: PRODUCTS)
+}
+
+type User @join__type(graph: ACCOUNTS, key: "id") @join__type(graph: REVIEWS, key: "id") {
+  id: ID!
+  name: String @join__field(graph: ACCOUNTS) @join__field(graph: REVIEWS, external: true)
+  reviewList: [Review!]! @join__field(graph: REVIEWS)
+}
+
+type Review @join__type(graph: REVIEWS) {
+  body: String
+  author: User @join__field(graph: REVIEWS, provides: "name")
+  product: Product
+}
+
+type Product @join__type(graph: REVIEWS, key: "upc", resolvable: false) @join__type(graph: PRODUCTS, key: "upc") {
+  upc: String!
+  name: String @join__field(graph: PRODUCTS)
+  weight: Int @join__field(graph: PRODUCTS, external: true)
+  shippingEstimate: Int @join__field(graph: PRODUCTS, requires: "weight")
+}
+"#
+        ));
+        assert_eq!(print_type_system(&parse_type_system(&sdl).unwrap()), sdl);
+    }
+
+    #[test]
+    fn invalid_subgraph() {
+        let error_list = validate_subgraph(&subgraph(
+            "inventory",
+            r#"type Product @key(fields: "upc { id }") @key(fields: "sku") @requires(fields: "id") {
+  upc: String!
+  stock(warehouse: ID): Int @requires(fields: "upc") @override(from: "inventory")
+  note: String @provides(fields: "text")
+  owner: User @key(fields: "id")
+}
+
+type User @key(fields: "id(") {
+  id: ID!
+}
+"#,
+        ));
+        let message_list: Vec<String> = error_list.iter().map(ToString::to_string).collect();
+        assert_eq!(
+            message_list,
+            vec![
+                "[inventory] 1:27: On type \"Product\", for @key(fields: \"upc { id }\"): Field \"upc\" must not have a selection since type \"String!\" has no subfields.",
+                "[inventory] 1:54: On type \"Product\", for @key(fields: \"sku\"): Cannot query field \"sku\" on type \"Product\".",
+                "[inventory] 1:61: Directive \"@requires\" may not be used on OBJECT.",
+                "[inventory] 3:29: On field \"Product.stock\", for @requires: field \"Product.upc\" must be marked @external.",
+                "[inventory] 3:54: Source and destination subgraphs \"inventory\" are the same for overridden field \"Product.stock\".",
+                "[inventory] 4:16: On field \"Product.note\", for @provides: type \"String\" has no fields to provide.",
+                "[inventory] 5:15: Directive \"@key\" may not be used on FIELD_DEFINITION.",
+                "[inventory] 8:24: On type \"User\", for @key(fields: \"id(\"): syntax error at column 3.",
+            ]
+        );
+    }
+
+    #[test]
+    fn non_shareable_field() {
+        let error_list = compose(&[
+            subgraph("accounts", ACCOUNTS),
+            subgraph(
+                "profiles",
+                "type User @key(fields: \"id\") {\n  id: ID!\n  name: String\n}\n",
+            ),
+        ])
+        .unwrap_err();
+        let message_list: Vec<String> = error_list.iter().map(ToString::to_string).collect();
+        assert_eq!(
+            message_list,
+            vec!["[profiles] 3:3: Non-shareable field \"User.name\" is resolved from multiple subgraphs: it is resolved from subgraphs \"accounts\" and \"profiles\" and defined as non-shareable in subgraph \"profiles\"."]
+        );
+    }
+
+    #[test]
+    fn entity_only_subgraph() {
+        // A subgraph printing its full SDL has a `Query` type holding nothing but
+        // `_entities` and `_service`, which must not make it a subgraph of `Query`.
+        let supergraph = compose(&[
+            subgraph("accounts", ACCOUNTS),
+            subgraph(
+                "inventory",
+                r#"scalar _Any
+
+union _Entity = User
+
+type _Service {
+  sdl: String
+}
+
+type Query {
+  _entities(representations: [_Any!]!): [_Entity]!
+  _service: _Service!
+}
+
+type User @key(fields: "id") {
+  id: ID!
+  stock: Int
+}
+"#,
+            ),
+        ])
+        .unwrap();
+        let sdl = supergraph.sdl();
+        assert!(sdl.contains("type Query @join__type(graph: ACCOUNTS) {\n  me: User\n}\n"));
+        assert!(!sdl.contains("_entities"));
+    }
+}
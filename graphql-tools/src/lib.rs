@@ -0,0 +1,21 @@
+#![warn(clippy::all)]
+
+mod check;
+mod codegen;
+mod diff;
+mod federation;
+mod lint;
+mod merge;
+mod operation_lint;
+mod planner;
+mod signature;
+
+pub use check::*;
+pub use codegen::*;
+pub use diff::*;
+pub use federation::*;
+pub use lint::*;
+pub use merge::*;
+pub use operation_lint::*;
+pub use planner::*;
+pub use signature::*;
@@ -0,0 +1,958 @@
+use graphql_parser::*;
+use std::collections::{BTreeMap, BTreeSet};
+
+const BUILTIN_SCALAR_LIST: [&str; 5] = ["Int", "Float", "String", "Boolean", "ID"];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum LintRule {
+    /// Type names are PascalCase.
+    TypeNamePascalCase,
+    /// Field names are camelCase.
+    FieldNameCamelCase,
+    /// Enum values are SCREAMING_SNAKE_CASE.
+    EnumValueScreamingCase,
+    /// Every type and field has a description.
+    DescriptionRequired,
+    /// Fields of type `ID` are non-null.
+    NonNullId,
+    /// `@deprecated` gives a reason.
+    DeprecationReason,
+    /// Input object type names end with `Input`.
+    InputTypeSuffix,
+    /// Every type is reachable from a root operation type.
+    UnusedType,
+    /// Connection types, edge types and the fields returning connections follow the Relay
+    /// cursor connections specification.
+    RelayConnection,
+    /// Operations are named.
+    OperationName,
+    /// An anonymous operation is the only operation of its document.
+    LoneAnonymousOperation,
+    /// Every fragment is spread by an operation.
+    UnusedFragment,
+    /// Every variable of an operation is used by it.
+    UnusedVariable,
+    /// Deprecated fields are not selected.
+    DeprecatedField,
+    /// Fragment names match the configured pattern.
+    FragmentNamePattern,
+    /// Selections on a type with an `id` field select it.
+    RequireId,
+    /// Operations are not nested deeper than the configured depth.
+    MaxDepth,
+}
+
+impl LintRule {
+    pub const ALL: [LintRule; 17] = [
+        LintRule::TypeNamePascalCase,
+        LintRule::FieldNameCamelCase,
+        LintRule::EnumValueScreamingCase,
+        LintRule::DescriptionRequired,
+        LintRule::NonNullId,
+        LintRule::DeprecationReason,
+        LintRule::InputTypeSuffix,
+        LintRule::UnusedType,
+        LintRule::RelayConnection,
+        LintRule::OperationName,
+        LintRule::LoneAnonymousOperation,
+        LintRule::UnusedFragment,
+        LintRule::UnusedVariable,
+        LintRule::DeprecatedField,
+        LintRule::FragmentNamePattern,
+        LintRule::RequireId,
+        LintRule::MaxDepth,
+    ];
+
+    /// The name rules are enabled and disabled by in configurations.
+    pub fn name(self) -> &'static str {
+        match self {
+            LintRule::TypeNamePascalCase => "type-name-pascal-case",
+            LintRule::FieldNameCamelCase => "field-name-camel-case",
+            LintRule::EnumValueScreamingCase => "enum-value-screaming-case",
+            LintRule::DescriptionRequired => "description-required",
+            LintRule::NonNullId => "non-null-id",
+            LintRule::DeprecationReason => "deprecation-reason",
+            LintRule::InputTypeSuffix => "input-type-suffix",
+            LintRule::UnusedType => "unused-type",
+            LintRule::RelayConnection => "relay-connection",
+            LintRule::OperationName => "operation-name",
+            LintRule::LoneAnonymousOperation => "lone-anonymous-operation",
+            LintRule::UnusedFragment => "unused-fragment",
+            LintRule::UnusedVariable => "unused-variable",
+            LintRule::DeprecatedField => "deprecated-field",
+            LintRule::FragmentNamePattern => "fragment-name-pattern",
+            LintRule::RequireId => "require-id",
+            LintRule::MaxDepth => "max-depth",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        LintRule::ALL
+            .iter()
+            .copied()
+            .find(|rule| rule.name() == name)
+    }
+}
+
+impl std::fmt::Display for LintRule {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.name())
+    }
+}
+
+/// The rules `lint_schema` and `lint_operations` check. Every rule is enabled by default,
+/// but `fragment-name-pattern` and `max-depth` only check something once given a pattern
+/// and a depth.
+#[derive(Debug, Clone, PartialEq)]
+pub struct LintConfig {
+    rule_set: BTreeSet<LintRule>,
+    pub(crate) fragment_name_pattern: Option<String>,
+    pub(crate) max_depth: Option<usize>,
+}
+
+impl Default for LintConfig {
+    fn default() -> Self {
+        LintConfig {
+            rule_set: LintRule::ALL.iter().copied().collect(),
+            fragment_name_pattern: None,
+            max_depth: None,
+        }
+    }
+}
+
+impl LintConfig {
+    /// A configuration with no rule enabled, to enable rules one by one.
+    pub fn none() -> Self {
+        LintConfig {
+            rule_set: BTreeSet::new(),
+            ..Default::default()
+        }
+    }
+
+    pub fn with_rule(mut self, rule: LintRule) -> Self {
+        self.rule_set.insert(rule);
+        self
+    }
+
+    pub fn without_rule(mut self, rule: LintRule) -> Self {
+        self.rule_set.remove(&rule);
+        self
+    }
+
+    /// Sets the pattern fragment names must match, where `*` matches any text and
+    /// `{type}` matches the type condition of the fragment, as in `{type}Fields`.
+    pub fn with_fragment_name_pattern<T: Into<String>>(mut self, pattern: T) -> Self {
+        self.fragment_name_pattern = Some(pattern.into());
+        self
+    }
+
+    /// Sets the deepest level of nested fields operations may select, where root fields
+    /// are at depth 1.
+    pub fn with_max_depth(mut self, max_depth: usize) -> Self {
+        self.max_depth = Some(max_depth);
+        self
+    }
+
+    pub fn is_enabled(&self, rule: LintRule) -> bool {
+        self.rule_set.contains(&rule)
+    }
+}
+
+/// A replacement of the source between `start` and `end` by `text`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct LintFix {
+    pub start: Position,
+    pub end: Position,
+    pub text: String,
+}
+
+/// A place where a schema or an operation document breaks a rule.
+///
+/// `fix_list` holds the replacements that make the document follow the rule, and is empty
+/// when the violation needs a decision, such as what a description should say.
+#[derive(Debug, Clone, PartialEq)]
+pub struct LintViolation {
+    pub rule: LintRule,
+    pub path: String,
+    pub message: String,
+    pub position: Position,
+    pub fix_list: Vec<LintFix>,
+}
+
+impl std::fmt::Display for LintViolation {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}:{}: {} ({})",
+            self.position.line, self.position.column, self.message, self.rule
+        )
+    }
+}
+
+/// Checks `document` against the rules enabled in `config`, and lists the violations in
+/// the order they appear in the document.
+pub fn lint_schema(document: &TypeSystemDocument, config: &LintConfig) -> Vec<LintViolation> {
+    let mut linter = Linter {
+        document,
+        config,
+        violation_list: vec![],
+    };
+    for definition in document.definition_list.iter() {
+        if let TypeSystemDefinition::Type(ty) = &definition.node {
+            linter.lint_type(&ty.node);
+        }
+    }
+    if config.is_enabled(LintRule::UnusedType) {
+        linter.lint_unused_type_list();
+    }
+    if config.is_enabled(LintRule::RelayConnection) {
+        linter.lint_relay_connection();
+    }
+    linter
+        .violation_list
+        .sort_by_key(|violation| violation.position.offset);
+    linter.violation_list
+}
+
+/// Applies the fixes of `violation_list` to `source`, the text the linted document was
+/// parsed from.
+///
+/// A fix overlapping one already applied is skipped along with the rest of its violation,
+/// so linting and fixing again may fix more.
+pub fn apply_lint_fixes(source: &str, violation_list: &[LintViolation]) -> String {
+    let mut fix_list: Vec<&LintFix> = vec![];
+    for violation in violation_list.iter() {
+        let is_overlapping = violation.fix_list.iter().any(|fix| {
+            fix_list.iter().any(|applied| {
+                fix.start.offset < applied.end.offset && applied.start.offset < fix.end.offset
+                    || fix.start.offset == applied.start.offset
+            })
+        });
+        if !is_overlapping {
+            fix_list.extend(violation.fix_list.iter());
+        }
+    }
+    fix_list.sort_by_key(|fix| fix.start.offset);
+
+    let mut output = String::new();
+    let mut offset = 0;
+    for fix in fix_list {
+        output.push_str(&source[offset..fix.start.offset]);
+        output.push_str(&fix.text);
+        offset = fix.end.offset;
+    }
+    output.push_str(&source[offset..]);
+    output
+}
+
+struct Linter<'a> {
+    document: &'a TypeSystemDocument,
+    config: &'a LintConfig,
+    violation_list: Vec<LintViolation>,
+}
+
+impl<'a> Linter<'a> {
+    fn push(
+        &mut self,
+        rule: LintRule,
+        path: &str,
+        message: String,
+        position: &Position,
+        fix_list: Vec<LintFix>,
+    ) {
+        if self.config.is_enabled(rule) {
+            self.violation_list.push(LintViolation {
+                rule,
+                path: path.to_owned(),
+                message,
+                position: position.clone(),
+                fix_list,
+            });
+        }
+    }
+
+    fn lint_type(&mut self, ty: &TypeDefinition) {
+        let (name, description, is_extend) = match ty {
+            TypeDefinition::Scalar(ty) => (&ty.node.name, &ty.node.description, ty.node.is_extend),
+            TypeDefinition::Object(ty) => (&ty.node.name, &ty.node.description, ty.node.is_extend),
+            TypeDefinition::Interface(ty) => {
+                (&ty.node.name, &ty.node.description, ty.node.is_extend)
+            }
+            TypeDefinition::Union(ty) => (&ty.node.name, &ty.node.description, ty.node.is_extend),
+            TypeDefinition::Enum(ty) => (&ty.node.name, &ty.node.description, ty.node.is_extend),
+            TypeDefinition::InputObject(ty) => {
+                (&ty.node.name, &ty.node.description, ty.node.is_extend)
+            }
+        };
+        let type_name = &name.node;
+
+        if !is_extend {
+            if !is_pascal_case(type_name) {
+                let new_name = to_pascal_case(type_name);
+                self.push(
+                    LintRule::TypeNamePascalCase,
+                    type_name,
+                    format!(
+                        "Type \"{}\" should be named in PascalCase, as \"{}\".",
+                        type_name, new_name
+                    ),
+                    &name.start,
+                    rename_type(self.document, type_name, &new_name),
+                );
+            }
+            if description.is_none() {
+                self.push(
+                    LintRule::DescriptionRequired,
+                    type_name,
+                    format!("Type \"{}\" should have a description.", type_name),
+                    &name.start,
+                    vec![],
+                );
+            }
+            if matches!(ty, TypeDefinition::InputObject(_)) && !type_name.ends_with("Input") {
+                let new_name = format!("{}Input", type_name);
+                self.push(
+                    LintRule::InputTypeSuffix,
+                    type_name,
+                    format!(
+                        "Input type \"{}\" should end with \"Input\", as \"{}\".",
+                        type_name, new_name
+                    ),
+                    &name.start,
+                    rename_type(self.document, type_name, &new_name),
+                );
+            }
+        }
+
+        match ty {
+            TypeDefinition::Object(ty) => {
+                self.lint_field_list(type_name, &ty.node.field_list, true)
+            }
+            TypeDefinition::Interface(ty) => {
+                self.lint_field_list(type_name, &ty.node.field_list, true)
+            }
+            TypeDefinition::InputObject(ty) => {
+                self.lint_field_list(type_name, &ty.node.field_list, false)
+            }
+            TypeDefinition::Enum(ty) => {
+                for member in ty.node.member_list.iter() {
+                    let name = &member.node.name;
+                    let path = format!("{}.{}", type_name, name.node);
+                    if !is_screaming_case(&name.node) {
+                        let new_name = to_screaming_case(&name.node);
+                        self.push(
+                            LintRule::EnumValueScreamingCase,
+                            &path,
+                            format!(
+                                "Enum value \"{}\" should be named in SCREAMING_SNAKE_CASE, as \"{}\".",
+                                path, new_name
+                            ),
+                            &name.start,
+                            vec![rename(name, new_name)],
+                        );
+                    }
+                    self.lint_deprecation(&path, &member.node.directive_list);
+                }
+            }
+            TypeDefinition::Scalar(_) | TypeDefinition::Union(_) => {}
+        }
+    }
+
+    fn lint_field_list(
+        &mut self,
+        type_name: &str,
+        field_list: &[Positioned<FieldDefinition>],
+        is_output: bool,
+    ) {
+        for field in field_list.iter() {
+            let field = &field.node;
+            let name = &field.name;
+            let path = format!("{}.{}", type_name, name.node);
+            if !is_camel_case(&name.node) {
+                let new_name = to_camel_case(&name.node);
+                self.push(
+                    LintRule::FieldNameCamelCase,
+                    &path,
+                    format!(
+                        "Field \"{}\" should be named in camelCase, as \"{}\".",
+                        path, new_name
+                    ),
+                    &name.start,
+                    vec![rename(name, new_name)],
+                );
+            }
+            if field.description.is_none() {
+                self.push(
+                    LintRule::DescriptionRequired,
+                    &path,
+                    format!("Field \"{}\" should have a description.", path),
+                    &name.start,
+                    vec![],
+                );
+            }
+            if is_output && field.ty.node.name() == "ID" && !field.ty.node.is_non_null() {
+                self.push(
+                    LintRule::NonNullId,
+                    &path,
+                    format!(
+                        "Field \"{}\" of type \"{}\" should be non-null.",
+                        path, field.ty.node
+                    ),
+                    &field.ty.start,
+                    vec![LintFix {
+                        start: field.ty.end.clone(),
+                        end: field.ty.end.clone(),
+                        text: "!".to_owned(),
+                    }],
+                );
+            }
+            self.lint_deprecation(&path, &field.directive_list);
+            for argument in field.argument_list.iter() {
+                let path = format!("{}({}:)", path, argument.node.name.node);
+                self.lint_deprecation(&path, &argument.node.directive_list);
+            }
+        }
+    }
+
+    fn lint_deprecation(&mut self, path: &str, directive_list: &[Positioned<Directive>]) {
+        let directive = match directive_list
+            .iter()
+            .find(|directive| directive.node.name.node == "deprecated")
+        {
+            Some(directive) => directive,
+            None => return,
+        };
+        let has_reason = directive.node.argument_list.iter().any(|argument| {
+            argument.node.name.node == "reason"
+                && matches!(&argument.node.value.node, Value::String(reason) if !reason.trim().is_empty())
+        });
+        if !has_reason {
+            self.push(
+                LintRule::DeprecationReason,
+                path,
+                format!("Deprecation of \"{}\" should give a reason.", path),
+                &directive.start,
+                vec![],
+            );
+        }
+    }
+
+    fn lint_unused_type_list(&mut self) {
+        let type_map = TypeMap::new(self.document);
+        let mut root_list: Vec<&str> = vec![];
+        for definition in self.document.definition_list.iter() {
+            if let TypeSystemDefinition::Schema(schema) = &definition.node {
+                root_list.extend(
+                    schema
+                        .node
+                        .field_list
+                        .iter()
+                        .map(|field| field.name.node.as_str()),
+                );
+            }
+        }
+        if root_list.is_empty() {
+            root_list = ["Query", "Mutation", "Subscription"]
+                .iter()
+                .copied()
+                .filter(|name| type_map.entry_map.contains_key(name))
+                .collect();
+        }
+        // Types only used by directives are still used.
+        for definition in self.document.definition_list.iter() {
+            if let TypeSystemDefinition::Directive(directive) = &definition.node {
+                root_list.extend(
+                    directive
+                        .node
+                        .argument_list
+                        .iter()
+                        .map(|argument| argument.node.ty.node.name()),
+                );
+            }
+        }
+        if root_list.is_empty() {
+            return;
+        }
+
+        let mut used_set: BTreeSet<&str> = BTreeSet::new();
+        let mut queue = root_list;
+        while let Some(name) = queue.pop() {
+            if !used_set.insert(name) {
+                continue;
+            }
+            let entry = match type_map.entry_map.get(name) {
+                Some(entry) => entry,
+                None => continue,
+            };
+            for field in entry.field_list.iter() {
+                queue.push(field.ty.node.name());
+                queue.extend(
+                    field
+                        .argument_list
+                        .iter()
+                        .map(|argument| argument.node.ty.node.name()),
+                );
+            }
+            queue.extend(entry.interface_list.iter().map(|name| name.node.as_str()));
+            queue.extend(entry.member_list.iter().map(|name| name.node.as_str()));
+            // An object is returned wherever an interface it implements is.
+            queue.extend(
+                type_map
+                    .entry_map
+                    .iter()
+                    .filter(|(_, other)| {
+                        other
+                            .interface_list
+                            .iter()
+                            .any(|interface| interface.node == name)
+                    })
+                    .map(|(name, _)| *name),
+            );
+        }
+
+        for (name, entry) in type_map.entry_map.iter() {
+            if !used_set.contains(name) {
+                let position = &entry.name.start;
+                self.push(
+                    LintRule::UnusedType,
+                    name,
+                    format!("Type \"{}\" is not reachable from a root type.", name),
+                    position,
+                    vec![],
+                );
+            }
+        }
+    }
+
+    fn lint_relay_connection(&mut self) {
+        let type_map = TypeMap::new(self.document);
+        let is_object = |name: &str| {
+            type_map
+                .entry_map
+                .get(name)
+                .is_some_and(|entry| entry.is_object)
+        };
+
+        for (type_name, entry) in type_map.entry_map.iter() {
+            if !entry.is_object {
+                continue;
+            }
+            let field = |name: &str| {
+                entry
+                    .field_list
+                    .iter()
+                    .find(|field| field.name.node == name)
+            };
+            let position = &entry.name.start;
+
+            if type_name.ends_with("Connection") {
+                let has_edges = field("edges").is_some_and(|field| {
+                    let item = match &field.ty.node {
+                        Type::NonNull(ty) => ty.as_ref(),
+                        ty => ty,
+                    };
+                    matches!(item, Type::List(_))
+                        && field.ty.node.name().ends_with("Edge")
+                        && is_object(field.ty.node.name())
+                });
+                if !has_edges {
+                    self.push(
+                        LintRule::RelayConnection,
+                        type_name,
+                        format!(
+                            "Connection type \"{}\" should have an \"edges\" field returning a list of edge types.",
+                            type_name
+                        ),
+                        position,
+                        vec![],
+                    );
+                }
+                let has_page_info =
+                    field("pageInfo").is_some_and(|field| field.ty.node.to_string() == "PageInfo!");
+                if !has_page_info {
+                    self.push(
+                        LintRule::RelayConnection,
+                        type_name,
+                        format!(
+                            "Connection type \"{}\" should have a \"pageInfo\" field of type \"PageInfo!\".",
+                            type_name
+                        ),
+                        position,
+                        vec![],
+                    );
+                }
+            }
+
+            if type_name.ends_with("Edge") {
+                let has_node = field("node").is_some();
+                // A cursor is an opaque scalar, so anything but a list will do.
+                let has_cursor = field("cursor").is_some_and(|field| {
+                    matches!(&field.ty.node, Type::NonNull(ty) if matches!(ty.as_ref(), Type::Named(_)))
+                });
+                if !has_node || !has_cursor {
+                    self.push(
+                        LintRule::RelayConnection,
+                        type_name,
+                        format!(
+                            "Edge type \"{}\" should have a \"node\" field and a non-null \"cursor\" field.",
+                            type_name
+                        ),
+                        position,
+                        vec![],
+                    );
+                }
+            }
+
+            for field in entry.field_list.iter() {
+                let return_type = field.ty.node.name();
+                if !return_type.ends_with("Connection") || !is_object(return_type) {
+                    continue;
+                }
+                let has_argument = |name: &str| {
+                    field
+                        .argument_list
+                        .iter()
+                        .any(|argument| argument.node.name.node == name)
+                };
+                if !(has_argument("first") && has_argument("after")
+                    || has_argument("last") && has_argument("before"))
+                {
+                    let path = format!("{}.{}", type_name, field.name.node);
+                    self.push(
+                        LintRule::RelayConnection,
+                        &path,
+                        format!(
+                            "Field \"{}\" returns a connection and should take \"first\" and \"after\" or \"last\" and \"before\" arguments.",
+                            path
+                        ),
+                        &field.name.start,
+                        vec![],
+                    );
+                }
+            }
+        }
+    }
+}
+
+struct TypeEntry<'a> {
+    name: &'a Positioned<String>,
+    is_object: bool,
+    field_list: Vec<&'a FieldDefinition>,
+    interface_list: Vec<&'a Positioned<String>>,
+    member_list: Vec<&'a Positioned<String>>,
+}
+
+/// The types of a document with their extensions folded in.
+struct TypeMap<'a> {
+    entry_map: BTreeMap<&'a str, TypeEntry<'a>>,
+}
+
+impl<'a> TypeMap<'a> {
+    fn new(document: &'a TypeSystemDocument) -> Self {
+        let mut entry_map: BTreeMap<&'a str, TypeEntry<'a>> = BTreeMap::new();
+        for definition in document.definition_list.iter() {
+            let ty = match &definition.node {
+                TypeSystemDefinition::Type(ty) => &ty.node,
+                _ => continue,
+            };
+            let name = match ty {
+                TypeDefinition::Scalar(ty) => &ty.node.name,
+                TypeDefinition::Object(ty) => &ty.node.name,
+                TypeDefinition::Interface(ty) => &ty.node.name,
+                TypeDefinition::Union(ty) => &ty.node.name,
+                TypeDefinition::Enum(ty) => &ty.node.name,
+                TypeDefinition::InputObject(ty) => &ty.node.name,
+            };
+            if BUILTIN_SCALAR_LIST.contains(&name.node.as_str()) {
+                continue;
+            }
+            let entry = entry_map
+                .entry(name.node.as_str())
+                .or_insert_with(|| TypeEntry {
+                    name,
+                    is_object: matches!(ty, TypeDefinition::Object(_)),
+                    field_list: vec![],
+                    interface_list: vec![],
+                    member_list: vec![],
+                });
+            match ty {
+                TypeDefinition::Object(ty) => {
+                    entry
+                        .field_list
+                        .extend(ty.node.field_list.iter().map(|field| &field.node));
+                    entry.interface_list.extend(ty.node.interface_list.iter());
+                }
+                TypeDefinition::Interface(ty) => entry
+                    .field_list
+                    .extend(ty.node.field_list.iter().map(|field| &field.node)),
+                TypeDefinition::InputObject(ty) => entry
+                    .field_list
+                    .extend(ty.node.field_list.iter().map(|field| &field.node)),
+                TypeDefinition::Union(ty) => entry.member_list.extend(ty.node.member_list.iter()),
+                TypeDefinition::Scalar(_) | TypeDefinition::Enum(_) => {}
+            }
+        }
+        TypeMap { entry_map }
+    }
+}
+
+fn rename(name: &Positioned<String>, text: String) -> LintFix {
+    LintFix {
+        start: name.start.clone(),
+        end: name.end.clone(),
+        text,
+    }
+}
+
+/// Renames a type everywhere the document names it.
+fn rename_type(document: &TypeSystemDocument, old: &str, new: &str) -> Vec<LintFix> {
+    let mut fix_list = vec![];
+    let rename_name = |name: &Positioned<String>, fix_list: &mut Vec<LintFix>| {
+        if name.node == old {
+            fix_list.push(rename(name, new.to_owned()));
+        }
+    };
+    let rename_reference = |ty: &Positioned<Type>, fix_list: &mut Vec<LintFix>| {
+        if ty.node.name() == old {
+            fix_list.push(LintFix {
+                start: ty.start.clone(),
+                end: ty.end.clone(),
+                text: renamed(&ty.node, new).to_string(),
+            });
+        }
+    };
+    let rename_field_list = |field_list: &[Positioned<FieldDefinition>],
+                             fix_list: &mut Vec<LintFix>| {
+        for field in field_list.iter() {
+            for argument in field.node.argument_list.iter() {
+                rename_reference(&argument.node.ty, fix_list);
+            }
+            rename_reference(&field.node.ty, fix_list);
+        }
+    };
+
+    for definition in document.definition_list.iter() {
+        match &definition.node {
+            TypeSystemDefinition::Schema(schema) => {
+                for field in schema.node.field_list.iter() {
+                    rename_name(&field.name, &mut fix_list);
+                }
+            }
+            TypeSystemDefinition::Directive(directive) => {
+                for argument in directive.node.argument_list.iter() {
+                    rename_reference(&argument.node.ty, &mut fix_list);
+                }
+            }
+            TypeSystemDefinition::Type(ty) => match &ty.node {
+                TypeDefinition::Scalar(ty) => rename_name(&ty.node.name, &mut fix_list),
+                TypeDefinition::Object(ty) => {
+                    rename_name(&ty.node.name, &mut fix_list);
+                    for interface in ty.node.interface_list.iter() {
+                        rename_name(interface, &mut fix_list);
+                    }
+                    rename_field_list(&ty.node.field_list, &mut fix_list);
+                }
+                TypeDefinition::Interface(ty) => {
+                    rename_name(&ty.node.name, &mut fix_list);
+                    rename_field_list(&ty.node.field_list, &mut fix_list);
+                }
+                TypeDefinition::Union(ty) => {
+                    rename_name(&ty.node.name, &mut fix_list);
+                    for member in ty.node.member_list.iter() {
+                        rename_name(member, &mut fix_list);
+                    }
+                }
+                TypeDefinition::Enum(ty) => rename_name(&ty.node.name, &mut fix_list),
+                TypeDefinition::InputObject(ty) => {
+                    rename_name(&ty.node.name, &mut fix_list);
+                    rename_field_list(&ty.node.field_list, &mut fix_list);
+                }
+            },
+        }
+    }
+    fix_list
+}
+
+fn renamed(ty: &Type, name: &str) -> Type {
+    match ty {
+        Type::NonNull(ty) => Type::NonNull(Box::new(renamed(ty, name))),
+        Type::List(ty) => Type::List(Box::new(renamed(ty, name))),
+        Type::Named(_) => Type::Named(name.to_owned()),
+    }
+}
+
+/// Splits a name into its words at underscores and at lower to upper case changes.
+pub(crate) fn word_list(name: &str) -> Vec<String> {
+    let mut word_list = vec![];
+    let mut word = String::new();
+    let mut previous: Option<char> = None;
+    for c in name.chars() {
+        if c == '_' {
+            if !word.is_empty() {
+                word_list.push(std::mem::take(&mut word));
+            }
+        } else {
+            let is_boundary = c.is_ascii_uppercase()
+                && previous.is_some_and(|previous| {
+                    previous.is_ascii_lowercase() || previous.is_ascii_digit()
+                });
+            if is_boundary && !word.is_empty() {
+                word_list.push(std::mem::take(&mut word));
+            }
+            word.push(c);
+        }
+        previous = Some(c);
+    }
+    if !word.is_empty() {
+        word_list.push(word);
+    }
+    word_list
+}
+
+fn capitalize(word: &str) -> String {
+    let mut chars = word.chars();
+    match chars.next() {
+        Some(first) => first.to_ascii_uppercase().to_string() + chars.as_str(),
+        None => String::new(),
+    }
+}
+
+fn is_pascal_case(name: &str) -> bool {
+    name.starts_with(|c: char| c.is_ascii_uppercase())
+        && name.chars().all(|c| c.is_ascii_alphanumeric())
+}
+
+fn to_pascal_case(name: &str) -> String {
+    word_list(name)
+        .iter()
+        .map(|word| capitalize(word))
+        .collect()
+}
+
+fn is_camel_case(name: &str) -> bool {
+    name.starts_with(|c: char| c.is_ascii_lowercase())
+        && name.chars().all(|c| c.is_ascii_alphanumeric())
+}
+
+fn to_camel_case(name: &str) -> String {
+    let pascal = to_pascal_case(name);
+    let mut chars = pascal.chars();
+    match chars.next() {
+        Some(first) => first.to_ascii_lowercase().to_string() + chars.as_str(),
+        None => pascal,
+    }
+}
+
+fn is_screaming_case(name: &str) -> bool {
+    name.starts_with(|c: char| c.is_ascii_uppercase())
+        && name
+            .chars()
+            .all(|c| c.is_ascii_uppercase() || c.is_ascii_digit() || c == '_')
+}
+
+fn to_screaming_case(name: &str) -> String {
+    word_list(name)
+        .iter()
+        .map(|word| word.to_ascii_uppercase())
+        .collect::<Vec<_>>()
+        .join("_")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SCHEMA: &str = r#""""The root query."""
+type Query {
+  """Finds a user."""
+  user(id: ID!): user_profile
+  """Lists users."""
+  users(first: Int, after: String): UserConnection!
+  legacy_name: String @deprecated
+}
+
+"""A user."""
+type user_profile {
+  """The identifier."""
+  id: ID
+  """The status."""
+  status: Status
+}
+
+"""A page of users."""
+type UserConnection {
+  """The users."""
+  edges: [UserEdge]
+}
+
+"""A user in a page."""
+type UserEdge {
+  """The user."""
+  node: user_profile
+}
+
+"""The status of a user."""
+enum Status {
+  active
+  IN_REVIEW
+}
+
+"""Never used."""
+input Filter {
+  """The text to match."""
+  text: String
+}
+"#;
+
+    #[test]
+    fn lint() {
+        let document = parse_type_system(SCHEMA).unwrap();
+        let violation_list = lint_schema(&document, &LintConfig::default());
+        let output: Vec<String> = violation_list.iter().map(ToString::to_string).collect();
+        assert_eq!(
+            output,
+            vec![
+                "7:3: Field \"Query.legacy_name\" should be named in camelCase, as \"legacyName\". (field-name-camel-case)",
+                "7:3: Field \"Query.legacy_name\" should have a description. (description-required)",
+                "7:23: Deprecation of \"Query.legacy_name\" should give a reason. (deprecation-reason)",
+                "11:6: Type \"user_profile\" should be named in PascalCase, as \"UserProfile\". (type-name-pascal-case)",
+                "13:7: Field \"user_profile.id\" of type \"ID\" should be non-null. (non-null-id)",
+                "19:6: Connection type \"UserConnection\" should have a \"pageInfo\" field of type \"PageInfo!\". (relay-connection)",
+                "25:6: Edge type \"UserEdge\" should have a \"node\" field and a non-null \"cursor\" field. (relay-connection)",
+                "32:3: Enum value \"Status.active\" should be named in SCREAMING_SNAKE_CASE, as \"ACTIVE\". (enum-value-screaming-case)",
+                "37:7: Input type \"Filter\" should end with \"Input\", as \"FilterInput\". (input-type-suffix)",
+                "37:7: Type \"Filter\" is not reachable from a root type. (unused-type)",
+            ]
+        );
+
+        let config = LintConfig::none()
+            .with_rule(LintRule::TypeNamePascalCase)
+            .with_rule(LintRule::NonNullId)
+            .with_rule(LintRule::EnumValueScreamingCase);
+        let violation_list = lint_schema(&document, &config);
+        assert_eq!(violation_list.len(), 3);
+        let fixed = apply_lint_fixes(SCHEMA, &violation_list);
+        assert!(fixed.contains("user(id: ID!): UserProfile\n"));
+        assert!(fixed.contains("type UserProfile {"));
+        assert!(fixed.contains("id: ID!\n"));
+        assert!(fixed.contains("node: UserProfile\n"));
+        assert!(fixed.contains("  ACTIVE\n"));
+        assert!(lint_schema(&parse_type_system(&fixed).unwrap(), &config).is_empty());
+        assert!(lint_schema(&document, &LintConfig::none()).is_empty());
+        assert_eq!(
+            LintRule::from_name("relay-connection"),
+            Some(LintRule::RelayConnection)
+        );
+    }
+
+    #[test]
+    fn case() {
+        assert_eq!(to_pascal_case("user_profile"), "UserProfile");
+        assert_eq!(to_pascal_case("userProfile"), "UserProfile");
+        assert_eq!(to_camel_case("Friend_list"), "friendList");
+        assert_eq!(to_screaming_case("inReview"), "IN_REVIEW");
+        assert_eq!(to_screaming_case("in_review2"), "IN_REVIEW2");
+        assert!(is_screaming_case("HTTP_2"));
+        assert!(!is_camel_case("__typename"));
+    }
+}
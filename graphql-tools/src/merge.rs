@@ -0,0 +1,804 @@
+use graphql_parser::*;
+use std::collections::{HashMap, HashSet};
+
+/// How `merge_schema` resolves two definitions that disagree.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ConflictPolicy {
+    /// Fail the merge and report every conflict.
+    Error,
+    /// Keep the definition from the document listed first.
+    KeepFirst,
+    /// Keep the definition from the document listed last.
+    KeepLast,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ConflictKind {
+    RootTypeChanged,
+    TypeKindChanged,
+    FieldTypeChanged,
+    ArgumentTypeChanged,
+    DefaultValueChanged,
+    DirectiveArgumentChanged,
+}
+
+/// Two definitions of the same schema element that disagree.
+///
+/// `document` is the index of the document whose definition conflicted with what was
+/// merged so far, and `incoming` is the position of that definition in it.
+#[derive(Debug, Clone, PartialEq)]
+pub struct MergeConflict {
+    pub kind: ConflictKind,
+    pub path: String,
+    pub message: String,
+    pub document: usize,
+    pub existing: Position,
+    pub incoming: Position,
+}
+
+impl std::fmt::Display for MergeConflict {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{} (document {} at {}:{})",
+            self.message, self.document, self.incoming.line, self.incoming.column
+        )
+    }
+}
+
+/// The merged document, along with the conflicts resolved by the policy.
+#[derive(Debug, Clone, PartialEq)]
+pub struct MergedSchema {
+    pub document: TypeSystemDocument,
+    pub conflict_list: Vec<MergeConflict>,
+}
+
+/// Merges several SDL documents into one.
+///
+/// Types, directives and root operation types are united by name in the order they first
+/// appear. Definitions sharing a name are merged member by member: fields, arguments, enum
+/// values, union members, interfaces, directive locations and applied directives are
+/// united, and the first description wins. A directive not declared `repeatable` is
+/// applied at most once per element; applying it again with other arguments is a
+/// conflict. A type is only kept as an extension when every document extends it.
+///
+/// Members that disagree, such as a field defined with two different types, are resolved
+/// by `policy`. With `ConflictPolicy::Error` every conflict is returned as an error.
+pub fn merge_schema(
+    document_list: &[TypeSystemDocument],
+    policy: ConflictPolicy,
+) -> Result<MergedSchema, Vec<MergeConflict>> {
+    let repeatable_set = document_list
+        .iter()
+        .flat_map(|document| document.definition_list.iter())
+        .filter_map(|definition| match &definition.node {
+            TypeSystemDefinition::Directive(directive) if directive.node.is_repeatable => {
+                Some(directive.node.name.node.clone())
+            }
+            _ => None,
+        })
+        .collect();
+    let mut merger = Merger {
+        policy,
+        repeatable_set,
+        document: 0,
+        definition_list: vec![],
+        schema_index: None,
+        type_index_map: HashMap::new(),
+        directive_index_map: HashMap::new(),
+        conflict_list: vec![],
+    };
+    for (index, document) in document_list.iter().enumerate() {
+        merger.document = index;
+        for definition in document.definition_list.iter() {
+            merger.merge_definition(definition);
+        }
+    }
+
+    if policy == ConflictPolicy::Error && !merger.conflict_list.is_empty() {
+        return Err(merger.conflict_list);
+    }
+    Ok(MergedSchema {
+        document: TypeSystemDocument {
+            definition_list: merger.definition_list,
+        },
+        conflict_list: merger.conflict_list,
+    })
+}
+
+struct Merger {
+    policy: ConflictPolicy,
+    repeatable_set: HashSet<String>,
+    document: usize,
+    definition_list: Vec<Positioned<TypeSystemDefinition>>,
+    schema_index: Option<usize>,
+    type_index_map: HashMap<String, usize>,
+    directive_index_map: HashMap<String, usize>,
+    conflict_list: Vec<MergeConflict>,
+}
+
+impl Merger {
+    /// Records a conflict and tells whether the incoming definition replaces the existing one.
+    fn conflict(
+        &mut self,
+        kind: ConflictKind,
+        path: String,
+        message: String,
+        existing: &Position,
+        incoming: &Position,
+    ) -> bool {
+        self.conflict_list.push(MergeConflict {
+            kind,
+            path,
+            message,
+            document: self.document,
+            existing: existing.clone(),
+            incoming: incoming.clone(),
+        });
+        self.policy == ConflictPolicy::KeepLast
+    }
+
+    fn merge_definition(&mut self, definition: &Positioned<TypeSystemDefinition>) {
+        let index = match &definition.node {
+            TypeSystemDefinition::Schema(_) => self.schema_index,
+            TypeSystemDefinition::Type(ty) => self.type_index_map.get(type_name(&ty.node)).copied(),
+            TypeSystemDefinition::Directive(directive) => self
+                .directive_index_map
+                .get(&directive.node.name.node)
+                .copied(),
+        };
+        let index = match index {
+            Some(index) => index,
+            None => {
+                let index = self.definition_list.len();
+                match &definition.node {
+                    TypeSystemDefinition::Schema(_) => self.schema_index = Some(index),
+                    TypeSystemDefinition::Type(ty) => {
+                        self.type_index_map
+                            .insert(type_name(&ty.node).to_owned(), index);
+                    }
+                    TypeSystemDefinition::Directive(directive) => {
+                        self.directive_index_map
+                            .insert(directive.node.name.node.clone(), index);
+                    }
+                }
+                self.definition_list.push(definition.clone());
+                return;
+            }
+        };
+
+        let mut existing = self.definition_list[index].clone();
+        match (&mut existing.node, &definition.node) {
+            (TypeSystemDefinition::Schema(existing), TypeSystemDefinition::Schema(incoming)) => {
+                self.merge_schema_definition(&mut existing.node, &incoming.node)
+            }
+            (TypeSystemDefinition::Type(existing), TypeSystemDefinition::Type(incoming)) => {
+                self.merge_type(existing, incoming)
+            }
+            (
+                TypeSystemDefinition::Directive(existing),
+                TypeSystemDefinition::Directive(incoming),
+            ) => self.merge_directive(&mut existing.node, &incoming.node),
+            _ => unreachable!(),
+        }
+        self.definition_list[index] = existing;
+    }
+
+    fn merge_schema_definition(
+        &mut self,
+        existing: &mut SchemaDefinition,
+        incoming: &SchemaDefinition,
+    ) {
+        merge_description(&mut existing.description, &incoming.description);
+        existing.is_extend = existing.is_extend && incoming.is_extend;
+        self.merge_directive_list(
+            "schema",
+            &mut existing.directive_list,
+            &incoming.directive_list,
+        );
+        for field in incoming.field_list.iter() {
+            let existing_field = match existing
+                .field_list
+                .iter_mut()
+                .find(|existing_field| existing_field.ty == field.ty)
+            {
+                Some(existing_field) => existing_field,
+                None => {
+                    existing.field_list.push(field.clone());
+                    continue;
+                }
+            };
+            if existing_field.name.node != field.name.node {
+                let operation = match field.ty {
+                    OperationType::Query => "query",
+                    OperationType::Mutation => "mutation",
+                    OperationType::Subscription => "subscription",
+                };
+                let message = format!(
+                    "Root operation type \"{}\" is defined as \"{}\" and \"{}\".",
+                    operation, existing_field.name.node, field.name.node
+                );
+                let position = existing_field.name.start.clone();
+                if self.conflict(
+                    ConflictKind::RootTypeChanged,
+                    operation.to_owned(),
+                    message,
+                    &position,
+                    &field.name.start,
+                ) {
+                    *existing_field = field.clone();
+                }
+            }
+        }
+    }
+
+    fn merge_type(
+        &mut self,
+        existing: &mut Positioned<TypeDefinition>,
+        incoming: &Positioned<TypeDefinition>,
+    ) {
+        match (&mut existing.node, &incoming.node) {
+            (TypeDefinition::Scalar(existing), TypeDefinition::Scalar(incoming)) => {
+                let (existing, incoming) = (&mut existing.node, &incoming.node);
+                merge_description(&mut existing.description, &incoming.description);
+                existing.is_extend = existing.is_extend && incoming.is_extend;
+                self.merge_directive_list(
+                    &existing.name.node,
+                    &mut existing.directive_list,
+                    &incoming.directive_list,
+                );
+            }
+            (TypeDefinition::Object(existing), TypeDefinition::Object(incoming)) => {
+                let (existing, incoming) = (&mut existing.node, &incoming.node);
+                merge_description(&mut existing.description, &incoming.description);
+                existing.is_extend = existing.is_extend && incoming.is_extend;
+                self.merge_directive_list(
+                    &existing.name.node,
+                    &mut existing.directive_list,
+                    &incoming.directive_list,
+                );
+                merge_name_list(&mut existing.interface_list, &incoming.interface_list);
+                self.merge_field_list(
+                    &existing.name.node,
+                    &mut existing.field_list,
+                    &incoming.field_list,
+                );
+            }
+            (TypeDefinition::Interface(existing), TypeDefinition::Interface(incoming)) => {
+                let (existing, incoming) = (&mut existing.node, &incoming.node);
+                merge_description(&mut existing.description, &incoming.description);
+                existing.is_extend = existing.is_extend && incoming.is_extend;
+                self.merge_directive_list(
+                    &existing.name.node,
+                    &mut existing.directive_list,
+                    &incoming.directive_list,
+                );
+                self.merge_field_list(
+                    &existing.name.node,
+                    &mut existing.field_list,
+                    &incoming.field_list,
+                );
+            }
+            (TypeDefinition::Union(existing), TypeDefinition::Union(incoming)) => {
+                let (existing, incoming) = (&mut existing.node, &incoming.node);
+                merge_description(&mut existing.description, &incoming.description);
+                existing.is_extend = existing.is_extend && incoming.is_extend;
+                self.merge_directive_list(
+                    &existing.name.node,
+                    &mut existing.directive_list,
+                    &incoming.directive_list,
+                );
+                merge_name_list(&mut existing.member_list, &incoming.member_list);
+            }
+            (TypeDefinition::Enum(existing), TypeDefinition::Enum(incoming)) => {
+                let (existing, incoming) = (&mut existing.node, &incoming.node);
+                merge_description(&mut existing.description, &incoming.description);
+                existing.is_extend = existing.is_extend && incoming.is_extend;
+                self.merge_directive_list(
+                    &existing.name.node,
+                    &mut existing.directive_list,
+                    &incoming.directive_list,
+                );
+                for member in incoming.member_list.iter() {
+                    match existing.member_list.iter_mut().find(|existing_member| {
+                        existing_member.node.name.node == member.node.name.node
+                    }) {
+                        Some(existing_member) => {
+                            let existing_member = &mut existing_member.node;
+                            merge_description(
+                                &mut existing_member.description,
+                                &member.node.description,
+                            );
+                            self.merge_directive_list(
+                                &format!("{}.{}", existing.name.node, existing_member.name.node),
+                                &mut existing_member.directive_list,
+                                &member.node.directive_list,
+                            );
+                        }
+                        None => existing.member_list.push(member.clone()),
+                    }
+                }
+            }
+            (TypeDefinition::InputObject(existing), TypeDefinition::InputObject(incoming)) => {
+                let (existing, incoming) = (&mut existing.node, &incoming.node);
+                merge_description(&mut existing.description, &incoming.description);
+                existing.is_extend = existing.is_extend && incoming.is_extend;
+                self.merge_directive_list(
+                    &existing.name.node,
+                    &mut existing.directive_list,
+                    &incoming.directive_list,
+                );
+                self.merge_field_list(
+                    &existing.name.node,
+                    &mut existing.field_list,
+                    &incoming.field_list,
+                );
+            }
+            (existing_node, incoming_node) => {
+                let name = type_name(existing_node).to_owned();
+                let message = format!(
+                    "Type \"{}\" is defined as {} and {}.",
+                    name,
+                    kind_name(existing_node),
+                    kind_name(incoming_node)
+                );
+                if self.conflict(
+                    ConflictKind::TypeKindChanged,
+                    name,
+                    message,
+                    &existing.start,
+                    &incoming.start,
+                ) {
+                    *existing = incoming.clone();
+                }
+            }
+        }
+    }
+
+    fn merge_field_list(
+        &mut self,
+        type_name: &str,
+        existing_list: &mut Vec<Positioned<FieldDefinition>>,
+        incoming_list: &[Positioned<FieldDefinition>],
+    ) {
+        for incoming in incoming_list.iter() {
+            let existing = match existing_list
+                .iter_mut()
+                .find(|existing| existing.node.name.node == incoming.node.name.node)
+            {
+                Some(existing) => existing,
+                None => {
+                    existing_list.push(incoming.clone());
+                    continue;
+                }
+            };
+            let path = format!("{}.{}", type_name, incoming.node.name.node);
+            if existing.node.ty.node != incoming.node.ty.node {
+                let message = format!(
+                    "Field \"{}\" is defined with type \"{}\" and \"{}\".",
+                    path, existing.node.ty.node, incoming.node.ty.node
+                );
+                if self.conflict(
+                    ConflictKind::FieldTypeChanged,
+                    path,
+                    message,
+                    &existing.node.ty.start,
+                    &incoming.node.ty.start,
+                ) {
+                    *existing = incoming.clone();
+                }
+                continue;
+            }
+
+            let existing = &mut existing.node;
+            let incoming = &incoming.node;
+            if self.is_default_value_replaced(
+                &path,
+                "Field",
+                &existing.default_value,
+                &incoming.default_value,
+            ) {
+                existing.default_value = incoming.default_value.clone();
+            }
+            merge_description(&mut existing.description, &incoming.description);
+            self.merge_directive_list(
+                &path,
+                &mut existing.directive_list,
+                &incoming.directive_list,
+            );
+            self.merge_argument_list(&path, &mut existing.argument_list, &incoming.argument_list);
+        }
+    }
+
+    fn merge_argument_list(
+        &mut self,
+        parent_path: &str,
+        existing_list: &mut Vec<Positioned<FieldArgument>>,
+        incoming_list: &[Positioned<FieldArgument>],
+    ) {
+        for incoming in incoming_list.iter() {
+            let existing = match existing_list
+                .iter_mut()
+                .find(|existing| existing.node.name.node == incoming.node.name.node)
+            {
+                Some(existing) => existing,
+                None => {
+                    existing_list.push(incoming.clone());
+                    continue;
+                }
+            };
+            let path = format!("{}({}:)", parent_path, incoming.node.name.node);
+            if existing.node.ty.node != incoming.node.ty.node {
+                let message = format!(
+                    "Argument \"{}\" is defined with type \"{}\" and \"{}\".",
+                    path, existing.node.ty.node, incoming.node.ty.node
+                );
+                if self.conflict(
+                    ConflictKind::ArgumentTypeChanged,
+                    path,
+                    message,
+                    &existing.node.ty.start,
+                    &incoming.node.ty.start,
+                ) {
+                    *existing = incoming.clone();
+                }
+                continue;
+            }
+
+            let existing = &mut existing.node;
+            let incoming = &incoming.node;
+            if self.is_default_value_replaced(
+                &path,
+                "Argument",
+                &existing.default_value,
+                &incoming.default_value,
+            ) {
+                existing.default_value = incoming.default_value.clone();
+            }
+            merge_description(&mut existing.description, &incoming.description);
+            self.merge_directive_list(
+                &path,
+                &mut existing.directive_list,
+                &incoming.directive_list,
+            );
+        }
+    }
+
+    fn merge_directive(
+        &mut self,
+        existing: &mut DirectiveDefinition,
+        incoming: &DirectiveDefinition,
+    ) {
+        merge_description(&mut existing.description, &incoming.description);
+        for location in incoming.location_list.iter() {
+            if !existing
+                .location_list
+                .iter()
+                .any(|existing_location| existing_location.node == location.node)
+            {
+                existing.location_list.push(location.clone());
+            }
+        }
+        self.merge_argument_list(
+            &format!("@{}", existing.name.node),
+            &mut existing.argument_list,
+            &incoming.argument_list,
+        );
+    }
+
+    /// Applied directives are compared by name and argument values, ignoring positions.
+    /// A non-repeatable directive applied with other arguments is a conflict.
+    fn merge_directive_list(
+        &mut self,
+        path: &str,
+        existing: &mut Vec<Positioned<Directive>>,
+        incoming: &[Positioned<Directive>],
+    ) {
+        for directive in incoming.iter() {
+            let name = &directive.node.name.node;
+            if self.repeatable_set.contains(name) {
+                if !existing.iter().any(|existing| {
+                    existing.node.name.node == *name
+                        && is_same_argument_list(&existing.node, &directive.node)
+                }) {
+                    existing.push(directive.clone());
+                }
+                continue;
+            }
+
+            let existing = match existing
+                .iter_mut()
+                .find(|existing| existing.node.name.node == *name)
+            {
+                Some(existing) => existing,
+                None => {
+                    existing.push(directive.clone());
+                    continue;
+                }
+            };
+            if !is_same_argument_list(&existing.node, &directive.node) {
+                let message = format!(
+                    "Directive \"@{}\" is applied to \"{}\" with arguments {} and {}.",
+                    name,
+                    path,
+                    argument_list_text(&existing.node),
+                    argument_list_text(&directive.node)
+                );
+                let position = existing.start.clone();
+                if self.conflict(
+                    ConflictKind::DirectiveArgumentChanged,
+                    format!("{} @{}", path, name),
+                    message,
+                    &position,
+                    &directive.start,
+                ) {
+                    *existing = directive.clone();
+                }
+            }
+        }
+    }
+
+    /// A default value only conflicts when both sides declare one.
+    fn is_default_value_replaced(
+        &mut self,
+        path: &str,
+        subject: &str,
+        existing: &Option<Positioned<Value>>,
+        incoming: &Option<Positioned<Value>>,
+    ) -> bool {
+        match (existing, incoming) {
+            (None, Some(_)) => true,
+            (Some(existing), Some(incoming)) if existing.node != incoming.node => {
+                let message = format!(
+                    "{} \"{}\" is defined with default values {} and {}.",
+                    subject, path, existing.node, incoming.node
+                );
+                self.conflict(
+                    ConflictKind::DefaultValueChanged,
+                    path.to_owned(),
+                    message,
+                    &existing.start,
+                    &incoming.start,
+                )
+            }
+            _ => false,
+        }
+    }
+}
+
+fn type_name(definition: &TypeDefinition) -> &str {
+    match definition {
+        TypeDefinition::Scalar(ty) => &ty.node.name.node,
+        TypeDefinition::Object(ty) => &ty.node.name.node,
+        TypeDefinition::Interface(ty) => &ty.node.name.node,
+        TypeDefinition::Union(ty) => &ty.node.name.node,
+        TypeDefinition::Enum(ty) => &ty.node.name.node,
+        TypeDefinition::InputObject(ty) => &ty.node.name.node,
+    }
+}
+
+fn kind_name(definition: &TypeDefinition) -> &'static str {
+    match definition {
+        TypeDefinition::Scalar(_) => "a scalar type",
+        TypeDefinition::Object(_) => "an object type",
+        TypeDefinition::Interface(_) => "an interface type",
+        TypeDefinition::Union(_) => "a union type",
+        TypeDefinition::Enum(_) => "an enum type",
+        TypeDefinition::InputObject(_) => "an input object type",
+    }
+}
+
+fn is_same_argument_list(a: &Directive, b: &Directive) -> bool {
+    a.argument_list.len() == b.argument_list.len()
+        && a.argument_list
+            .iter()
+            .zip(b.argument_list.iter())
+            .all(|(a, b)| {
+                a.node.name.node == b.node.name.node && a.node.value.node == b.node.value.node
+            })
+}
+
+fn argument_list_text(directive: &Directive) -> String {
+    let argument_list: Vec<String> = directive
+        .argument_list
+        .iter()
+        .map(|argument| format!("{}: {}", argument.node.name.node, argument.node.value.node))
+        .collect();
+    format!("({})", argument_list.join(", "))
+}
+
+fn merge_description(
+    existing: &mut Option<Positioned<String>>,
+    incoming: &Option<Positioned<String>>,
+) {
+    if existing.is_none() {
+        *existing = incoming.clone();
+    }
+}
+
+fn merge_name_list(existing: &mut Vec<Positioned<String>>, incoming: &[Positioned<String>]) {
+    for name in incoming.iter() {
+        if !existing.iter().any(|existing| existing.node == name.node) {
+            existing.push(name.clone());
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const USERS: &str = r#""""
+A member of the organization.
+"""
+type User @key(fields: "id") {
+  id: ID!
+  name: String
+}
+
+type Query {
+  user(id: ID!): User
+}
+
+enum Role {
+  ADMIN
+}
+
+directive @key(fields: String!) on OBJECT
+"#;
+
+    const TEAMS: &str = r#"type User @key(fields: "id") {
+  id: ID!
+  teamList(first: Int = 10): [Team!]!
+}
+
+type Team {
+  name: String
+}
+
+extend type Query {
+  team(name: String!): Team
+}
+
+enum Role {
+  ADMIN
+  GUEST
+}
+
+directive @key(fields: String!) on INTERFACE
+"#;
+
+    const BILLING: &str = r#"type User {
+  id: String
+  teamList(first: Int = 20): [Team!]!
+}
+
+scalar Team
+"#;
+
+    fn parse(source_list: &[&str]) -> Vec<TypeSystemDocument> {
+        source_list
+            .iter()
+            .map(|source| parse_type_system(source).unwrap())
+            .collect()
+    }
+
+    #[test]
+    fn merge() {
+        let merged = merge_schema(&parse(&[USERS, TEAMS]), ConflictPolicy::Error).unwrap();
+        assert_eq!(merged.conflict_list, vec![]);
+        assert_eq!(
+            print_type_system(&merged.document),
+            r#""""
+A member of the organization.
+"""
+type User @key(fields: "id") {
+  id: ID!
+  name: String
+  teamList(first: Int = 10): [Team!]!
+}
+
+type Query {
+  user(id: ID!): User
+  team(name: String!): Team
+}
+
+enum Role {
+  ADMIN
+  GUEST
+}
+
+directive @key(fields: String!) on OBJECT | INTERFACE
+
+type Team {
+  name: String
+}
+"#
+        );
+    }
+
+    #[test]
+    fn conflict() {
+        let document_list = parse(&[USERS, TEAMS, BILLING]);
+        let message_list: Vec<String> = merge_schema(&document_list, ConflictPolicy::Error)
+            .unwrap_err()
+            .iter()
+            .map(ToString::to_string)
+            .collect();
+        assert_eq!(
+            message_list,
+            vec![
+                "Field \"User.id\" is defined with type \"ID!\" and \"String\". (document 2 at 2:7)",
+                "Argument \"User.teamList(first:)\" is defined with default values 10 and 20. (document 2 at 3:25)",
+                "Type \"Team\" is defined as an object type and a scalar type. (document 2 at 6:1)",
+            ]
+        );
+
+        let merged = merge_schema(&document_list, ConflictPolicy::KeepFirst).unwrap();
+        assert_eq!(merged.conflict_list.len(), 3);
+        assert!(print_type_system(&merged.document).contains("  id: ID!\n"));
+
+        let merged = merge_schema(&document_list, ConflictPolicy::KeepLast).unwrap();
+        let printed = print_type_system(&merged.document);
+        assert!(printed.contains("  id: String\n"));
+        assert!(printed.contains("teamList(first: Int = 20)"));
+        assert!(printed.contains("scalar Team\n"));
+    }
+
+    #[test]
+    fn repeatable_directive() {
+        let document_list = parse(&[
+            r#"type User @key(fields: "id") @cache(maxAge: 10) {
+  name: String @deprecated(reason: "Use fullName.")
+}
+
+directive @key(fields: String!) repeatable on OBJECT
+
+directive @cache(maxAge: Int!) on OBJECT
+"#,
+            r#"type User @key(fields: "email") @cache(maxAge: 20) @key(fields: "id") {
+  name: String @deprecated(reason: "Use displayName.")
+}
+"#,
+        ]);
+        let message_list: Vec<String> = merge_schema(&document_list, ConflictPolicy::Error)
+            .unwrap_err()
+            .iter()
+            .map(ToString::to_string)
+            .collect();
+        assert_eq!(
+            message_list,
+            vec![
+                "Directive \"@cache\" is applied to \"User\" with arguments (maxAge: 10) and (maxAge: 20). (document 1 at 1:33)",
+                "Directive \"@deprecated\" is applied to \"User.name\" with arguments (reason: \"Use fullName.\") and (reason: \"Use displayName.\"). (document 1 at 2:16)",
+            ]
+        );
+
+        let merged = merge_schema(&document_list, ConflictPolicy::KeepLast).unwrap();
+        assert_eq!(
+            print_type_system(&merged.document),
+            r#"type User @key(fields: "id") @cache(maxAge: 20) @key(fields: "email") {
+  name: String @deprecated(reason: "Use displayName.")
+}
+
+directive @key(fields: String!) repeatable on OBJECT
+
+directive @cache(maxAge: Int!) on OBJECT
+"#
+        );
+
+        let merged = merge_schema(&document_list, ConflictPolicy::KeepFirst).unwrap();
+        assert_eq!(merged.conflict_list.len(), 2);
+        assert_eq!(
+            print_type_system(&merged.document),
+            r#"type User @key(fields: "id") @cache(maxAge: 10) @key(fields: "email") {
+  name: String @deprecated(reason: "Use fullName.")
+}
+
+directive @key(fields: String!) repeatable on OBJECT
+
+directive @cache(maxAge: Int!) on OBJECT
+"#
+        );
+    }
+}
@@ -0,0 +1,671 @@
+use crate::{LintConfig, LintFix, LintRule, LintViolation};
+use graphql_executor::{MetaType, Schema};
+use graphql_parser::*;
+use std::collections::{BTreeMap, BTreeSet};
+
+/// Checks the operations and fragments of `document` against the rules enabled in
+/// `config`, and lists the violations in the order they appear in the document.
+///
+/// The `deprecated-field` and `require-id` rules need to know the types selections are
+/// made on, so they are only checked when a schema is given.
+pub fn lint_operations(
+    document: &ExecutableDocument,
+    schema: Option<&Schema>,
+    config: &LintConfig,
+) -> Vec<LintViolation> {
+    lint_operation_set(&[document], schema, config)
+        .pop()
+        .unwrap_or_default()
+}
+
+/// Checks documents that share their fragments, such as the files of a client where
+/// fragments live apart from the operations spreading them, and lists the violations of
+/// each document in the order of `document_list`.
+///
+/// A fragment is used when an operation of any document spreads it. The `unused-fragment`
+/// rule is skipped when some spread names a fragment none of the documents defines, since
+/// the set is then incomplete.
+pub fn lint_operation_set(
+    document_list: &[&ExecutableDocument],
+    schema: Option<&Schema>,
+    config: &LintConfig,
+) -> Vec<Vec<LintViolation>> {
+    let mut fragment_map = BTreeMap::new();
+    for document in document_list.iter() {
+        for definition in document.definition_list.iter() {
+            if let ExecutableDefinition::Fragment(fragment) = &definition.node {
+                fragment_map
+                    .entry(fragment.node.name.node.as_str())
+                    .or_insert(&fragment.node);
+            }
+        }
+    }
+    let mut linter = OperationLinter {
+        config,
+        schema,
+        fragment_map,
+        violation_list: vec![],
+    };
+
+    let mut used_fragment_set = BTreeSet::new();
+    let mut is_complete = true;
+    for document in document_list.iter() {
+        for definition in document.definition_list.iter() {
+            let selection_list = match &definition.node {
+                ExecutableDefinition::Operation(operation) => {
+                    linter.collect_fragment_set(
+                        &operation.node.selection_list,
+                        &mut used_fragment_set,
+                    );
+                    &operation.node.selection_list
+                }
+                ExecutableDefinition::Fragment(fragment) => &fragment.node.selection_list,
+            };
+            is_complete &= !linter.has_missing_fragment(selection_list);
+        }
+    }
+
+    let mut violation_list_list = vec![];
+    for document in document_list.iter() {
+        let operation_count = document
+            .definition_list
+            .iter()
+            .filter(|definition| matches!(definition.node, ExecutableDefinition::Operation(_)))
+            .count();
+        for definition in document.definition_list.iter() {
+            match &definition.node {
+                ExecutableDefinition::Operation(operation) => {
+                    linter.lint_operation(operation, operation_count)
+                }
+                ExecutableDefinition::Fragment(fragment) => linter.lint_fragment(&fragment.node),
+            }
+        }
+        if config.is_enabled(LintRule::UnusedFragment) && is_complete {
+            linter.lint_unused_fragment_list(document, &used_fragment_set);
+        }
+
+        let mut violation_list = std::mem::take(&mut linter.violation_list);
+        violation_list.sort_by_key(|violation| violation.position.offset);
+        violation_list_list.push(violation_list);
+    }
+    violation_list_list
+}
+
+struct OperationLinter<'a> {
+    config: &'a LintConfig,
+    schema: Option<&'a Schema>,
+    fragment_map: BTreeMap<&'a str, &'a FragmentDefinition>,
+    violation_list: Vec<LintViolation>,
+}
+
+impl<'a> OperationLinter<'a> {
+    fn push(
+        &mut self,
+        rule: LintRule,
+        path: &str,
+        message: String,
+        position: &Position,
+        fix_list: Vec<LintFix>,
+    ) {
+        if self.config.is_enabled(rule) {
+            self.violation_list.push(LintViolation {
+                rule,
+                path: path.to_owned(),
+                message,
+                position: position.clone(),
+                fix_list,
+            });
+        }
+    }
+
+    fn lint_operation(&mut self, operation: &'a Positioned<OperationDefinition>, count: usize) {
+        let path = match &operation.node.name {
+            Some(name) => name.node.clone(),
+            None => operation_keyword(&operation.node.ty).to_owned(),
+        };
+        if operation.node.name.is_none() {
+            self.push(
+                LintRule::OperationName,
+                &path,
+                "Operation should be named.".to_owned(),
+                &operation.start,
+                vec![],
+            );
+            if count > 1 {
+                self.push(
+                    LintRule::LoneAnonymousOperation,
+                    &path,
+                    "Anonymous operation must be the only operation in the document.".to_owned(),
+                    &operation.start,
+                    vec![],
+                );
+            }
+        }
+
+        if self.config.is_enabled(LintRule::UnusedVariable) {
+            self.lint_unused_variable_list(&path, &operation.node);
+        }
+        if let Some(max_depth) = self.config.max_depth {
+            let mut stack = vec![];
+            if let Some(field) =
+                self.deep_field(&operation.node.selection_list, 1, max_depth, &mut stack)
+            {
+                let message = match &operation.node.name {
+                    Some(name) => format!(
+                        "Operation \"{}\" is deeper than the maximum depth of {}.",
+                        name.node, max_depth
+                    ),
+                    None => format!(
+                        "Anonymous operation is deeper than the maximum depth of {}.",
+                        max_depth
+                    ),
+                };
+                self.push(
+                    LintRule::MaxDepth,
+                    &path,
+                    message,
+                    &field.name.start,
+                    vec![],
+                );
+            }
+        }
+
+        let root_type = self
+            .schema
+            .and_then(|schema| schema.root_type(&operation.node.ty));
+        if let Some(root_type) = root_type {
+            self.lint_selection_list(
+                &path,
+                root_type,
+                Some(&operation.start),
+                &operation.node.selection_list,
+            );
+        }
+    }
+
+    fn lint_fragment(&mut self, fragment: &'a FragmentDefinition) {
+        let path = fragment.name.node.clone();
+        if let Some(pattern) = &self.config.fragment_name_pattern {
+            let pattern = pattern.replace("{type}", &fragment.on.node);
+            if !matches_pattern(&pattern, &fragment.name.node) {
+                let message = format!(
+                    "Fragment \"{}\" should match the pattern \"{}\".",
+                    fragment.name.node, pattern
+                );
+                self.push(
+                    LintRule::FragmentNamePattern,
+                    &path,
+                    message,
+                    &fragment.name.start,
+                    vec![],
+                );
+            }
+        }
+
+        let ty = self
+            .schema
+            .and_then(|schema| schema.get_type(&fragment.on.node));
+        if let Some(ty) = ty {
+            self.lint_selection_list(
+                &path,
+                ty,
+                Some(&fragment.name.start),
+                &fragment.selection_list,
+            );
+        }
+    }
+
+    /// Checks the fields selected on `ty`. Fragment spreads are not followed, since every
+    /// fragment is checked on its own.
+    ///
+    /// `parent` is where the selection set is opened, and is `None` for inline fragments,
+    /// whose `id` counts towards the selection set they are part of.
+    fn lint_selection_list(
+        &mut self,
+        path: &str,
+        ty: &'a MetaType,
+        parent: Option<&Position>,
+        selection_list: &'a [Positioned<Selection>],
+    ) {
+        let is_missing_id =
+            ty.field("id").is_some() && !self.selects_id(selection_list, &mut vec![]);
+        if let Some(parent) = parent.filter(|_| is_missing_id) {
+            let fix_list = match selection_list.first() {
+                Some(first) if first.start.line == parent.line => vec![LintFix {
+                    start: first.start.clone(),
+                    end: first.start.clone(),
+                    text: "id ".to_owned(),
+                }],
+                Some(first) => vec![LintFix {
+                    start: first.start.clone(),
+                    end: first.start.clone(),
+                    text: format!("id\n{}", " ".repeat(first.start.column - 1)),
+                }],
+                None => vec![],
+            };
+            let message = format!("Selection on type \"{}\" should include \"id\".", ty.name);
+            self.push(LintRule::RequireId, path, message, parent, fix_list);
+        }
+
+        for selection in selection_list.iter() {
+            match &selection.node {
+                Selection::Field(field) => {
+                    let path = format!("{}.{}", path, field.name.node);
+                    let meta_field = match ty.field(&field.name.node) {
+                        Some(meta_field) => meta_field,
+                        None => continue,
+                    };
+                    if let Some(reason) = &meta_field.deprecation {
+                        let message = format!(
+                            "Field \"{}.{}\" is deprecated: {}",
+                            ty.name, meta_field.name, reason
+                        );
+                        self.push(
+                            LintRule::DeprecatedField,
+                            &path,
+                            message,
+                            &field.name.start,
+                            vec![],
+                        );
+                    }
+                    let child = self
+                        .schema
+                        .and_then(|schema| schema.get_type(meta_field.ty.name()));
+                    if let (Some(child), false) = (child, field.selection_list.is_empty()) {
+                        self.lint_selection_list(
+                            &path,
+                            child,
+                            Some(&field.name.start),
+                            &field.selection_list,
+                        );
+                    }
+                }
+                Selection::FragmentSpread(_) => {}
+                Selection::InlineFragment(fragment) => {
+                    let ty = match &fragment.on {
+                        Some(on) => {
+                            match self.schema.and_then(|schema| schema.get_type(&on.node)) {
+                                Some(ty) => ty,
+                                None => continue,
+                            }
+                        }
+                        None => ty,
+                    };
+                    self.lint_selection_list(path, ty, None, &fragment.selection_list);
+                }
+            }
+        }
+    }
+
+    fn selects_id(
+        &self,
+        selection_list: &'a [Positioned<Selection>],
+        stack: &mut Vec<&'a str>,
+    ) -> bool {
+        selection_list
+            .iter()
+            .any(|selection| match &selection.node {
+                Selection::Field(field) => field.name.node == "id",
+                Selection::FragmentSpread(spread) => {
+                    let name = spread.name.node.as_str();
+                    match self.fragment_map.get(name) {
+                        Some(fragment) if !stack.contains(&name) => {
+                            stack.push(name);
+                            let selects_id = self.selects_id(&fragment.selection_list, stack);
+                            stack.pop();
+                            selects_id
+                        }
+                        Some(_) => false,
+                        // A fragment defined elsewhere may well select it.
+                        None => true,
+                    }
+                }
+                Selection::InlineFragment(fragment) => {
+                    self.selects_id(&fragment.selection_list, stack)
+                }
+            })
+    }
+
+    /// The first field nested deeper than `max_depth`, with fragments expanded.
+    fn deep_field(
+        &self,
+        selection_list: &'a [Positioned<Selection>],
+        depth: usize,
+        max_depth: usize,
+        stack: &mut Vec<&'a str>,
+    ) -> Option<&'a Field> {
+        for selection in selection_list.iter() {
+            let field = match &selection.node {
+                Selection::Field(field) if depth > max_depth => Some(field),
+                Selection::Field(field) => {
+                    self.deep_field(&field.selection_list, depth + 1, max_depth, stack)
+                }
+                Selection::FragmentSpread(spread) => {
+                    let name = spread.name.node.as_str();
+                    match self.fragment_map.get(name) {
+                        Some(fragment) if !stack.contains(&name) => {
+                            stack.push(name);
+                            let field =
+                                self.deep_field(&fragment.selection_list, depth, max_depth, stack);
+                            stack.pop();
+                            field
+                        }
+                        _ => None,
+                    }
+                }
+                Selection::InlineFragment(fragment) => {
+                    self.deep_field(&fragment.selection_list, depth, max_depth, stack)
+                }
+            };
+            if field.is_some() {
+                return field;
+            }
+        }
+        None
+    }
+
+    fn lint_unused_variable_list(&mut self, path: &str, operation: &'a OperationDefinition) {
+        let mut used_set = BTreeSet::new();
+        collect_directive_variable_set(&operation.directive_list, &mut used_set);
+        let mut fragment_set = BTreeSet::new();
+        self.collect_fragment_set(&operation.selection_list, &mut fragment_set);
+        collect_variable_set(&operation.selection_list, &mut used_set);
+        for name in fragment_set.iter() {
+            if let Some(fragment) = self.fragment_map.get(name) {
+                collect_directive_variable_set(&fragment.directive_list, &mut used_set);
+                collect_variable_set(&fragment.selection_list, &mut used_set);
+            }
+        }
+
+        for variable in operation.variable_list.iter() {
+            let name = &variable.node.name.node;
+            if used_set.contains(name.as_str()) {
+                continue;
+            }
+            let message = match &operation.name {
+                Some(operation) => format!(
+                    "Variable \"${}\" is never used in operation \"{}\".",
+                    name, operation.node
+                ),
+                None => format!(
+                    "Variable \"${}\" is never used in the anonymous operation.",
+                    name
+                ),
+            };
+            self.push(
+                LintRule::UnusedVariable,
+                path,
+                message,
+                &variable.start,
+                vec![],
+            );
+        }
+    }
+
+    fn has_missing_fragment(&self, selection_list: &[Positioned<Selection>]) -> bool {
+        selection_list
+            .iter()
+            .any(|selection| match &selection.node {
+                Selection::Field(field) => self.has_missing_fragment(&field.selection_list),
+                Selection::FragmentSpread(spread) => {
+                    !self.fragment_map.contains_key(spread.name.node.as_str())
+                }
+                Selection::InlineFragment(fragment) => {
+                    self.has_missing_fragment(&fragment.selection_list)
+                }
+            })
+    }
+
+    /// Adds the fragments `selection_list` spreads, directly or through other fragments.
+    fn collect_fragment_set(
+        &self,
+        selection_list: &'a [Positioned<Selection>],
+        fragment_set: &mut BTreeSet<&'a str>,
+    ) {
+        for selection in selection_list.iter() {
+            match &selection.node {
+                Selection::Field(field) => {
+                    self.collect_fragment_set(&field.selection_list, fragment_set)
+                }
+                Selection::FragmentSpread(spread) => {
+                    let name = spread.name.node.as_str();
+                    if let Some(fragment) = self.fragment_map.get(name) {
+                        if fragment_set.insert(name) {
+                            self.collect_fragment_set(&fragment.selection_list, fragment_set);
+                        }
+                    }
+                }
+                Selection::InlineFragment(fragment) => {
+                    self.collect_fragment_set(&fragment.selection_list, fragment_set)
+                }
+            }
+        }
+    }
+
+    /// Reports the fragments no operation spreads, with a fix deleting them up to the next
+    /// definition.
+    fn lint_unused_fragment_list(
+        &mut self,
+        document: &ExecutableDocument,
+        used_set: &BTreeSet<&str>,
+    ) {
+        let definition_list = &document.definition_list;
+        for (index, definition) in definition_list.iter().enumerate() {
+            let fragment = match &definition.node {
+                ExecutableDefinition::Fragment(fragment) => &fragment.node,
+                ExecutableDefinition::Operation(_) => continue,
+            };
+            if used_set.contains(fragment.name.node.as_str()) {
+                continue;
+            }
+            let fix = match (definition_list.get(index + 1), index.checked_sub(1)) {
+                (Some(next), _) => LintFix {
+                    start: definition.start.clone(),
+                    end: next.start.clone(),
+                    text: String::new(),
+                },
+                (None, Some(previous)) => LintFix {
+                    start: definition_list[previous].end.clone(),
+                    end: definition.end.clone(),
+                    text: String::new(),
+                },
+                (None, None) => LintFix {
+                    start: definition.start.clone(),
+                    end: definition.end.clone(),
+                    text: String::new(),
+                },
+            };
+            let message = format!("Fragment \"{}\" is never used.", fragment.name.node);
+            self.push(
+                LintRule::UnusedFragment,
+                &fragment.name.node,
+                message,
+                &fragment.name.start,
+                vec![fix],
+            );
+        }
+    }
+}
+
+fn operation_keyword(ty: &OperationType) -> &'static str {
+    match ty {
+        OperationType::Query => "query",
+        OperationType::Mutation => "mutation",
+        OperationType::Subscription => "subscription",
+    }
+}
+
+fn collect_variable_set<'a>(
+    selection_list: &'a [Positioned<Selection>],
+    variable_set: &mut BTreeSet<&'a str>,
+) {
+    for selection in selection_list.iter() {
+        match &selection.node {
+            Selection::Field(field) => {
+                for argument in field.argument_list.iter() {
+                    collect_value_variable_set(&argument.node.value.node, variable_set);
+                }
+                collect_directive_variable_set(&field.directive_list, variable_set);
+                collect_variable_set(&field.selection_list, variable_set);
+            }
+            Selection::FragmentSpread(spread) => {
+                collect_directive_variable_set(&spread.directive_list, variable_set)
+            }
+            Selection::InlineFragment(fragment) => {
+                collect_directive_variable_set(&fragment.directive_list, variable_set);
+                collect_variable_set(&fragment.selection_list, variable_set);
+            }
+        }
+    }
+}
+
+fn collect_directive_variable_set<'a>(
+    directive_list: &'a [Positioned<Directive>],
+    variable_set: &mut BTreeSet<&'a str>,
+) {
+    for directive in directive_list.iter() {
+        for argument in directive.node.argument_list.iter() {
+            collect_value_variable_set(&argument.node.value.node, variable_set);
+        }
+    }
+}
+
+fn collect_value_variable_set<'a>(value: &'a Value, variable_set: &mut BTreeSet<&'a str>) {
+    match value {
+        Value::Variable(name) => {
+            variable_set.insert(name);
+        }
+        Value::List(value_list) => {
+            for value in value_list.iter() {
+                collect_value_variable_set(value, variable_set);
+            }
+        }
+        Value::Object(value_map) => {
+            for value in value_map.values() {
+                collect_value_variable_set(value, variable_set);
+            }
+        }
+        _ => {}
+    }
+}
+
+/// Whether `name` matches `pattern`, where `*` matches any text.
+fn matches_pattern(pattern: &str, name: &str) -> bool {
+    match pattern.find('*') {
+        None => pattern == name,
+        Some(index) => {
+            let (prefix, rest) = (&pattern[..index], &pattern[index + 1..]);
+            match name.strip_prefix(prefix) {
+                Some(name) => (0..=name.len())
+                    .filter(|start| name.is_char_boundary(*start))
+                    .any(|start| matches_pattern(rest, &name[start..])),
+                None => false,
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::apply_lint_fixes;
+
+    const SCHEMA: &str = r#"
+type Query {
+  user(id: ID!): User
+  viewer: User
+}
+
+type User {
+  id: ID!
+  name: String
+  nickname: String @deprecated(reason: "Use name.")
+  friend_list: [User!]!
+}
+"#;
+
+    #[test]
+    fn lint() {
+        let schema = Schema::build(&parse_type_system(SCHEMA).unwrap()).unwrap();
+        let source = r#"{ viewer { id } }
+
+query Profile($id: ID!, $unused: Int) {
+  user(id: $id) {
+    ...userFields
+    friend_list { friend_list { id } }
+  }
+}
+
+fragment userFields on User {
+  nickname
+}
+
+fragment Orphan on User { id }
+"#;
+        let document = parse_executable(source).unwrap();
+        let config = LintConfig::default()
+            .with_fragment_name_pattern("{type}*")
+            .with_max_depth(3);
+        let violation_list = lint_operations(&document, Some(&schema), &config);
+        let line_list: Vec<String> = violation_list
+            .iter()
+            .map(|violation| violation.to_string())
+            .collect();
+        assert_eq!(
+            line_list,
+            vec![
+                "1:1: Operation should be named. (operation-name)",
+                "1:1: Anonymous operation must be the only operation in the document. (lone-anonymous-operation)",
+                "3:25: Variable \"$unused\" is never used in operation \"Profile\". (unused-variable)",
+                "4:3: Selection on type \"User\" should include \"id\". (require-id)",
+                "6:5: Selection on type \"User\" should include \"id\". (require-id)",
+                "6:33: Operation \"Profile\" is deeper than the maximum depth of 3. (max-depth)",
+                "10:10: Fragment \"userFields\" should match the pattern \"User*\". (fragment-name-pattern)",
+                "10:10: Selection on type \"User\" should include \"id\". (require-id)",
+                "11:3: Field \"User.nickname\" is deprecated: Use name. (deprecated-field)",
+                "14:10: Fragment \"Orphan\" should match the pattern \"User*\". (fragment-name-pattern)",
+                "14:10: Fragment \"Orphan\" is never used. (unused-fragment)",
+            ]
+        );
+        assert_eq!(violation_list[4].path, "Profile.user.friend_list");
+
+        let fixed = apply_lint_fixes(source, &violation_list);
+        assert!(fixed.contains("user(id: $id) {\n    id\n    ...userFields"));
+        assert!(fixed.contains("friend_list { id friend_list { id } }"));
+        assert!(fixed.contains("fragment userFields on User {\n  id\n  nickname\n}"));
+        assert!(!fixed.contains("Orphan"));
+
+        // `id` selected through a fragment counts, and the schema rules need a schema.
+        let source = "query Viewer { viewer { ...UserId name } }\nfragment UserId on User { id }";
+        let document = parse_executable(source).unwrap();
+        assert!(lint_operations(&document, Some(&schema), &LintConfig::default()).is_empty());
+        let document = parse_executable("query A { user(id: 1) { nickname } }").unwrap();
+        assert!(lint_operations(&document, None, &LintConfig::default()).is_empty());
+    }
+
+    #[test]
+    fn operation_set() {
+        let schema = Schema::build(&parse_type_system(SCHEMA).unwrap()).unwrap();
+        let config = LintConfig::default();
+        let operations = parse_executable("query Viewer { viewer { ...UserId name } }").unwrap();
+        let fragments = parse_executable("fragment UserId on User { id }").unwrap();
+        let violation_list_list =
+            lint_operation_set(&[&operations, &fragments], Some(&schema), &config);
+        assert_eq!(violation_list_list, vec![vec![], vec![]]);
+
+        // Alone, neither document knows enough to report the fragment or the missing `id`.
+        assert!(lint_operations(&operations, Some(&schema), &config).is_empty());
+        let violation_list = lint_operations(&fragments, Some(&schema), &config);
+        assert_eq!(violation_list.len(), 1);
+        assert_eq!(violation_list[0].rule, LintRule::UnusedFragment);
+    }
+
+    #[test]
+    fn pattern() {
+        assert!(matches_pattern("UserFields", "UserFields"));
+        assert!(matches_pattern("User*", "UserFields"));
+        assert!(matches_pattern("*_*Fragment", "Profile_userFragment"));
+        assert!(!matches_pattern("*Fragment", "UserFields"));
+        assert!(!matches_pattern("User", "UserFields"));
+    }
+}
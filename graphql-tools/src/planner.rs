@@ -0,0 +1,1881 @@
+use crate::*;
+use futures::future::{join_all, BoxFuture, FutureExt};
+use graphql_executor::*;
+use graphql_parser::*;
+use serde_json::{Map, Value as JsonValue};
+use std::collections::HashMap;
+use std::fmt::Write;
+use std::sync::{Arc, Mutex};
+
+const INDENT: &str = "  ";
+
+/// How to fetch the fields of an operation from the subgraphs of a supergraph.
+#[derive(Debug, Clone, PartialEq)]
+pub struct QueryPlan {
+    /// Missing when the operation only selects `__typename` on its root type.
+    pub node: Option<PlanNode>,
+    root_type: String,
+    selection_list: Vec<Item>,
+    /// The default values of the operation's variables, to evaluate fragment conditions.
+    default_variables: Variables,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum PlanNode {
+    Fetch(FetchNode),
+    /// Runs its nodes one after the other.
+    Sequence(Vec<PlanNode>),
+    /// Runs its nodes concurrently.
+    Parallel(Vec<PlanNode>),
+    /// Runs its node against the objects found at `path` in the response.
+    Flatten(FlattenNode),
+}
+
+/// One request sent to a subgraph.
+#[derive(Debug, Clone, PartialEq)]
+pub struct FetchNode {
+    pub service_name: String,
+    /// The fields of each object sent as an `_entities` representation, as a selection
+    /// set with one inline fragment per entity type. Missing for root fetches.
+    pub requires: Option<String>,
+    /// The variables of the client operation the fetch forwards.
+    pub variable_usage_list: Vec<String>,
+    pub operation: String,
+}
+
+/// `path` lists response keys, where `@` stands for every element of a list.
+#[derive(Debug, Clone, PartialEq)]
+pub struct FlattenNode {
+    pub path: Vec<String>,
+    pub node: Box<PlanNode>,
+}
+
+impl std::fmt::Display for QueryPlan {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let mut output = String::from("QueryPlan {\n");
+        if let Some(node) = &self.node {
+            print_node(&mut output, node, INDENT);
+        }
+        output.push('}');
+        f.write_str(&output)
+    }
+}
+
+fn print_node(output: &mut String, node: &PlanNode, indent: &str) {
+    let inner = format!("{}{}", indent, INDENT);
+    match node {
+        PlanNode::Fetch(fetch) => {
+            writeln!(
+                output,
+                "{}Fetch(service: \"{}\") {{",
+                indent, fetch.service_name
+            )
+            .unwrap();
+            if let Some(requires) = &fetch.requires {
+                print_block(
+                    output,
+                    &format!("{{\n{}}} =>", indent_text(requires, INDENT)),
+                    &inner,
+                );
+            }
+            print_block(output, &fetch.operation, &inner);
+        }
+        PlanNode::Sequence(node_list) | PlanNode::Parallel(node_list) => {
+            let name = match node {
+                PlanNode::Sequence(_) => "Sequence",
+                _ => "Parallel",
+            };
+            writeln!(output, "{}{} {{", indent, name).unwrap();
+            for node in node_list.iter() {
+                print_node(output, node, &inner);
+            }
+        }
+        PlanNode::Flatten(flatten) => {
+            writeln!(
+                output,
+                "{}Flatten(path: \"{}\") {{",
+                indent,
+                flatten.path.join(".")
+            )
+            .unwrap();
+            print_node(output, &flatten.node, &inner);
+        }
+    }
+    writeln!(output, "{}}},", indent).unwrap();
+}
+
+fn print_block(output: &mut String, text: &str, indent: &str) {
+    for line in text.lines() {
+        writeln!(output, "{}{}", indent, line).unwrap();
+    }
+}
+
+fn indent_text(text: &str, indent: &str) -> String {
+    text.lines()
+        .map(|line| format!("{}{}\n", indent, line))
+        .collect()
+}
+
+/// A field or inline fragment of an operation, after fragment spreads are inlined.
+///
+/// A fragment keeps its `@skip` and `@include` directives, and has no type condition when
+/// only those make it a fragment.
+#[derive(Debug, Clone, PartialEq)]
+enum Item {
+    Field(ItemField),
+    Fragment(Option<String>, Vec<Directive>, Vec<Item>),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+struct ItemField {
+    alias: Option<String>,
+    name: String,
+    argument_list: Vec<(String, Value)>,
+    directive_list: Vec<Directive>,
+    selection_list: Vec<Item>,
+}
+
+impl ItemField {
+    fn new(name: &str) -> Self {
+        ItemField {
+            alias: None,
+            name: name.to_owned(),
+            argument_list: vec![],
+            directive_list: vec![],
+            selection_list: vec![],
+        }
+    }
+
+    fn response_key(&self) -> &str {
+        self.alias.as_deref().unwrap_or(&self.name)
+    }
+}
+
+fn print_item_list(output: &mut String, item_list: &[Item], indent: &str) {
+    let inner = format!("{}{}", indent, INDENT);
+    for item in item_list.iter() {
+        match item {
+            Item::Field(field) => {
+                output.push_str(indent);
+                if let Some(alias) = &field.alias {
+                    write!(output, "{}: ", alias).unwrap();
+                }
+                output.push_str(&field.name);
+                print_argument_list(output, &field.argument_list);
+                print_directive_list(output, &field.directive_list);
+                if field.selection_list.is_empty() {
+                    output.push('\n');
+                } else {
+                    output.push_str(" {\n");
+                    print_item_list(output, &field.selection_list, &inner);
+                    writeln!(output, "{}}}", indent).unwrap();
+                }
+            }
+            Item::Fragment(on, directive_list, selection_list) => {
+                write!(output, "{}...", indent).unwrap();
+                if let Some(on) = on {
+                    write!(output, " on {}", on).unwrap();
+                }
+                print_directive_list(output, directive_list);
+                output.push_str(" {\n");
+                print_item_list(output, selection_list, &inner);
+                writeln!(output, "{}}}", indent).unwrap();
+            }
+        }
+    }
+}
+
+fn print_directive_list(output: &mut String, directive_list: &[Directive]) {
+    for directive in directive_list.iter() {
+        write!(output, " @{}", directive.name.node).unwrap();
+        let argument_list: Vec<(String, Value)> = directive
+            .argument_list
+            .iter()
+            .map(|argument| {
+                (
+                    argument.node.name.node.clone(),
+                    argument.node.value.node.clone(),
+                )
+            })
+            .collect();
+        print_argument_list(output, &argument_list);
+    }
+}
+
+fn print_argument_list(output: &mut String, argument_list: &[(String, Value)]) {
+    if argument_list.is_empty() {
+        return;
+    }
+    let argument_list: Vec<String> = argument_list
+        .iter()
+        .map(|(name, value)| format!("{}: {}", name, value))
+        .collect();
+    write!(output, "({})", argument_list.join(", ")).unwrap();
+}
+
+/// Adds `item` to `item_list`, merging it with a field of the same response key.
+fn merge_item(item_list: &mut Vec<Item>, item: Item) {
+    match item {
+        Item::Field(field) => {
+            let existing = item_list.iter_mut().find_map(|existing| match existing {
+                Item::Field(existing) if existing.response_key() == field.response_key() => {
+                    Some(existing)
+                }
+                _ => None,
+            });
+            match existing {
+                Some(existing) => {
+                    for item in field.selection_list {
+                        merge_item(&mut existing.selection_list, item);
+                    }
+                }
+                None => item_list.push(Item::Field(field)),
+            }
+        }
+        Item::Fragment(on, directive_list, selection_list) => {
+            let existing = item_list.iter_mut().find_map(|existing| match existing {
+                Item::Fragment(existing_on, existing_directive_list, existing)
+                    if *existing_on == on && *existing_directive_list == directive_list =>
+                {
+                    Some(existing)
+                }
+                _ => None,
+            });
+            match existing {
+                Some(existing) => {
+                    for item in selection_list {
+                        merge_item(existing, item);
+                    }
+                }
+                None => item_list.push(Item::Fragment(on, directive_list, selection_list)),
+            }
+        }
+    }
+}
+
+/// A subgraph able to resolve a field, read from its `@join__field`.
+#[derive(Debug, Clone)]
+struct FieldSource {
+    service_name: String,
+    requires: Option<String>,
+}
+
+/// What the `@join__*` directives of a supergraph say about each type and field.
+struct JoinIndex {
+    /// For each type, the subgraphs defining it with their resolvable keys.
+    type_map: HashMap<String, Vec<(String, Vec<String>)>>,
+    /// For each field with a `@join__field`, the subgraphs resolving it.
+    field_map: HashMap<(String, String), Vec<FieldSource>>,
+}
+
+impl JoinIndex {
+    fn new(document: &TypeSystemDocument) -> Self {
+        let mut service_map = HashMap::new();
+        let mut type_map: HashMap<String, Vec<(String, Vec<String>)>> = HashMap::new();
+        let mut field_map = HashMap::new();
+
+        for definition in document.definition_list.iter() {
+            if let TypeSystemDefinition::Type(ty) = &definition.node {
+                if let TypeDefinition::Enum(ty) = &ty.node {
+                    if ty.node.name.node == "join__Graph" {
+                        for member in ty.node.member_list.iter() {
+                            if let Some(Value::String(name)) =
+                                find_directive(&member.node.directive_list, "join__graph")
+                                    .and_then(|directive| argument(directive, "name"))
+                            {
+                                service_map.insert(member.node.name.node.clone(), name.clone());
+                            }
+                        }
+                    }
+                }
+            }
+        }
+        let service_name = |directive: &Directive| match argument(directive, "graph") {
+            Some(Value::Enum(graph)) => service_map.get(graph).cloned(),
+            _ => None,
+        };
+
+        for definition in document.definition_list.iter() {
+            let ty = match &definition.node {
+                TypeSystemDefinition::Type(ty) => &ty.node,
+                _ => continue,
+            };
+            let parts = type_parts(ty);
+            let owner_list: &mut Vec<(String, Vec<String>)> =
+                type_map.entry(parts.name.to_owned()).or_default();
+            for directive in parts.directive_list.iter() {
+                let directive = &directive.node;
+                if directive.name.node != "join__type" {
+                    continue;
+                }
+                let service_name = match service_name(directive) {
+                    Some(service_name) => service_name,
+                    None => continue,
+                };
+                let index = match owner_list
+                    .iter()
+                    .position(|(name, _)| *name == service_name)
+                {
+                    Some(index) => index,
+                    None => {
+                        owner_list.push((service_name, vec![]));
+                        owner_list.len() - 1
+                    }
+                };
+                let resolvable = !matches!(
+                    argument(directive, "resolvable"),
+                    Some(Value::Boolean(false))
+                );
+                if let (Some(Value::String(key)), true) = (argument(directive, "key"), resolvable) {
+                    owner_list[index].1.push(key.clone());
+                }
+            }
+
+            for field in parts.field_list.iter() {
+                let source_list: Vec<FieldSource> = field
+                    .node
+                    .directive_list
+                    .iter()
+                    .map(|directive| &directive.node)
+                    .filter(|directive| directive.name.node == "join__field")
+                    .filter(|directive| {
+                        !matches!(argument(directive, "external"), Some(Value::Boolean(true)))
+                    })
+                    .filter_map(|directive| {
+                        Some(FieldSource {
+                            service_name: service_name(directive)?,
+                            requires: match argument(directive, "requires") {
+                                Some(Value::String(requires)) => Some(requires.clone()),
+                                _ => None,
+                            },
+                        })
+                    })
+                    .collect();
+                let has_join_field = has_directive(&field.node.directive_list, "join__field");
+                if has_join_field {
+                    field_map.insert(
+                        (parts.name.to_owned(), field.node.name.node.clone()),
+                        source_list,
+                    );
+                }
+            }
+        }
+
+        JoinIndex {
+            type_map,
+            field_map,
+        }
+    }
+
+    fn source_list(&self, type_name: &str, field_name: &str) -> Vec<FieldSource> {
+        match self
+            .field_map
+            .get(&(type_name.to_owned(), field_name.to_owned()))
+        {
+            Some(source_list) => source_list.clone(),
+            None => self
+                .type_map
+                .get(type_name)
+                .into_iter()
+                .flatten()
+                .map(|(service_name, _)| FieldSource {
+                    service_name: service_name.clone(),
+                    requires: None,
+                })
+                .collect(),
+        }
+    }
+
+    fn key_list(&self, type_name: &str, service_name: &str) -> &[String] {
+        self.type_map
+            .get(type_name)
+            .and_then(|owner_list| owner_list.iter().find(|(name, _)| name == service_name))
+            .map(|(_, key_list)| key_list.as_slice())
+            .unwrap_or(&[])
+    }
+}
+
+/// Plans operations against a supergraph.
+pub struct QueryPlanner {
+    schema: Schema,
+    join: JoinIndex,
+}
+
+/// Fields a fetch leaves to another subgraph, resolved through `_entities` once the fetch
+/// has returned the keys of their parent objects.
+struct EntityFetch {
+    service_name: String,
+    path: Vec<String>,
+    requires: Vec<Item>,
+    selection_list: Vec<Item>,
+}
+
+impl QueryPlanner {
+    pub fn new(supergraph: &Supergraph) -> Result<Self, SchemaError> {
+        Ok(QueryPlanner {
+            schema: Schema::build(&supergraph.document)?,
+            join: JoinIndex::new(&supergraph.document),
+        })
+    }
+
+    /// The schema of the composed graph, against which client operations are validated.
+    pub fn schema(&self) -> &Schema {
+        &self.schema
+    }
+
+    pub fn plan(
+        &self,
+        document: &ExecutableDocument,
+        operation_name: Option<&str>,
+    ) -> Result<QueryPlan, Vec<ServerError>> {
+        let operation = get_operation(document, operation_name).map_err(|error| vec![error])?;
+        let error_list = validate(&self.schema, document);
+        if !error_list.is_empty() {
+            return Err(error_list);
+        }
+        let root_type = self.schema.root_type(&operation.ty).unwrap();
+        let selection_list = self.normalize(document, &root_type.name, &operation.selection_list);
+
+        let mut group_list: Vec<(String, Vec<Item>)> = vec![];
+        for item in selection_list.iter() {
+            let field = match item {
+                Item::Field(field) if field.name != "__typename" => field,
+                _ => continue,
+            };
+            let source_list = self.join.source_list(&root_type.name, &field.name);
+            let group = group_list.iter_mut().find(|(service_name, _)| {
+                operation.ty != OperationType::Mutation
+                    && source_list
+                        .iter()
+                        .any(|source| source.service_name == *service_name)
+            });
+            match (group, operation.ty == OperationType::Mutation) {
+                (Some((_, item_list)), false) => item_list.push(item.clone()),
+                _ => {
+                    let source = source_list.first().ok_or_else(|| {
+                        vec![ServerError::new(format!(
+                            "No subgraph can resolve field \"{}.{}\".",
+                            root_type.name, field.name
+                        ))]
+                    })?;
+                    group_list.push((source.service_name.clone(), vec![item.clone()]));
+                }
+            }
+        }
+
+        let mut node_list = vec![];
+        for (service_name, item_list) in group_list {
+            let mut fetch_selection_list = vec![];
+            let mut entity_fetch_list = vec![];
+            self.plan_item_list(
+                &service_name,
+                &root_type.name,
+                &item_list,
+                &[],
+                &mut fetch_selection_list,
+                &mut entity_fetch_list,
+            )
+            .map_err(|message| vec![ServerError::new(message)])?;
+            let operation_text = self.root_operation(operation, &fetch_selection_list);
+            let fetch = PlanNode::Fetch(FetchNode {
+                service_name,
+                requires: None,
+                variable_usage_list: variable_usage_list(&fetch_selection_list),
+                operation: operation_text,
+            });
+            node_list.push(self.with_entity_fetch_list(operation, fetch, entity_fetch_list)?);
+        }
+
+        let node = match node_list.len() {
+            0 => None,
+            1 => node_list.pop(),
+            _ if operation.ty == OperationType::Mutation => Some(PlanNode::Sequence(node_list)),
+            _ => Some(PlanNode::Parallel(node_list)),
+        };
+        let default_variables = operation
+            .variable_list
+            .iter()
+            .filter_map(|variable| {
+                let default_value = variable.node.default_value.as_ref()?;
+                Some((
+                    variable.node.name.node.clone(),
+                    value_to_json(&default_value.node),
+                ))
+            })
+            .collect();
+        Ok(QueryPlan {
+            node,
+            root_type: root_type.name.clone(),
+            selection_list,
+            default_variables,
+        })
+    }
+
+    /// Runs `fetch`, then the entity fetches depending on it.
+    fn with_entity_fetch_list(
+        &self,
+        operation: &OperationDefinition,
+        fetch: PlanNode,
+        entity_fetch_list: Vec<EntityFetch>,
+    ) -> Result<PlanNode, Vec<ServerError>> {
+        let mut dependent_list = vec![];
+        for entity_fetch in entity_fetch_list {
+            let mut fetch_selection_list = vec![];
+            let mut nested_list = vec![];
+            for item in entity_fetch.selection_list.iter() {
+                if let Item::Fragment(Some(on), _, selection_list) = item {
+                    let mut fragment_selection_list = vec![];
+                    self.plan_item_list(
+                        &entity_fetch.service_name,
+                        on,
+                        selection_list,
+                        &entity_fetch.path,
+                        &mut fragment_selection_list,
+                        &mut nested_list,
+                    )
+                    .map_err(|message| vec![ServerError::new(message)])?;
+                    merge_item(
+                        &mut fetch_selection_list,
+                        Item::Fragment(Some(on.clone()), vec![], fragment_selection_list),
+                    );
+                }
+            }
+
+            let mut requires = String::new();
+            print_item_list(&mut requires, &entity_fetch.requires, "");
+            let node = PlanNode::Flatten(FlattenNode {
+                path: entity_fetch.path.clone(),
+                node: Box::new(PlanNode::Fetch(FetchNode {
+                    service_name: entity_fetch.service_name.clone(),
+                    requires: Some(requires),
+                    variable_usage_list: variable_usage_list(&fetch_selection_list),
+                    operation: self.entity_operation(operation, &fetch_selection_list),
+                })),
+            });
+            dependent_list.push(self.with_entity_fetch_list(operation, node, nested_list)?);
+        }
+
+        let dependent = match dependent_list.len() {
+            0 => return Ok(fetch),
+            1 => dependent_list.pop().unwrap(),
+            _ => PlanNode::Parallel(dependent_list),
+        };
+        Ok(match dependent {
+            PlanNode::Sequence(node_list) => {
+                PlanNode::Sequence(std::iter::once(fetch).chain(node_list).collect())
+            }
+            dependent => PlanNode::Sequence(vec![fetch, dependent]),
+        })
+    }
+
+    /// Splits `item_list`, selected on `type_name` at `path`, into what `service_name`
+    /// resolves itself and the entity fetches for the rest.
+    fn plan_item_list(
+        &self,
+        service_name: &str,
+        type_name: &str,
+        item_list: &[Item],
+        path: &[String],
+        fetch_selection_list: &mut Vec<Item>,
+        entity_fetch_list: &mut Vec<EntityFetch>,
+    ) -> Result<(), String> {
+        for item in item_list.iter() {
+            let field = match item {
+                Item::Field(field) => field,
+                Item::Fragment(on, directive_list, selection_list) => {
+                    let mut fragment_selection_list = vec![];
+                    self.plan_item_list(
+                        service_name,
+                        on.as_deref().unwrap_or(type_name),
+                        selection_list,
+                        path,
+                        &mut fragment_selection_list,
+                        entity_fetch_list,
+                    )?;
+                    merge_item(
+                        fetch_selection_list,
+                        Item::Fragment(on.clone(), directive_list.clone(), fragment_selection_list),
+                    );
+                    continue;
+                }
+            };
+            if field.name == "__typename" {
+                merge_item(fetch_selection_list, item.clone());
+                continue;
+            }
+
+            let source_list = self.join.source_list(type_name, &field.name);
+            if source_list
+                .iter()
+                .any(|source| source.service_name == service_name)
+            {
+                let mut local = field.clone();
+                if !field.selection_list.is_empty() {
+                    let meta_field = self
+                        .schema
+                        .get_type(type_name)
+                        .and_then(|ty| ty.field(&field.name))
+                        .ok_or_else(|| {
+                            format!("Unknown field \"{}.{}\".", type_name, field.name)
+                        })?;
+                    let field_type = meta_field.ty.name();
+                    let mut field_path = path.to_vec();
+                    field_path.push(field.response_key().to_owned());
+                    for _ in 0..list_depth(&meta_field.ty) {
+                        field_path.push("@".to_owned());
+                    }
+                    local.selection_list = vec![];
+                    if self
+                        .schema
+                        .get_type(field_type)
+                        .is_some_and(MetaType::is_abstract)
+                    {
+                        local
+                            .selection_list
+                            .push(Item::Field(ItemField::new("__typename")));
+                    }
+                    self.plan_item_list(
+                        service_name,
+                        field_type,
+                        &field.selection_list,
+                        &field_path,
+                        &mut local.selection_list,
+                        entity_fetch_list,
+                    )?;
+                }
+                merge_item(fetch_selection_list, Item::Field(local));
+                continue;
+            }
+
+            let source = source_list.first().ok_or_else(|| {
+                format!(
+                    "No subgraph can resolve field \"{}.{}\".",
+                    type_name, field.name
+                )
+            })?;
+            let key = self
+                .join
+                .key_list(type_name, &source.service_name)
+                .iter()
+                .find(|key| self.can_resolve(service_name, type_name, key))
+                .ok_or_else(|| {
+                    format!(
+                        "Cannot resolve field \"{}.{}\": no key of \"{}\" in subgraph \"{}\" can be fetched from subgraph \"{}\".",
+                        type_name, field.name, type_name, source.service_name, service_name
+                    )
+                })?;
+
+            let mut requires = vec![Item::Field(ItemField::new("__typename"))];
+            for fields in std::iter::once(key).chain(source.requires.iter()) {
+                for item in self.parse_field_set(type_name, fields)? {
+                    merge_item(&mut requires, item);
+                }
+            }
+            for item in requires.iter() {
+                merge_item(fetch_selection_list, item.clone());
+            }
+
+            let existing = entity_fetch_list.iter_mut().find(|entity_fetch| {
+                entity_fetch.service_name == source.service_name && entity_fetch.path == path
+            });
+            let entity_fetch = match existing {
+                Some(entity_fetch) => entity_fetch,
+                None => {
+                    entity_fetch_list.push(EntityFetch {
+                        service_name: source.service_name.clone(),
+                        path: path.to_vec(),
+                        requires: vec![],
+                        selection_list: vec![],
+                    });
+                    entity_fetch_list.last_mut().unwrap()
+                }
+            };
+            merge_item(
+                &mut entity_fetch.requires,
+                Item::Fragment(Some(type_name.to_owned()), vec![], requires),
+            );
+            merge_item(
+                &mut entity_fetch.selection_list,
+                Item::Fragment(Some(type_name.to_owned()), vec![], vec![item.clone()]),
+            );
+        }
+        Ok(())
+    }
+
+    /// Whether `service_name` resolves the top-level fields of `key` on `type_name`.
+    fn can_resolve(&self, service_name: &str, type_name: &str, key: &str) -> bool {
+        parse_selection_list(key).is_ok_and(|selection_list| {
+            selection_list
+                .iter()
+                .all(|selection| match &selection.node {
+                    Selection::Field(field) => self
+                        .join
+                        .source_list(type_name, &field.name.node)
+                        .iter()
+                        .any(|source| source.service_name == service_name),
+                    _ => false,
+                })
+        })
+    }
+
+    fn parse_field_set(&self, type_name: &str, fields: &str) -> Result<Vec<Item>, String> {
+        let selection_list = parse_selection_list(fields).map_err(|_| {
+            format!(
+                "Invalid field set \"{}\" on type \"{}\".",
+                fields, type_name
+            )
+        })?;
+        Ok(self.normalize_selection_list(&HashMap::new(), type_name, &selection_list))
+    }
+
+    fn normalize(
+        &self,
+        document: &ExecutableDocument,
+        type_name: &str,
+        selection_list: &[Positioned<Selection>],
+    ) -> Vec<Item> {
+        let fragment_map = document
+            .definition_list
+            .iter()
+            .filter_map(|definition| match &definition.node {
+                ExecutableDefinition::Fragment(fragment) => {
+                    Some((fragment.node.name.node.as_str(), &fragment.node))
+                }
+                ExecutableDefinition::Operation(_) => None,
+            })
+            .collect();
+        self.normalize_selection_list(&fragment_map, type_name, selection_list)
+    }
+
+    /// Inlines fragment spreads, and drops the type conditions that always or never apply
+    /// to `type_name`, so that fragments only remain below abstract types or where `@skip`
+    /// or `@include` applies to them.
+    fn normalize_selection_list(
+        &self,
+        fragment_map: &HashMap<&str, &FragmentDefinition>,
+        type_name: &str,
+        selection_list: &[Positioned<Selection>],
+    ) -> Vec<Item> {
+        let mut item_list = vec![];
+        for selection in selection_list.iter() {
+            let (on, directive_list, selection_list) = match &selection.node {
+                Selection::Field(field) => {
+                    let field_type = self
+                        .schema
+                        .get_type(type_name)
+                        .and_then(|ty| ty.field(&field.name.node))
+                        .map(|field| field.ty.name().to_owned())
+                        .unwrap_or_default();
+                    merge_item(
+                        &mut item_list,
+                        Item::Field(ItemField {
+                            alias: field.alias.as_ref().map(|alias| alias.node.clone()),
+                            name: field.name.node.clone(),
+                            argument_list: field
+                                .argument_list
+                                .iter()
+                                .map(|argument| {
+                                    (
+                                        argument.node.name.node.clone(),
+                                        argument.node.value.node.clone(),
+                                    )
+                                })
+                                .collect(),
+                            directive_list: field
+                                .directive_list
+                                .iter()
+                                .map(|directive| directive.node.clone())
+                                .collect(),
+                            selection_list: self.normalize_selection_list(
+                                fragment_map,
+                                &field_type,
+                                &field.selection_list,
+                            ),
+                        }),
+                    );
+                    continue;
+                }
+                Selection::FragmentSpread(spread) => {
+                    match fragment_map.get(spread.name.node.as_str()) {
+                        Some(fragment) => (
+                            Some(&fragment.on.node),
+                            &spread.directive_list,
+                            &fragment.selection_list,
+                        ),
+                        None => continue,
+                    }
+                }
+                Selection::InlineFragment(fragment) => (
+                    fragment.on.as_ref().map(|on| &on.node),
+                    &fragment.directive_list,
+                    &fragment.selection_list,
+                ),
+            };
+            let condition_list: Vec<Directive> = directive_list
+                .iter()
+                .filter(|directive| matches!(directive.node.name.node.as_str(), "skip" | "include"))
+                .map(|directive| directive.node.clone())
+                .collect();
+            let mut fragment_list = vec![];
+
+            let is_abstract = self
+                .schema
+                .get_type(type_name)
+                .is_some_and(MetaType::is_abstract);
+            let item_list_of =
+                |on: &str| self.normalize_selection_list(fragment_map, on, selection_list);
+            match on {
+                Some(on) if is_abstract && on != type_name => {
+                    let possible_type_list: Vec<String> = self
+                        .schema
+                        .get_type(on)
+                        .map(|ty| {
+                            if ty.is_abstract() {
+                                ty.possible_type_list.clone()
+                            } else {
+                                vec![ty.name.clone()]
+                            }
+                        })
+                        .unwrap_or_default();
+                    for object in possible_type_list {
+                        if self.schema.is_possible_type(type_name, &object) {
+                            let fragment_item_list = self.normalize_selection_list(
+                                fragment_map,
+                                &object,
+                                selection_list,
+                            );
+                            merge_item(
+                                &mut fragment_list,
+                                Item::Fragment(Some(object), vec![], fragment_item_list),
+                            );
+                        }
+                    }
+                }
+                Some(on) if !self.schema.is_possible_type(on, type_name) => {}
+                _ => {
+                    for item in item_list_of(type_name) {
+                        merge_item(&mut fragment_list, item);
+                    }
+                }
+            }
+            if condition_list.is_empty() {
+                for item in fragment_list {
+                    merge_item(&mut item_list, item);
+                }
+            } else if !fragment_list.is_empty() {
+                merge_item(
+                    &mut item_list,
+                    Item::Fragment(None, condition_list, fragment_list),
+                );
+            }
+        }
+        item_list
+    }
+
+    fn variable_definition_list(
+        &self,
+        operation: &OperationDefinition,
+        usage_list: &[String],
+    ) -> Vec<String> {
+        operation
+            .variable_list
+            .iter()
+            .filter(|variable| usage_list.contains(&variable.node.name.node))
+            .map(|variable| {
+                let variable = &variable.node;
+                match &variable.default_value {
+                    Some(default_value) => format!(
+                        "${}: {} = {}",
+                        variable.name.node, variable.ty.node, default_value.node
+                    ),
+                    None => format!("${}: {}", variable.name.node, variable.ty.node),
+                }
+            })
+            .collect()
+    }
+
+    fn root_operation(&self, operation: &OperationDefinition, selection_list: &[Item]) -> String {
+        let keyword = match operation.ty {
+            OperationType::Query => "query",
+            OperationType::Mutation => "mutation",
+            OperationType::Subscription => "subscription",
+        };
+        let definition_list =
+            self.variable_definition_list(operation, &variable_usage_list(selection_list));
+        let mut output = if definition_list.is_empty() && operation.ty == OperationType::Query {
+            String::from("{\n")
+        } else if definition_list.is_empty() {
+            format!("{} {{\n", keyword)
+        } else {
+            format!("{}({}) {{\n", keyword, definition_list.join(", "))
+        };
+        print_item_list(&mut output, selection_list, INDENT);
+        output.push('}');
+        output
+    }
+
+    fn entity_operation(&self, operation: &OperationDefinition, selection_list: &[Item]) -> String {
+        let mut definition_list = vec!["$representations: [_Any!]!".to_owned()];
+        definition_list
+            .extend(self.variable_definition_list(operation, &variable_usage_list(selection_list)));
+        let mut output = format!(
+            "query({}) {{\n{}_entities(representations: $representations) {{\n",
+            definition_list.join(", "),
+            INDENT
+        );
+        print_item_list(&mut output, selection_list, &INDENT.repeat(2));
+        writeln!(output, "{}}}", INDENT).unwrap();
+        output.push('}');
+        output
+    }
+}
+
+fn list_depth(ty: &Type) -> usize {
+    match ty {
+        Type::NonNull(ty) => list_depth(ty),
+        Type::List(ty) => 1 + list_depth(ty),
+        Type::Named(_) => 0,
+    }
+}
+
+fn variable_usage_list(item_list: &[Item]) -> Vec<String> {
+    fn collect_value(value: &Value, usage_list: &mut Vec<String>) {
+        match value {
+            Value::Variable(name) if !usage_list.contains(name) => usage_list.push(name.clone()),
+            Value::List(list) => list
+                .iter()
+                .for_each(|value| collect_value(value, usage_list)),
+            Value::Object(object) => object
+                .values()
+                .for_each(|value| collect_value(value, usage_list)),
+            _ => {}
+        }
+    }
+    fn collect(item_list: &[Item], usage_list: &mut Vec<String>) {
+        for item in item_list.iter() {
+            match item {
+                Item::Field(field) => {
+                    for (_, value) in field.argument_list.iter() {
+                        collect_value(value, usage_list);
+                    }
+                    for directive in field.directive_list.iter() {
+                        for argument in directive.argument_list.iter() {
+                            collect_value(&argument.node.value.node, usage_list);
+                        }
+                    }
+                    collect(&field.selection_list, usage_list);
+                }
+                Item::Fragment(_, directive_list, selection_list) => {
+                    for directive in directive_list.iter() {
+                        for argument in directive.argument_list.iter() {
+                            collect_value(&argument.node.value.node, usage_list);
+                        }
+                    }
+                    collect(selection_list, usage_list);
+                }
+            }
+        }
+    }
+
+    let mut usage_list = vec![];
+    collect(item_list, &mut usage_list);
+    usage_list
+}
+
+/// Sends operations to one subgraph of a supergraph.
+pub trait SubgraphClient: Send + Sync {
+    fn fetch<'a>(&'a self, operation: &'a str, variables: Variables) -> BoxFuture<'a, Response>;
+}
+
+/// A subgraph executed in process, such as a stand-in for a remote service in tests.
+///
+/// Its schema must declare `_entities(representations: [_Any!]!): [_Entity]!` on the query
+/// type for the entities it resolves.
+pub struct LocalSubgraph {
+    schema: Schema,
+    root: Arc<dyn Resolver>,
+}
+
+impl LocalSubgraph {
+    pub fn new<R: Resolver + 'static>(schema: Schema, root: R) -> Self {
+        LocalSubgraph {
+            schema,
+            root: Arc::new(root),
+        }
+    }
+}
+
+impl SubgraphClient for LocalSubgraph {
+    fn fetch<'a>(&'a self, operation: &'a str, variables: Variables) -> BoxFuture<'a, Response> {
+        async move {
+            match parse_executable(operation) {
+                Ok(document) => {
+                    execute_async(
+                        &self.schema,
+                        &document,
+                        None,
+                        &variables,
+                        self.root.as_ref(),
+                    )
+                    .await
+                }
+                Err(error) => Response::from_errors(vec![
+                    ServerError::new("Syntax Error").at(&error.position())
+                ]),
+            }
+        }
+        .boxed()
+    }
+}
+
+struct PlanExecution<'a> {
+    client_map: &'a HashMap<String, Box<dyn SubgraphClient>>,
+    variables: &'a Variables,
+    data: Mutex<JsonValue>,
+    error_list: Mutex<Vec<ServerError>>,
+}
+
+/// Executes a plan against the clients of its subgraphs, keyed by subgraph name, and
+/// shapes the merged data like the planned operation.
+pub async fn execute_plan(
+    plan: &QueryPlan,
+    client_map: &HashMap<String, Box<dyn SubgraphClient>>,
+    variables: &Variables,
+) -> Response {
+    let execution = PlanExecution {
+        client_map,
+        variables,
+        data: Mutex::new(JsonValue::Object(Map::new())),
+        error_list: Mutex::new(vec![]),
+    };
+    if let Some(node) = &plan.node {
+        execution.execute_node(node, &[]).await;
+    }
+
+    let mut data = execution.data.into_inner().unwrap();
+    if let JsonValue::Object(object) = &mut data {
+        object.insert(
+            "__typename".to_owned(),
+            JsonValue::String(plan.root_type.clone()),
+        );
+    }
+    let mut variables = variables.clone();
+    for (name, value) in plan.default_variables.iter() {
+        if !variables.contains_key(name) {
+            variables.insert(name.clone(), value.clone());
+        }
+    }
+    Response {
+        data: Some(project(&plan.selection_list, &data, &variables)),
+        errors: execution.error_list.into_inner().unwrap(),
+    }
+}
+
+impl<'a> PlanExecution<'a> {
+    fn execute_node<'b>(&'b self, node: &'b PlanNode, path: &'b [String]) -> BoxFuture<'b, ()> {
+        async move {
+            match node {
+                PlanNode::Fetch(fetch) => self.execute_fetch(fetch, path).await,
+                PlanNode::Sequence(node_list) => {
+                    for node in node_list.iter() {
+                        self.execute_node(node, path).await;
+                    }
+                }
+                PlanNode::Parallel(node_list) => {
+                    join_all(node_list.iter().map(|node| self.execute_node(node, path))).await;
+                }
+                PlanNode::Flatten(flatten) => self.execute_node(&flatten.node, &flatten.path).await,
+            }
+        }
+        .boxed()
+    }
+
+    async fn execute_fetch(&self, fetch: &FetchNode, path: &[String]) {
+        let client = match self.client_map.get(&fetch.service_name) {
+            Some(client) => client,
+            None => {
+                self.error_list
+                    .lock()
+                    .unwrap()
+                    .push(ServerError::new(format!(
+                        "Unknown subgraph \"{}\".",
+                        fetch.service_name
+                    )));
+                return;
+            }
+        };
+        let mut variables: Variables = fetch
+            .variable_usage_list
+            .iter()
+            .filter_map(|name| Some((name.clone(), self.variables.get(name)?.clone())))
+            .collect();
+
+        let requires = match &fetch.requires {
+            Some(requires) => requires,
+            None => {
+                let response = client.fetch(&fetch.operation, variables).await;
+                if let Some(data) = response.data {
+                    merge_json(&mut self.data.lock().unwrap(), data);
+                }
+                self.error_list.lock().unwrap().extend(response.errors);
+                return;
+            }
+        };
+
+        let requires = match parse_selection_list(requires) {
+            Ok(requires) => requires,
+            Err(_) => return,
+        };
+        let mut entity_path_list = vec![];
+        let mut representation_list = vec![];
+        {
+            let data = self.data.lock().unwrap();
+            let mut object_list = vec![];
+            collect_objects(&data, path, &mut vec![], &mut object_list);
+            for (object_path, object) in object_list {
+                if let Some(representation) = representation(&requires, object) {
+                    entity_path_list.push(object_path);
+                    representation_list.push(representation);
+                }
+            }
+        }
+        if representation_list.is_empty() {
+            return;
+        }
+
+        variables.insert(
+            "representations".to_owned(),
+            JsonValue::Array(representation_list),
+        );
+        let response = client.fetch(&fetch.operation, variables).await;
+        let entity_list = match response
+            .data
+            .and_then(|mut data| data.get_mut("_entities").map(JsonValue::take))
+        {
+            Some(JsonValue::Array(entity_list)) => entity_list,
+            _ => vec![],
+        };
+        {
+            let mut data = self.data.lock().unwrap();
+            for (entity_path, entity) in entity_path_list.iter().zip(entity_list) {
+                if let Some(target) = json_at_mut(&mut data, entity_path) {
+                    merge_json(target, entity);
+                }
+            }
+        }
+
+        let mut error_list = self.error_list.lock().unwrap();
+        for mut error in response.errors {
+            if let [PathSegment::Field(name), PathSegment::Index(index), ..] = error.path.as_slice()
+            {
+                if name == "_entities" {
+                    if let Some(entity_path) = entity_path_list.get(*index) {
+                        let mut path = entity_path.clone();
+                        path.extend(error.path.drain(2..));
+                        error.path = path;
+                    }
+                }
+            }
+            error_list.push(error);
+        }
+    }
+}
+
+/// Finds the objects at `path`, along with where they are in the response.
+fn collect_objects<'a>(
+    value: &'a JsonValue,
+    path: &[String],
+    current: &mut Vec<PathSegment>,
+    object_list: &mut Vec<(Vec<PathSegment>, &'a JsonValue)>,
+) {
+    match (path.split_first(), value) {
+        (None, JsonValue::Object(_)) => object_list.push((current.clone(), value)),
+        (Some((segment, rest)), JsonValue::Array(list)) if segment == "@" => {
+            for (index, item) in list.iter().enumerate() {
+                current.push(PathSegment::Index(index));
+                collect_objects(item, rest, current, object_list);
+                current.pop();
+            }
+        }
+        (Some((segment, rest)), JsonValue::Object(object)) => {
+            if let Some(value) = object.get(segment) {
+                current.push(PathSegment::Field(segment.clone()));
+                collect_objects(value, rest, current, object_list);
+                current.pop();
+            }
+        }
+        _ => {}
+    }
+}
+
+fn json_at_mut<'a>(value: &'a mut JsonValue, path: &[PathSegment]) -> Option<&'a mut JsonValue> {
+    path.iter().try_fold(value, |value, segment| match segment {
+        PathSegment::Field(name) => value.get_mut(name.as_str()),
+        PathSegment::Index(index) => value.get_mut(*index),
+    })
+}
+
+/// The representation of `object` for the entity type whose fragment in `requires`
+/// matches its `__typename`.
+fn representation(requires: &[Positioned<Selection>], object: &JsonValue) -> Option<JsonValue> {
+    let type_name = object.get("__typename")?.as_str()?;
+    requires.iter().find_map(|selection| match &selection.node {
+        Selection::InlineFragment(fragment)
+            if fragment.on.as_ref().map(|on| on.node.as_str()) == Some(type_name) =>
+        {
+            Some(select(&fragment.selection_list, object))
+        }
+        _ => None,
+    })
+}
+
+fn select(selection_list: &[Positioned<Selection>], value: &JsonValue) -> JsonValue {
+    match value {
+        JsonValue::Object(object) => {
+            let mut selected = Map::new();
+            for selection in selection_list.iter() {
+                if let Selection::Field(field) = &selection.node {
+                    if let Some(value) = object.get(&field.name.node) {
+                        selected.insert(
+                            field.name.node.clone(),
+                            select(&field.selection_list, value),
+                        );
+                    }
+                }
+            }
+            JsonValue::Object(selected)
+        }
+        JsonValue::Array(list) => JsonValue::Array(
+            list.iter()
+                .map(|item| select(selection_list, item))
+                .collect(),
+        ),
+        value => value.clone(),
+    }
+}
+
+fn merge_json(target: &mut JsonValue, source: JsonValue) {
+    match (target, source) {
+        (JsonValue::Object(target), JsonValue::Object(source)) => {
+            for (key, value) in source {
+                match target.get_mut(&key) {
+                    Some(existing) => merge_json(existing, value),
+                    None => {
+                        target.insert(key, value);
+                    }
+                }
+            }
+        }
+        (JsonValue::Array(target), JsonValue::Array(source)) if target.len() == source.len() => {
+            for (target, source) in target.iter_mut().zip(source) {
+                merge_json(target, source);
+            }
+        }
+        (target, source) => *target = source,
+    }
+}
+
+/// Keeps only what the client selected, dropping the keys and type names added for fetching.
+fn project(item_list: &[Item], value: &JsonValue, variables: &Variables) -> JsonValue {
+    match value {
+        JsonValue::Object(object) => {
+            let mut projected = Map::new();
+            project_into(item_list, object, variables, &mut projected);
+            JsonValue::Object(projected)
+        }
+        JsonValue::Array(list) => JsonValue::Array(
+            list.iter()
+                .map(|item| project(item_list, item, variables))
+                .collect(),
+        ),
+        value => value.clone(),
+    }
+}
+
+fn project_into(
+    item_list: &[Item],
+    object: &Map<String, JsonValue>,
+    variables: &Variables,
+    projected: &mut Map<String, JsonValue>,
+) {
+    for item in item_list.iter() {
+        match item {
+            Item::Field(field) => {
+                let key = field.response_key();
+                if let Some(value) = object.get(key) {
+                    let value = if field.selection_list.is_empty() {
+                        value.clone()
+                    } else {
+                        project(&field.selection_list, value, variables)
+                    };
+                    projected.insert(key.to_owned(), value);
+                }
+            }
+            Item::Fragment(on, directive_list, selection_list) => {
+                let is_on = on.as_ref().is_none_or(|on| {
+                    object.get("__typename").and_then(JsonValue::as_str) == Some(on.as_str())
+                });
+                if is_on && is_included(directive_list, variables) {
+                    project_into(selection_list, object, variables, projected);
+                }
+            }
+        }
+    }
+}
+
+/// Whether the `@skip` and `@include` directives of a fragment let it through.
+fn is_included(directive_list: &[Directive], variables: &Variables) -> bool {
+    directive_list.iter().all(|directive| {
+        let condition = directive
+            .argument_list
+            .iter()
+            .find(|argument| argument.node.name.node == "if")
+            .and_then(|argument| match &argument.node.value.node {
+                Value::Boolean(condition) => Some(*condition),
+                Value::Variable(name) => variables.get(name).and_then(JsonValue::as_bool),
+                _ => None,
+            });
+        match directive.name.node.as_str() {
+            "skip" => condition != Some(true),
+            "include" => condition == Some(true),
+            _ => true,
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use futures::executor::block_on;
+    use serde_json::json;
+
+    const ACCOUNTS: &str = r#"type Query {
+  me: User
+  _entities(representations: [_Any!]!): [_Entity]!
+}
+
+type User @key(fields: "id") {
+  id: ID!
+  name: String
+}
+
+scalar _Any
+
+union _Entity = User
+"#;
+
+    const REVIEWS: &str = r#"type Query {
+  _entities(representations: [_Any!]!): [_Entity]!
+}
+
+type User @key(fields: "id") {
+  id: ID!
+  reviewList: [Review!]!
+}
+
+type Review {
+  body: String
+  product: Product
+}
+
+type Product @key(fields: "upc", resolvable: false) {
+  upc: String!
+}
+
+scalar _Any
+
+union _Entity = User
+"#;
+
+    const PRODUCTS: &str = r#"type Query {
+  topProductList(first: Int = 5): [Product!]!
+  _entities(representations: [_Any!]!): [_Entity]!
+}
+
+type Product @key(fields: "upc") {
+  upc: String!
+  name: String
+}
+
+scalar _Any
+
+union _Entity = Product
+"#;
+
+    /// An object backed by JSON, whose `__typename` names its type.
+    struct Object(JsonValue);
+
+    impl Resolver for Object {
+        fn type_name(&self) -> Option<&str> {
+            self.0.get("__typename").and_then(JsonValue::as_str)
+        }
+
+        fn resolve(&self, info: &ResolveInfo<'_>) -> FieldResult<Resolved> {
+            Ok(resolved(
+                self.0
+                    .get(info.field_name)
+                    .cloned()
+                    .unwrap_or(JsonValue::Null),
+            ))
+        }
+    }
+
+    fn resolved(value: JsonValue) -> Resolved {
+        match value {
+            JsonValue::Null => Resolved::Null,
+            JsonValue::Array(list) => Resolved::list(list.into_iter().map(resolved)),
+            JsonValue::Object(_) => Resolved::object(Object(value)),
+            value => Resolved::Value(value),
+        }
+    }
+
+    fn local_subgraph(
+        source: &str,
+        root: JsonValue,
+        entity: fn(&JsonValue) -> JsonValue,
+    ) -> Box<dyn SubgraphClient> {
+        let mut document = parse_type_system(source).unwrap();
+        document.definition_list.extend(
+            parse_type_system(FEDERATION_DEFINITION_LIST)
+                .unwrap()
+                .definition_list,
+        );
+        let schema = Schema::build(&document).unwrap();
+        Box::new(LocalSubgraph::new(
+            schema,
+            move |info: &ResolveInfo<'_>| match info.field_name {
+                "_entities" => match value_to_json(&info.arguments["representations"]) {
+                    JsonValue::Array(list) => Ok(Resolved::list(
+                        list.iter()
+                            .map(|representation| resolved(entity(representation))),
+                    )),
+                    _ => Err("representations must be a list".into()),
+                },
+                name => Ok(resolved(root.get(name).cloned().unwrap_or(JsonValue::Null))),
+            },
+        ))
+    }
+
+    fn subgraph(name: &str, source: &str) -> Subgraph {
+        Subgraph {
+            name: name.to_owned(),
+            url: format!("http://{}.local/graphql", name),
+            document: parse_type_system(source).unwrap(),
+        }
+    }
+
+    #[test]
+    fn plan_and_execute() {
+        let supergraph = compose(&[
+            subgraph("accounts", ACCOUNTS),
+            subgraph("reviews", REVIEWS),
+            subgraph("products", PRODUCTS),
+        ])
+        .unwrap();
+        let planner = QueryPlanner::new(&supergraph).unwrap();
+        let document = parse_executable(
+            r#"query Home($first: Int) {
+  me {
+    name
+    reviewList { body product { name } }
+  }
+  top: topProductList(first: $first) { ...ProductFields }
+}
+
+fragment ProductFields on Product { upc name }
+"#,
+        )
+        .unwrap();
+        let plan = planner.plan(&document, None).unwrap();
+        assert_eq!(
+            plan.to_string(),
+            r#"QueryPlan {
+  Parallel {
+    Sequence {
+      Fetch(service: "accounts") {
+        {
+          me {
+            name
+            __typename
+            id
+          }
+        }
+      },
+      Flatten(path: "me") {
+        Fetch(service: "reviews") {
+          {
+            ... on User {
+              __typename
+              id
+            }
+          } =>
+          query($representations: [_Any!]!) {
+            _entities(representations: $representations) {
+              ... on User {
+                reviewList {
+                  body
+                  product {
+                    __typename
+                    upc
+                  }
+                }
+              }
+            }
+          }
+        },
+      },
+      Flatten(path: "me.reviewList.@.product") {
+        Fetch(service: "products") {
+          {
+            ... on Product {
+              __typename
+              upc
+            }
+          } =>
+          query($representations: [_Any!]!) {
+            _entities(representations: $representations) {
+              ... on Product {
+                name
+              }
+            }
+          }
+        },
+      },
+    },
+    Fetch(service: "products") {
+      query($first: Int) {
+        top: topProductList(first: $first) {
+          upc
+          name
+        }
+      }
+    },
+  },
+}"#
+        );
+
+        let mut client_map = HashMap::new();
+        client_map.insert(
+            "accounts".to_owned(),
+            local_subgraph(
+                ACCOUNTS,
+                json!({"me": {"__typename": "User", "id": "1", "name": "Ada"}}),
+                |representation| json!({"__typename": "User", "id": representation["id"], "name": "Ada"}),
+            ),
+        );
+        client_map.insert(
+            "reviews".to_owned(),
+            local_subgraph(REVIEWS, json!({}), |representation| {
+                json!({
+                    "__typename": "User",
+                    "id": representation["id"],
+                    "reviewList": [
+                        {"__typename": "Review", "body": "Great", "product": {"__typename": "Product", "upc": "p1"}},
+                        {"__typename": "Review", "body": "Fine", "product": {"__typename": "Product", "upc": "p2"}},
+                    ],
+                })
+            }),
+        );
+        client_map.insert(
+            "products".to_owned(),
+            local_subgraph(
+                PRODUCTS,
+                json!({"topProductList": [{"__typename": "Product", "upc": "p1", "name": "Lamp"}]}),
+                |representation| {
+                    let name = match representation["upc"].as_str() {
+                        Some("p1") => "Lamp",
+                        _ => "Desk",
+                    };
+                    json!({"__typename": "Product", "upc": representation["upc"], "name": name})
+                },
+            ),
+        );
+
+        let variables = match json!({"first": 1}) {
+            JsonValue::Object(variables) => variables,
+            _ => unreachable!(),
+        };
+        let response = block_on(execute_plan(&plan, &client_map, &variables));
+        assert_eq!(
+            serde_json::to_value(response).unwrap(),
+            json!({
+                "data": {
+                    "me": {
+                        "name": "Ada",
+                        "reviewList": [
+                            {"body": "Great", "product": {"name": "Lamp"}},
+                            {"body": "Fine", "product": {"name": "Desk"}},
+                        ],
+                    },
+                    "top": [{"upc": "p1", "name": "Lamp"}],
+                }
+            })
+        );
+    }
+
+    const INVENTORY: &str = r#"type Query {
+  node(id: ID!): Node
+}
+
+type Mutation {
+  restock(upc: String!): Product
+}
+
+interface Node {
+  id: ID!
+}
+
+type Product implements Node @key(fields: "upc") {
+  id: ID!
+  upc: String!
+  weight: Int
+  price: Int
+}
+"#;
+
+    const SHIPPING: &str = r#"type Query {
+  _entities(representations: [_Any!]!): [_Entity]!
+}
+
+type Mutation {
+  ship(upc: String!): Boolean
+}
+
+type Product @key(fields: "upc") {
+  upc: String!
+  weight: Int @external
+  price: Int @external
+  estimate: Int @requires(fields: "weight price")
+}
+
+scalar _Any
+
+union _Entity = Product
+"#;
+
+    #[test]
+    fn conditional_fragment() {
+        const PROFILES: &str = r#"type Query {
+  me: User
+  _entities(representations: [_Any!]!): [_Entity]!
+}
+
+type User @key(fields: "id") {
+  id: ID!
+  name: String
+  email: String
+}
+
+scalar _Any
+
+union _Entity = User
+"#;
+        let supergraph = compose(&[subgraph("profiles", PROFILES)]).unwrap();
+        let planner = QueryPlanner::new(&supergraph).unwrap();
+        let document = parse_executable(
+            r#"query Me($withName: Boolean = false) {
+  me {
+    id
+    ... @skip(if: true) { email }
+    ...UserName @include(if: $withName)
+  }
+}
+
+fragment UserName on User { name }
+"#,
+        )
+        .unwrap();
+        let plan = planner.plan(&document, None).unwrap();
+        assert_eq!(
+            plan.to_string(),
+            r#"QueryPlan {
+  Fetch(service: "profiles") {
+    query($withName: Boolean = false) {
+      me {
+        id
+        ... @skip(if: true) {
+          email
+        }
+        ... @include(if: $withName) {
+          name
+        }
+      }
+    }
+  },
+}"#
+        );
+        // Fields a subgraph returns anyway are only kept when their fragment applies.
+        let data = json!({"me": {"__typename": "User", "id": "1", "name": "Ada", "email": "ada@example.com"}});
+        assert_eq!(
+            project(&plan.selection_list, &data, &plan.default_variables),
+            json!({"me": {"id": "1"}})
+        );
+
+        let mut client_map = HashMap::new();
+        client_map.insert(
+            "profiles".to_owned(),
+            local_subgraph(
+                PROFILES,
+                json!({"me": {"__typename": "User", "id": "1", "name": "Ada", "email": "ada@example.com"}}),
+                |representation| representation.clone(),
+            ),
+        );
+        let response = block_on(execute_plan(&plan, &client_map, &Variables::new()));
+        assert_eq!(response.errors, vec![]);
+        assert_eq!(response.data, Some(json!({"me": {"id": "1"}})));
+        let mut variables = Variables::new();
+        variables.insert("withName".to_owned(), json!(true));
+        let response = block_on(execute_plan(&plan, &client_map, &variables));
+        assert_eq!(
+            response.data,
+            Some(json!({"me": {"id": "1", "name": "Ada"}}))
+        );
+    }
+
+    #[test]
+    fn mutation_and_abstract_type() {
+        let supergraph = compose(&[
+            subgraph("inventory", INVENTORY),
+            subgraph("shipping", SHIPPING),
+        ])
+        .unwrap();
+        let planner = QueryPlanner::new(&supergraph).unwrap();
+
+        // Mutation fields run one at a time, even when a subgraph resolves several.
+        let document = parse_executable(
+            r#"mutation { restock(upc: "a") { upc } ship(upc: "a") again: restock(upc: "b") { upc } }"#,
+        )
+        .unwrap();
+        assert_eq!(
+            planner.plan(&document, None).unwrap().to_string(),
+            r#"QueryPlan {
+  Sequence {
+    Fetch(service: "inventory") {
+      mutation {
+        restock(upc: "a") {
+          upc
+        }
+      }
+    },
+    Fetch(service: "shipping") {
+      mutation {
+        ship(upc: "a")
+      }
+    },
+    Fetch(service: "inventory") {
+      mutation {
+        again: restock(upc: "b") {
+          upc
+        }
+      }
+    },
+  },
+}"#
+        );
+
+        // `__typename` is fetched for abstract types, and `@requires` fields are sent in
+        // the representations.
+        let document =
+            parse_executable(r#"{ node(id: "1") { id ... on Product { estimate } } }"#).unwrap();
+        let plan = planner.plan(&document, None).unwrap();
+        assert_eq!(
+            plan.to_string(),
+            r#"QueryPlan {
+  Sequence {
+    Fetch(service: "inventory") {
+      {
+        node(id: "1") {
+          __typename
+          id
+          ... on Product {
+            __typename
+            upc
+            weight
+            price
+          }
+        }
+      }
+    },
+    Flatten(path: "node") {
+      Fetch(service: "shipping") {
+        {
+          ... on Product {
+            __typename
+            upc
+            weight
+            price
+          }
+        } =>
+        query($representations: [_Any!]!) {
+          _entities(representations: $representations) {
+            ... on Product {
+              estimate
+            }
+          }
+        }
+      },
+    },
+  },
+}"#
+        );
+
+        let mut client_map = HashMap::new();
+        client_map.insert(
+            "inventory".to_owned(),
+            local_subgraph(
+                INVENTORY,
+                json!({"node": {"__typename": "Product", "id": "1", "upc": "a", "weight": 3, "price": 40}}),
+                |representation| representation.clone(),
+            ),
+        );
+        client_map.insert(
+            "shipping".to_owned(),
+            local_subgraph(SHIPPING, json!({}), |representation| {
+                let estimate = representation["weight"].as_i64().unwrap() * 2
+                    + representation["price"].as_i64().unwrap() / 10;
+                json!({"__typename": "Product", "upc": representation["upc"], "estimate": estimate})
+            }),
+        );
+        let response = block_on(execute_plan(&plan, &client_map, &Variables::new()));
+        assert_eq!(
+            serde_json::to_value(response).unwrap(),
+            json!({"data": {"node": {"id": "1", "estimate": 10}}})
+        );
+    }
+
+    #[test]
+    fn unresolvable_field() {
+        let sdl = compose(&[
+            subgraph("inventory", INVENTORY),
+            subgraph("shipping", SHIPPING),
+        ])
+        .unwrap()
+        .sdl();
+        let plan_error = |sdl: String| {
+            let supergraph = Supergraph {
+                document: parse_type_system(&sdl).unwrap(),
+            };
+            let planner = QueryPlanner::new(&supergraph).unwrap();
+            let document =
+                parse_executable(r#"{ node(id: "1") { ... on Product { estimate } } }"#).unwrap();
+            planner.plan(&document, None).unwrap_err()[0]
+                .message
+                .clone()
+        };
+
+        assert_eq!(
+            plan_error(sdl.replace(
+                "@join__field(graph: SHIPPING, requires: \"weight price\")",
+                "@join__field(graph: SHIPPING, external: true)",
+            )),
+            "No subgraph can resolve field \"Product.estimate\"."
+        );
+        assert_eq!(
+            plan_error(sdl.replace(
+                "@join__type(graph: SHIPPING, key: \"upc\")",
+                "@join__type(graph: SHIPPING, key: \"upc\", resolvable: false)",
+            )),
+            "Cannot resolve field \"Product.estimate\": no key of \"Product\" in subgraph \"shipping\" can be fetched from subgraph \"inventory\"."
+        );
+    }
+}
@@ -0,0 +1,380 @@
+use graphql_executor::*;
+use graphql_parser::*;
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use std::fmt::Write;
+
+/// Which details of an operation are erased by `normalize_operation`.
+///
+/// Field, argument, directive and variable order, whitespace and unused fragments are
+/// always erased.
+#[derive(Debug, Clone, PartialEq)]
+pub struct NormalizationConfig {
+    /// Replaces fragment spreads with inline fragments instead of printing the sorted
+    /// fragment definitions after the operation.
+    pub inline_fragments: bool,
+    pub strip_aliases: bool,
+    /// Replaces numbers with `0`, strings with `""`, lists with `[]` and input objects
+    /// with `{}`, leaving variables, enums, booleans and `null` untouched.
+    pub hide_literals: bool,
+}
+
+impl Default for NormalizationConfig {
+    fn default() -> Self {
+        NormalizationConfig {
+            inline_fragments: false,
+            strip_aliases: false,
+            hide_literals: true,
+        }
+    }
+}
+
+/// Prints the operation of `document` selected by `operation_name` in a canonical,
+/// compact form, so that operations differing only in formatting or ordering print the
+/// same.
+#[allow(clippy::result_large_err)]
+pub fn normalize_operation(
+    document: &ExecutableDocument,
+    operation_name: Option<&str>,
+    config: &NormalizationConfig,
+) -> Result<String, ServerError> {
+    let operation = get_operation(document, operation_name)?;
+    let mut normalizer = Normalizer {
+        config,
+        fragment_map: document
+            .definition_list
+            .iter()
+            .filter_map(|definition| match &definition.node {
+                ExecutableDefinition::Fragment(fragment) => {
+                    Some((fragment.node.name.node.as_str(), &fragment.node))
+                }
+                ExecutableDefinition::Operation(_) => None,
+            })
+            .collect(),
+        fragment_stack: vec![],
+        used_fragment_list: vec![],
+    };
+
+    let mut output = String::new();
+    output.push_str(match operation.ty {
+        OperationType::Query => "query",
+        OperationType::Mutation => "mutation",
+        OperationType::Subscription => "subscription",
+    });
+    if let Some(name) = &operation.name {
+        write!(output, " {}", name.node).unwrap();
+    }
+    let mut variable_list: Vec<&VariableDefinition> = operation
+        .variable_list
+        .iter()
+        .map(|definition| &definition.node)
+        .collect();
+    variable_list.sort_by(|a, b| a.name.node.cmp(&b.name.node));
+    if !variable_list.is_empty() {
+        output.push('(');
+        for (index, definition) in variable_list.iter().enumerate() {
+            if index > 0 {
+                output.push(',');
+            }
+            write!(output, "${}:{}", definition.name.node, definition.ty.node).unwrap();
+            if let Some(default_value) = &definition.default_value {
+                output.push('=');
+                normalizer.print_value(&mut output, &default_value.node);
+            }
+        }
+        output.push(')');
+    }
+    output.push_str(&normalizer.print_directive_list(&operation.directive_list));
+    output.push_str(&normalizer.print_selection_list(&operation.selection_list));
+
+    // Fragments spread by the printed fragments are collected while printing them.
+    let mut index = 0;
+    let mut fragment_list = vec![];
+    while index < normalizer.used_fragment_list.len() {
+        let fragment = normalizer.used_fragment_list[index];
+        index += 1;
+        let printed = format!(
+            "fragment {} on {}{}{}",
+            fragment.name.node,
+            fragment.on.node,
+            normalizer.print_directive_list(&fragment.directive_list),
+            normalizer.print_selection_list(&fragment.selection_list)
+        );
+        fragment_list.push(printed);
+    }
+    fragment_list.sort();
+    for fragment in fragment_list {
+        write!(output, " {}", fragment).unwrap();
+    }
+
+    Ok(output)
+}
+
+/// Hashes the normalized form of an operation into a stable, lowercase hexadecimal
+/// SHA-256 signature.
+#[allow(clippy::result_large_err)]
+pub fn operation_signature(
+    document: &ExecutableDocument,
+    operation_name: Option<&str>,
+    config: &NormalizationConfig,
+) -> Result<String, ServerError> {
+    let normalized = normalize_operation(document, operation_name, config)?;
+    Ok(sha256_hex(&normalized))
+}
+
+pub(crate) fn sha256_hex(text: &str) -> String {
+    Sha256::digest(text.as_bytes())
+        .iter()
+        .fold(String::with_capacity(64), |mut output, byte| {
+            write!(output, "{:02x}", byte).unwrap();
+            output
+        })
+}
+
+struct Normalizer<'a> {
+    config: &'a NormalizationConfig,
+    fragment_map: HashMap<&'a str, &'a FragmentDefinition>,
+    /// The fragments being inlined, to stop at a fragment that spreads itself.
+    fragment_stack: Vec<&'a str>,
+    used_fragment_list: Vec<&'a FragmentDefinition>,
+}
+
+impl<'a> Normalizer<'a> {
+    fn print_selection_list(&mut self, selection_list: &'a [Positioned<Selection>]) -> String {
+        let mut printed_list: Vec<(u8, String)> = vec![];
+        for selection in selection_list.iter() {
+            let printed = match &selection.node {
+                Selection::Field(field) => {
+                    let mut output = String::new();
+                    match &field.alias {
+                        Some(alias) if !self.config.strip_aliases => {
+                            write!(output, "{}:{}", alias.node, field.name.node).unwrap()
+                        }
+                        _ => output.push_str(&field.name.node),
+                    }
+                    self.print_argument_list(&mut output, &field.argument_list);
+                    output.push_str(&self.print_directive_list(&field.directive_list));
+                    output.push_str(&self.print_selection_list(&field.selection_list));
+                    (0, output)
+                }
+                Selection::FragmentSpread(spread) => {
+                    let name = spread.name.node.as_str();
+                    let directive_list = self.print_directive_list(&spread.directive_list);
+                    let fragment = match self.fragment_map.get(name) {
+                        Some(fragment) => *fragment,
+                        None => {
+                            printed_list.push((1, format!("...{}{}", name, directive_list)));
+                            continue;
+                        }
+                    };
+                    if !self.config.inline_fragments {
+                        if !self
+                            .used_fragment_list
+                            .iter()
+                            .any(|used| std::ptr::eq(*used, fragment))
+                        {
+                            self.used_fragment_list.push(fragment);
+                        }
+                        (1, format!("...{}{}", name, directive_list))
+                    } else if self.fragment_stack.contains(&name) {
+                        continue;
+                    } else {
+                        self.fragment_stack.push(name);
+                        let printed = format!(
+                            "...on {}{}{}",
+                            fragment.on.node,
+                            directive_list,
+                            self.print_selection_list(&fragment.selection_list)
+                        );
+                        self.fragment_stack.pop();
+                        (2, printed)
+                    }
+                }
+                Selection::InlineFragment(inline) => {
+                    let mut output = String::from("...");
+                    if let Some(on) = &inline.on {
+                        write!(output, "on {}", on.node).unwrap();
+                    }
+                    output.push_str(&self.print_directive_list(&inline.directive_list));
+                    output.push_str(&self.print_selection_list(&inline.selection_list));
+                    (2, output)
+                }
+            };
+            printed_list.push(printed);
+        }
+        if printed_list.is_empty() {
+            return String::new();
+        }
+
+        printed_list.sort();
+        printed_list.dedup();
+        let mut output = String::from("{");
+        for (index, (_, printed)) in printed_list.iter().enumerate() {
+            if index > 0 {
+                output.push(' ');
+            }
+            output.push_str(printed);
+        }
+        output.push('}');
+        output
+    }
+
+    fn print_directive_list(&self, directive_list: &[Positioned<Directive>]) -> String {
+        let mut printed_list: Vec<String> = directive_list
+            .iter()
+            .map(|directive| {
+                let mut output = format!("@{}", directive.node.name.node);
+                self.print_argument_list(&mut output, &directive.node.argument_list);
+                output
+            })
+            .collect();
+        printed_list.sort();
+        printed_list.concat()
+    }
+
+    fn print_argument_list(&self, output: &mut String, argument_list: &[Positioned<Argument>]) {
+        if argument_list.is_empty() {
+            return;
+        }
+        let mut argument_list: Vec<&Argument> = argument_list
+            .iter()
+            .map(|argument| &argument.node)
+            .collect();
+        argument_list.sort_by(|a, b| a.name.node.cmp(&b.name.node));
+        output.push('(');
+        for (index, argument) in argument_list.iter().enumerate() {
+            if index > 0 {
+                output.push(',');
+            }
+            write!(output, "{}:", argument.name.node).unwrap();
+            self.print_value(output, &argument.value.node);
+        }
+        output.push(')');
+    }
+
+    fn print_value(&self, output: &mut String, value: &Value) {
+        match value {
+            Value::Int(_) | Value::Float(_) if self.config.hide_literals => output.push('0'),
+            Value::String(_) if self.config.hide_literals => output.push_str("\"\""),
+            Value::List(_) if self.config.hide_literals => output.push_str("[]"),
+            Value::Object(_) if self.config.hide_literals => output.push_str("{}"),
+            Value::List(list) => {
+                output.push('[');
+                for (index, value) in list.iter().enumerate() {
+                    if index > 0 {
+                        output.push(',');
+                    }
+                    self.print_value(output, value);
+                }
+                output.push(']');
+            }
+            Value::Object(map) => {
+                output.push('{');
+                for (index, (name, value)) in map.iter().enumerate() {
+                    if index > 0 {
+                        output.push(',');
+                    }
+                    write!(output, "{}:", name).unwrap();
+                    self.print_value(output, value);
+                }
+                output.push('}');
+            }
+            value => write!(output, "{}", value).unwrap(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const QUERY: &str = r#"
+query Profile($id: ID!, $avatarSize: Int = 64) {
+  me: user(id: $id) {
+    ...UserFields
+    id
+    avatar(size: $avatarSize, shape: ROUND)
+  }
+  search(text: "ada", filter: {limit: 10, tags: ["a", "b"]}) { id }
+}
+
+fragment UserFields on User {
+  name
+  friends(first: 3) { ...Friend }
+}
+
+fragment Friend on User { id }
+
+fragment Unused on User { id }
+"#;
+
+    const REORDERED: &str = r#"
+fragment Friend on User {
+  id
+}
+
+query Profile($avatarSize: Int = 64, $id: ID!) {
+  search(filter: {tags: ["c"], limit: 25}, text: "grace") {
+    id
+  }
+  me: user(id: $id) {
+    avatar(shape: ROUND, size: $avatarSize)
+    id
+    ...UserFields
+  }
+}
+
+fragment UserFields on User {
+  friends(first: 5) {
+    ...Friend
+  }
+  name
+}
+"#;
+
+    #[test]
+    fn normalize() {
+        let document = parse_executable(QUERY).unwrap();
+        let config = NormalizationConfig::default();
+        assert_eq!(
+            normalize_operation(&document, None, &config).unwrap(),
+            "query Profile($avatarSize:Int=0,$id:ID!){me:user(id:$id){avatar(shape:ROUND,size:$avatarSize) id ...UserFields} search(filter:{},text:\"\"){id}} fragment Friend on User{id} fragment UserFields on User{friends(first:0){...Friend} name}"
+        );
+
+        let config = NormalizationConfig {
+            inline_fragments: true,
+            strip_aliases: true,
+            hide_literals: false,
+        };
+        assert_eq!(
+            normalize_operation(&document, None, &config).unwrap(),
+            "query Profile($avatarSize:Int=64,$id:ID!){search(filter:{limit:10,tags:[\"a\",\"b\"]},text:\"ada\"){id} user(id:$id){avatar(shape:ROUND,size:$avatarSize) id ...on User{friends(first:3){...on User{id}} name}}}"
+        );
+    }
+
+    #[test]
+    fn signature() {
+        let document = parse_executable(QUERY).unwrap();
+        let reordered = parse_executable(REORDERED).unwrap();
+        let config = NormalizationConfig::default();
+        let signature = operation_signature(&document, None, &config).unwrap();
+        assert_eq!(signature.len(), 64);
+        assert_eq!(
+            operation_signature(&reordered, Some("Profile"), &config).unwrap(),
+            signature
+        );
+
+        let config = NormalizationConfig {
+            hide_literals: false,
+            ..NormalizationConfig::default()
+        };
+        assert_ne!(
+            operation_signature(&reordered, None, &config).unwrap(),
+            operation_signature(&document, None, &config).unwrap()
+        );
+        assert_eq!(
+            sha256_hex("abc"),
+            "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad"
+        );
+    }
+}
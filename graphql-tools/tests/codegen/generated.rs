@@ -0,0 +1,172 @@
+// Generated from GraphQL operations. Do not edit.
+
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ColorInput {
+    pub red: i32,
+    pub green: i32,
+    pub blue: i32,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub blend: Option<Vec<ColorInput>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub next: Option<Box<ColorInput>>,
+}
+
+/// A film of the original trilogy.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum Episode {
+    #[serde(rename = "NEW_HOPE")]
+    NewHope,
+    #[serde(rename = "EMPIRE")]
+    Empire,
+    #[serde(rename = "JEDI")]
+    Jedi,
+    /// A value this code was generated without.
+    #[serde(other)]
+    Unknown,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ReviewInput {
+    pub stars: i32,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub commentary: Option<String>,
+    #[serde(rename = "favoriteColor", skip_serializing_if = "Option::is_none")]
+    pub favorite_color: Option<ColorInput>,
+}
+
+pub mod hero_and_friends {
+    use serde::{Deserialize, Serialize};
+
+    pub const OPERATION_NAME: &str = "HeroAndFriends";
+
+    pub const QUERY: &str = "query HeroAndFriends($episode: Episode, $withFriends: Boolean! = true, $anonymous: Boolean! = false) {\n  hero(episode: $episode) {\n    __typename\n    ...CharacterName\n    ... @skip(if: $anonymous) {\n      id\n    }\n    ... on Droid {\n      primaryFunction\n    }\n    friends @include(if: $withFriends) {\n      __typename\n      name\n    }\n  }\n}\n\nfragment CharacterName on Character {\n  name\n}\n";
+
+    #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+    pub struct Variables {
+        #[serde(skip_serializing_if = "Option::is_none")]
+        pub episode: Option<super::Episode>,
+        #[serde(rename = "withFriends", skip_serializing_if = "Option::is_none")]
+        pub with_friends: Option<bool>,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        pub anonymous: Option<bool>,
+    }
+
+    #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+    pub struct ResponseData {
+        pub hero: Option<Hero>,
+    }
+
+    #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+    #[serde(tag = "__typename")]
+    pub enum Hero {
+        Droid(HeroOnDroid),
+        Human(HeroOnHuman),
+        /// A value this code was generated without.
+        #[serde(other)]
+        Unknown,
+    }
+
+    #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+    pub struct HeroOnDroid {
+        pub name: String,
+        pub id: Option<String>,
+        /// What the droid was built for.
+        #[serde(rename = "primaryFunction")]
+        pub primary_function: Option<String>,
+        pub friends: Option<Vec<Option<HeroOnDroidFriends>>>,
+    }
+
+    #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+    pub struct HeroOnHuman {
+        pub name: String,
+        pub id: Option<String>,
+        pub friends: Option<Vec<Option<HeroOnHumanFriends>>>,
+    }
+
+    #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+    pub struct HeroOnDroidFriends {
+        pub name: String,
+    }
+
+    #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+    pub struct HeroOnHumanFriends {
+        pub name: String,
+    }
+}
+
+pub mod search {
+    use serde::{Deserialize, Serialize};
+
+    pub const OPERATION_NAME: &str = "Search";
+
+    pub const QUERY: &str = "query Search($text: String!) {\n  results: search(text: $text) {\n    __typename\n    ... on Character {\n      id\n      name\n    }\n    ... on Starship {\n      name\n      length\n    }\n  }\n}\n";
+
+    #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+    pub struct Variables {
+        pub text: String,
+    }
+
+    #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+    pub struct ResponseData {
+        pub results: Vec<Results>,
+    }
+
+    #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+    #[serde(tag = "__typename")]
+    pub enum Results {
+        Human(ResultsOnHuman),
+        Droid(ResultsOnDroid),
+        Starship(ResultsOnStarship),
+        /// A value this code was generated without.
+        #[serde(other)]
+        Unknown,
+    }
+
+    #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+    pub struct ResultsOnHuman {
+        pub id: String,
+        pub name: String,
+    }
+
+    #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+    pub struct ResultsOnDroid {
+        pub id: String,
+        pub name: String,
+    }
+
+    #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+    pub struct ResultsOnStarship {
+        pub name: String,
+        pub length: Option<f64>,
+    }
+}
+
+pub mod create_review {
+    use serde::{Deserialize, Serialize};
+
+    pub const OPERATION_NAME: &str = "CreateReview";
+
+    pub const QUERY: &str = "mutation CreateReview($episode: Episode!, $review: ReviewInput!) {\n  createReview(episode: $episode, review: $review) {\n    stars\n    commentary\n    createdAt\n  }\n}\n";
+
+    #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+    pub struct Variables {
+        pub episode: super::Episode,
+        pub review: super::ReviewInput,
+    }
+
+    #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+    pub struct ResponseData {
+        #[serde(rename = "createReview")]
+        pub create_review: Option<CreateReview>,
+    }
+
+    #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+    pub struct CreateReview {
+        pub stars: i32,
+        pub commentary: Option<String>,
+        #[serde(rename = "createdAt")]
+        pub created_at: Option<serde_json::Value>,
+    }
+}
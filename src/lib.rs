@@ -1,3 +1,6 @@
 #![warn(clippy::all)]
 
+pub use graphql_executor::*;
 pub use graphql_parser::*;
+pub use graphql_server::*;
+pub use graphql_tools::*;